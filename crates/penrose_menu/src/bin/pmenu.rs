@@ -0,0 +1,33 @@
+//! A drop in replacement for dmenu using penrose_menu
+use penrose_menu::{
+    dmenu::{self, DmenuArgs},
+    PMenu, PMenuConfig, Result,
+};
+use std::{
+    io::{stdin, stdout},
+    process::exit,
+};
+
+fn main() {
+    match run() {
+        Ok(true) => (),
+        Ok(false) => exit(1),
+        Err(e) => {
+            eprintln!("pmenu: {}", e);
+            exit(1)
+        }
+    }
+}
+
+fn run() -> Result<bool> {
+    let args = DmenuArgs::parse(std::env::args().skip(1))?;
+    let mut config = PMenuConfig::default();
+    if let Some(n_lines) = args.n_lines {
+        config.n_lines = n_lines;
+    }
+
+    dmenu::run(&args, stdin().lock(), stdout(), |prompt, lines| {
+        let mut menu = PMenu::new(config)?;
+        menu.get_selection_from_input(prompt, lines, 0, None)
+    })
+}
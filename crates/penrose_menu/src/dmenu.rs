@@ -0,0 +1,76 @@
+//! A dmenu compatible entry point for [PMenu][crate::PMenu].
+//!
+//! Options are read from stdin (one per line) and the selection made by the user is
+//! written to stdout. The `-p prompt` and `-l lines` flags from dmenu are supported.
+use crate::{Error, PMenuMatch, Result};
+use std::io::{BufRead, Write};
+
+/// Command line arguments for running [PMenu][crate::PMenu] in the style of dmenu.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DmenuArgs {
+    /// The prompt to display to the left of the user input (`-p`)
+    pub prompt: Option<String>,
+    /// The number of lines to display (`-l`)
+    pub n_lines: Option<usize>,
+}
+
+impl DmenuArgs {
+    /// Parse dmenu style command line arguments, not including the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Error::InvalidArgs(format!("missing value for {}", arg)))
+            };
+
+            match arg.as_str() {
+                "-p" => parsed.prompt = Some(value()?),
+                "-l" => {
+                    let raw = value()?;
+                    let n = raw.parse().map_err(|_| {
+                        Error::InvalidArgs(format!("invalid number of lines: {raw}"))
+                    })?;
+                    parsed.n_lines = Some(n);
+                }
+                _ => return Err(Error::InvalidArgs(format!("unknown argument: {}", arg))),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Read newline separated options from `input`, run `select` to obtain a choice from the user
+/// and then write the result to `output`.
+///
+/// `select` is called with the prompt and options to display and will typically be a call to
+/// [PMenu::get_selection_from_input][crate::PMenu::get_selection_from_input]. The returned
+/// boolean indicates whether or not a selection was made: if there were no options to select
+/// from, or the user exited without making a selection, nothing is written to `output`.
+pub fn run<F>(
+    args: &DmenuArgs,
+    input: impl BufRead,
+    mut output: impl Write,
+    select: F,
+) -> Result<bool>
+where
+    F: FnOnce(Option<&str>, Vec<String>) -> Result<PMenuMatch>,
+{
+    let lines = input.lines().collect::<std::io::Result<Vec<String>>>()?;
+
+    if lines.is_empty() {
+        return Ok(false);
+    }
+
+    let selection = match select(args.prompt.as_deref(), lines)? {
+        PMenuMatch::Line(_, s) | PMenuMatch::UserInput(s) => s,
+        PMenuMatch::NoMatch => return Ok(false),
+    };
+
+    writeln!(output, "{}", selection)?;
+
+    Ok(true)
+}
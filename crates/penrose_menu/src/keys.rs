@@ -0,0 +1,175 @@
+//! Converting X key codes into the key presses understood by a [PMenu][crate::PMenu].
+use penrose::{
+    core::bindings::{KeyCode, ModifierKey},
+    x::XConn,
+    Result,
+};
+use std::collections::HashMap;
+
+// The core X protocol mask for the lock (caps lock) modifier
+const LOCK_MASK: u16 = 1 << 1;
+
+// The keysym used for levels of a key that have nothing bound to them
+const NO_SYMBOL: u32 = 0;
+
+// The keysyms for the non-printing keys that a PMenu responds to
+const XK_BACKSPACE: u32 = 0xff08;
+const XK_RETURN: u32 = 0xff0d;
+const XK_ESCAPE: u32 = 0xff1b;
+const XK_UP: u32 = 0xff52;
+const XK_DOWN: u32 = 0xff54;
+const XK_KP_ENTER: u32 = 0xff8d;
+const XK_KP_UP: u32 = 0xff97;
+const XK_KP_DOWN: u32 = 0xff99;
+
+// Keysyms for unicode characters outside of Latin-1 are the code point with this offset
+const UNICODE_OFFSET: u32 = 0x0100_0000;
+
+/// A key press that can be handled by a [PMenu][crate::PMenu]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyPress {
+    /// A printable character (or an ASCII control character if Ctrl was held)
+    Utf8(String),
+    /// Return or keypad Enter
+    Return,
+    /// Escape
+    Escape,
+    /// Backspace
+    Backspace,
+    /// The up arrow key
+    Up,
+    /// The down arrow key
+    Down,
+}
+
+impl KeyPress {
+    fn from_keysym(sym: u32) -> Option<Self> {
+        match sym {
+            XK_RETURN | XK_KP_ENTER => Some(Self::Return),
+            XK_ESCAPE => Some(Self::Escape),
+            XK_BACKSPACE => Some(Self::Backspace),
+            XK_UP | XK_KP_UP => Some(Self::Up),
+            XK_DOWN | XK_KP_DOWN => Some(Self::Down),
+            _ => keysym_char(sym).map(|c| Self::Utf8(c.to_string())),
+        }
+    }
+}
+
+// The printable character for a Latin-1 or unicode keysym
+fn keysym_char(sym: u32) -> Option<char> {
+    let c = match sym {
+        0x20..=0x7e | 0xa0..=0xff => char::from_u32(sym)?,
+        _ if sym >= UNICODE_OFFSET => char::from_u32(sym - UNICODE_OFFSET)?,
+        _ => return None,
+    };
+
+    if c.is_control() {
+        None
+    } else {
+        Some(c)
+    }
+}
+
+/// The keysyms bound to each key code for the current keyboard layout.
+///
+/// Only the first keyboard group is used when converting key codes: the first shift level is
+/// used for unshifted key presses and the second when Shift is held.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    syms: HashMap<u8, Vec<u32>>,
+}
+
+impl Keymap {
+    /// Construct a [Keymap] from the raw keysyms for each key code, as returned by
+    /// [XConn::keyboard_mapping]: keysyms for the first group are followed by those for any
+    /// additional groups.
+    pub fn new(syms: HashMap<u8, Vec<u32>>) -> Self {
+        Self { syms }
+    }
+
+    /// Fetch the current keyboard mapping from the X server.
+    pub fn try_from_conn<X: XConn>(conn: &X) -> Result<Self> {
+        Ok(Self::new(conn.keyboard_mapping()?))
+    }
+
+    /// Convert a [KeyCode] received from the X server into a [KeyPress].
+    ///
+    /// Returns `None` if the key code is unknown or does not correspond to a [KeyPress].
+    pub fn key_press(&self, k: KeyCode) -> Option<KeyPress> {
+        let syms = self.syms.get(&k.code)?;
+        let held = |m: ModifierKey| k.mask & u16::from(m) > 0;
+
+        let sym = if held(ModifierKey::Shift) {
+            syms.get(1)
+                .filter(|&&s| s != NO_SYMBOL)
+                .or_else(|| syms.first())
+        } else {
+            syms.first()
+        };
+
+        match KeyPress::from_keysym(*sym?)? {
+            KeyPress::Utf8(s) => {
+                let mut c = s.chars().next()?;
+                if k.mask & LOCK_MASK > 0 {
+                    c = c.to_uppercase().next().unwrap_or(c);
+                }
+                if held(ModifierKey::Ctrl) {
+                    c = control_char(c)?;
+                }
+
+                Some(KeyPress::Utf8(c.to_string()))
+            }
+
+            other => Some(other),
+        }
+    }
+}
+
+// The ASCII control character produced by holding Ctrl and pressing the given key
+fn control_char(c: char) -> Option<char> {
+    match c.to_ascii_uppercase() {
+        c @ '@'..='_' => Some(char::from(c as u8 & 0x1f)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_test_case::test_case;
+
+    fn keymap() -> Keymap {
+        let mut raw = HashMap::new();
+        raw.insert(24, vec![0x71, 0x51]); // q Q
+        raw.insert(10, vec![0x31, 0x21]); // 1 !
+        raw.insert(36, vec![0xff0d, 0]); // Return
+        raw.insert(22, vec![0xff08]); // BackSpace
+        raw.insert(111, vec![0xff52]); // Up
+        raw.insert(49, vec![0x01000101, 0x01000100]); // amacron Amacron
+
+        Keymap::new(raw)
+    }
+
+    fn code(code: u8, mods: &[ModifierKey]) -> KeyCode {
+        let mask = mods.iter().fold(0, |acc, &m| acc | u16::from(m));
+
+        KeyCode { mask, code }
+    }
+
+    #[test_case(code(24, &[]), Some(KeyPress::Utf8("q".into())); "plain")]
+    #[test_case(code(24, &[ModifierKey::Shift]), Some(KeyPress::Utf8("Q".into())); "shifted")]
+    #[test_case(code(10, &[ModifierKey::Shift]), Some(KeyPress::Utf8("!".into())); "shifted symbol")]
+    #[test_case(KeyCode { mask: LOCK_MASK, code: 24 }, Some(KeyPress::Utf8("Q".into())); "caps lock")]
+    #[test_case(code(24, &[ModifierKey::Ctrl]), Some(KeyPress::Utf8("\x11".into())); "control")]
+    #[test_case(code(10, &[ModifierKey::Ctrl]), None; "control without a control char")]
+    #[test_case(code(36, &[]), Some(KeyPress::Return); "return key")]
+    #[test_case(code(36, &[ModifierKey::Shift]), Some(KeyPress::Return); "shifted single level key")]
+    #[test_case(code(22, &[]), Some(KeyPress::Backspace); "backspace")]
+    #[test_case(code(111, &[]), Some(KeyPress::Up); "up")]
+    #[test_case(code(49, &[ModifierKey::Shift]), Some(KeyPress::Utf8("Ā".into())); "unicode keysym")]
+    #[test_case(code(99, &[]), None; "unknown code")]
+    #[test]
+    fn key_press(k: KeyCode, expected: Option<KeyPress>) {
+        assert_eq!(keymap().key_press(k), expected);
+    }
+}
@@ -1,33 +1,69 @@
-//! A penrose Draw backed implementation of dmenu
+//! A penrose_ui Draw backed implementation of dmenu
 #![warn(
-    broken_intra_doc_links,
-    clippy::all,
-    missing_debug_implementations,
+    clippy::complexity,
+    clippy::correctness,
+    clippy::style,
     future_incompatible,
+    missing_debug_implementations,
     missing_docs,
-    rust_2018_idioms
+    rust_2018_idioms,
+    rustdoc::all
 )]
 
-#[macro_use]
-extern crate log;
-
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use penrose::{
-    common::{bindings::KeyPress, geometry::Region, Xid},
-    draw::{
-        widget::{InputBox, LinesWithSelection, Text},
-        Color, DrawContext, Error, KeyPressDraw, KeyboardControlled, Result, TextStyle, Widget,
-    },
-    xconnection::{Atom, ExposeEvent, KeyPressParseAttempt, Prop, WinType, XEvent},
+    pure::geometry::Rect,
+    x::{event::ExposeEvent, Atom, Prop, WinType, XConn, XEvent},
+    Color, Xid,
 };
+use penrose_ui::Draw;
+use std::collections::HashMap;
+use tracing::debug;
+
+pub mod dmenu;
+pub mod keys;
+
+pub use keys::{KeyPress, Keymap};
+
+const PAD_PX: u32 = 2;
+const MASK_CHAR: char = '•';
+// Used to determine the height of each line regardless of the characters being displayed
+const LINE_HEIGHT_SAMPLE: &str = "Mg|";
+
+/// Error variants from penrose_menu.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The provided [PMenuConfig] was invalid
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+
+    /// The command line arguments passed to pmenu were invalid
+    #[error("invalid arguments: {0}")]
+    InvalidArgs(String),
+
+    /// The requested screen index does not exist
+    #[error("screen index {0} is out of range")]
+    ScreenOutOfRange(usize),
+
+    /// An IO error occurred when reading input or writing output
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// An error was returned when interacting with the X server
+    #[error(transparent)]
+    Penrose(#[from] penrose::Error),
+
+    /// An error was returned when rendering the menu
+    #[error(transparent)]
+    Ui(#[from] penrose_ui::Error),
+}
 
-use std::convert::TryInto;
-
-const PAD_PX: f64 = 2.0;
+/// A Result where the error type is a penrose_menu [Error]
+pub type Result<T> = std::result::Result<T, Error>;
 
 /// The result of attempting to match against user input in a call to
 /// [PMenu::get_selection_from_input]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PMenuMatch {
     /// The selected line along its line number (0 indexed)
     Line(usize, String),
@@ -37,6 +73,37 @@ pub enum PMenuMatch {
     NoMatch,
 }
 
+/// An action that can be bound to a [KeyPress] while a [PMenu] is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MenuAction {
+    /// Move the selection down to the next matching line
+    NextLine,
+    /// Move the selection up to the previous matching line
+    PrevLine,
+    /// Accept the currently selected line (or the user input if nothing matches)
+    Accept,
+    /// Exit without making a selection
+    Cancel,
+    /// Delete the last character of the user input
+    DeleteChar,
+}
+
+// The bindings used for any key press that is not found in the user provided bindings
+fn default_action(k: &KeyPress) -> Option<MenuAction> {
+    match k {
+        KeyPress::Down => Some(MenuAction::NextLine),
+        KeyPress::Up => Some(MenuAction::PrevLine),
+        KeyPress::Return => Some(MenuAction::Accept),
+        KeyPress::Escape => Some(MenuAction::Cancel),
+        KeyPress::Backspace => Some(MenuAction::DeleteChar),
+        _ => None,
+    }
+}
+
+fn action_for(bindings: &HashMap<KeyPress, MenuAction>, k: &KeyPress) -> Option<MenuAction> {
+    bindings.get(k).copied().or_else(|| default_action(k))
+}
+
 /// Config for running a [PMenu] match
 #[derive(Debug, Clone)]
 pub struct PMenuConfig {
@@ -50,7 +117,7 @@ pub struct PMenuConfig {
     pub sort_by_relevance: bool,
     /// Background color for the rendered window
     ///
-    /// Default: #111111
+    /// Default: #1d2021
     pub bg_color: Color,
     /// Foreground color for text
     ///
@@ -67,7 +134,7 @@ pub struct PMenuConfig {
     /// Font point size
     ///
     /// Default: 12
-    pub point_size: i32,
+    pub point_size: u8,
     /// Number of lines to display at a time
     ///
     /// Default: 10
@@ -76,6 +143,29 @@ pub struct PMenuConfig {
     ///
     /// Default: 0.5
     pub min_width_perc: f64,
+    /// Should user input be masked when it is displayed?
+    ///
+    /// When set, typed characters are rendered as bullets and no lines are offered for
+    /// matching: the menu acts as a plain prompt (e.g. for reading a password) and any input
+    /// is returned as [PMenuMatch::UserInput].
+    ///
+    /// Default: false
+    pub password: bool,
+    /// Additional key bindings for navigating the menu.
+    ///
+    /// Any key not found in this map falls back to the default bindings of Up, Down,
+    /// Return, Escape and Backspace. Key presses made while holding Ctrl are received as
+    /// their ASCII control character, so C-n would be bound using `KeyPress::Utf8("\x0e")`.
+    ///
+    /// Default: empty
+    pub key_bindings: HashMap<KeyPress, MenuAction>,
+    /// Should matching always ignore case?
+    ///
+    /// When false, matching is "smart case": case is ignored unless the user input contains
+    /// an uppercase character.
+    ///
+    /// Default: false
+    pub case_insensitive: bool,
 }
 
 impl Default for PMenuConfig {
@@ -83,89 +173,62 @@ impl Default for PMenuConfig {
         Self {
             show_line_numbers: false,
             sort_by_relevance: true,
-            bg_color: "#1d2021".try_into().unwrap(),
-            fg_color: "#ebdbb2".try_into().unwrap(),
-            selected_color: "#504945".try_into().unwrap(),
+            bg_color: 0x1d2021.into(),
+            fg_color: 0xebdbb2.into(),
+            selected_color: 0x504945.into(),
             font: "monospace".into(),
             point_size: 12,
             n_lines: 10,
             min_width_perc: 0.5,
+            password: false,
+            key_bindings: HashMap::new(),
+            case_insensitive: false,
         }
     }
 }
 
 /// Utility struct for obtaining input from the user
 #[derive(Debug)]
-pub struct PMenu<D>
-where
-    D: KeyPressDraw,
-{
-    drw: D,
+pub struct PMenu {
+    drw: Draw,
+    keymap: Keymap,
     id: Option<Xid>,
-    bg: Color,
+    fg: Color,
     ac: Color,
-    prompt: Text,
-    patt: InputBox,
-    txt: LinesWithSelection,
-    w: f64,
-    h: f64,
-    show_line_numbers: bool,
-    sort_by_relevance: bool,
+    prompt: Option<String>,
+    input: Input,
+    w: u32,
+    line_h: u32,
     min_width_perc: f64,
 }
 
-impl<D> PMenu<D>
-where
-    D: KeyPressDraw,
-{
+impl PMenu {
     /// Construct a new [PMenu] with the given config.
-    pub fn new(mut drw: D, config: PMenuConfig) -> Result<Self> {
+    ///
+    /// ### Errors
+    /// This method will error if it is unable to establish a connection with the X server.
+    pub fn new(config: PMenuConfig) -> Result<Self> {
         if !(0.0..=1.0).contains(&config.min_width_perc) {
-            return Err(Error::Raw(format!(
+            return Err(Error::InvalidConfig(format!(
                 "min_width_perc must be in the range 0.0..1.0: {}",
                 config.min_width_perc
             )));
         }
 
-        drw.register_font(&config.font);
-
-        let default_style = TextStyle {
-            font: config.font.clone(),
-            point_size: config.point_size,
-            fg: config.fg_color,
-            bg: Some(config.bg_color),
-            padding: (PAD_PX, PAD_PX),
-        };
-
-        let inverted_style = TextStyle {
-            fg: config.fg_color,
-            bg: Some(config.selected_color),
-            ..default_style.clone()
-        };
+        let drw = Draw::new(&config.font, config.point_size, config.bg_color)?;
+        let keymap = Keymap::try_from_conn(drw.conn())?;
 
         Ok(Self {
             drw,
-            bg: config.bg_color,
-            ac: config.selected_color,
-            txt: LinesWithSelection::new(
-                config.font,
-                config.point_size,
-                PAD_PX,
-                config.bg_color,
-                config.fg_color,
-                config.selected_color,
-                config.fg_color,
-                config.n_lines,
-                false,
-            ),
-            patt: InputBox::new(&default_style, false, true),
-            prompt: Text::new("", &inverted_style, false, true),
-            w: 0.0,
-            h: 0.0,
+            keymap,
             id: None,
-            show_line_numbers: config.show_line_numbers,
-            sort_by_relevance: config.sort_by_relevance,
+            fg: config.fg_color,
+            ac: config.selected_color,
+            prompt: None,
+            w: 0,
+            line_h: 0,
             min_width_perc: config.min_width_perc,
+            input: Input::new(config),
         })
     }
 
@@ -173,31 +236,33 @@ where
         debug!("getting screen size");
         let screen_region = *self
             .drw
-            .screen_sizes()?
+            .conn()
+            .screen_details()?
             .get(screen_index)
-            .ok_or_else(|| Error::Raw("screen_index out of range".into()))?;
+            .ok_or(Error::ScreenOutOfRange(screen_index))?;
 
-        let (_, _, sw, sh) = screen_region.values();
+        let mut txts = vec![LINE_HEIGHT_SAMPLE, self.prompt.as_deref().unwrap_or("")];
+        txts.extend(self.input.txt.lines.iter().map(|s| s.as_str()));
+        let extents = text_extents(&mut self.drw, &txts)?;
 
-        let mut ctx = self.drw.temp_context(sw, sh)?;
-        let (prompt_w, prompt_h) = self.prompt.current_extent(&mut ctx, 1.0)?;
+        let prompt_w = extents[1].0;
+        let lines_w = extents[2..].iter().map(|&(w, _)| w).max().unwrap_or(0);
+        let min_w = (screen_region.w as f64 * self.min_width_perc) as u32;
 
-        let (input_w, input_h) = self.txt.current_extent(&mut ctx, 1.0)?;
-
-        self.w = (prompt_w + input_w + PAD_PX).max((sw as f64) * self.min_width_perc);
-        self.h = prompt_h + input_h + PAD_PX * 4.0;
+        self.line_h = extents.iter().map(|&(_, h)| h).max().unwrap_or(0) + 2 * PAD_PX;
+        self.w = (prompt_w + lines_w + 4 * PAD_PX).max(min_w);
+        let h = self.line_h * (1 + self.input.txt.n_visible() as u32);
 
+        let r = Rect::new(0, 0, self.w, h);
         let id = self.drw.new_window(
             WinType::InputOutput(Atom::NetWindowTypeDialog),
-            Region::new(0, 0, self.w as u32, self.h as u32)
-                .centered_in(&screen_region)
-                .unwrap(),
+            r.centered_in(&screen_region).unwrap_or(r),
             true,
         )?;
 
         let prop = Prop::UTF8String(vec!["penrose-menu".into()]);
         for a in &[Atom::NetWmName, Atom::WmName, Atom::WmClass] {
-            self.drw.change_prop(id, a.as_ref(), prop.clone())?;
+            self.drw.conn().set_prop(id, a.as_ref(), prop.clone())?;
         }
 
         self.drw.flush(id)?;
@@ -206,34 +271,33 @@ where
         Ok(())
     }
 
-    fn redraw(&mut self, with_prompt: bool) -> Result<()> {
-        let id = self.id.unwrap();
+    fn redraw(&mut self) -> Result<()> {
+        let id = self.id.expect("redraw is only called with an open window");
+        let (w, line_h) = (self.w, self.line_h);
         let mut ctx = self.drw.context_for(id)?;
 
         ctx.clear()?;
-        ctx.color(&self.bg);
-        ctx.rectangle(0.0, 0.0, self.w, self.h)?;
-
-        let (w, h) = if with_prompt {
-            let (w, h) = self.prompt.current_extent(&mut ctx, self.h)?;
-            ctx.color(&self.ac);
-            ctx.rectangle(0.0, 0.0, w + PAD_PX, h + PAD_PX)?;
-            (w, h)
-        } else {
-            let (_, h) = self.patt.current_extent(&mut ctx, self.h)?;
-            (0.0, h)
-        };
 
-        ctx.translate(PAD_PX, PAD_PX);
+        let mut prompt_w = 0;
+        if let Some(prompt) = &self.prompt {
+            prompt_w = ctx.text_extent(prompt)?.0 + 2 * PAD_PX;
+            ctx.fill_rect(Rect::new(0, 0, prompt_w, line_h), self.ac)?;
+            ctx.draw_text(prompt, PAD_PX, (PAD_PX, PAD_PX), self.fg)?;
+        }
+
+        let patt = self.input.displayed_patt();
+        ctx.set_x_offset(prompt_w as i32);
+        ctx.draw_text(&patt, PAD_PX, (PAD_PX, PAD_PX), self.fg)?;
 
-        if with_prompt {
-            self.prompt.draw(&mut ctx, 0, false, w, h)?;
-            ctx.translate(w, 0.0);
+        for (i, (ix, line)) in self.input.txt.visible().enumerate() {
+            ctx.set_offset(0, (line_h * (i as u32 + 1)) as i32);
+            if ix == self.input.txt.selected_index() {
+                ctx.fill_rect(Rect::new(0, 0, w, line_h), self.ac)?;
+            }
+            ctx.draw_text(line, PAD_PX, (PAD_PX, PAD_PX), self.fg)?;
         }
 
-        self.patt.draw(&mut ctx, 0, false, self.w - w, h)?;
-        ctx.translate(0.0, h);
-        self.txt.draw(&mut ctx, 0, true, self.w - w, h)?;
+        ctx.reset_offset();
         self.drw.flush(id)?;
 
         Ok(())
@@ -243,22 +307,25 @@ where
     ///
     /// Defaults to 10
     pub fn set_n_lines(&mut self, n_lines: usize) {
-        self.txt.set_n_lines(n_lines);
+        self.input.txt.n_lines = n_lines;
     }
 
-    /// Spawn a temporary window using the embedded [KeyPressDraw] impl and fetch input from the user.
+    /// Spawn a temporary window and fetch input from the user.
+    ///
+    /// If `preselect` is provided then the line with that index is initially selected (and
+    /// scrolled into view) rather than the first line. Indices past the end of `input` select
+    /// the last line.
     ///
     /// ## NOTE
     /// This method will block the current thread while it runs.
     ///
     /// # Example
-    /// ```
-    /// # use penrose::draw::{Result, KeyPressDraw};
-    /// # use penrose_menu::{PMenu, PMenuMatch};
-    /// # fn example<T: KeyPressDraw>(mut pmenu: PMenu<T>) -> Result<()> {
+    /// ```no_run
+    /// # use penrose_menu::{PMenu, PMenuMatch, Result};
+    /// # fn example(mut pmenu: PMenu) -> Result<()> {
     /// let lines = vec!["foo", "bar", "baz"];
     ///
-    /// match pmenu.get_selection_from_input(Some(">>> "), lines, 0)? {
+    /// match pmenu.get_selection_from_input(Some(">>> "), lines, 0, None)? {
     ///     PMenuMatch::Line(i, s) => println!("matched {} on line {}", s, i),
     ///     PMenuMatch::UserInput(s) => println!("user input: {}", s),
     ///     PMenuMatch::NoMatch => println!("no match"),
@@ -271,31 +338,92 @@ where
         prompt: Option<impl Into<String>>,
         input: Vec<impl Into<String>>,
         screen_index: usize,
+        preselect: Option<usize>,
     ) -> Result<PMenuMatch> {
-        let input: Vec<String> = input.into_iter().map(|s| s.into()).collect();
-
-        let with_prompt = if let Some(p) = prompt {
-            self.prompt.set_text(p);
-            true
-        } else {
-            false
-        };
-
-        self.txt.set_input(input.clone())?;
+        self.prompt = prompt.map(|p| p.into());
+        self.input
+            .set_lines(input.into_iter().map(|s| s.into()).collect(), preselect);
         self.init_window(screen_index)?;
 
-        self.drw.grab_keyboard()?;
-        let selection = self.get_selection_inner(input, with_prompt);
-        self.drw.ungrab_keyboard()?;
+        self.drw.conn().grab_keyboard()?;
+        let selection = self.get_selection_inner();
+        self.drw.conn().ungrab_keyboard()?;
 
-        self.drw.destroy_client(self.id.unwrap())?;
-        self.id = None;
+        self.drw
+            .destroy_window_and_surface(self.id.take().expect("window to be open"))?;
+        self.drw.conn().flush();
 
         selection
     }
 
-    fn get_selection_inner(&mut self, input: Vec<String>, with_prompt: bool) -> Result<PMenuMatch> {
-        let display_lines = if self.show_line_numbers {
+    fn get_selection_inner(&mut self) -> Result<PMenuMatch> {
+        self.redraw()?;
+
+        loop {
+            debug!("waiting for keypress");
+            let k = match self.drw.conn().next_event()? {
+                XEvent::Expose(ExposeEvent { id, count, .. }) => {
+                    debug!("got expose event");
+                    if Some(id) == self.id && count == 0 {
+                        self.redraw()?;
+                    }
+                    continue;
+                }
+
+                XEvent::KeyPress(code) => match self.keymap.key_press(code) {
+                    Some(k) => k,
+                    None => continue,
+                },
+
+                _ => continue,
+            };
+
+            debug!(?k, "got keypress event");
+            if let Some(selection) = self.input.handle_key(k) {
+                return Ok(selection);
+            }
+
+            self.redraw()?;
+        }
+    }
+}
+
+// The user input for a running selection along with the lines that currently match it. Key
+// presses are handled here rather than in the X event loop of PMenu::get_selection_inner.
+#[derive(Debug, Clone)]
+struct Input {
+    patt: String,
+    lines: Vec<String>,
+    matches: Vec<usize>,
+    txt: Lines,
+    show_line_numbers: bool,
+    sort_by_relevance: bool,
+    password: bool,
+    key_bindings: HashMap<KeyPress, MenuAction>,
+    case_insensitive: bool,
+}
+
+impl Input {
+    fn new(config: PMenuConfig) -> Self {
+        Self {
+            patt: String::new(),
+            lines: Vec::new(),
+            matches: Vec::new(),
+            txt: Lines::new(config.n_lines),
+            show_line_numbers: config.show_line_numbers,
+            sort_by_relevance: config.sort_by_relevance,
+            password: config.password,
+            key_bindings: config.key_bindings,
+            case_insensitive: config.case_insensitive,
+        }
+    }
+
+    // Clear any previous input and set the lines available for selection. No lines are
+    // offered when running in password mode.
+    fn set_lines(&mut self, input: Vec<String>, preselect: Option<usize>) {
+        let lines: Vec<String> = if self.password {
+            vec![]
+        } else if self.show_line_numbers {
             input
                 .iter()
                 .enumerate()
@@ -305,84 +433,335 @@ where
             input
         };
 
-        self.txt.set_input(display_lines.clone())?;
-        self.drw.map_client(self.id.unwrap())?;
-        self.redraw(with_prompt)?;
+        self.patt.clear();
+        self.matches = (0..lines.len()).collect();
+        self.txt.set_lines(lines.clone());
+        if let Some(ix) = preselect {
+            self.txt.select(ix);
+        }
+        self.lines = lines;
+    }
 
-        let mut matches: Vec<(usize, &String)> = display_lines.iter().enumerate().collect();
-        let matcher = SkimMatcherV2::default();
+    // The user input as it is shown in the menu
+    fn displayed_patt(&self) -> String {
+        if self.password {
+            masked(&self.patt)
+        } else {
+            self.patt.clone()
+        }
+    }
 
-        loop {
-            debug!("waiting for keypress");
-            match self.drw.next_keypress_blocking()? {
-                KeyPressParseAttempt::XEvent(XEvent::Expose(ExposeEvent { id, count, .. })) => {
-                    debug!("got expose event");
-                    if Some(id) == self.id && count == 0 {
-                        self.redraw(with_prompt)?;
-                    }
-                }
+    // Update the selection for a key press, returning the result once the selection is complete
+    fn handle_key(&mut self, k: KeyPress) -> Option<PMenuMatch> {
+        match action_for(&self.key_bindings, &k) {
+            Some(MenuAction::Accept) => return Some(self.accept()),
+            Some(MenuAction::Cancel) => return Some(PMenuMatch::NoMatch),
+            Some(MenuAction::NextLine) => self.txt.select_next(),
+            Some(MenuAction::PrevLine) => self.txt.select_prev(),
+
+            Some(MenuAction::DeleteChar) => {
+                self.patt.pop();
+                self.update_matches();
+            }
 
-                KeyPressParseAttempt::KeyPress(k) => {
-                    debug!("got keypress event");
-                    match k {
-                        KeyPress::Return if self.txt.selected_index() < matches.len() => {
-                            let ix = self.txt.selected_index();
-                            let (_, raw) = matches[ix];
-                            let s = if self.show_line_numbers {
-                                raw.split_at(4).1.to_string()
-                            } else {
-                                raw.to_string()
-                            };
-                            return Ok(PMenuMatch::Line(ix, s));
-                        }
-
-                        KeyPress::Return => {
-                            let patt = self.patt.get_text();
-                            return if patt.is_empty() {
-                                Ok(PMenuMatch::NoMatch)
-                            } else {
-                                Ok(PMenuMatch::UserInput(patt.clone()))
-                            };
-                        }
-
-                        KeyPress::Escape => {
-                            return Ok(PMenuMatch::NoMatch);
-                        }
-
-                        KeyPress::Backspace | KeyPress::Utf8(_) => {
-                            self.patt.handle_keypress(k)?;
-
-                            let mut scored = display_lines
-                                .iter()
-                                .enumerate()
-                                .flat_map(|(i, line)| {
-                                    matcher
-                                        .fuzzy_match(line, self.patt.get_text())
-                                        .map(|score| (score, (i, line)))
-                                })
-                                .collect::<Vec<_>>();
-
-                            if self.sort_by_relevance {
-                                scored.sort_by_key(|(score, _)| -*score);
-                            }
-
-                            matches = scored.into_iter().map(|(_, data)| data).collect();
-                            let lines = matches.iter().map(|(_, line)| line.to_string()).collect();
-                            self.txt.set_input(lines)?;
-                        }
-
-                        KeyPress::Up | KeyPress::Down => {
-                            self.txt.handle_keypress(k)?;
-                        }
-
-                        _ => continue,
-                    };
-
-                    self.redraw(with_prompt)?;
+            // Unbound control characters (such as C-a) are ignored rather than being
+            // added to the input
+            None => match k {
+                KeyPress::Utf8(s) if !s.chars().any(char::is_control) => {
+                    self.patt.push_str(&s);
+                    self.update_matches();
                 }
 
                 _ => (),
+            },
+        };
+
+        None
+    }
+
+    fn accept(&self) -> PMenuMatch {
+        match self.matches.get(self.txt.selected_index()) {
+            Some(&i) => {
+                let raw = &self.lines[i];
+                let s = if self.show_line_numbers {
+                    raw.split_at(4).1
+                } else {
+                    raw
+                };
+
+                PMenuMatch::Line(i, s.to_string())
             }
+
+            None => user_input(&self.patt),
+        }
+    }
+
+    fn update_matches(&mut self) {
+        if self.password {
+            return;
+        }
+
+        let matcher = matcher(self.case_insensitive);
+        let mut scored = self
+            .lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                matcher
+                    .fuzzy_match(line, &self.patt)
+                    .map(|score| (score, i))
+            })
+            .collect::<Vec<_>>();
+
+        if self.sort_by_relevance {
+            scored.sort_by_key(|(score, _)| -*score);
+        }
+
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.txt.set_lines(
+            self.matches
+                .iter()
+                .map(|&i| self.lines[i].clone())
+                .collect(),
+        );
+    }
+}
+
+// The lines available for selection along with the current selection and which lines are
+// currently scrolled into view.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Lines {
+    lines: Vec<String>,
+    n_lines: usize,
+    selected: usize,
+    first: usize,
+}
+
+impl Lines {
+    fn new(n_lines: usize) -> Self {
+        Self {
+            n_lines,
+            ..Default::default()
+        }
+    }
+
+    fn set_lines(&mut self, lines: Vec<String>) {
+        self.lines = lines;
+        self.selected = 0;
+        self.first = 0;
+    }
+
+    fn n_visible(&self) -> usize {
+        self.lines.len().min(self.n_lines)
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    fn visible(&self) -> impl Iterator<Item = (usize, &String)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(self.first)
+            .take(self.n_lines)
+    }
+
+    fn select_next(&mut self) {
+        if self.selected + 1 < self.lines.len() {
+            self.selected += 1;
+        }
+        if self.selected >= self.first + self.n_lines.max(1) {
+            self.first = self.selected + 1 - self.n_lines.max(1);
+        }
+    }
+
+    fn select_prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+        self.first = self.first.min(self.selected);
+    }
+
+    // Move the selection down to the requested line, clamped to the last line. The selection
+    // is moved rather than set directly so that the displayed lines scroll to keep it visible.
+    fn select(&mut self, ix: usize) {
+        for _ in 0..ix.min(self.lines.len().saturating_sub(1)) {
+            self.select_next();
+        }
+    }
+}
+
+// Measure text using a temporary surface as we need to know the size of the menu window before
+// we are able to create it.
+fn text_extents(drw: &mut Draw, txts: &[&str]) -> Result<Vec<(u32, u32)>> {
+    let id = drw.new_window(WinType::InputOnly, Rect::new(0, 0, 1, 1), false)?;
+
+    let extents = {
+        let mut ctx = drw.context_for(id)?;
+        txts.iter()
+            .map(|txt| ctx.text_extent(txt))
+            .collect::<penrose_ui::Result<Vec<_>>>()
+    };
+    drw.destroy_window_and_surface(id)?;
+
+    Ok(extents?)
+}
+
+fn matcher(case_insensitive: bool) -> SkimMatcherV2 {
+    if case_insensitive {
+        SkimMatcherV2::default().ignore_case()
+    } else {
+        SkimMatcherV2::default().smart_case()
+    }
+}
+
+// The text displayed in place of user input when running in password mode
+fn masked(s: &str) -> String {
+    s.chars().map(|_| MASK_CHAR).collect()
+}
+
+fn user_input(patt: &str) -> PMenuMatch {
+    if patt.is_empty() {
+        PMenuMatch::NoMatch
+    } else {
+        PMenuMatch::UserInput(patt.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(n_lines: usize, n: usize) -> Lines {
+        let mut txt = Lines::new(n_lines);
+        txt.set_lines((0..n).map(|i| format!("line {}", i)).collect());
+
+        txt
+    }
+
+    #[test]
+    fn preselected_lines_start_selected() {
+        let mut txt = lines(5, 20);
+        txt.select(7);
+
+        assert_eq!(txt.selected_index(), 7);
+        let visible: Vec<usize> = txt.visible().map(|(i, _)| i).collect();
+        assert_eq!(visible, vec![3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn out_of_range_preselection_selects_the_last_line() {
+        let mut txt = lines(5, 20);
+        txt.select(50);
+
+        assert_eq!(txt.selected_index(), 19);
+    }
+
+    #[test]
+    fn moving_up_scrolls_back_to_the_selection() {
+        let mut txt = lines(5, 20);
+        txt.select(7);
+        for _ in 0..6 {
+            txt.select_prev();
         }
+
+        assert_eq!(txt.selected_index(), 1);
+        let visible: Vec<usize> = txt.visible().map(|(i, _)| i).collect();
+        assert_eq!(visible, vec![1, 2, 3, 4, 5]);
+    }
+
+    fn ctrl(c: char) -> KeyPress {
+        KeyPress::Utf8(char::from(c as u8 & 0x1f).to_string())
+    }
+
+    #[test]
+    fn custom_key_bindings_drive_the_selection() {
+        let mut bindings = HashMap::new();
+        bindings.insert(ctrl('n'), MenuAction::NextLine);
+        bindings.insert(ctrl('p'), MenuAction::PrevLine);
+        bindings.insert(ctrl('j'), MenuAction::Accept);
+
+        let mut txt = lines(10, 3);
+
+        let keys = vec![ctrl('n'), ctrl('n'), ctrl('p'), KeyPress::Down];
+        for k in keys {
+            match action_for(&bindings, &k) {
+                Some(MenuAction::NextLine) => txt.select_next(),
+                Some(MenuAction::PrevLine) => txt.select_prev(),
+                other => panic!("unexpected action for {:?}: {:?}", k, other),
+            };
+        }
+
+        assert_eq!(txt.selected_index(), 2);
+        assert_eq!(action_for(&bindings, &ctrl('j')), Some(MenuAction::Accept));
+        assert_eq!(
+            action_for(&bindings, &KeyPress::Escape),
+            Some(MenuAction::Cancel)
+        );
+        assert_eq!(action_for(&bindings, &KeyPress::Utf8("n".into())), None);
+    }
+
+    // Feed key presses to the input in the same way as PMenu::get_selection_inner
+    fn run(input: &mut Input, keys: Vec<KeyPress>) -> Option<PMenuMatch> {
+        keys.into_iter().find_map(|k| input.handle_key(k))
+    }
+
+    fn utf8(s: &str) -> KeyPress {
+        KeyPress::Utf8(s.into())
+    }
+
+    #[test]
+    fn password_input_is_masked_but_returned_in_full() {
+        let mut input = Input::new(PMenuConfig {
+            password: true,
+            ..Default::default()
+        });
+        input.set_lines(vec!["hunter2".into(), "other".into()], Some(1));
+        assert!(input.txt.lines.is_empty(), "no lines are offered");
+
+        let keys = "hunter23".chars().map(|c| utf8(&c.to_string())).collect();
+        assert_eq!(run(&mut input, keys), None);
+        assert_eq!(input.displayed_patt(), "••••••••");
+
+        let selection = run(&mut input, vec![KeyPress::Backspace, KeyPress::Return]);
+        assert_eq!(selection, Some(PMenuMatch::UserInput("hunter2".into())));
+        assert!(
+            input.txt.lines.is_empty(),
+            "input is not matched against lines"
+        );
+    }
+
+    #[test]
+    fn unbound_control_characters_are_ignored() {
+        let mut key_bindings = HashMap::new();
+        key_bindings.insert(ctrl('n'), MenuAction::NextLine);
+        let mut input = Input::new(PMenuConfig {
+            key_bindings,
+            ..Default::default()
+        });
+        input.set_lines(vec!["alpha".into(), "bar".into(), "baz".into()], None);
+
+        let keys = vec![ctrl('a'), utf8("b"), ctrl('e'), utf8("a"), ctrl('n')];
+        assert_eq!(run(&mut input, keys), None);
+        assert_eq!(input.patt, "ba");
+        assert_eq!(input.txt.lines, vec!["bar", "baz"]);
+
+        let selection = run(&mut input, vec![ctrl('u'), KeyPress::Return]);
+        assert_eq!(selection, Some(PMenuMatch::Line(2, "baz".into())));
+    }
+
+    #[test]
+    fn lowercase_input_matches_mixed_case_lines() {
+        for case_insensitive in [false, true] {
+            let m = matcher(case_insensitive);
+            assert!(m.fuzzy_match("Firefox Browser", "fire").is_some());
+            assert!(m.fuzzy_match("Firefox Browser", "browser").is_some());
+        }
+    }
+
+    #[test]
+    fn uppercase_input_is_case_sensitive_unless_ignoring_case() {
+        assert!(matcher(false).fuzzy_match("firefox", "Fire").is_none());
+        assert!(matcher(false).fuzzy_match("Firefox", "Fire").is_some());
+        assert!(matcher(true).fuzzy_match("firefox", "Fire").is_some());
     }
 }
@@ -0,0 +1,70 @@
+use penrose_menu::{
+    dmenu::{run, DmenuArgs},
+    PMenuMatch,
+};
+
+fn args(raw: &[&str]) -> Vec<String> {
+    raw.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn dmenu_flags_are_parsed() {
+    let parsed = DmenuArgs::parse(args(&["-p", "run:", "-l", "5"])).unwrap();
+
+    assert_eq!(parsed.prompt.as_deref(), Some("run:"));
+    assert_eq!(parsed.n_lines, Some(5));
+}
+
+#[test]
+fn invalid_dmenu_flags_are_an_error() {
+    assert!(DmenuArgs::parse(args(&["-l", "many"])).is_err());
+    assert!(DmenuArgs::parse(args(&["-p"])).is_err());
+    assert!(DmenuArgs::parse(args(&["-x"])).is_err());
+}
+
+#[test]
+fn the_selected_line_is_written_to_stdout() {
+    let dmenu_args = DmenuArgs::parse(args(&["-p", ">>>"])).unwrap();
+    let mut out = Vec::new();
+
+    let selected = run(
+        &dmenu_args,
+        &b"foo\nbar\nbaz\n"[..],
+        &mut out,
+        |prompt, lines| {
+            assert_eq!(prompt, Some(">>>"));
+            assert_eq!(lines, vec!["foo", "bar", "baz"]);
+            Ok(PMenuMatch::Line(1, lines[1].clone()))
+        },
+    )
+    .unwrap();
+
+    assert!(selected);
+    assert_eq!(String::from_utf8(out).unwrap(), "bar\n");
+}
+
+#[test]
+fn empty_stdin_writes_nothing() {
+    let mut out = Vec::new();
+
+    let selected = run(&DmenuArgs::default(), &b""[..], &mut out, |_, _| {
+        panic!("menu should not be shown without any options")
+    })
+    .unwrap();
+
+    assert!(!selected);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn no_match_writes_nothing() {
+    let mut out = Vec::new();
+
+    let selected = run(&DmenuArgs::default(), &b"foo\n"[..], &mut out, |_, _| {
+        Ok(PMenuMatch::NoMatch)
+    })
+    .unwrap();
+
+    assert!(!selected);
+    assert!(out.is_empty());
+}
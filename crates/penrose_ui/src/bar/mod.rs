@@ -1,9 +1,14 @@
 //! A lightweight and configurable status bar for penrose
 use crate::{core::Draw, Result};
 use penrose::{
-    core::{State, WindowManager},
+    builtin::layout::transformers::{ReserveBottom, ReserveTop},
+    core::{
+        bindings::{MouseEvent, MouseEventKind},
+        layout::Layout,
+        State, WindowManager,
+    },
     pure::geometry::Rect,
-    x::{event::XEvent, Atom, ClientConfig, Prop, WinType, XConn},
+    x::{event::XEvent, Atom, ClientAttr, ClientConfig, Prop, WinType, XConn, XConnExt},
     Color, Xid,
 };
 use std::fmt;
@@ -24,6 +29,89 @@ pub enum Position {
     Bottom,
 }
 
+impl Position {
+    /// Wrap a [Layout] so that `px` pixels are reserved for a status bar at this position.
+    ///
+    /// Uses either the [ReserveTop] or [ReserveBottom] layout transformer.
+    pub fn reserve(&self, layout: Box<dyn Layout>, px: u32) -> Box<dyn Layout> {
+        match self {
+            Self::Top => ReserveTop::wrap(layout, px),
+            Self::Bottom => ReserveBottom::wrap(layout, px),
+        }
+    }
+}
+
+/// The DPI that the configured height and point size of a [StatusBar] are assumed to be for
+/// when using [Scaling::Dpi].
+pub const BASE_DPI: f64 = 96.0;
+
+/// How the height and font point size of a [StatusBar] should be scaled on each screen.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Scaling {
+    /// Use the configured height and point size on every screen
+    #[default]
+    None,
+    /// Scale relative to [BASE_DPI] using the physical size of each output reported by RandR.
+    ///
+    /// Outputs that do not report a physical size are not scaled.
+    Dpi,
+    /// Use the given scale factor for each screen.
+    ///
+    /// If more screens are attached than scale factors provided, the last factor will be used
+    /// as a fallback.
+    Manual(Vec<f64>),
+}
+
+impl Scaling {
+    // The scale factor to use for each of the given screens along with their physical size
+    fn factors(&self, screens: &[Rect], mm: &[Option<(u32, u32)>]) -> Vec<f64> {
+        match self {
+            Self::None => vec![1.0; screens.len()],
+            Self::Dpi => screens
+                .iter()
+                .enumerate()
+                .map(|(i, &r)| match mm.get(i).copied().flatten() {
+                    Some(mm) => dpi(r, mm) / BASE_DPI,
+                    None => 1.0,
+                })
+                .collect(),
+            Self::Manual(fs) => (0..screens.len())
+                .map(|i| fs.get(i).or(fs.last()).copied().unwrap_or(1.0))
+                .collect(),
+        }
+    }
+}
+
+/// The DPI of a screen with the given pixel dimensions and physical size in millimetres.
+///
+/// The larger of the horizontal and vertical DPI is used.
+pub fn dpi(r: Rect, (mm_w, mm_h): (u32, u32)) -> f64 {
+    let per_inch = |px: u32, mm: u32| {
+        if mm == 0 {
+            0.0
+        } else {
+            px as f64 * 25.4 / mm as f64
+        }
+    };
+
+    per_inch(r.w, mm_w).max(per_inch(r.h, mm_h))
+}
+
+/// Scale a font point size so that text rendered at `dpi` matches the size of the given
+/// point size at [BASE_DPI].
+pub fn scaled_point_size(point_size: u8, dpi: f64) -> u8 {
+    scale_point_size(point_size, dpi / BASE_DPI)
+}
+
+fn scale_point_size(point_size: u8, factor: f64) -> u8 {
+    scale(point_size as u32, factor).min(u8::MAX as u32) as u8
+}
+
+// Scale a size in pixels (or points) by the given factor, never returning zero
+fn scale(n: u32, factor: f64) -> u32 {
+    ((n as f64 * factor).round() as u32).max(1)
+}
+
 /// A group of [Widget]s and associated point size to use for rendering a [StatusBar] on a single
 /// screen.
 pub struct PerScreen<X: XConn> {
@@ -46,6 +134,15 @@ impl<X: XConn> PerScreen<X> {
     pub fn new(point_size: u8, h: u32, ws: Vec<Box<dyn Widget<X>>>) -> Self {
         Self { point_size, ws, h }
     }
+
+    // The font used to measure and draw each widget: either the widget's own font or `default`
+    // if it does not specify one.
+    fn draw_fonts<'a>(&'a self, default: &'a str) -> Vec<&'a str> {
+        self.ws
+            .iter()
+            .map(|w| w.font().unwrap_or(default))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -88,6 +185,26 @@ impl<X: XConn> Widgets<X> {
         }
     }
 
+    // The distinct font and point size combinations required to render all widgets, falling
+    // back to `default` for any widgets that do not specify their own font.
+    fn fonts<'a>(&'a self, default: &'a str) -> Vec<(&'a str, u8)> {
+        let pss = match self {
+            Self::Shared(ps) => std::slice::from_ref(ps),
+            Self::PerScreen(pss) => pss.as_slice(),
+        };
+
+        let mut fonts = Vec::new();
+        for ps in pss {
+            for f in ps.draw_fonts(default) {
+                if !fonts.contains(&(f, ps.point_size)) {
+                    fonts.push((f, ps.point_size));
+                }
+            }
+        }
+
+        fonts
+    }
+
     fn update_schedules(&mut self) -> Vec<UpdateSchedule> {
         match self {
             Self::Shared(ps) => ps
@@ -104,14 +221,21 @@ impl<X: XConn> Widgets<X> {
 }
 
 /// A simple text based status bar that renders a user defined array of [`Widget`]s.
+///
+/// A separate bar window is created for each connected screen, positioned using that screen's
+/// geometry. Bar windows are recreated whenever screens are added or removed.
 #[derive(Debug)]
 pub struct StatusBar<X: XConn> {
     draw: Draw,
     position: Position,
     widgets: Widgets<X>,
     screens: Vec<(Xid, u32)>,
+    positions: Vec<Vec<(u32, u32)>>, // rendered widget (x, w) per screen
     active_screen: usize,
     font: String,
+    scaling: Scaling,
+    scale_factors: Vec<f64>,
+    heights: Vec<u32>,
 }
 
 impl<X: XConn> StatusBar<X> {
@@ -126,15 +250,23 @@ impl<X: XConn> StatusBar<X> {
         widgets: Vec<Box<dyn Widget<X>>>,
     ) -> Result<Self> {
         let bg = bg.into();
-        let draw = Draw::new(font, point_size, bg)?;
+        let mut draw = Draw::new(font, point_size, bg)?;
+        let widgets = Widgets::Shared(PerScreen::new(point_size, h, widgets));
+        for (f, point_size) in widgets.fonts(font) {
+            draw.add_font(f, point_size)?;
+        }
 
         Ok(Self {
             draw,
             position,
-            widgets: Widgets::Shared(PerScreen::new(point_size, h, widgets)),
+            widgets,
             screens: vec![],
+            positions: vec![],
             active_screen: 0,
             font: font.to_string(),
+            scaling: Scaling::None,
+            scale_factors: vec![],
+            heights: vec![],
         })
     }
 
@@ -151,20 +283,46 @@ impl<X: XConn> StatusBar<X> {
         let bg = bg.into();
         let point_size = widgets[0].point_size;
         let mut draw = Draw::new(font, point_size, bg)?;
-        for &PerScreen { point_size, .. } in widgets.iter() {
-            draw.add_font(font, point_size)?;
+        let widgets = Widgets::PerScreen(widgets);
+        for (f, point_size) in widgets.fonts(font) {
+            draw.add_font(f, point_size)?;
         }
 
         Ok(Self {
             draw,
             position,
-            widgets: Widgets::PerScreen(widgets),
+            widgets,
             screens: vec![],
+            positions: vec![],
             active_screen: 0,
             font: font.to_string(),
+            scaling: Scaling::None,
+            scale_factors: vec![],
+            heights: vec![],
         })
     }
 
+    /// Set how the height and font point size of this bar should be scaled on each screen.
+    ///
+    /// By default no scaling is applied. Use [Scaling::Dpi] to scale based on the physical size
+    /// of each screen or [Scaling::Manual] to provide your own scale factor for each screen.
+    ///
+    /// As the height of the bar may differ between screens when scaling is applied, each bar
+    /// window sets `_NET_WM_STRUT_PARTIAL` so that the window manager reserves space matching
+    /// its scaled height: your layouts should not also be wrapped using [Position::reserve].
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+
+        self
+    }
+
+    /// The height in pixels of the bar on each screen once any [Scaling] has been applied.
+    ///
+    /// This is empty until the bar windows have been created when the window manager starts.
+    pub fn heights(&self) -> &[u32] {
+        &self.heights
+    }
+
     /// Add this [`StatusBar`] into the given [`WindowManager`] along with the required
     /// hooks for driving it from the main WindowManager event loop.
     ///
@@ -191,21 +349,26 @@ impl<X: XConn> StatusBar<X> {
     fn init_for_screens(&mut self) -> Result<()> {
         info!("initialising per screen status bar windows");
         let screen_details = self.draw.conn.screen_details()?;
-
-        self.screens = screen_details
-            .iter()
-            .enumerate()
-            .map(|(i, &Rect { x, y, w, h })| {
-                let bar_h = self.widgets.for_screen_mut(i).h;
-                let y = match self.position {
-                    Position::Top => y,
-                    Position::Bottom => h - bar_h,
-                };
-
-                debug!("creating new window");
+        let mm = match self.scaling {
+            Scaling::Dpi => self.draw.conn.output_physical_sizes()?,
+            _ => vec![],
+        };
+        self.scale_factors = self.scaling.factors(&screen_details, &mm);
+        debug!(factors = ?self.scale_factors, "computed per screen scale factors");
+
+        let rects = bar_rects(&screen_details, self.position, |i| {
+            scale(self.widgets.for_screen_mut(i).h, self.scale_factors[i])
+        });
+        self.heights = rects.iter().map(|r| r.h).collect();
+        let r_root = bounding_rect(&screen_details);
+
+        self.screens = rects
+            .into_iter()
+            .map(|r| {
+                debug!(?r, "creating new window");
                 let id = self.draw.new_window(
                     WinType::InputOutput(Atom::NetWindowTypeDock),
-                    Rect::new(x, y, w, bar_h),
+                    r,
                     false,
                 )?;
 
@@ -218,55 +381,90 @@ impl<X: XConn> StatusBar<X> {
                     self.draw.conn.set_prop(id, atom.as_ref(), p.clone())?;
                 }
 
+                if self.scaling != Scaling::None {
+                    let strut = Prop::Cardinal(bar_strut(r, r_root, self.position));
+                    let atom = Atom::NetWmStrutPartial.as_ref();
+                    self.draw.conn.set_prop(id, atom, strut)?;
+                }
+
                 debug!("flushing");
                 self.draw.flush(id)?;
 
-                Ok((id, w))
+                Ok((id, r.w))
             })
             .collect::<Result<Vec<(Xid, u32)>>>()?;
 
+        self.positions = vec![Vec::new(); self.screens.len()];
+
+        Ok(())
+    }
+
+    // The status bar windows are owned by our own connection so we need to request that button
+    // press events are sent to the window manager's connection in order to handle clicks.
+    fn select_click_events(&self, x: &X) -> Result<()> {
+        for &(id, _) in self.screens.iter() {
+            x.set_client_attributes(id, &[ClientAttr::DockEventMask])?;
+        }
+
         Ok(())
     }
 
+    // Returns true if the event was a click on one of our windows
+    fn handle_click(&mut self, e: &MouseEvent, state: &mut State<X>, x: &X) -> bool {
+        if e.kind != MouseEventKind::Press {
+            return false;
+        }
+
+        let screen = match self.screens.iter().position(|&(id, _)| id == e.data.id) {
+            Some(screen) => screen,
+            None => return false,
+        };
+
+        if let Some((ix, offset)) = widget_at(&self.positions[screen], e.data.wpt.x) {
+            let w = &mut self.widgets.for_screen_mut(screen).ws[ix];
+            if let Err(e) = w.on_click(e.state.button, offset, state, x) {
+                error!(%e, "error running widget click hook");
+            }
+        }
+
+        true
+    }
+
     /// Re-render all widgets in this status bar for a single screen.
     /// Will panic if `i` is out of bounds
     fn redraw_screen(&mut self, i: usize) -> Result<()> {
         let (id, w_screen) = self.screens[i];
         let screen_has_focus = self.active_screen == i;
+        let factor = self.scale_factors[i];
         let ps = self.widgets.for_screen_mut(i);
+        let point_size = scale_point_size(ps.point_size, factor);
+        let h = scale(ps.h, factor);
 
-        self.draw.set_font(&self.font, ps.point_size)?;
+        self.draw.set_font(&self.font, point_size)?;
         let mut ctx = self.draw.context_for(id)?;
         ctx.clear()?;
 
-        let mut extents = Vec::new();
-        let mut greedy_indices = Vec::new();
-
-        for (j, w) in ps.ws.iter_mut().enumerate() {
-            extents.push(w.current_extent(&mut ctx, ps.h)?);
-            if w.is_greedy() {
-                greedy_indices.push(j)
-            }
+        let fonts: Vec<String> = ps
+            .draw_fonts(&self.font)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let mut extents = Vec::with_capacity(ps.ws.len());
+        for (w, font) in ps.ws.iter_mut().zip(fonts.iter()) {
+            ctx.set_font(font, point_size)?;
+            extents.push((w.current_extent(&mut ctx, h)?.0, w.is_greedy()));
         }
 
-        let total = extents.iter().map(|(w, _)| w).sum::<u32>();
-        let n_greedy = greedy_indices.len();
-
-        if total < w_screen && n_greedy > 0 {
-            let per_greedy = (w_screen - total) / n_greedy as u32;
-            for i in greedy_indices.iter() {
-                let (w, h) = extents[*i];
-                extents[*i] = (w + per_greedy, h);
-            }
-        }
+        let positions = widget_positions(&extents, w_screen);
 
-        let mut x = 0;
-        for (wd, (w, _)) in ps.ws.iter_mut().zip(extents) {
-            wd.draw(&mut ctx, self.active_screen, screen_has_focus, w, ps.h)?;
-            x += w;
+        for ((wd, &(x, w)), font) in ps.ws.iter_mut().zip(positions.iter()).zip(fonts.iter()) {
             ctx.set_x_offset(x as i32);
+            ctx.set_font(font, point_size)?;
+            wd.draw(&mut ctx, i, screen_has_focus, w, h)?;
         }
 
+        self.positions[i] = positions;
+
         self.draw.flush(id)?;
 
         Ok(())
@@ -300,6 +498,10 @@ pub fn startup_hook<X: XConn + 'static>(state: &mut State<X>, x: &X) -> penrose:
         return Err(penrose::Error::NoScreens);
     }
 
+    if let Err(e) = bar.select_click_events(x) {
+        error!(%e, "unable to select click events for status bar windows");
+    }
+
     info!("running startup widget hooks");
     let n_screens = bar.screens.len();
     bar.widgets.for_each_mut(n_screens, |w| {
@@ -361,8 +563,17 @@ pub fn event_hook<X: XConn + 'static>(
             error!(%e, "unabled to initialise for screens");
             return Err(penrose::Error::NoScreens);
         }
+
+        if let Err(e) = bar.select_click_events(x) {
+            error!(%e, "unable to select click events for status bar windows");
+        }
     }
 
+    let clicked = match event {
+        XEvent::MouseEvent(e) => bar.handle_click(e, state, x),
+        _ => false,
+    };
+
     bar.active_screen = state.client_set.current_screen().index();
     let n_screens = bar.screens.len();
     bar.widgets.for_each_mut(n_screens, |w| {
@@ -375,7 +586,125 @@ pub fn event_hook<X: XConn + 'static>(
         error!(%e, "error redrawing status bar");
     }
 
-    Ok(true)
+    if clicked {
+        // Release our borrow of the status bar so that the refresh hook is able to run
+        drop(bar);
+        x.refresh(state)?;
+    }
+
+    Ok(!clicked)
+}
+
+// The position of the status bar window for each screen.
+fn bar_rects(
+    screens: &[Rect],
+    position: Position,
+    mut bar_height: impl FnMut(usize) -> u32,
+) -> Vec<Rect> {
+    screens
+        .iter()
+        .enumerate()
+        .map(|(i, &Rect { x, y, w, h })| {
+            let bar_h = bar_height(i);
+            let y = match position {
+                Position::Top => y,
+                Position::Bottom => y + h.saturating_sub(bar_h),
+            };
+
+            Rect::new(x, y, w, bar_h)
+        })
+        .collect()
+}
+
+// The smallest Rect containing all of the given screens
+fn bounding_rect(screens: &[Rect]) -> Rect {
+    screens
+        .iter()
+        .copied()
+        .reduce(|a, b| {
+            let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+            let w = (a.x + a.w).max(b.x + b.w) - x;
+            let h = (a.y + a.h).max(b.y + b.h) - y;
+
+            Rect::new(x, y, w, h)
+        })
+        .unwrap_or_default()
+}
+
+// The _NET_WM_STRUT_PARTIAL reserving space for a bar window at r_bar. Struts are measured
+// from the edges of the bounding Rect of all screens and are limited to the width of the bar.
+fn bar_strut(r_bar: Rect, r_root: Rect, position: Position) -> Vec<u32> {
+    let (start_x, end_x) = (r_bar.x, (r_bar.x + r_bar.w).saturating_sub(1));
+
+    match position {
+        Position::Top => {
+            let top = r_bar.y + r_bar.h - r_root.y;
+            vec![0, 0, top, 0, 0, 0, 0, 0, start_x, end_x, 0, 0]
+        }
+        Position::Bottom => {
+            let bottom = r_root.y + r_root.h - r_bar.y;
+            vec![0, 0, 0, bottom, 0, 0, 0, 0, 0, 0, start_x, end_x]
+        }
+    }
+}
+
+// The (x, w) position of each widget given their (width, is_greedy) extents.
+//
+// Any space remaining after laying out all widgets at their requested widths is split evenly
+// between greedy widgets. Widgets following the last greedy widget are positioned relative to
+// the right hand edge of the screen so that they remain visible if the bar overflows.
+fn widget_positions(extents: &[(u32, bool)], w_screen: u32) -> Vec<(u32, u32)> {
+    let mut widths: Vec<u32> = extents.iter().map(|&(w, _)| w).collect();
+    let total = widths.iter().sum::<u32>();
+    let greedy: Vec<usize> = (0..extents.len()).filter(|&i| extents[i].1).collect();
+
+    let last_greedy = match greedy.last() {
+        Some(&ix) => ix,
+        None => return left_to_right(&widths, 0),
+    };
+
+    if total < w_screen {
+        let remaining = w_screen - total;
+        let per_greedy = remaining / greedy.len() as u32;
+        for &ix in greedy.iter() {
+            widths[ix] += per_greedy;
+        }
+        // Any remainder goes to the last greedy widget so we fill the full width
+        widths[last_greedy] += remaining % greedy.len() as u32;
+    }
+
+    let (left, right) = widths.split_at(last_greedy + 1);
+    let right_start = w_screen.saturating_sub(right.iter().sum());
+
+    let mut positions = left_to_right(left, 0);
+    positions.extend(left_to_right(right, right_start));
+
+    positions
+}
+
+fn left_to_right(widths: &[u32], start: u32) -> Vec<(u32, u32)> {
+    let mut x = start;
+
+    widths
+        .iter()
+        .map(|&w| {
+            let pos = (x, w);
+            x += w;
+            pos
+        })
+        .collect()
+}
+
+// The index of the widget found at the given horizontal position along with the position
+// relative to the left edge of that widget. Later widgets are drawn over earlier ones so
+// they take priority if widgets overlap.
+fn widget_at(positions: &[(u32, u32)], x: u32) -> Option<(usize, u32)> {
+    positions
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, &(start, w))| x >= start && x < start + w)
+        .map(|(ix, &(start, _))| (ix, x - start))
 }
 
 /// Run any widget on_new_client actions and then redraw if needed
@@ -401,3 +730,228 @@ pub fn manage_hook<X: XConn + 'static>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bar_is_positioned_on_each_screen() {
+        let screens = [Rect::new(0, 0, 1920, 1080), Rect::new(1920, 200, 1280, 800)];
+        let heights = [20, 30];
+
+        let cases = [
+            (
+                Position::Top,
+                vec![Rect::new(0, 0, 1920, 20), Rect::new(1920, 200, 1280, 30)],
+            ),
+            (
+                Position::Bottom,
+                vec![Rect::new(0, 1060, 1920, 20), Rect::new(1920, 970, 1280, 30)],
+            ),
+        ];
+
+        for (position, expected) in cases {
+            let rects = bar_rects(&screens, position, |i| heights[i]);
+            assert_eq!(rects, expected, "{position:?}");
+        }
+    }
+
+    #[test]
+    fn a_bottom_bar_sits_directly_below_the_reserved_region() {
+        let screen = Rect::new(1920, 200, 1280, 800);
+        let rects = bar_rects(&[screen], Position::Bottom, |_| 30);
+        assert_eq!(rects, vec![Rect::new(1920, 970, 1280, 30)]);
+
+        let mut layout = Position::Bottom.reserve(penrose::builtin::layout::Monocle::boxed(), 30);
+        let (_, positions) = layout.layout(&penrose::stack!(Xid::from(1)), screen);
+        let r = positions[0].1;
+
+        assert_eq!(r.y + r.h, rects[0].y, "bar is below the reserved region");
+        assert_eq!(
+            rects[0].y + rects[0].h,
+            screen.y + screen.h,
+            "bar is at the bottom edge"
+        );
+    }
+
+    #[test]
+    fn bar_struts_reserve_the_scaled_height_on_their_own_screen() {
+        use penrose::x::property::Strut;
+
+        let screens = [Rect::new(0, 0, 1920, 1080), Rect::new(1920, 200, 1280, 800)];
+        let heights = [20, 30];
+        let r_root = bounding_rect(&screens);
+
+        for position in [Position::Top, Position::Bottom] {
+            let rects = bar_rects(&screens, position, |i| heights[i]);
+            let struts: Vec<Strut> = rects
+                .iter()
+                .map(|&r| Strut::try_from_bytes(&bar_strut(r, r_root, position)).unwrap())
+                .collect();
+
+            for (i, &screen) in screens.iter().enumerate() {
+                let usable = struts
+                    .iter()
+                    .fold(screen, |r, strut| strut.apply_to(r, r_root));
+                let expected = match position {
+                    Position::Top => Rect::new(
+                        screen.x,
+                        screen.y + heights[i],
+                        screen.w,
+                        screen.h - heights[i],
+                    ),
+                    Position::Bottom => {
+                        Rect::new(screen.x, screen.y, screen.w, screen.h - heights[i])
+                    }
+                };
+
+                assert_eq!(usable, expected, "{position:?} screen {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn point_sizes_are_scaled_relative_to_the_base_dpi() {
+        // A 27" 4k monitor is ~163 DPI
+        let r = Rect::new(0, 0, 3840, 2160);
+        let hidpi = dpi(r, (597, 336));
+
+        assert_eq!(hidpi.round(), 163.0);
+        assert_eq!(scaled_point_size(10, hidpi), 17);
+        assert_eq!(scaled_point_size(10, BASE_DPI), 10);
+        assert_eq!(scaled_point_size(10, 48.0), 5);
+    }
+
+    #[test]
+    fn scale_factors_are_computed_for_each_screen() {
+        let screens = [Rect::new(0, 0, 3840, 2160), Rect::new(3840, 0, 1920, 1080)];
+        let mm = [Some((597, 336)), None];
+
+        let factors = |s: Scaling| -> Vec<f64> {
+            s.factors(&screens, &mm)
+                .into_iter()
+                .map(|f| (f * 10.0).round() / 10.0)
+                .collect()
+        };
+
+        assert_eq!(factors(Scaling::None), vec![1.0, 1.0]);
+        assert_eq!(factors(Scaling::Dpi), vec![1.7, 1.0]);
+        assert_eq!(factors(Scaling::Manual(vec![2.0])), vec![2.0, 2.0]);
+        assert_eq!(factors(Scaling::Manual(vec![])), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn widget_fonts_are_registered_alongside_the_bar_font() {
+        use crate::{bar::widgets::Text, TextStyle};
+        use penrose::x11rb::RustConn;
+
+        let text = |font: Option<&str>| -> Box<dyn Widget<RustConn>> {
+            let style = TextStyle {
+                font: font.map(String::from),
+                ..Default::default()
+            };
+            Box::new(Text::new("txt", style, false, false))
+        };
+
+        let widgets = Widgets::PerScreen(vec![
+            PerScreen::new(12, 20, vec![text(None), text(Some("mono")), text(None)]),
+            PerScreen::new(14, 30, vec![text(Some("serif")), text(Some("mono"))]),
+        ]);
+
+        assert_eq!(
+            widgets.fonts("sans"),
+            vec![("sans", 12), ("mono", 12), ("serif", 14), ("mono", 14)]
+        );
+    }
+
+    #[test]
+    fn widgets_are_drawn_using_their_own_font() {
+        use crate::{bar::widgets::Text, TextStyle};
+        use penrose::x11rb::RustConn;
+
+        let text = |font: Option<&str>| -> Box<dyn Widget<RustConn>> {
+            let style = match font {
+                Some(font) => TextStyle::default().with_font(font),
+                None => TextStyle::default(),
+            };
+            Box::new(Text::new("txt", style, false, false))
+        };
+
+        let ps = PerScreen::new(12, 20, vec![text(None), text(Some("mono")), text(None)]);
+
+        assert_eq!(ps.draw_fonts("sans"), vec!["sans", "mono", "sans"]);
+    }
+
+    #[test]
+    fn wrapping_widgets_use_the_font_from_their_style() {
+        use crate::{
+            bar::widgets::{IntervalText, RefreshText},
+            TextStyle,
+        };
+        use penrose::x11rb::RustConn;
+        use std::time::Duration;
+
+        // Fonts may be loaded at runtime rather than being known at compile time
+        let font = String::from("mono");
+        let style = TextStyle::default().with_font(font);
+
+        let ps: PerScreen<RustConn> = PerScreen::new(
+            12,
+            20,
+            vec![
+                Box::new(RefreshText::new(style.clone(), String::new)),
+                Box::new(IntervalText::new(style, || None, Duration::from_secs(1))),
+                Box::new(RefreshText::new(TextStyle::default(), String::new)),
+            ],
+        );
+
+        assert_eq!(ps.draw_fonts("sans"), vec!["mono", "mono", "sans"]);
+    }
+
+    #[test]
+    fn widgets_after_a_spacer_are_right_aligned() {
+        // [workspaces][spacer][clock]
+        let cases = [
+            (1000, vec![(0, 200), (200, 680), (880, 120)]),
+            (301, vec![(0, 200), (200, 0), (181, 120)]),
+        ];
+
+        for (w_screen, expected) in cases {
+            let positions = widget_positions(&[(200, false), (0, true), (120, false)], w_screen);
+            assert_eq!(positions, expected, "w_screen={w_screen}");
+        }
+    }
+
+    #[test]
+    fn remaining_space_is_split_between_greedy_widgets() {
+        let extents = [(100, false), (10, true), (0, true), (50, false)];
+        let positions = widget_positions(&extents, 1001);
+
+        assert_eq!(positions, vec![(0, 100), (100, 430), (530, 421), (951, 50)]);
+    }
+
+    #[test]
+    fn widgets_without_a_spacer_are_left_aligned() {
+        let positions = widget_positions(&[(200, false), (120, false)], 1000);
+
+        assert_eq!(positions, vec![(0, 200), (200, 120)]);
+    }
+
+    #[test]
+    fn clicks_are_routed_to_the_widget_under_the_pointer() {
+        let positions = [(0, 30), (30, 100), (130, 20)];
+        let cases = [
+            (0, Some((0, 0))),
+            (29, Some((0, 29))),
+            (30, Some((1, 0))),
+            (75, Some((1, 45))),
+            (135, Some((2, 5))),
+            (150, None),
+        ];
+
+        for (x, expected) in cases {
+            assert_eq!(widget_at(&positions, x), expected, "x={x}");
+        }
+    }
+}
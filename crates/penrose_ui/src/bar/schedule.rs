@@ -1,5 +1,5 @@
 //! Utilities for running scheduled updates to widgets
-use crate::bar::widgets::Text;
+use crate::bar::widgets::{lock, Text};
 use penrose::util::spawn_with_args;
 use std::{
     cmp::max,
@@ -69,11 +69,7 @@ impl UpdateSchedule {
         trace!(?s, "ouput from running get_text");
 
         if let Some(s) = s {
-            let mut t = match self.txt.lock() {
-                Ok(inner) => inner,
-                Err(poisoned) => poisoned.into_inner(),
-            };
-            t.set_text(s);
+            lock(&self.txt).set_text(s);
         }
 
         let next = self.next + self.interval;
@@ -83,6 +79,13 @@ impl UpdateSchedule {
     }
 }
 
+/// Ensure that an event drops into the main window manager event loop so that the `on_event`
+/// hook of the status bar runs and widgets updated from other threads are redrawn.
+// FIXME: this is a hack at the moment until there is a way to wake the event loop directly.
+pub(crate) fn trigger_redraw() {
+    let _ = spawn_with_args("xsetroot", &["-name", ""]);
+}
+
 /// Run the polling thread for a set of [UpdateSchedule]s and update their contents on
 /// their requested intervals.
 pub(crate) fn run_update_schedules(mut schedules: Vec<UpdateSchedule>) {
@@ -90,12 +93,10 @@ pub(crate) fn run_update_schedules(mut schedules: Vec<UpdateSchedule>) {
         trace!("running UpdateSchedule updates for all pending widgets");
         while schedules[0].next < Instant::now() {
             schedules[0].update_text();
-            schedules.sort_by(|a, b| a.next.cmp(&b.next));
+            schedules.sort_by_key(|a| a.next);
         }
 
-        // FIXME: this is a hack at the moment to ensure that an event drops into the main
-        // window manager event loop and triggers the `on_event` hook of the status bar.
-        let _ = spawn_with_args("xsetroot", &["-name", ""]);
+        trigger_redraw();
 
         let interval = schedules[0].next - Instant::now();
         trace!(?interval, "sleeping until next update point");
@@ -0,0 +1,95 @@
+//! A simple clock widget
+use crate::{
+    bar::{
+        schedule::UpdateSchedule,
+        widgets::{IntervalText, Widget},
+    },
+    Context, Result, TextStyle,
+};
+use chrono::{DateTime, Local, TimeZone};
+use penrose::x::XConn;
+use std::{fmt::Write, time::Duration};
+
+/// Display the current local date and/or time using a `strftime` style format string.
+///
+/// See the [chrono docs][0] for details of the supported format specifiers. The widget
+/// contents are updated on the interval provided and the status bar is only redrawn when
+/// the formatted output changes.
+///
+///   [0]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+#[derive(Debug)]
+pub struct Clock {
+    inner: IntervalText,
+}
+
+impl Clock {
+    /// Construct a new [Clock] using the given format string.
+    ///
+    /// If the format string is invalid then this widget will render as an empty string.
+    pub fn new(format: impl Into<String>, style: TextStyle, interval: Duration) -> Self {
+        let format = format.into();
+
+        Self {
+            inner: IntervalText::new(style, move || format_time(&Local::now(), &format), interval),
+        }
+    }
+}
+
+impl<X: XConn> Widget<X> for Clock {
+    fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
+        Widget::<X>::draw(&mut self.inner, ctx, s, f, w, h)
+    }
+
+    fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        Widget::<X>::current_extent(&mut self.inner, ctx, h)
+    }
+
+    fn is_greedy(&self) -> bool {
+        Widget::<X>::is_greedy(&self.inner)
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
+    fn require_draw(&self) -> bool {
+        Widget::<X>::require_draw(&self.inner)
+    }
+
+    fn update_schedule(&mut self) -> Option<UpdateSchedule> {
+        Widget::<X>::update_schedule(&mut self.inner)
+    }
+}
+
+// Returns None rather than panicking if the format string is invalid
+fn format_time<Tz>(t: &DateTime<Tz>, format: &str) -> Option<String>
+where
+    Tz: TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    let mut s = String::new();
+    write!(s, "{}", t.format(format)).ok()?;
+
+    Some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn fixed_timestamps_are_formatted() {
+        let t = Utc.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap();
+        let cases = [
+            ("%F %R", Some("2024-03-09 14:05")),
+            ("%H:%M:%S", Some("14:05:07")),
+            ("%a %d %b", Some("Sat 09 Mar")),
+            ("%Q", None),
+        ];
+
+        for (format, expected) in cases {
+            assert_eq!(format_time(&t, format).as_deref(), expected, "{format}");
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! A widget displaying the output of an external command
+use crate::{
+    bar::{schedule::UpdateSchedule, widgets::lock, widgets::Text, widgets::Widget},
+    Context, Result, TextStyle,
+};
+use penrose::x::XConn;
+use std::{
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::warn;
+
+/// The default maximum number of characters displayed by a [CommandWidget].
+pub const DEFAULT_MAX_LEN: usize = 50;
+
+/// Display the output of running an external command on a specified interval.
+///
+/// The command is run in the background thread used for [UpdateSchedule]s so a slow running
+/// command will not block the window manager event loop. Trailing whitespace is trimmed from
+/// the output and it is capped to a maximum length (see [CommandWidget::with_max_len]). If the
+/// command fails to run or exits with a non-zero status then the fallback text is displayed
+/// instead (see [CommandWidget::with_fallback]).
+///
+/// # Example
+/// ```no_run
+/// use penrose_ui::{bar::widgets::CommandWidget, core::TextStyle};
+/// use std::time::Duration;
+///
+/// let style = TextStyle {
+///     fg: 0xebdbb2ff.into(),
+///     bg: Some(0x282828ff.into()),
+///     padding: (2, 2),
+///     ..Default::default()
+/// };
+///
+/// let cpu_temp = CommandWidget::new("cpu-temp.sh", &["--celsius"], style, Duration::from_secs(5))
+///     .with_max_len(10)
+///     .with_fallback("??°C");
+/// ```
+#[derive(Debug)]
+pub struct CommandWidget {
+    inner: Arc<Mutex<Text>>,
+    style: TextStyle,
+    interval: Duration,
+    spec: Option<CommandSpec>,
+}
+
+impl CommandWidget {
+    /// Construct a new [CommandWidget] that runs `cmd` with the given arguments on the
+    /// interval provided.
+    pub fn new(
+        cmd: impl Into<String>,
+        args: &[&str],
+        style: TextStyle,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Text::new("", style.clone(), false, false))),
+            style,
+            interval,
+            spec: Some(CommandSpec {
+                cmd: cmd.into(),
+                args: args.iter().map(|s| s.to_string()).collect(),
+                max_len: DEFAULT_MAX_LEN,
+                fallback: String::new(),
+            }),
+        }
+    }
+
+    /// Set the maximum number of characters to display. Longer output will be truncated.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        if let Some(spec) = self.spec.as_mut() {
+            spec.max_len = max_len;
+        }
+
+        self
+    }
+
+    /// Set the text to display if the command fails.
+    pub fn with_fallback(mut self, fallback: impl Into<String>) -> Self {
+        if let Some(spec) = self.spec.as_mut() {
+            spec.fallback = fallback.into();
+        }
+
+        self
+    }
+}
+
+impl<X: XConn> Widget<X> for CommandWidget {
+    fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
+        Widget::<X>::draw(&mut *lock(&self.inner), ctx, s, f, w, h)
+    }
+
+    fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        Widget::<X>::current_extent(&mut *lock(&self.inner), ctx, h)
+    }
+
+    fn is_greedy(&self) -> bool {
+        Widget::<X>::is_greedy(&*lock(&self.inner))
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Some(&self.style)
+    }
+
+    fn require_draw(&self) -> bool {
+        Widget::<X>::require_draw(&*lock(&self.inner))
+    }
+
+    fn update_schedule(&mut self) -> Option<UpdateSchedule> {
+        let spec = self.spec.take()?;
+
+        Some(UpdateSchedule::new(
+            self.interval,
+            Box::new(move || Some(spec.run())),
+            self.inner.clone(),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CommandSpec {
+    cmd: String,
+    args: Vec<String>,
+    max_len: usize,
+    fallback: String,
+}
+
+impl CommandSpec {
+    fn run(&self) -> String {
+        let output = Command::new(&self.cmd)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+
+        let raw = match output {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+            Ok(output) => {
+                warn!(cmd = self.cmd, status = %output.status, "command exited unsuccessfully");
+                return self.fallback.clone();
+            }
+            Err(e) => {
+                warn!(cmd = self.cmd, %e, "unable to run command");
+                return self.fallback.clone();
+            }
+        };
+
+        let s = raw.trim_end();
+
+        match s.char_indices().nth(self.max_len) {
+            Some((ix, _)) => format!("{}…", &s[..ix]),
+            None => s.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(cmd: &str, args: &[&str]) -> CommandSpec {
+        CommandSpec {
+            cmd: cmd.to_string(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+            max_len: 10,
+            fallback: "fallback".to_string(),
+        }
+    }
+
+    #[test]
+    fn command_output_is_displayed() {
+        let cases = [
+            (spec("echo", &["hello"]), "hello"),
+            (spec("printf", &["trailing\\n\\n"]), "trailing"),
+            (spec("echo", &["this is too long"]), "this is to…"),
+            (spec("sh", &["-c", "exit 1"]), "fallback"),
+            (spec("this-command-does-not-exist", &[]), "fallback"),
+        ];
+
+        for (spec, expected) in cases {
+            assert_eq!(spec.run(), expected, "{spec:?}");
+        }
+    }
+}
@@ -34,6 +34,10 @@ impl<X: XConn> Widget<X> for ActiveWindowId {
         Widget::<X>::is_greedy(&self.inner)
     }
 
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
     fn require_draw(&self) -> bool {
         Widget::<X>::require_draw(&self.inner)
     }
@@ -84,6 +88,10 @@ impl<X: XConn> Widget<X> for StateSummary {
         Widget::<X>::is_greedy(&self.inner)
     }
 
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
     fn require_draw(&self) -> bool {
         Widget::<X>::require_draw(&self.inner)
     }
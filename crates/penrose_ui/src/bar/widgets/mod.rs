@@ -1,10 +1,10 @@
 //! Self rendering building blocks for text based UI elements
 use crate::{bar::schedule::UpdateSchedule, Context, Result, TextStyle};
 use penrose::{
-    core::State,
+    core::{bindings::MouseButton, State},
     pure::geometry::Rect,
     x::{XConn, XEvent},
-    Color, Xid,
+    Xid,
 };
 use std::{
     fmt,
@@ -15,12 +15,35 @@ use std::{
 pub mod debug;
 pub mod sys;
 
+mod clock;
+mod command;
+mod pipe;
+mod scrolling;
 mod simple;
+mod sparkline;
 mod workspaces;
 
-pub use simple::{ActiveWindowName, CurrentLayout, RootWindowName};
+pub use clock::Clock;
+pub use command::CommandWidget;
+pub use pipe::PipeWidget;
+pub use scrolling::ScrollingText;
+pub use simple::{
+    ActiveKeyMode, ActiveWindowName, CurrentLayout, RootWindowName, Spacer, Truncation,
+};
+pub use sparkline::Sparkline;
+pub use sys::Battery;
 pub use workspaces::{DefaultUi, FocusState, Workspaces, WorkspacesUi, WorkspacesWidget, WsMeta};
 
+// Widgets updated from a background thread share their state behind a Mutex. A panic while
+// holding the lock shouldn't take the rest of the status bar down with it so we recover the
+// state from a poisoned lock rather than propagating the panic.
+pub(crate) fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    match m.lock() {
+        Ok(inner) => inner,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
 /// A status bar widget that can be rendered using a [Context]
 pub trait Widget<X>
 where
@@ -47,6 +70,19 @@ where
     /// space will be split evenly between all widgets.
     fn is_greedy(&self) -> bool;
 
+    /// The [TextStyle] used when rendering this widget, if it has one.
+    fn style(&self) -> Option<&TextStyle> {
+        None
+    }
+
+    /// The font that should be used when rendering this widget.
+    ///
+    /// By default this is the font set on the [style][Widget::style] of the widget. If this is
+    /// `None` then the default font for the status bar will be used.
+    fn font(&self) -> Option<&str> {
+        self.style().and_then(|s| s.font.as_deref())
+    }
+
     /// An [UpdateSchedule] to allow for external updates to this Widget's state independently of
     /// the window manager event loop.
     fn update_schedule(&mut self) -> Option<UpdateSchedule> {
@@ -76,6 +112,22 @@ where
     fn on_new_client(&mut self, id: Xid, state: &mut State<X>, x: &X) -> Result<()> {
         Ok(())
     }
+
+    #[allow(unused_variables)]
+    /// A hook to be run when this Widget is clicked. `offset` is the horizontal position of the
+    /// click relative to the left edge of the widget.
+    ///
+    /// The window manager state will be refreshed after this hook has run so there is no need to
+    /// (and you should not) call `refresh` from within this method.
+    fn on_click(
+        &mut self,
+        button: MouseButton,
+        offset: u32,
+        state: &mut State<X>,
+        x: &X,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// A simple piece of static text with an optional background color.
@@ -85,11 +137,10 @@ where
 #[derive(Clone, Debug, PartialEq)]
 pub struct Text {
     txt: String,
-    fg: Color,
-    bg: Option<Color>,
-    padding: (u32, u32),
+    style: TextStyle,
     is_greedy: bool,
     right_justified: bool,
+    markup: bool,
     extent: Option<(u32, u32)>,
     require_draw: bool,
 }
@@ -104,16 +155,34 @@ impl Text {
     ) -> Self {
         Self {
             txt: txt.into(),
-            fg: style.fg,
-            bg: style.bg,
-            padding: style.padding,
+            style,
             is_greedy,
             right_justified,
+            markup: false,
             extent: None,
             require_draw: true,
         }
     }
 
+    /// Set whether or not the contents of this widget should be rendered as Pango style markup.
+    ///
+    /// See [markup][crate::core::markup] for details of the supported markup.
+    pub fn set_markup(&mut self, markup: bool) {
+        if self.markup != markup {
+            self.markup = markup;
+            self.extent = None;
+            self.require_draw = true;
+        }
+    }
+
+    fn draw_contents(&self, ctx: &mut Context<'_>, h_offset: u32) -> Result<(u32, u32)> {
+        if self.markup {
+            ctx.draw_markup(&self.txt, h_offset, self.style.padding, self.style.fg)
+        } else {
+            ctx.draw_text(&self.txt, h_offset, self.style.padding, self.style.fg)
+        }
+    }
+
     /// Borrow the current contents of the widget.
     pub fn get_text(&self) -> &String {
         &self.txt
@@ -137,7 +206,7 @@ impl Text {
 
 impl<X: XConn> Widget<X> for Text {
     fn draw(&mut self, ctx: &mut Context<'_>, _: usize, _: bool, w: u32, h: u32) -> Result<()> {
-        if let Some(color) = self.bg {
+        if let Some(color) = self.style.bg {
             ctx.fill_rect(Rect::new(0, 0, w, h), color)?;
         }
 
@@ -146,10 +215,10 @@ impl<X: XConn> Widget<X> for Text {
         let right_justify = self.right_justified && self.is_greedy && offset > 0;
         if right_justify {
             ctx.translate(offset, 0);
-            ctx.draw_text(&self.txt, h - eh, self.padding, self.fg)?;
+            self.draw_contents(ctx, h - eh)?;
             ctx.translate(-offset, 0);
         } else {
-            ctx.draw_text(&self.txt, h - eh, self.padding, self.fg)?;
+            self.draw_contents(ctx, h - eh)?;
         }
 
         self.require_draw = false;
@@ -161,8 +230,12 @@ impl<X: XConn> Widget<X> for Text {
         match self.extent {
             Some(extent) => Ok(extent),
             None => {
-                let (l, r) = self.padding;
-                let (w, h) = ctx.text_extent(&self.txt)?;
+                let (l, r) = self.style.padding;
+                let (w, h) = if self.markup {
+                    ctx.markup_extent(&self.txt)?
+                } else {
+                    ctx.text_extent(&self.txt)?
+                };
                 let extent = (w + l + r, h);
                 self.extent = Some(extent);
 
@@ -178,6 +251,10 @@ impl<X: XConn> Widget<X> for Text {
     fn is_greedy(&self) -> bool {
         self.is_greedy
     }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Some(&self.style)
+    }
 }
 
 /// A simple widget that does not care about window manager state.
@@ -216,6 +293,7 @@ impl<X: XConn> Widget<X> for Text {
 ///     fg: 0xebdbb2ff.into(),
 ///     bg: Some(0x282828ff.into()),
 ///     padding: (2, 2),
+///     ..Default::default()
 /// };
 ///
 /// let my_widget = RefreshText::new(style, my_get_text);
@@ -260,6 +338,10 @@ impl<X: XConn> Widget<X> for RefreshText {
         Widget::<X>::is_greedy(&self.inner)
     }
 
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
     fn require_draw(&self) -> bool {
         Widget::<X>::require_draw(&self.inner)
     }
@@ -305,6 +387,7 @@ impl<X: XConn> Widget<X> for RefreshText {
 ///     fg: 0xebdbb2ff.into(),
 ///     bg: Some(0x282828ff.into()),
 ///     padding: (2, 2),
+///     ..Default::default()
 /// };
 ///
 ///
@@ -316,6 +399,7 @@ impl<X: XConn> Widget<X> for RefreshText {
 /// ```
 pub struct IntervalText {
     inner: Arc<Mutex<Text>>,
+    style: TextStyle,
     interval: Duration,
     get_text: Option<Box<dyn Fn() -> Option<String> + Send + 'static>>,
 }
@@ -337,38 +421,36 @@ impl IntervalText {
     where
         F: Fn() -> Option<String> + Send + 'static,
     {
-        let inner = Arc::new(Mutex::new(Text::new("", style, false, false)));
+        let inner = Arc::new(Mutex::new(Text::new("", style.clone(), false, false)));
 
         Self {
             inner,
+            style,
             interval,
             get_text: Some(Box::new(get_text)),
         }
     }
-
-    fn inner_guard(&self) -> MutexGuard<'_, Text> {
-        match self.inner.lock() {
-            Ok(inner) => inner,
-            Err(poisoned) => poisoned.into_inner(),
-        }
-    }
 }
 
 impl<X: XConn> Widget<X> for IntervalText {
     fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
-        Widget::<X>::draw(&mut *self.inner_guard(), ctx, s, f, w, h)
+        Widget::<X>::draw(&mut *lock(&self.inner), ctx, s, f, w, h)
     }
 
     fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
-        Widget::<X>::current_extent(&mut *self.inner_guard(), ctx, h)
+        Widget::<X>::current_extent(&mut *lock(&self.inner), ctx, h)
     }
 
     fn is_greedy(&self) -> bool {
-        Widget::<X>::is_greedy(&*self.inner_guard())
+        Widget::<X>::is_greedy(&*lock(&self.inner))
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Some(&self.style)
     }
 
     fn require_draw(&self) -> bool {
-        Widget::<X>::require_draw(&*self.inner_guard())
+        Widget::<X>::require_draw(&*lock(&self.inner))
     }
 
     fn update_schedule(&mut self) -> Option<UpdateSchedule> {
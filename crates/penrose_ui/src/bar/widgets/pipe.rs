@@ -0,0 +1,212 @@
+//! A widget displaying text pushed to it via a named pipe
+use crate::{
+    bar::{widgets::lock, widgets::Text, widgets::Widget},
+    Context, Result, TextStyle,
+};
+use nix::{sys::stat::Mode, unistd::mkfifo};
+use penrose::{core::State, x::XConn, x11rb::Waker};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, ErrorKind},
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+use tracing::{debug, error, warn};
+
+/// The client message type used to wake the main event loop when a [PipeWidget] is updated.
+pub const PIPE_MESSAGE: &str = "_PENROSE_PIPE";
+
+/// Display the most recent line of text written to a named pipe (FIFO).
+///
+/// The pipe is read from a background thread which is started when the status bar starts up.
+/// If no file exists at the given path then a new named pipe is created. Each line written to
+/// the pipe replaces the current contents of the widget and triggers a redraw of the status bar.
+/// Writers are free to close and re-open the pipe as needed. If something other than a named
+/// pipe already exists at the given path then an error is logged and the widget is left empty.
+///
+/// # Example
+/// ```no_run
+/// use penrose_ui::{bar::widgets::PipeWidget, core::TextStyle};
+///
+/// let style = TextStyle {
+///     fg: 0xebdbb2ff.into(),
+///     bg: Some(0x282828ff.into()),
+///     padding: (2, 2),
+///     ..Default::default()
+/// };
+///
+/// // Update using `echo "some status" > /tmp/penrose-status`
+/// let status = PipeWidget::new("/tmp/penrose-status", style);
+/// ```
+#[derive(Debug)]
+pub struct PipeWidget {
+    inner: Arc<Mutex<Text>>,
+    style: TextStyle,
+    path: PathBuf,
+    started: bool,
+}
+
+impl PipeWidget {
+    /// Construct a new [PipeWidget] reading from the named pipe at `path`.
+    pub fn new(path: impl Into<PathBuf>, style: TextStyle) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Text::new("", style.clone(), false, false))),
+            style,
+            path: path.into(),
+            started: false,
+        }
+    }
+}
+
+impl<X: XConn> Widget<X> for PipeWidget {
+    fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
+        Widget::<X>::draw(&mut *lock(&self.inner), ctx, s, f, w, h)
+    }
+
+    fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        Widget::<X>::current_extent(&mut *lock(&self.inner), ctx, h)
+    }
+
+    fn is_greedy(&self) -> bool {
+        Widget::<X>::is_greedy(&*lock(&self.inner))
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Some(&self.style)
+    }
+
+    fn require_draw(&self) -> bool {
+        Widget::<X>::require_draw(&*lock(&self.inner))
+    }
+
+    fn on_startup(&mut self, _: &mut State<X>, _: &X) -> Result<()> {
+        if !self.started {
+            let waker = Waker::new(PIPE_MESSAGE)?;
+            spawn_reader(self.path.clone(), self.inner.clone(), move || waker.wake());
+            self.started = true;
+        }
+
+        Ok(())
+    }
+}
+
+// Lines are read until the path is found to not be a named pipe, calling `wake` after each
+// update so that the status bar is able to redraw.
+fn spawn_reader<F>(path: PathBuf, inner: Arc<Mutex<Text>>, wake: F) -> JoinHandle<()>
+where
+    F: Fn() -> penrose::Result<()> + Send + 'static,
+{
+    thread::spawn(move || loop {
+        match read_lines(&path, &inner, &wake) {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::InvalidInput => {
+                error!(%e, ?path, "unable to read from named pipe");
+                return;
+            }
+            Err(e) => {
+                warn!(%e, ?path, "error reading from named pipe: retrying");
+                thread::sleep(Duration::from_secs(1));
+            }
+        }
+    })
+}
+
+// Opening a FIFO for reading blocks until there is a writer and reading lines will return EOF
+// once all writers have closed the pipe, at which point we return and the pipe is re-opened.
+// Anything other than a FIFO would return EOF immediately every time it was opened so we
+// refuse to read from it rather than spinning.
+fn read_lines<F>(path: &Path, inner: &Mutex<Text>, wake: &F) -> io::Result<()>
+where
+    F: Fn() -> penrose::Result<()>,
+{
+    match fs::metadata(path) {
+        Ok(meta) if !meta.file_type().is_fifo() => {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "not a named pipe"));
+        }
+        Ok(_) => (),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            debug!(?path, "creating named pipe");
+            mkfifo(path, Mode::S_IRUSR | Mode::S_IWUSR)?;
+        }
+        Err(e) => return Err(e),
+    }
+
+    for line in BufReader::new(File::open(path)?).lines() {
+        lock(inner).set_text(line?.trim_end());
+        if let Err(e) = wake() {
+            warn!(%e, "unable to wake event loop for named pipe update");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::OpenOptions, io::Write, time::Instant};
+
+    fn wait_for_text(inner: &Mutex<Text>, expected: &str) {
+        let start = Instant::now();
+        while lock(inner).get_text() != expected {
+            if start.elapsed() > Duration::from_secs(5) {
+                panic!("timed out waiting for {expected:?}");
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn test_widget(path: &Path) -> PipeWidget {
+        let style = TextStyle {
+            fg: 0xebdbb2ff.into(),
+            bg: None,
+            padding: (0, 0),
+            ..Default::default()
+        };
+
+        PipeWidget::new(path, style)
+    }
+
+    #[test]
+    fn lines_written_to_the_pipe_update_the_text() {
+        let path = std::env::temp_dir().join(format!("penrose-pipe-test-{}", std::process::id()));
+        let w = test_widget(&path);
+        spawn_reader(path.clone(), w.inner.clone(), || Ok(()));
+
+        for line in ["hello", "world"] {
+            // wait for the reader to create the pipe
+            while !path.exists() {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            // Each write re-opens the pipe to check that we handle the writer closing
+            let mut f = OpenOptions::new().write(true).open(&path).unwrap();
+            writeln!(f, "{line}").unwrap();
+            drop(f);
+
+            wait_for_text(&w.inner, line);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn regular_files_are_not_read() {
+        let path =
+            std::env::temp_dir().join(format!("penrose-pipe-file-test-{}", std::process::id()));
+        fs::write(&path, "not a pipe\n").unwrap();
+        let w = test_widget(&path);
+
+        let handle = spawn_reader(path.clone(), w.inner.clone(), || {
+            panic!("unexpected update")
+        });
+        let res = handle.join();
+        fs::remove_file(&path).unwrap();
+
+        assert!(res.is_ok(), "reader thread panicked");
+        assert_eq!(lock(&w.inner).get_text(), "");
+    }
+}
@@ -0,0 +1,332 @@
+//! Text that scrolls horizontally when it is too long to fit in the space available
+use crate::{
+    bar::widgets::{lock, Text, Widget},
+    Context, Result, TextStyle,
+};
+use penrose::{
+    core::State,
+    pure::geometry::Rect,
+    x::{XConn, XEvent},
+    x11rb::Waker,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use tracing::warn;
+
+/// The default number of spaces inserted between the end and start of scrolling text.
+pub const DEFAULT_GAP: usize = 3;
+
+/// The client message type used to wake the main event loop when scrolling text needs redrawing.
+pub const SCROLL_MESSAGE: &str = "_PENROSE_SCROLL";
+
+/// A piece of text rendered within a fixed width that scrolls horizontally, one character per
+/// tick of the interval provided, if it is too long to fit in the space available.
+///
+/// When scrolling, the text wraps around with a gap of spaces separating the end of the text
+/// from its start. Text that fits within the available width is rendered as-is.
+///
+/// Scrolling is driven by a background thread started when the status bar starts up which
+/// wakes the main event loop on each tick for as long as the text is overflowing. Intervals
+/// shorter than one second are supported for smoother scrolling.
+#[derive(Debug)]
+pub struct ScrollingText {
+    inner: Arc<Mutex<Text>>,
+    style: TextStyle,
+    max_width: u32,
+    gap: usize,
+    interval: Duration,
+    ticks: Arc<AtomicUsize>,
+    overflowing: Arc<AtomicBool>,
+    drawn_tick: usize,
+    widths: Option<CharWidths>,
+    started: bool,
+    require_draw: bool,
+}
+
+// The width in pixels of each character of some text followed by the gap used when scrolling,
+// along with the text and total width they were computed for.
+#[derive(Debug)]
+struct CharWidths {
+    txt: String,
+    text_width: u32,
+    chars: Vec<(char, u32)>,
+}
+
+impl CharWidths {
+    fn new(ctx: &mut Context<'_>, txt: &str, text_width: u32, gap: usize) -> Result<Self> {
+        let mut known: HashMap<char, u32> = HashMap::new();
+        let mut chars = Vec::with_capacity(txt.len() + gap);
+
+        for c in txt.chars().chain(std::iter::repeat_n(' ', gap)) {
+            let w = match known.get(&c) {
+                Some(&w) => w,
+                None => {
+                    let (w, _) = ctx.text_extent(&c.to_string())?;
+                    known.insert(c, w);
+                    w
+                }
+            };
+            chars.push((c, w));
+        }
+
+        Ok(Self {
+            txt: txt.to_owned(),
+            text_width,
+            chars,
+        })
+    }
+
+    fn is_valid_for(&self, txt: &str, text_width: u32) -> bool {
+        self.txt == txt && self.text_width == text_width
+    }
+}
+
+impl ScrollingText {
+    /// Construct a new [ScrollingText] that is `max_width` pixels wide.
+    pub fn new(
+        txt: impl Into<String>,
+        style: TextStyle,
+        max_width: u32,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Text::new(txt, style.clone(), false, false))),
+            style,
+            max_width,
+            gap: DEFAULT_GAP,
+            interval,
+            ticks: Arc::new(AtomicUsize::new(0)),
+            overflowing: Arc::new(AtomicBool::new(false)),
+            drawn_tick: 0,
+            widths: None,
+            started: false,
+            require_draw: true,
+        }
+    }
+
+    /// Set the number of spaces to insert between the end and start of the text when scrolling.
+    pub fn with_gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Set the text to be displayed, restarting scrolling from the beginning of the text if it
+    /// has changed.
+    pub fn set_text(&mut self, txt: impl Into<String>) {
+        let mut inner = lock(&self.inner);
+        let txt = txt.into();
+        if inner.txt != txt {
+            inner.set_text(txt);
+            self.ticks.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<X: XConn> Widget<X> for ScrollingText {
+    fn draw(&mut self, ctx: &mut Context<'_>, _: usize, _: bool, w: u32, h: u32) -> Result<()> {
+        let tick = self.ticks.load(Ordering::Relaxed);
+        let gap = self.gap;
+        let mut inner = lock(&self.inner);
+
+        if let Some(color) = inner.style.bg {
+            ctx.fill_rect(Rect::new(0, 0, w, h), color)?;
+        }
+
+        let (l, r) = inner.style.padding;
+        let available = w.saturating_sub(l + r);
+        let (tw, th) = ctx.text_extent(&inner.txt)?;
+        let overflowing = tw > available;
+
+        let txt = if overflowing {
+            let widths = match self.widths.take() {
+                Some(widths) if widths.is_valid_for(&inner.txt, tw) => widths,
+                _ => CharWidths::new(ctx, &inner.txt, tw, gap)?,
+            };
+            let txt = visible_text(&widths.chars, tick, available);
+            self.widths = Some(widths);
+
+            txt
+        } else {
+            inner.txt.clone()
+        };
+
+        ctx.draw_text(
+            &txt,
+            h.saturating_sub(th),
+            inner.style.padding,
+            inner.style.fg,
+        )?;
+        inner.require_draw = false;
+        drop(inner);
+
+        self.overflowing.store(overflowing, Ordering::Relaxed);
+        self.drawn_tick = tick;
+        self.require_draw = false;
+
+        Ok(())
+    }
+
+    fn current_extent(&mut self, ctx: &mut Context<'_>, _h: u32) -> Result<(u32, u32)> {
+        let (_, th) = ctx.text_extent(&lock(&self.inner).txt)?;
+
+        Ok((self.max_width, th))
+    }
+
+    fn is_greedy(&self) -> bool {
+        false
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Some(&self.style)
+    }
+
+    fn require_draw(&self) -> bool {
+        self.require_draw || lock(&self.inner).require_draw
+    }
+
+    fn on_startup(&mut self, _: &mut State<X>, _: &X) -> Result<()> {
+        if !self.started {
+            let waker = Waker::new(SCROLL_MESSAGE)?;
+            spawn_ticker(
+                self.interval,
+                self.ticks.clone(),
+                self.overflowing.clone(),
+                move || waker.wake(),
+            );
+            self.started = true;
+        }
+
+        Ok(())
+    }
+
+    // The ticker thread wakes the main event loop on each tick while we are overflowing
+    fn on_event(&mut self, _: &XEvent, _: &mut State<X>, _: &X) -> Result<()> {
+        if self.overflowing.load(Ordering::Relaxed)
+            && self.ticks.load(Ordering::Relaxed) != self.drawn_tick
+        {
+            self.require_draw = true;
+        }
+
+        Ok(())
+    }
+}
+
+// Advance the scroll position once per interval while the text is overflowing, calling `wake`
+// so that the status bar is able to redraw. Text that fits is left alone without waking.
+fn spawn_ticker<F>(
+    interval: Duration,
+    ticks: Arc<AtomicUsize>,
+    overflowing: Arc<AtomicBool>,
+    wake: F,
+) where
+    F: Fn() -> penrose::Result<()> + Send + 'static,
+{
+    thread::spawn(move || loop {
+        thread::sleep(interval);
+        if !overflowing.load(Ordering::Relaxed) {
+            continue;
+        }
+
+        ticks.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = wake() {
+            warn!(%e, "unable to wake event loop for scrolling text");
+        }
+    });
+}
+
+// The text visible within `max_width` pixels after scrolling `tick` characters to the left,
+// wrapping around to the start of the text once the end has been reached.
+fn visible_text(chars: &[(char, u32)], tick: usize, max_width: u32) -> String {
+    if chars.is_empty() {
+        return String::new();
+    }
+
+    let offset = tick % chars.len();
+    let mut remaining = max_width;
+
+    chars[offset..]
+        .iter()
+        .chain(&chars[..offset])
+        .map_while(|&(c, w)| {
+            remaining = remaining.checked_sub(w)?;
+            Some(c)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_widths(txt: &str, gap: usize) -> Vec<(char, u32)> {
+        txt.chars()
+            .chain(std::iter::repeat_n(' ', gap))
+            .map(|c| (c, 1))
+            .collect()
+    }
+
+    #[test]
+    fn scrolling_wraps_around_with_a_gap() {
+        let expected = [
+            "hello  ", "ello  h", "llo  he", "lo  hel", "o  hell", "  hello", " hello ", "hello  ",
+            "ello  h",
+        ];
+        let chars = unit_widths("hello", 2);
+
+        for (tick, s) in expected.iter().enumerate() {
+            assert_eq!(visible_text(&chars, tick, 100), *s, "tick={tick}");
+        }
+    }
+
+    #[test]
+    fn only_characters_that_fit_are_visible() {
+        let chars = vec![('a', 3), ('b', 5), ('c', 2), (' ', 1)];
+
+        assert_eq!(visible_text(&chars, 0, 8), "ab");
+        assert_eq!(visible_text(&chars, 0, 7), "a");
+        assert_eq!(visible_text(&chars, 1, 8), "bc ");
+        assert_eq!(visible_text(&chars, 2, 6), "c a");
+    }
+
+    #[test]
+    fn empty_text_does_not_scroll() {
+        assert_eq!(visible_text(&[], 3, 100), "");
+    }
+
+    #[test]
+    fn ticks_only_advance_while_overflowing() {
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let overflowing = Arc::new(AtomicBool::new(false));
+        let wakes = Arc::new(AtomicUsize::new(0));
+        let w = wakes.clone();
+
+        spawn_ticker(
+            Duration::from_millis(5),
+            ticks.clone(),
+            overflowing.clone(),
+            move || {
+                w.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            },
+        );
+
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(ticks.load(Ordering::Relaxed), 0);
+        assert_eq!(wakes.load(Ordering::Relaxed), 0);
+
+        overflowing.store(true, Ordering::Relaxed);
+        thread::sleep(Duration::from_millis(50));
+        overflowing.store(false, Ordering::Relaxed);
+        thread::sleep(Duration::from_millis(20));
+
+        assert!(ticks.load(Ordering::Relaxed) > 0);
+        assert_eq!(ticks.load(Ordering::Relaxed), wakes.load(Ordering::Relaxed));
+    }
+}
@@ -8,8 +8,49 @@ use penrose::{
     core::State,
     pure::geometry::Rect,
     x::{event::PropertyEvent, Atom, XConn, XConnExt, XEvent},
+    Color,
 };
 
+/// An empty widget that expands to fill any available space in the status bar.
+///
+/// Widgets following a [Spacer] are aligned to the right hand edge of the status bar.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spacer {
+    bg: Option<Color>,
+    drawn: bool,
+}
+
+impl Spacer {
+    /// Create a new Spacer with an optional background color.
+    pub fn new(bg: Option<Color>) -> Self {
+        Self { bg, drawn: false }
+    }
+}
+
+impl<X: XConn> Widget<X> for Spacer {
+    fn draw(&mut self, ctx: &mut Context<'_>, _: usize, _: bool, w: u32, h: u32) -> Result<()> {
+        match self.bg {
+            Some(color) => ctx.fill_rect(Rect::new(0, 0, w, h), color)?,
+            None => ctx.fill_bg(Rect::new(0, 0, w, h))?,
+        }
+        self.drawn = true;
+
+        Ok(())
+    }
+
+    fn current_extent(&mut self, _: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        Ok((0, h))
+    }
+
+    fn is_greedy(&self) -> bool {
+        true
+    }
+
+    fn require_draw(&self) -> bool {
+        !self.drawn
+    }
+}
+
 /// A text widget that is set via updating the root window name a la dwm
 #[derive(Clone, Debug, PartialEq)]
 pub struct RootWindowName {
@@ -38,6 +79,10 @@ impl<X: XConn> Widget<X> for RootWindowName {
         Widget::<X>::is_greedy(&self.inner)
     }
 
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
     fn require_draw(&self) -> bool {
         Widget::<X>::require_draw(&self.inner)
     }
@@ -59,31 +104,86 @@ impl<X: XConn> Widget<X> for RootWindowName {
     }
 }
 
+/// How text that is longer than the maximum character count for a widget should be truncated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Truncation {
+    /// Keep the start of the text, replacing the end with "..."
+    #[default]
+    End,
+    /// Keep the start and end of the text, replacing the middle with "…"
+    Middle,
+}
+
+impl Truncation {
+    fn truncate(&self, txt: &str, max_chars: usize) -> String {
+        let n_chars = txt.chars().count();
+        if n_chars <= max_chars {
+            return txt.to_owned();
+        }
+
+        // Slicing on char boundaries ensures that we never split a multi-byte codepoint
+        let byte_offset = |n: usize| txt.char_indices().nth(n).map_or(txt.len(), |(i, _)| i);
+
+        match self {
+            Self::End => format!("{}...", &txt[..byte_offset(max_chars - 3)]),
+            Self::Middle => {
+                let keep = max_chars - 1;
+                let (n_prefix, n_suffix) = (keep - keep / 2, keep / 2);
+
+                format!(
+                    "{}…{}",
+                    &txt[..byte_offset(n_prefix)],
+                    &txt[byte_offset(n_chars - n_suffix)..]
+                )
+            }
+        }
+    }
+}
+
 /// A text widget that shows the name of the currently focused window
 #[derive(Clone, Debug, PartialEq)]
 pub struct ActiveWindowName {
     inner: Text,
     max_chars: usize,
+    truncation: Truncation,
 }
 
 impl ActiveWindowName {
     /// Create a new ActiveWindowName widget with a maximum character count.
     ///
-    /// max_chars can not be lower than 3.
+    /// Titles longer than max_chars are truncated at the end. max_chars can not be lower than 3.
     pub fn new(max_chars: usize, style: TextStyle, is_greedy: bool, right_justified: bool) -> Self {
+        Self::new_with_truncation(
+            max_chars,
+            Truncation::End,
+            style,
+            is_greedy,
+            right_justified,
+        )
+    }
+
+    /// Create a new ActiveWindowName widget with a maximum character count, truncating long
+    /// titles as specified.
+    ///
+    /// Using [Truncation::Middle] preserves the end of long titles, which typically contains
+    /// the name of the file being edited. max_chars can not be lower than 3.
+    pub fn new_with_truncation(
+        max_chars: usize,
+        truncation: Truncation,
+        style: TextStyle,
+        is_greedy: bool,
+        right_justified: bool,
+    ) -> Self {
         Self {
             inner: Text::new("", style, is_greedy, right_justified),
             max_chars: max_chars.max(3),
+            truncation,
         }
     }
 
     fn set_text(&mut self, txt: &str) {
-        if txt.chars().count() <= self.max_chars {
-            self.inner.set_text(txt);
-        } else {
-            let s: String = txt.chars().take(self.max_chars - 3).collect();
-            self.inner.set_text(format!("{}...", s));
-        }
+        self.inner
+            .set_text(self.truncation.truncate(txt, self.max_chars));
     }
 }
 
@@ -104,6 +204,10 @@ impl<X: XConn> Widget<X> for ActiveWindowName {
         Widget::<X>::is_greedy(&self.inner)
     }
 
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
     fn require_draw(&self) -> bool {
         Widget::<X>::require_draw(&self.inner)
     }
@@ -137,10 +241,59 @@ impl<X: XConn> Widget<X> for ActiveWindowName {
     }
 }
 
-/// A text widget that shows the current layout name
+/// A text widget that shows the name of the active [KeyMode][0] (if there is one)
+///
+///   [0]: penrose::core::bindings::KeyMode
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveKeyMode {
+    inner: Text,
+}
+
+impl ActiveKeyMode {
+    /// Create a new ActiveKeyMode widget
+    pub fn new(style: TextStyle, is_greedy: bool, right_justified: bool) -> Self {
+        Self {
+            inner: Text::new("", style, is_greedy, right_justified),
+        }
+    }
+}
+
+impl<X: XConn> Widget<X> for ActiveKeyMode {
+    fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
+        Widget::<X>::draw(&mut self.inner, ctx, s, f, w, h)
+    }
+
+    fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        Widget::<X>::current_extent(&mut self.inner, ctx, h)
+    }
+
+    fn is_greedy(&self) -> bool {
+        Widget::<X>::is_greedy(&self.inner)
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
+    fn require_draw(&self) -> bool {
+        Widget::<X>::require_draw(&self.inner)
+    }
+
+    fn on_refresh(&mut self, state: &mut State<X>, _: &X) -> Result<()> {
+        self.inner
+            .set_text(state.active_key_mode().unwrap_or_default());
+
+        Ok(())
+    }
+}
+
+/// A text widget that shows the name of the layout in use on the workspace of each screen
 #[derive(Clone, Debug, PartialEq)]
 pub struct CurrentLayout {
     inner: Text,
+    names: Vec<String>, // layout name per screen
+    extent: Option<(u32, u32)>,
+    require_draw: bool,
 }
 
 impl CurrentLayout {
@@ -148,31 +301,100 @@ impl CurrentLayout {
     pub fn new(style: TextStyle) -> Self {
         Self {
             inner: Text::new("", style, false, false),
+            names: Vec::new(),
+            extent: None,
+            require_draw: false,
+        }
+    }
+
+    fn name_for_screen(&self, screen: usize) -> &str {
+        match self.names.get(screen).or(self.names.last()) {
+            Some(name) => name,
+            None => "",
         }
     }
 }
 
 impl<X: XConn> Widget<X> for CurrentLayout {
     fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
-        Widget::<X>::draw(&mut self.inner, ctx, s, f, w, h)
+        self.inner.set_text(self.name_for_screen(s).to_owned());
+        Widget::<X>::draw(&mut self.inner, ctx, s, f, w, h)?;
+        self.require_draw = false;
+
+        Ok(())
     }
 
+    // Sized to fit the longest layout name so that the extent is consistent across screens
     fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
-        Widget::<X>::current_extent(&mut self.inner, ctx, h)
+        if let Some(extent) = self.extent {
+            return Ok(extent);
+        }
+
+        let mut extent = (0, 0);
+        for name in self.names.clone() {
+            self.inner.set_text(name);
+            let (w, h) = Widget::<X>::current_extent(&mut self.inner, ctx, h)?;
+            extent = (extent.0.max(w), extent.1.max(h));
+        }
+        self.extent = Some(extent);
+
+        Ok(extent)
     }
 
     fn is_greedy(&self) -> bool {
         Widget::<X>::is_greedy(&self.inner)
     }
 
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
     fn require_draw(&self) -> bool {
-        Widget::<X>::require_draw(&self.inner)
+        self.require_draw
     }
 
     fn on_refresh(&mut self, state: &mut State<X>, _: &X) -> Result<()> {
-        let layout_name = state.client_set.current_workspace().layout_name();
-        self.inner.set_text(format!("[{layout_name}]"));
+        let mut screens: Vec<_> = state.client_set.screens().collect();
+        screens.sort_by_key(|s| s.index());
+
+        let names: Vec<String> = screens
+            .iter()
+            .map(|s| format!("[{}]", s.workspace.layout_name()))
+            .collect();
+
+        if names != self.names {
+            self.names = names;
+            self.extent = None;
+            self.require_draw = true;
+        }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_test_case::test_case;
+
+    #[test_case("short", Truncation::End, "short"; "short end")]
+    #[test_case("short", Truncation::Middle, "short"; "short middle")]
+    #[test_case("0123456789abc", Truncation::End, "0123456...";  "long end")]
+    #[test_case("0123456789abc", Truncation::Middle, "01234…9abc"; "long middle")]
+    #[test_case("ééééé", Truncation::Middle, "ééééé"; "multi-byte at limit")]
+    #[test]
+    fn truncation(txt: &str, truncation: Truncation, expected: &str) {
+        assert_eq!(truncation.truncate(txt, 10), expected);
+    }
+
+    #[test]
+    fn middle_truncation_preserves_both_ends_of_multi_byte_titles() {
+        let title = "nvim — ~/projets/crème-brûlée/src/façade/überschrift.rs";
+        let truncated = Truncation::Middle.truncate(title, 29);
+
+        assert_eq!(truncated.chars().count(), 29);
+        assert_eq!(truncated, "nvim — ~/proje…überschrift.rs");
+        assert!(title.starts_with(truncated.split('…').next().unwrap()));
+        assert!(title.ends_with(truncated.split('…').nth(1).unwrap()));
+    }
+}
@@ -0,0 +1,283 @@
+//! A bar graph of recent numeric samples such as CPU load or network throughput
+use crate::{
+    bar::{
+        schedule::UpdateSchedule,
+        widgets::{lock, Text, Widget},
+    },
+    Context, Result, TextStyle,
+};
+use penrose::{
+    core::State,
+    pure::geometry::Rect,
+    x::{XConn, XEvent},
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// The default width in pixels of each column in a [Sparkline].
+pub const DEFAULT_COLUMN_WIDTH: u32 = 2;
+
+/// A small bar graph showing the most recent numeric samples, one column per sample.
+///
+/// On the requested interval, the provided `get_sample` function is called and the value it
+/// returns (if any) is added to a fixed size buffer of samples, replacing the oldest sample
+/// once the buffer is full. Samples are drawn from left to right, oldest first, using the
+/// foreground color of the [TextStyle] provided.
+///
+/// Column heights are scaled to the height of the status bar relative to the largest sample
+/// currently held (or the value set using [Sparkline::with_max]) with zero as the baseline. If
+/// any samples are negative then the smallest sample is used as the baseline instead.
+///
+/// # Example
+/// ```no_run
+/// use penrose::util::spawn_for_output_with_args;
+/// use penrose_ui::{bar::widgets::Sparkline, core::TextStyle};
+/// use std::time::Duration;
+///
+/// // The 1 minute load average
+/// fn load() -> Option<f64> {
+///     spawn_for_output_with_args("cut", &["-d", " ", "-f", "1", "/proc/loadavg"])
+///         .ok()?
+///         .trim()
+///         .parse()
+///         .ok()
+/// }
+///
+/// let style = TextStyle {
+///     fg: 0xebdbb2ff.into(),
+///     bg: Some(0x282828ff.into()),
+///     padding: (2, 2),
+///     ..Default::default()
+/// };
+///
+/// let my_widget = Sparkline::new(style, 30, load, Duration::from_secs(2));
+/// ```
+pub struct Sparkline {
+    inner: Arc<Mutex<Text>>,
+    samples: Arc<Mutex<VecDeque<f64>>>,
+    capacity: usize,
+    column_width: u32,
+    max: Option<f64>,
+    interval: Duration,
+    get_sample: Option<Box<dyn Fn() -> Option<f64> + Send + 'static>>,
+    received: Arc<AtomicUsize>,
+    drawn: usize,
+    require_draw: bool,
+}
+
+impl fmt::Debug for Sparkline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sparkline")
+            .field("samples", &self.samples)
+            .field("capacity", &self.capacity)
+            .field("column_width", &self.column_width)
+            .field("max", &self.max)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl Sparkline {
+    /// Construct a new [Sparkline] holding up to `capacity` samples, using the specified
+    /// styling and a function for fetching new samples. The function for fetching samples will
+    /// be run in its own thread on the interval provided.
+    pub fn new<F>(style: TextStyle, capacity: usize, get_sample: F, interval: Duration) -> Self
+    where
+        F: Fn() -> Option<f64> + Send + 'static,
+    {
+        Self {
+            inner: Arc::new(Mutex::new(Text::new("", style, false, false))),
+            samples: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            column_width: DEFAULT_COLUMN_WIDTH,
+            max: None,
+            interval,
+            get_sample: Some(Box::new(get_sample)),
+            received: Arc::new(AtomicUsize::new(0)),
+            drawn: 0,
+            require_draw: true,
+        }
+    }
+
+    /// Set the width in pixels of each column.
+    pub fn with_column_width(mut self, column_width: u32) -> Self {
+        self.column_width = column_width;
+        self
+    }
+
+    /// Scale columns relative to a fixed maximum value (such as 100 for percentages) rather
+    /// than the largest sample currently held.
+    pub fn with_max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Add a sample directly, replacing the oldest sample if the buffer is full.
+    ///
+    /// Samples that are not finite are ignored.
+    pub fn push(&mut self, sample: f64) {
+        if push_sample(&mut lock(&self.samples), self.capacity, sample) {
+            self.require_draw = true;
+        }
+    }
+
+    /// The samples currently held, oldest first.
+    pub fn samples(&self) -> Vec<f64> {
+        lock(&self.samples).iter().copied().collect()
+    }
+}
+
+impl<X: XConn> Widget<X> for Sparkline {
+    fn draw(&mut self, ctx: &mut Context<'_>, _: usize, _: bool, w: u32, h: u32) -> Result<()> {
+        let mut inner = lock(&self.inner);
+
+        if let Some(color) = inner.style.bg {
+            ctx.fill_rect(Rect::new(0, 0, w, h), color)?;
+        }
+
+        let samples = lock(&self.samples);
+        let offset =
+            inner.style.padding.0 + (self.capacity - samples.len()) as u32 * self.column_width;
+
+        for (i, ch) in column_heights(samples.iter().copied(), self.max, h)
+            .into_iter()
+            .enumerate()
+        {
+            if ch > 0 {
+                let x = offset + i as u32 * self.column_width;
+                ctx.fill_rect(Rect::new(x, h - ch, self.column_width, ch), inner.style.fg)?;
+            }
+        }
+
+        inner.require_draw = false;
+        self.drawn = self.received.load(Ordering::Relaxed);
+        self.require_draw = false;
+
+        Ok(())
+    }
+
+    fn current_extent(&mut self, _: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        let (l, r) = lock(&self.inner).style.padding;
+
+        Ok((l + r + self.capacity as u32 * self.column_width, h))
+    }
+
+    fn is_greedy(&self) -> bool {
+        false
+    }
+
+    fn require_draw(&self) -> bool {
+        self.require_draw
+    }
+
+    fn update_schedule(&mut self) -> Option<UpdateSchedule> {
+        let get_sample = self.get_sample.take()?;
+        let samples = self.samples.clone();
+        let received = self.received.clone();
+        let capacity = self.capacity;
+
+        Some(UpdateSchedule::new(
+            self.interval,
+            Box::new(move || {
+                if let Some(sample) = get_sample() {
+                    if push_sample(&mut lock(&samples), capacity, sample) {
+                        received.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                None
+            }),
+            self.inner.clone(),
+        ))
+    }
+
+    // The update schedule triggers an event in the main event loop after each new sample
+    fn on_event(&mut self, _: &XEvent, _: &mut State<X>, _: &X) -> Result<()> {
+        if self.received.load(Ordering::Relaxed) != self.drawn {
+            self.require_draw = true;
+        }
+
+        Ok(())
+    }
+}
+
+// Returns false if the sample was ignored
+fn push_sample(samples: &mut VecDeque<f64>, capacity: usize, sample: f64) -> bool {
+    if !sample.is_finite() || capacity == 0 {
+        return false;
+    }
+
+    if samples.len() == capacity {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+
+    true
+}
+
+// The height in pixels of the column for each sample when scaled to `h`. If every sample is
+// equal to the baseline then all columns are empty.
+fn column_heights(
+    samples: impl Iterator<Item = f64> + Clone,
+    max: Option<f64>,
+    h: u32,
+) -> Vec<u32> {
+    let lo = samples.clone().fold(0.0, f64::min);
+    let hi = max.unwrap_or_else(|| samples.clone().fold(lo, f64::max));
+    let range = hi - lo;
+
+    samples
+        .map(|s| {
+            if range <= 0.0 {
+                return 0;
+            }
+
+            let frac = ((s - lo) / range).clamp(0.0, 1.0);
+
+            (frac * h as f64).round() as u32
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_test_case::test_case;
+
+    #[test_case(&[], None, &[]; "empty")]
+    #[test_case(&[0.0, 25.0, 50.0, 100.0], None, &[0, 5, 10, 20]; "scaled to largest")]
+    #[test_case(&[1.0, 2.5, 3.0], None, &[7, 17, 20]; "rounded to nearest pixel")]
+    #[test_case(&[4.0, 4.0, 4.0], None, &[20, 20, 20]; "all equal")]
+    #[test_case(&[0.0, 0.0], None, &[0, 0]; "all zero")]
+    #[test_case(&[10.0, 50.0, 150.0], Some(100.0), &[2, 10, 20]; "fixed max")]
+    #[test_case(&[-10.0, 0.0, 10.0], None, &[0, 10, 20]; "negative baseline")]
+    #[test]
+    fn column_heights_are_scaled_to_the_bar(samples: &[f64], max: Option<f64>, expected: &[u32]) {
+        let heights = column_heights(samples.iter().copied(), max, 20);
+
+        assert_eq!(heights, expected);
+    }
+
+    #[test]
+    fn the_oldest_sample_is_dropped_when_full() {
+        let style = TextStyle {
+            fg: 0xebdbb2ff.into(),
+            bg: None,
+            padding: (0, 0),
+            ..Default::default()
+        };
+        let mut s = Sparkline::new(style, 3, || None, Duration::from_secs(1));
+
+        for sample in [1.0, 2.0, f64::NAN, 3.0, 4.0] {
+            s.push(sample);
+        }
+
+        assert_eq!(s.samples(), vec![2.0, 3.0, 4.0]);
+    }
+}
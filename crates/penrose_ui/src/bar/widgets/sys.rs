@@ -1,4 +1,13 @@
 //! System monitor widgets and utility functions
+use crate::{
+    bar::{
+        schedule::UpdateSchedule,
+        widgets::{IntervalText, Widget},
+    },
+    Context, Result, TextStyle,
+};
+use penrose::x::XConn;
+use std::time::Duration;
 
 /// Helper functions for obtaining system information for use in status bar widgets
 pub mod helpers {
@@ -49,10 +58,28 @@ pub mod helpers {
     /// requested battery.
     pub fn battery_text(bat: &str) -> Option<String> {
         let status = read_sys_file(bat, "status")?;
-        let energy_now: u32 = read_sys_file(bat, "charge_now")?.parse().ok()?;
-        let energy_full: u32 = read_sys_file(bat, "charge_full")?.parse().ok()?;
+        let (now, full) = ["charge", "energy"].iter().find_map(|prefix| {
+            let now = read_sys_file(bat, &format!("{prefix}_now"))?.parse().ok()?;
+            let full = read_sys_file(bat, &format!("{prefix}_full"))?
+                .parse()
+                .ok()?;
 
-        let charge = energy_now * 100 / energy_full;
+            Some((now, full))
+        })?;
+
+        format_battery_text(&status, now, full)
+    }
+
+    /// Format the contents of the `status`, `charge_now` and `charge_full` files for a battery
+    /// as its charge percentage along with an icon indicating its current state.
+    ///
+    /// Returns `None` if `full` is `0`.
+    pub fn format_battery_text(status: &str, now: u32, full: u32) -> Option<String> {
+        if full == 0 {
+            return None;
+        }
+
+        let charge = (now as u64 * 100 / full as u64).min(100);
 
         let icon = if status == "Charging" {
             ""
@@ -235,3 +262,79 @@ pub mod interval {
         IntervalText::new(style, move || helpers::amixer_text(channel), interval)
     }
 }
+
+/// Display the current charge level and status of a battery.
+///
+/// The battery state is read from `/sys/class/power_supply/$device` on the interval provided.
+/// If the requested device is not found on this system, this widget will render as an empty
+/// string.
+#[derive(Debug)]
+pub struct Battery {
+    inner: IntervalText,
+}
+
+impl Battery {
+    /// Construct a new [Battery] widget for the named device (e.g. "BAT0").
+    pub fn new(device: impl Into<String>, style: TextStyle, interval: Duration) -> Self {
+        let device = device.into();
+
+        Self {
+            inner: IntervalText::new(style, move || helpers::battery_text(&device), interval),
+        }
+    }
+
+    /// Construct a new [Battery] widget for the first battery device found on this system.
+    pub fn first_found(style: TextStyle, interval: Duration) -> Self {
+        let device = helpers::battery_file_search().unwrap_or_else(|| "BAT0".to_owned());
+
+        Self::new(device, style, interval)
+    }
+}
+
+impl<X: XConn> Widget<X> for Battery {
+    fn draw(&mut self, ctx: &mut Context<'_>, s: usize, f: bool, w: u32, h: u32) -> Result<()> {
+        Widget::<X>::draw(&mut self.inner, ctx, s, f, w, h)
+    }
+
+    fn current_extent(&mut self, ctx: &mut Context<'_>, h: u32) -> Result<(u32, u32)> {
+        Widget::<X>::current_extent(&mut self.inner, ctx, h)
+    }
+
+    fn is_greedy(&self) -> bool {
+        Widget::<X>::is_greedy(&self.inner)
+    }
+
+    fn style(&self) -> Option<&TextStyle> {
+        Widget::<X>::style(&self.inner)
+    }
+
+    fn require_draw(&self) -> bool {
+        Widget::<X>::require_draw(&self.inner)
+    }
+
+    fn update_schedule(&mut self) -> Option<UpdateSchedule> {
+        Widget::<X>::update_schedule(&mut self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::helpers::format_battery_text;
+
+    #[test]
+    fn battery_text_is_formatted_from_sysfs_contents() {
+        let cases = [
+            ("Charging", "2500000", "5000000", Some("\u{f1e6} 50%")),
+            ("Discharging", "4800000", "5000000", Some("\u{f240} 96%")),
+            ("Discharging", "3600000", "5000000", Some("\u{f241} 72%")),
+            ("Discharging", "500000", "5000000", Some("\u{f244} 10%")),
+            ("Full", "5100000", "5000000", Some("\u{f240} 100%")),
+            ("Unknown", "0", "0", None),
+        ];
+
+        for (status, now, full, expected) in cases {
+            let txt = format_battery_text(status, now.parse().unwrap(), full.parse().unwrap());
+            assert_eq!(txt.as_deref(), expected, "{status} {now}/{full}");
+        }
+    }
+}
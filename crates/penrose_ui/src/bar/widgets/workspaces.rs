@@ -5,7 +5,7 @@ use crate::{
     Result,
 };
 use penrose::{
-    core::{ClientSpace, State},
+    core::{bindings::MouseButton, ClientSpace, State},
     pure::geometry::Rect,
     x::XConn,
     Color,
@@ -55,12 +55,19 @@ pub trait WorkspacesUi {
 
     /// The current UI tag string to be shown for a given workspace.
     fn ui_tag(&self, workspace_meta: &WsMeta) -> String {
-        workspace_meta.tag.clone()
+        workspace_meta.name.clone()
     }
 
     /// The background color to be used for the parent [WorkspacesWidget].
     fn background_color(&self) -> Color;
 
+    /// The font to be used for rendering the parent [WorkspacesWidget].
+    ///
+    /// If this is `None` then the default font for the status bar will be used.
+    fn font(&self) -> Option<&str> {
+        None
+    }
+
     /// The foreground and background color to be used for rendering a given workspace.
     ///
     /// The [FocusState] provided indicates the current state of the workspace itself, while
@@ -81,15 +88,24 @@ pub struct DefaultUi {
     fg_2: Color,
     bg_1: Color,
     bg_2: Color,
+    urgent: Color,
+    font: Option<String>,
 }
 
 impl DefaultUi {
-    fn new(style: TextStyle, highlight: impl Into<Color>, empty_fg: impl Into<Color>) -> Self {
+    fn new(
+        style: TextStyle,
+        highlight: impl Into<Color>,
+        empty_fg: impl Into<Color>,
+        urgent: impl Into<Color>,
+    ) -> Self {
         Self {
             fg_1: style.fg,
             fg_2: empty_fg.into(),
             bg_1: highlight.into(),
             bg_2: style.bg.unwrap_or_else(|| 0x000000.into()),
+            urgent: urgent.into(),
+            font: style.font,
         }
     }
 }
@@ -99,9 +115,15 @@ impl WorkspacesUi for DefaultUi {
         self.bg_2
     }
 
+    fn font(&self) -> Option<&str> {
+        self.font.as_deref()
+    }
+
     fn colors_for_workspace(
         &self,
-        &WsMeta { occupied, .. }: &WsMeta,
+        &WsMeta {
+            occupied, urgent, ..
+        }: &WsMeta,
         focus_state: FocusState,
         screen_has_focus: bool,
     ) -> (Color, Color) {
@@ -112,6 +134,7 @@ impl WorkspacesUi for DefaultUi {
             FocusedOnThisScreen if screen_has_focus => (self.fg_2, self.bg_1),
             FocusedOnThisScreen => (self.fg_1, self.fg_2),
             FocusedOnOtherScreen => (self.bg_1, self.fg_2),
+            Unfocused if urgent => (self.bg_2, self.urgent),
             Unfocused if occupied => (self.fg_1, self.bg_2),
             Unfocused => (self.fg_2, self.bg_2),
         }
@@ -123,7 +146,9 @@ impl WorkspacesUi for DefaultUi {
 #[derive(Clone, Debug, PartialEq)]
 pub struct WsMeta {
     tag: String,
+    name: String,
     occupied: bool,
+    urgent: bool,
     extent: (u32, u32),
 }
 
@@ -133,11 +158,23 @@ impl WsMeta {
         &self.tag
     }
 
+    /// The name to display for this workspace (see [Workspace::display_name][0])
+    ///
+    ///   [0]: penrose::pure::Workspace::display_name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Whether or not this workspace currently contains any clients
     pub fn occupied(&self) -> bool {
         self.occupied
     }
 
+    /// Whether or not this workspace currently contains any urgent clients
+    pub fn urgent(&self) -> bool {
+        self.urgent
+    }
+
     fn from_state<X>(state: &State<X>) -> Vec<Self>
     where
         X: XConn,
@@ -145,7 +182,10 @@ impl WsMeta {
         state
             .client_set
             .ordered_workspaces()
-            .map(WsMeta::from)
+            .map(|w| WsMeta {
+                urgent: state.workspace_is_urgent(w.tag()),
+                ..WsMeta::from(w)
+            })
             .collect()
     }
 }
@@ -154,7 +194,9 @@ impl From<&ClientSpace> for WsMeta {
     fn from(w: &ClientSpace) -> Self {
         Self {
             tag: w.tag().to_owned(),
+            name: w.display_name().to_owned(),
             occupied: !w.is_empty(),
+            urgent: false,
             extent: (0, 0),
         }
     }
@@ -180,8 +222,17 @@ pub type Workspaces = WorkspacesWidget<DefaultUi>;
 
 impl Workspaces {
     /// Construct a new [WorkspacesWidget] using the [DefaultUi].
-    pub fn new(style: TextStyle, highlight: impl Into<Color>, empty_fg: impl Into<Color>) -> Self {
-        WorkspacesWidget::new_with_ui(DefaultUi::new(style, highlight, empty_fg))
+    ///
+    /// Workspaces that are not currently focused but contain urgent clients are rendered
+    /// using `urgent_color` as their background. Urgency is cleared by penrose once the
+    /// client in question is focused, at which point the workspace is drawn as normal.
+    pub fn new(
+        style: TextStyle,
+        highlight: impl Into<Color>,
+        empty_fg: impl Into<Color>,
+        urgent_color: impl Into<Color>,
+    ) -> Self {
+        WorkspacesWidget::new_with_ui(DefaultUi::new(style, highlight, empty_fg, urgent_color))
     }
 }
 
@@ -230,7 +281,7 @@ where
         if ui_updated || tags_changed {
             self.require_draw = true;
             self.extent = None;
-        } else if self.focused_ws != focused_ws || self.occupied_changed(&wss) {
+        } else if self.focused_ws != focused_ws || self.status_changed(&wss) {
             self.require_draw = true;
         }
 
@@ -245,11 +296,11 @@ where
     }
 
     // Called after tags_changed above so we assume that tags are matching
-    fn occupied_changed(&self, workspaces: &[WsMeta]) -> bool {
+    fn status_changed(&self, workspaces: &[WsMeta]) -> bool {
         self.workspaces
             .iter()
             .zip(workspaces)
-            .any(|(l, r)| l.occupied != r.occupied)
+            .any(|(l, r)| l.occupied != r.occupied || l.urgent != r.urgent || l.name != r.name)
     }
 
     fn ws_colors(&self, meta: &WsMeta, screen: usize, screen_has_focus: bool) -> (Color, Color) {
@@ -267,6 +318,20 @@ where
 
         self.ui.colors_for_workspace(meta, state, screen_has_focus)
     }
+
+    // The tag of the workspace rendered at the given horizontal offset from the left edge
+    // of the widget.
+    fn tag_at(&self, offset: u32) -> Option<&str> {
+        let mut start = PADDING;
+        for ws in self.workspaces.iter() {
+            if offset >= start && offset < start + ws.extent.0 {
+                return Some(&ws.tag);
+            }
+            start += ws.extent.0;
+        }
+
+        None
+    }
 }
 
 impl<X, U> Widget<X> for WorkspacesWidget<U>
@@ -323,6 +388,10 @@ where
         false
     }
 
+    fn font(&self) -> Option<&str> {
+        self.ui.font()
+    }
+
     fn require_draw(&self) -> bool {
         self.require_draw
     }
@@ -338,4 +407,96 @@ where
 
         Ok(())
     }
+
+    fn on_click(
+        &mut self,
+        button: MouseButton,
+        offset: u32,
+        state: &mut State<X>,
+        _: &X,
+    ) -> Result<()> {
+        if button == MouseButton::Left {
+            if let Some(tag) = self.tag_at(offset) {
+                state.client_set.focus_tag(tag);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ws(tag: &str, w: u32) -> WsMeta {
+        WsMeta {
+            tag: tag.to_owned(),
+            name: tag.to_owned(),
+            occupied: false,
+            urgent: false,
+            extent: (w, 10),
+        }
+    }
+
+    const FG: u32 = 0xebdbb2ff;
+    const BG: u32 = 0x282828ff;
+    const HIGHLIGHT: u32 = 0x458588ff;
+    const EMPTY: u32 = 0x504945ff;
+    const URGENT: u32 = 0xcc241dff;
+
+    fn test_widget() -> Workspaces {
+        Workspaces::new(
+            TextStyle {
+                fg: FG.into(),
+                bg: Some(BG.into()),
+                padding: (0, 0),
+                ..Default::default()
+            },
+            HIGHLIGHT,
+            EMPTY,
+            URGENT,
+        )
+    }
+
+    #[test]
+    fn urgent_workspaces_use_the_urgent_color() {
+        let mut widget = test_widget();
+        let mut urgent = ws("2", 10);
+        urgent.occupied = true;
+        urgent.urgent = true;
+        widget.workspaces = vec![ws("1", 10), urgent.clone()];
+        widget.focused_ws = vec!["1".to_owned()];
+
+        assert_eq!(
+            widget.ws_colors(&urgent, 0, true),
+            (BG.into(), URGENT.into())
+        );
+
+        // Viewing the workspace takes priority over the urgent styling
+        widget.focused_ws = vec!["2".to_owned()];
+        assert_eq!(
+            widget.ws_colors(&urgent, 0, true),
+            (FG.into(), HIGHLIGHT.into())
+        );
+    }
+
+    #[test]
+    fn clicks_select_the_workspace_under_the_pointer() {
+        let mut widget = test_widget();
+        widget.workspaces = vec![ws("1", 10), ws("2", 20), ws("3", 10)];
+
+        let cases = [
+            (0, None),
+            (PADDING, Some("1")),
+            (PADDING + 9, Some("1")),
+            (PADDING + 10, Some("2")),
+            (PADDING + 35, Some("3")),
+            (PADDING + 40, None),
+        ];
+
+        for (offset, expected) in cases {
+            assert_eq!(widget.tag_at(offset), expected, "offset={offset}");
+        }
+    }
 }
@@ -0,0 +1,248 @@
+//! Parsing for a minimal subset of Pango style markup.
+//!
+//! Markup can be used to style individual segments of a string of text when it is rendered
+//! using [Context::draw_markup][crate::Context::draw_markup]. The following tags are supported:
+//!
+//!   - `<b>` and `<i>`: render the enclosed text in a bold or italic variant of the current font
+//!   - `<span>`: apply the styles given by the following attributes to the enclosed text:
+//!     - `foreground` (or `fg` / `color`): a hex color code such as `#cc241d`
+//!     - `weight`: either `bold` or `normal`
+//!     - `style`: either `italic` or `normal`
+//!
+//! Tags may be nested. The entities `&lt;`, `&gt;`, `&amp;`, `&quot;` and `&apos;` can be used
+//! to include the corresponding characters as literal text.
+use crate::{Error, Result};
+use penrose::Color;
+
+/// A segment of text along with the styles that should be applied when rendering it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The text to be rendered
+    pub text: String,
+    /// The color to render the text with (defaults to the color passed when drawing if None)
+    pub fg: Option<Color>,
+    /// Whether or not the text should be rendered using a bold font
+    pub bold: bool,
+    /// Whether or not the text should be rendered using an italic font
+    pub italic: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Style {
+    fg: Option<Color>,
+    bold: bool,
+    italic: bool,
+}
+
+/// Parse the given markup into a list of styled [Span]s.
+///
+/// Empty segments of text are not included in the output.
+pub fn parse_markup(markup: &str) -> Result<Vec<Span>> {
+    let invalid = |reason: String| Error::InvalidMarkup {
+        markup: markup.to_owned(),
+        reason,
+    };
+
+    let mut spans: Vec<Span> = Vec::new();
+    let mut stack: Vec<(&str, Style)> = Vec::new();
+    let mut rest = markup;
+
+    while !rest.is_empty() {
+        let style = stack.last().map(|(_, s)| *s).unwrap_or_default();
+
+        let (text, tail) = match rest.find('<') {
+            Some(ix) => rest.split_at(ix),
+            None => (rest, ""),
+        };
+
+        if !text.is_empty() {
+            spans.push(Span {
+                text: unescape(text).map_err(invalid)?,
+                fg: style.fg,
+                bold: style.bold,
+                italic: style.italic,
+            });
+        }
+
+        if tail.is_empty() {
+            break;
+        }
+
+        let end = tail
+            .find('>')
+            .ok_or_else(|| invalid("unterminated tag".to_owned()))?;
+        let tag = &tail[1..end];
+        rest = &tail[end + 1..];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            match stack.pop() {
+                Some((open, _)) if open == name.trim() => (),
+                Some((open, _)) => return Err(invalid(format!("expected </{open}>, got <{tag}>"))),
+                None => return Err(invalid(format!("unexpected closing tag <{tag}>"))),
+            }
+        } else {
+            let (name, attrs) = match tag.split_once(char::is_whitespace) {
+                Some((name, attrs)) => (name, attrs),
+                None => (tag, ""),
+            };
+            let style = apply_tag(name, attrs, style).map_err(invalid)?;
+            stack.push((name, style));
+        }
+    }
+
+    if let Some((open, _)) = stack.last() {
+        return Err(invalid(format!("unclosed tag <{open}>")));
+    }
+
+    Ok(spans)
+}
+
+/// Remove all markup from the given string, returning the text that would be rendered.
+pub fn strip_markup(markup: &str) -> Result<String> {
+    let spans = parse_markup(markup)?;
+
+    Ok(spans.into_iter().map(|s| s.text).collect())
+}
+
+fn apply_tag(name: &str, attrs: &str, mut style: Style) -> std::result::Result<Style, String> {
+    match name {
+        "b" => style.bold = true,
+        "i" => style.italic = true,
+        "span" => {
+            for (k, v) in parse_attrs(attrs)? {
+                match (k, v) {
+                    ("foreground" | "fg" | "color", v) => {
+                        let c = Color::try_from(v).map_err(|_| format!("invalid color: {v}"))?;
+                        style.fg = Some(c);
+                    }
+                    ("weight", "bold") => style.bold = true,
+                    ("weight", "normal") => style.bold = false,
+                    ("style", "italic") => style.italic = true,
+                    ("style", "normal") => style.italic = false,
+                    (k, v) => return Err(format!("unsupported attribute: {k}=\"{v}\"")),
+                }
+            }
+        }
+        _ => return Err(format!("unsupported tag <{name}>")),
+    }
+
+    if name != "span" && !attrs.trim().is_empty() {
+        return Err(format!("unexpected attributes for <{name}>"));
+    }
+
+    Ok(style)
+}
+
+fn parse_attrs(mut s: &str) -> std::result::Result<Vec<(&str, &str)>, String> {
+    let mut attrs = Vec::new();
+
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            return Ok(attrs);
+        }
+
+        let (k, rest) = s
+            .split_once('=')
+            .ok_or_else(|| format!("malformed attributes: {s}"))?;
+        let rest = rest.trim_start();
+        let quote = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => return Err(format!("unquoted attribute value: {rest}")),
+        };
+        let (v, rest) = rest[1..]
+            .split_once(quote)
+            .ok_or_else(|| format!("unterminated attribute value: {rest}"))?;
+
+        attrs.push((k.trim(), v));
+        s = rest;
+    }
+}
+
+fn unescape(s: &str) -> std::result::Result<String, String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(ix) = rest.find('&') {
+        unescaped.push_str(&rest[..ix]);
+        let end = rest[ix..]
+            .find(';')
+            .ok_or_else(|| format!("unterminated entity: {}", &rest[ix..]))?;
+
+        unescaped.push(match &rest[ix + 1..ix + end] {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            entity => return Err(format!("unknown entity: &{entity};")),
+        });
+        rest = &rest[ix + end + 1..];
+    }
+    unescaped.push_str(rest);
+
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_test_case::test_case;
+
+    fn span(text: &str, fg: Option<u32>, bold: bool, italic: bool) -> Span {
+        Span {
+            text: text.to_owned(),
+            fg: fg.map(Color::from),
+            bold,
+            italic,
+        }
+    }
+
+    #[test_case("plain text", vec![span("plain text", None, false, false)]; "plain")]
+    #[test_case("<b>bold</b>", vec![span("bold", None, true, false)]; "bold")]
+    #[test_case("<i>italic</i>", vec![span("italic", None, false, true)]; "italic")]
+    #[test_case(
+        "load: <span foreground=\"#cc241d\">1.50</span>",
+        vec![span("load: ", None, false, false), span("1.50", Some(0xcc241dff), false, false)];
+        "colored span"
+    )]
+    #[test_case(
+        "<span color='#cc241d' weight=\"bold\">a<i>b</i></span>c",
+        vec![
+            span("a", Some(0xcc241dff), true, false),
+            span("b", Some(0xcc241dff), true, true),
+            span("c", None, false, false),
+        ];
+        "nested"
+    )]
+    #[test_case("a &lt;b&gt; &amp; c", vec![span("a <b> & c", None, false, false)]; "entities")]
+    #[test_case("<b></b>", vec![]; "empty tag")]
+    #[test]
+    fn valid_markup_parses(markup: &str, expected: Vec<Span>) {
+        assert_eq!(parse_markup(markup).unwrap(), expected);
+    }
+
+    #[test_case("<b>unclosed"; "unclosed tag")]
+    #[test_case("<b>mismatched</i>"; "mismatched tag")]
+    #[test_case("</b>"; "unexpected closing tag")]
+    #[test_case("<b"; "unterminated tag")]
+    #[test_case("<u>underline</u>"; "unsupported tag")]
+    #[test_case("<span size=\"large\">big</span>"; "unsupported attribute")]
+    #[test_case("<span fg=\"red\">red</span>"; "invalid color")]
+    #[test_case("<span fg=#ff0000>red</span>"; "unquoted attribute")]
+    #[test_case("a &nbsp; b"; "unknown entity")]
+    #[test_case("a & b"; "unterminated entity")]
+    #[test]
+    fn invalid_markup_errors(markup: &str) {
+        let res = parse_markup(markup);
+
+        assert!(matches!(res, Err(Error::InvalidMarkup { .. })), "{res:?}");
+    }
+
+    #[test]
+    fn strip_markup_returns_the_rendered_text() {
+        let stripped = strip_markup("<b>load</b>: <span fg=\"#cc241d\">1.50</span>").unwrap();
+
+        assert_eq!(stripped, "load: 1.50");
+    }
+}
@@ -36,17 +36,22 @@ use x11::{
 };
 
 mod fontset;
+pub mod markup;
+
 use fontset::Fontset;
+use markup::{parse_markup, Span};
 
 // Xlib manual: https://www.x.org/releases/current/doc/libX11/libX11/libX11.pdf
 
 pub(crate) const SCREEN: i32 = 0;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// A set of styling options for a text string that is to be rendered using [Draw].
 ///
-/// The font itself is specified on the [Draw] instance when it is created or by using the
-/// `set_font` method.
+/// Unless a font is set on the style, the font used is the one specified on the [Draw] instance
+/// when it is created or by using the `set_font` method.
+///
+/// The [Default] style renders white text with no background, padding or font.
 pub struct TextStyle {
     /// The foreground color to be used for rendering the text itself.
     pub fg: Color,
@@ -54,6 +59,31 @@ pub struct TextStyle {
     pub bg: Option<Color>,
     /// Padding in pixels around the text to the left and right.
     pub padding: (u32, u32),
+    /// The font to use for rendering the text (defaults to the font of the parent [StatusBar][0]
+    /// if None).
+    ///
+    ///   [0]: crate::StatusBar
+    pub font: Option<String>,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            fg: 0xffffffff.into(),
+            bg: None,
+            padding: (0, 0),
+            font: None,
+        }
+    }
+}
+
+impl TextStyle {
+    /// Set the font to be used when rendering text with this style.
+    pub fn with_font(mut self, font: impl Into<String>) -> Self {
+        self.font = Some(font.into());
+
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -278,10 +308,8 @@ impl Draw {
             dpy: self.dpy,
             s,
             bg: self.bg,
-            fs: self
-                .fss
-                .get_mut(&self.active_font)
-                .expect("active_font to be present"),
+            font: self.active_font.clone(),
+            fss: &mut self.fss,
             colors: &mut self.colors,
         })
     }
@@ -318,7 +346,8 @@ pub struct Context<'a> {
     dpy: *mut Display,
     s: &'a Surface,
     bg: Color,
-    fs: &'a mut Fontset,
+    font: String,
+    fss: &'a mut HashMap<String, Fontset>,
     colors: &'a mut HashMap<Color, XColor>,
 }
 
@@ -435,6 +464,39 @@ impl<'a> Context<'a> {
         self.fill_rect(r, self.bg)
     }
 
+    /// Set the font used for rendering text in future drawing operations.
+    ///
+    /// The font will be loaded if it has not already been added to the parent [Draw].
+    pub fn set_font(&mut self, font: &str, point_size: u8) -> Result<()> {
+        let k = font_key(font, point_size);
+        if let Entry::Vacant(e) = self.fss.entry(k.clone()) {
+            let fs = Fontset::try_new(self.dpy, e.key())?;
+            e.insert(fs);
+        }
+        self.font = k;
+
+        Ok(())
+    }
+
+    // Bold and italic variants of the active font are loaded the first time they are needed
+    fn fontset(&mut self, bold: bool, italic: bool) -> Result<&mut Fontset> {
+        let mut k = self.font.to_owned();
+        if bold {
+            k.push_str(":weight=bold");
+        }
+        if italic {
+            k.push_str(":slant=italic");
+        }
+
+        match self.fss.entry(k) {
+            Entry::Occupied(e) => Ok(e.into_mut()),
+            Entry::Vacant(e) => {
+                let fs = Fontset::try_new(self.dpy, e.key())?;
+                Ok(e.insert(fs))
+            }
+        }
+    }
+
     /// Render the provided text at the current context offset using the supplied color.
     pub fn draw_text(
         &mut self,
@@ -442,6 +504,37 @@ impl<'a> Context<'a> {
         h_offset: u32,
         padding: (u32, u32),
         c: Color,
+    ) -> Result<(u32, u32)> {
+        let span = Span {
+            text: txt.to_owned(),
+            ..Default::default()
+        };
+
+        self.draw_spans(&[span], h_offset, padding, c)
+    }
+
+    /// Render the provided Pango style markup at the current context offset, using the supplied
+    /// color for any text that does not have a color specified in the markup.
+    ///
+    /// See the [markup] module for details of the supported markup.
+    pub fn draw_markup(
+        &mut self,
+        markup: &str,
+        h_offset: u32,
+        padding: (u32, u32),
+        c: Color,
+    ) -> Result<(u32, u32)> {
+        let spans = parse_markup(markup)?;
+
+        self.draw_spans(&spans, h_offset, padding, c)
+    }
+
+    fn draw_spans(
+        &mut self,
+        spans: &[Span],
+        h_offset: u32,
+        padding: (u32, u32),
+        c: Color,
     ) -> Result<(u32, u32)> {
         // SAFETY:
         //   - the pointers for self.dpy and s.drawable are known to be non-null
@@ -459,39 +552,46 @@ impl<'a> Context<'a> {
         let _drop_draw = DropXftDraw { ptr: d };
 
         let (lpad, rpad) = (padding.0 as i32, padding.1);
-        let (mut x, y) = (lpad + self.dx, self.dy);
-        let (mut total_w, mut total_h) = (x as u32, 0);
-        let xcol = self.get_or_try_init_xcolor(c)?;
-
-        for (chunk, fm) in self.fs.per_font_chunks(txt).into_iter() {
-            let fnt = self.fs.fnt(fm);
-            let (chunk_w, chunk_h) = fnt.get_exts(self.dpy, chunk)?;
-
-            // SAFETY: fnt pointer is non-null
-            let chunk_y = unsafe { y + h_offset as i32 + (*fnt.xfont).ascent };
-            let c_str = CString::new(chunk)?;
-
-            // SAFETY:
-            // - fnt.xfont is known to be non-null
-            // - the string character pointer and length have been obtained from a Rust CString
-            unsafe {
-                XftDrawStringUtf8(
-                    d,
-                    xcol,
-                    fnt.xfont,
-                    x,
-                    chunk_y,
-                    c_str.as_ptr() as *mut _,
-                    c_str.as_bytes().len() as i32,
-                );
+        let (x, y) = (lpad + self.dx, self.dy);
+        let dpy = self.dpy;
+
+        let (w, h) = layout_spans(spans, x, |span, mut x| {
+            let xcol = self.get_or_try_init_xcolor(span.fg.unwrap_or(c))?;
+            let fs = self.fontset(span.bold, span.italic)?;
+            let (mut span_w, mut span_h) = (0, 0);
+
+            for (chunk, fm) in fs.per_font_chunks(&span.text).into_iter() {
+                let fnt = fs.fnt(fm);
+                let (chunk_w, chunk_h) = fnt.get_exts(dpy, chunk)?;
+
+                // SAFETY: fnt pointer is non-null
+                let chunk_y = unsafe { y + h_offset as i32 + (*fnt.xfont).ascent };
+                let c_str = CString::new(chunk)?;
+
+                // SAFETY:
+                // - fnt.xfont is known to be non-null
+                // - the string character pointer and length have been obtained from a Rust CString
+                unsafe {
+                    XftDrawStringUtf8(
+                        d,
+                        xcol,
+                        fnt.xfont,
+                        x,
+                        chunk_y,
+                        c_str.as_ptr() as *mut _,
+                        c_str.as_bytes().len() as i32,
+                    );
+                }
+
+                x += chunk_w as i32;
+                span_w += chunk_w;
+                span_h = max(span_h, chunk_h);
             }
 
-            x += chunk_w as i32;
-            total_w += chunk_w;
-            total_h = max(total_h, chunk_h);
-        }
+            Ok((span_w, span_h))
+        })?;
 
-        return Ok((total_w + rpad, total_h));
+        return Ok((x as u32 + w + rpad, h));
 
         // There are multiple error paths here where we need to make sure that we correctly destroy
         // the XftDraw we created. Rather than complicate the error handling we use a Drop wrapper
@@ -511,14 +611,21 @@ impl<'a> Context<'a> {
 
     /// Determine the width and height taken up by a given string in pixels.
     pub fn text_extent(&mut self, txt: &str) -> Result<(u32, u32)> {
-        let (mut w, mut h) = (0, 0);
-        for (chunk, fm) in self.fs.per_font_chunks(txt) {
-            let (cw, ch) = self.fs.fnt(fm).get_exts(self.dpy, chunk)?;
-            w += cw;
-            h = max(h, ch);
-        }
+        let dpy = self.dpy;
+        let fs = self.fontset(false, false)?;
 
-        Ok((w, h))
+        fontset_text_extent(dpy, fs, txt)
+    }
+
+    /// Determine the width and height taken up by the given Pango style markup in pixels once
+    /// it has been rendered.
+    pub fn markup_extent(&mut self, markup: &str) -> Result<(u32, u32)> {
+        let dpy = self.dpy;
+
+        layout_spans(&parse_markup(markup)?, 0, |span, _| {
+            let fs = self.fontset(span.bold, span.italic)?;
+            fontset_text_extent(dpy, fs, &span.text)
+        })
     }
 
     /// Flush pending requests to the X server.
@@ -531,6 +638,35 @@ impl<'a> Context<'a> {
     }
 }
 
+// Lay out each span one after another starting from the given x offset, returning the
+// combined extent. Both drawing and measuring spans run through here so that the extent
+// reported for a piece of markup always matches what is drawn for it.
+fn layout_spans<F>(spans: &[Span], x: i32, mut render: F) -> Result<(u32, u32)>
+where
+    F: FnMut(&Span, i32) -> Result<(u32, u32)>,
+{
+    let (mut x, mut w, mut h) = (x, 0, 0);
+    for span in spans {
+        let (sw, sh) = render(span, x)?;
+        x += sw as i32;
+        w += sw;
+        h = max(h, sh);
+    }
+
+    Ok((w, h))
+}
+
+fn fontset_text_extent(dpy: *mut Display, fs: &mut Fontset, txt: &str) -> Result<(u32, u32)> {
+    let (mut w, mut h) = (0, 0);
+    for (chunk, fm) in fs.per_font_chunks(txt) {
+        let (cw, ch) = fs.fnt(fm).get_exts(dpy, chunk)?;
+        w += cw;
+        h = max(h, ch);
+    }
+
+    Ok((w, h))
+}
+
 #[derive(Debug)]
 struct XColor(*mut XftColor);
 
@@ -574,3 +710,68 @@ unsafe fn try_xftcolor_from_name(dpy: *mut Display, color: &str) -> Result<*mut
         Ok(ptr as *mut XftColor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fixed width font where each character is 10px wide (12px when bold) and 20px tall
+    fn fake_extent(span: &Span) -> (u32, u32) {
+        let char_w = if span.bold { 12 } else { 10 };
+
+        (span.text.chars().count() as u32 * char_w, 20)
+    }
+
+    #[test]
+    fn markup_is_drawn_from_the_stripped_text() {
+        let markup = "<b>load</b>: <span fg=\"#cc241d\">1.50</span>";
+        let spans = parse_markup(markup).unwrap();
+        let mut drawn = Vec::new();
+
+        layout_spans(&spans, 5, |span, x| {
+            drawn.push((span.text.clone(), x));
+            Ok(fake_extent(span))
+        })
+        .unwrap();
+
+        let text: String = drawn.iter().map(|(t, _)| t.as_str()).collect();
+        let offsets: Vec<i32> = drawn.iter().map(|&(_, x)| x).collect();
+
+        assert_eq!(text, markup::strip_markup(markup).unwrap());
+        assert_eq!(offsets, vec![5, 5 + 48, 5 + 48 + 20]);
+    }
+
+    #[test]
+    fn markup_extent_matches_what_is_drawn() {
+        let markup = "<b>load</b>: <span fg=\"#cc241d\">1.50</span>";
+        let spans = parse_markup(markup).unwrap();
+
+        let measured = layout_spans(&spans, 0, |span, _| Ok(fake_extent(span))).unwrap();
+        let mut drawn_w = 0;
+        let drawn = layout_spans(&spans, 5, |span, _| {
+            let (w, h) = fake_extent(span);
+            drawn_w += w;
+            Ok((w, h))
+        })
+        .unwrap();
+
+        assert_eq!(measured, drawn);
+        assert_eq!(measured, (drawn_w, 20));
+        assert_eq!(measured, (48 + 20 + 40, 20));
+    }
+
+    #[test]
+    fn unstyled_markup_has_the_extent_of_its_stripped_text() {
+        let markup = "<span fg=\"#cc241d\">load: 1.50</span>";
+        let plain = Span {
+            text: markup::strip_markup(markup).unwrap(),
+            ..Default::default()
+        };
+
+        let from_markup =
+            layout_spans(&parse_markup(markup).unwrap(), 0, |s, _| Ok(fake_extent(s))).unwrap();
+        let from_text = layout_spans(&[plain], 0, |s, _| Ok(fake_extent(s))).unwrap();
+
+        assert_eq!(from_markup, from_text);
+    }
+}
@@ -42,7 +42,7 @@ pub mod core;
 pub mod layout_viewer;
 
 pub use crate::core::{Context, Draw, TextStyle};
-pub use bar::{Position, StatusBar};
+pub use bar::{Position, Scaling, StatusBar};
 
 use bar::widgets::{ActiveWindowName, CurrentLayout, RootWindowName, Workspaces};
 
@@ -60,6 +60,15 @@ pub enum Error {
     #[error("Unable to find a fallback font for '{0}'")]
     NoFallbackFontForChar(char),
 
+    /// A string of markup could not be parsed
+    #[error("Invalid markup '{markup}': {reason}")]
+    InvalidMarkup {
+        /// The markup that failed to parse
+        markup: String,
+        /// Why the markup was invalid
+        reason: String,
+    },
+
     /// A string being passed to underlying C APIs contained an internal null byte
     #[error(transparent)]
     NulError(#[from] NulError),
@@ -102,6 +111,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 /// Create a default dwm style status bar that displays content pulled from the
 /// WM_NAME property of the root window.
+///
+/// Workspaces containing urgent clients are highlighted using `urgent`.
+#[allow(clippy::too_many_arguments)]
 pub fn status_bar<X: XConn>(
     height: u32,
     font: &str,
@@ -109,6 +121,7 @@ pub fn status_bar<X: XConn>(
     style: TextStyle,
     highlight: impl Into<Color>,
     empty_ws: impl Into<Color>,
+    urgent: impl Into<Color>,
     position: Position,
 ) -> Result<StatusBar<X>> {
     let max_active_window_chars = 80;
@@ -121,14 +134,14 @@ pub fn status_bar<X: XConn>(
         font,
         point_size,
         vec![
-            Box::new(Workspaces::new(style, highlight, empty_ws)),
-            Box::new(CurrentLayout::new(style)),
+            Box::new(Workspaces::new(style.clone(), highlight, empty_ws, urgent)),
+            Box::new(CurrentLayout::new(style.clone())),
             Box::new(ActiveWindowName::new(
                 max_active_window_chars,
                 TextStyle {
                     bg: Some(highlight),
                     padding: (6, 4),
-                    ..style
+                    ..style.clone()
                 },
                 true,
                 false,
@@ -12,7 +12,7 @@ use penrose::{
         actions::{exit, log_current_state, modify_with, send_layout_message, spawn},
         layout::{
             messages::{ExpandMain, IncMain, ShrinkMain},
-            transformers::{Gaps, ReserveTop},
+            transformers::Gaps,
             MainAndStack, Monocle,
         },
     },
@@ -35,6 +35,7 @@ const BLACK: u32 = 0x282828ff;
 const WHITE: u32 = 0xebdbb2ff;
 const GREY: u32 = 0x3c3836ff;
 const BLUE: u32 = 0x458588ff;
+const RED: u32 = 0xcc241dff;
 
 const MAX_MAIN: u32 = 1;
 const RATIO: f32 = 0.6;
@@ -42,6 +43,7 @@ const RATIO_STEP: f32 = 0.1;
 const OUTER_PX: u32 = 5;
 const INNER_PX: u32 = 5;
 const BAR_HEIGHT_PX: u32 = 18;
+const BAR_POSITION: Position = Position::Top;
 
 fn raw_key_bindings() -> HashMap<String, Box<dyn KeyEventHandler<RustConn>>> {
     let mut raw_bindings = map! {
@@ -90,7 +92,7 @@ fn layouts() -> LayoutStack {
         MainAndStack::bottom(MAX_MAIN, RATIO, RATIO_STEP),
         Monocle::boxed()
     )
-    .map(|layout| ReserveTop::wrap(Gaps::wrap(layout, OUTER_PX, INNER_PX), BAR_HEIGHT_PX))
+    .map(|layout| BAR_POSITION.reserve(Gaps::wrap(layout, OUTER_PX, INNER_PX), BAR_HEIGHT_PX))
 }
 
 fn main() -> Result<()> {
@@ -110,9 +112,10 @@ fn main() -> Result<()> {
         fg: WHITE.into(),
         bg: Some(BLACK.into()),
         padding: (2, 2),
+        ..Default::default()
     };
 
-    let bar = status_bar(BAR_HEIGHT_PX, FONT, 8, style, BLUE, GREY, Position::Top).unwrap();
+    let bar = status_bar(BAR_HEIGHT_PX, FONT, 8, style, BLUE, GREY, RED, BAR_POSITION).unwrap();
 
     let wm = bar.add_to(WindowManager::new(
         config,
@@ -5,7 +5,7 @@ use penrose::{
     builtin::{
         actions::{exit, log_current_state, modify_with, send_layout_message, spawn},
         layout::{
-            messages::{ExpandMain, IncMain, ShrinkMain},
+            messages::{DecGaps, ExpandMain, IncGaps, IncMain, ShrinkMain, ToggleGaps},
             transformers::{Gaps, ReflectHorizontal},
             MainAndStack,
         },
@@ -40,6 +40,9 @@ fn raw_key_bindings() -> HashMap<String, Box<dyn KeyEventHandler<RustConn>>> {
         "M-Down" => send_layout_message(|| IncMain(-1)),
         "M-Right" => send_layout_message(|| ExpandMain),
         "M-Left" => send_layout_message(|| ShrinkMain),
+        "M-equal" => send_layout_message(|| IncGaps(2)),
+        "M-minus" => send_layout_message(|| DecGaps(2)),
+        "M-g" => send_layout_message(|| ToggleGaps),
         "M-semicolon" => spawn("dmenu_run"),
         "M-S-s" => log_current_state(),
         "M-Return" => spawn("st"),
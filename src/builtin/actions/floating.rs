@@ -52,6 +52,72 @@ pub fn reposition<X: XConn>(dx: i32, dy: i32) -> Box<dyn KeyEventHandler<X>> {
     })
 }
 
+/// Move focus to the next floating client on the current workspace.
+pub fn focus_next_floating<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    modify_with(|cs| cs.focus_next_floating())
+}
+
+/// Move focus to the next tiled client on the current workspace.
+pub fn focus_next_tiled<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    modify_with(|cs| cs.focus_next_tiled())
+}
+
+/// Move the focused client by a given (x, y) delta if it is floating, keeping it within the
+/// bounds of the current screen.
+///
+/// This is a no-op if the focused client is tiled.
+pub fn move_floating_focused<X: XConn>(dx: i32, dy: i32) -> Box<dyn KeyEventHandler<X>> {
+    key_handler(move |state, x: &X| update_floating_focused(state, x, |r| r.reposition(dx, dy)))
+}
+
+/// Resize the focused client by a given (width, height) delta if it is floating, keeping it
+/// within the bounds of the current screen.
+///
+/// This is a no-op if the focused client is tiled.
+pub fn resize_floating_focused<X: XConn>(dw: i32, dh: i32) -> Box<dyn KeyEventHandler<X>> {
+    key_handler(move |state, x: &X| update_floating_focused(state, x, |r| r.resize(dw, dh)))
+}
+
+/// Move the focused client to the center of the current screen if it is floating, keeping
+/// its current size.
+///
+/// This is a no-op if the focused client is tiled.
+pub fn center_floating_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    key_handler(|state, x: &X| {
+        let s = state.client_set.screens.focus.r;
+        update_floating_focused(state, x, |r| {
+            let clamped = r.clamped_to(&s);
+            *r = clamped.centered_in(&s).unwrap_or(clamped);
+        })
+    })
+}
+
+// Apply `f` to the stored position of the focused client (if it is floating) and then
+// immediately reposition it, clamping the result to the current screen.
+fn update_floating_focused<X: XConn>(
+    state: &mut State<X>,
+    x: &X,
+    f: impl Fn(&mut Rect),
+) -> Result<()> {
+    let cs = &state.client_set;
+    let id = match cs.current_client() {
+        Some(&id) => id,
+        None => return Ok(()),
+    };
+    let s = cs.screens.focus.r;
+    let mut r = match cs.floating.get(&id) {
+        Some(rr) => rr.applied_to(&s),
+        None => return Ok(()),
+    };
+
+    f(&mut r);
+    let r = r.clamped_to(&s);
+
+    state.client_set.float(id, r)?;
+    state.applied_positions.borrow_mut().remove(&id);
+    x.position_client(id, r)
+}
+
 /// Move the currently focused window to the floating layer in its current on screen position
 pub fn float_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
     key_handler(|state, x: &X| {
@@ -83,6 +149,9 @@ pub fn sink_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
 }
 
 /// Sink the current window if it was floating, float it if it was tiled.
+///
+/// Newly floated windows are placed at the position they were last assigned by the
+/// active layout, falling back to their current on screen position if that is unknown.
 pub fn toggle_floating_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
     key_handler(|state, x: &X| {
         let id = match state.client_set.current_client() {
@@ -90,13 +159,12 @@ pub fn toggle_floating_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
             None => return Ok(()),
         };
 
-        let r = x.client_geometry(id)?;
+        let r = match state.client_geometry(id) {
+            Some(r) => r,
+            None => x.client_geometry(id)?,
+        };
 
-        x.modify_and_refresh(state, |cs| {
-            if let Err(err) = cs.toggle_floating_state(id, r) {
-                error!(%err, %id, "unable to float requested client window");
-            }
-        })
+        x.modify_and_refresh(state, |cs| cs.toggle_floating_focused(r))
     })
 }
 
@@ -117,7 +185,12 @@ pub fn float_all<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
 
 /// Sink all floating windows back into their tiled positions
 pub fn sink_all<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
-    modify_with(|cs| cs.floating.clear())
+    modify_with(|cs| cs.sink_all())
+}
+
+/// Sink all floating windows on the current workspace back into their tiled positions
+pub fn sink_all_floating<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    modify_with(|cs| cs.sink_current_workspace())
 }
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -142,6 +215,7 @@ impl ClickData {
         (f)(&mut r, dx, dy);
 
         state.client_set.float(id, r)?;
+        state.applied_positions.borrow_mut().remove(&id);
         x.position_client(id, r)?;
 
         Ok(())
@@ -256,3 +330,94 @@ impl<X: XConn> MouseEventHandler<X> for MouseResizeHandler {
         ClickWrapper::on_motion(self, evt, state, x)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::Config, x::test::TestXConn};
+    use simple_test_case::test_case;
+
+    const SCREEN: Rect = Rect::new(0, 0, 1024, 512);
+
+    fn test_xconn() -> TestXConn {
+        (1..=3).fold(TestXConn::new(vec![SCREEN]), |x, n| {
+            x.with_client(Xid(n), SCREEN)
+        })
+    }
+
+    #[test]
+    fn sink_all_floating_retiles_the_current_workspace() {
+        let x = test_xconn();
+        let mut state = x.new_state(Config::default()).unwrap();
+        let r = Rect::new(256, 128, 256, 128);
+        for n in 1..=3 {
+            state.client_set.insert(Xid(n));
+            state.client_set.float(Xid(n), r).unwrap();
+        }
+
+        sink_all_floating().call(&mut state, &x).unwrap();
+
+        assert!(state.client_set.floating.is_empty());
+        for n in 1..=3 {
+            let pos = x.geometry(Xid(n)).unwrap();
+            assert!(
+                pos != r && pos != SCREEN,
+                "client {n} was not retiled: {pos:?}"
+            );
+        }
+    }
+
+    fn state_with_floating_client(x: &TestXConn) -> State<TestXConn> {
+        let mut state = x.new_state(Config::default()).unwrap();
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+        state
+            .client_set
+            .float(Xid(2), Rect::new(256, 128, 256, 128))
+            .unwrap();
+
+        state
+    }
+
+    fn floating_rect(state: &State<TestXConn>, id: Xid) -> Rect {
+        state.client_set.floating[&id].applied_to(&SCREEN)
+    }
+
+    #[test_case(move_floating_focused(32, -64), Rect::new(288, 64, 256, 128); "reposition")]
+    #[test_case(resize_floating_focused(64, -32), Rect::new(256, 128, 320, 96); "resize")]
+    #[test_case(move_floating_focused(-512, 1024), Rect::new(0, 384, 256, 128); "move clamped")]
+    #[test_case(resize_floating_focused(1024, 0), Rect::new(0, 128, 1024, 128); "resize clamped")]
+    #[test_case(center_floating_focused(), Rect::new(384, 192, 256, 128); "center")]
+    #[test]
+    fn floating_focused_actions_update_and_reposition(
+        mut action: Box<dyn KeyEventHandler<TestXConn>>,
+        expected: Rect,
+    ) {
+        let x = test_xconn();
+        let mut state = state_with_floating_client(&x);
+
+        action.call(&mut state, &x).unwrap();
+
+        assert_eq!(floating_rect(&state, Xid(2)), expected);
+        assert_eq!(x.geometry(Xid(2)), Some(expected));
+        assert_eq!(x.geometry(Xid(1)), Some(SCREEN));
+    }
+
+    #[test_case(move_floating_focused(32, 32); "reposition")]
+    #[test_case(resize_floating_focused(32, 32); "resize")]
+    #[test_case(center_floating_focused(); "center")]
+    #[test]
+    fn floating_focused_actions_ignore_tiled_clients(
+        mut action: Box<dyn KeyEventHandler<TestXConn>>,
+    ) {
+        let x = test_xconn();
+        let mut state = state_with_floating_client(&x);
+        state.client_set.focus_client(&Xid(1));
+
+        action.call(&mut state, &x).unwrap();
+
+        assert_eq!(floating_rect(&state, Xid(2)), Rect::new(256, 128, 256, 128));
+        assert_eq!(x.geometry(Xid(1)), Some(SCREEN));
+        assert_eq!(x.geometry(Xid(2)), Some(SCREEN));
+    }
+}
@@ -0,0 +1,116 @@
+//! Actions for managing fullscreen windows.
+use crate::{
+    builtin::actions::key_handler,
+    core::{bindings::KeyEventHandler, State},
+    x::{atom::Atom, property::Prop, ClientConfig, XConn, XConnExt},
+    Error, Result, Xid,
+};
+use tracing::debug;
+
+/// The possible valid actions to use when manipulating full screen state
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FullScreenAction {
+    /// Force the window out of fullscreen state
+    Remove,
+    /// Force the window into fullscreen state
+    Add,
+    /// Toggle the fullscreen state of the window
+    Toggle,
+}
+
+impl FullScreenAction {
+    /// Parse the action field of a `_NET_WM_STATE` client message.
+    pub fn try_from_u32(raw: u32) -> Option<Self> {
+        match raw {
+            0 => Some(Self::Remove),
+            1 => Some(Self::Add),
+            2 => Some(Self::Toggle),
+            _ => None,
+        }
+    }
+}
+
+/// The current value of `_NET_WM_STATE` for the given client as a list of atom IDs.
+///
+/// Clients may set this property themselves using the `ATOM` type while penrose sets it
+/// using `CARDINAL` so both are accepted here.
+pub(crate) fn net_wm_state<X: XConn>(id: Xid, x: &X) -> Result<Vec<u32>> {
+    match x.get_prop(id, Atom::NetWmState.as_ref())? {
+        Some(Prop::Cardinal(vals)) => Ok(vals),
+        Some(Prop::Atom(atoms)) => atoms
+            .iter()
+            .map(|a| x.intern_atom(a).map(|id| *id))
+            .collect(),
+        _ => Ok(vec![]),
+    }
+}
+
+/// Whether or not the given client currently has `_NET_WM_STATE_FULLSCREEN` set.
+pub(crate) fn is_fullscreen<X: XConn>(id: Xid, x: &X) -> bool {
+    let full_screen = match x.intern_atom(Atom::NetWmStateFullscreen.as_ref()) {
+        Ok(atom) => *atom,
+        Err(_) => return false,
+    };
+
+    net_wm_state(id, x)
+        .map(|wstate| wstate.contains(&full_screen))
+        .unwrap_or(false)
+}
+
+/// Set the fullscreen state of a particular client
+pub fn set_fullscreen_state<X: XConn>(
+    id: Xid,
+    action: FullScreenAction,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    use FullScreenAction::*;
+
+    let net_wm_state_atom = Atom::NetWmState.as_ref();
+    let full_screen = x.intern_atom(Atom::NetWmStateFullscreen.as_ref())?;
+
+    let mut wstate = net_wm_state(id, x).unwrap_or_default();
+
+    let currently_fullscreen = wstate.contains(&full_screen);
+    debug!(%currently_fullscreen, ?action, %id, "setting fullscreen state");
+
+    if action == Add || (action == Toggle && !currently_fullscreen) {
+        let r = state
+            .client_set
+            .screen_for_client(&id)
+            .ok_or(Error::UnknownClient(id))?
+            .r;
+        state.client_set.float(id, r)?;
+        if !currently_fullscreen {
+            wstate.push(*full_screen);
+        }
+        x.set_client_config(id, &[ClientConfig::BorderPx(0)])?; // remove borders
+    } else if currently_fullscreen && (action == Remove || action == Toggle) {
+        // Not using sink as we don't want the fullscreen position to be restored if the client
+        // is floated again later
+        state.client_set.floating.remove(&id);
+        wstate.retain(|&val| val != *full_screen);
+        // replace borders
+        x.set_client_config(id, &[ClientConfig::BorderPx(state.config.border_width)])?;
+    }
+
+    x.set_prop(id, net_wm_state_atom, Prop::Cardinal(wstate))?;
+    x.refresh(state)
+}
+
+/// Toggle the fullscreen state of the currently focused window.
+///
+/// **NOTE**: Clients will only be aware that penrose supports fullscreen windows if you
+///           make use of [add_ewmh_hooks][0].
+///
+///   [0]: crate::extensions::hooks::add_ewmh_hooks
+pub fn toggle_fullscreen<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    key_handler(|state, x: &X| {
+        let id = match state.client_set.current_client() {
+            Some(&id) => id,
+            None => return Ok(()),
+        };
+
+        set_fullscreen_state(id, FullScreenAction::Toggle, state, x)
+    })
+}
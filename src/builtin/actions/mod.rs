@@ -1,13 +1,23 @@
 //! Helpers and pre-defined actions for use in user defined key bindings
 use crate::{
-    core::{bindings::KeyEventHandler, layout::IntoMessage, ClientSet, State},
+    core::{
+        bindings::{KeyBindings, KeyEventHandler},
+        layout::IntoMessage,
+        ClientSet, Config, State,
+    },
+    pure::{
+        geometry::{Direction, Rect},
+        Stack,
+    },
     util,
-    x::{XConn, XConnExt},
-    Result,
+    x::{Query, XConn, XConnExt},
+    Color, Result, Xid,
 };
-use tracing::info;
+use std::{rc::Rc, time::Duration};
+use tracing::{error, info};
 
 pub mod floating;
+pub mod fullscreen;
 
 // NOTE: this is here to force the correct lifetime requirements on closures being
 //       used as handlers. The generic impl in crate::bindings for functions of the
@@ -31,6 +41,55 @@ where
     Box::new(move |s: &mut State<X>, x: &X| x.modify_and_refresh(s, f.clone()))
 }
 
+/// Mutate the [ClientSet] using information queried from the X server and refresh the on
+/// screen state.
+///
+/// The closure is given a reference to the [XConn] alongside the [ClientSet] so that it is
+/// able to look up things such as client properties before deciding how to modify the state.
+///
+/// > **NOTE**: the X server is not updated until after the closure has returned, so any
+/// > queries made from inside the closure will reflect the on screen state from _before_ any
+/// > changes made to the [ClientSet] are applied. Making requests that modify clients directly
+/// > from inside the closure should be avoided as they will be overwritten by the refresh.
+/// > If the closure returns an error, any changes it had already made to the [ClientSet] are
+/// > still applied before the error is returned.
+pub fn modify_with_conn<F, X>(mut f: F) -> Box<dyn KeyEventHandler<X>>
+where
+    F: FnMut(&mut ClientSet, &X) -> Result<()> + 'static,
+    X: XConn,
+{
+    key_handler(move |s: &mut State<X>, x: &X| {
+        let mut res = Ok(());
+        x.modify_and_refresh(s, |cs| res = f(cs, x))?;
+
+        res
+    })
+}
+
+/// Enter the named [KeyMode][crate::core::bindings::KeyMode] and refresh the on screen state
+pub fn enter_mode<X>(name: impl Into<String>) -> Box<dyn KeyEventHandler<X>>
+where
+    X: XConn,
+{
+    let name = name.into();
+
+    key_handler(move |s: &mut State<X>, x: &X| {
+        s.enter_key_mode(&name)?;
+        x.refresh(s)
+    })
+}
+
+/// Exit the active [KeyMode][crate::core::bindings::KeyMode] and refresh the on screen state
+pub fn exit_mode<X>() -> Box<dyn KeyEventHandler<X>>
+where
+    X: XConn,
+{
+    key_handler(|s: &mut State<X>, x: &X| match s.exit_key_mode() {
+        Some(_) => x.refresh(s),
+        None => Ok(()),
+    })
+}
+
 /// Send a message to the currently active layout
 pub fn send_layout_message<F, M, X>(f: F) -> Box<dyn KeyEventHandler<X>>
 where
@@ -59,6 +118,22 @@ where
     })
 }
 
+/// Jump directly to the layout with the given name on the current workspace.
+///
+/// Layouts marked as [skipped when cycling][crate::core::layout::SkipInCycle] can still be
+/// selected using this action. If no layout with the given name is available for the current
+/// workspace then this is a no-op.
+pub fn jump_to_layout<X>(name: impl Into<String>) -> Box<dyn KeyEventHandler<X>>
+where
+    X: XConn,
+{
+    let name = name.into();
+
+    key_handler(move |s: &mut State<X>, x: &X| {
+        x.modify_and_refresh(s, |cs| cs.set_layout_by_name(&name))
+    })
+}
+
 /// Spawn an external program as part of a key binding
 pub fn spawn<X>(program: &'static str) -> Box<dyn KeyEventHandler<X>>
 where
@@ -67,6 +142,343 @@ where
     key_handler(move |_, _| util::spawn(program))
 }
 
+/// How long [spawn_and_focus] waits for a matching client to appear before giving up.
+pub const SPAWN_AND_FOCUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Spawn an external program and focus the first new client matching `query`.
+///
+/// Programs are spawned asynchronously so the client they create may not be the next one to
+/// appear, or it may be moved to another workspace by your manage hook. If no matching client
+/// appears within [SPAWN_AND_FOCUS_TIMEOUT] then focus is left unchanged so that an unrelated
+/// client created later on does not steal focus.
+pub fn spawn_and_focus<Q, X>(program: impl Into<String>, query: Q) -> Box<dyn KeyEventHandler<X>>
+where
+    Q: Query<X> + 'static,
+    X: XConn + 'static,
+{
+    let program = program.into();
+    let query: Rc<dyn Query<X>> = Rc::new(query);
+
+    key_handler(move |s: &mut State<X>, _: &X| {
+        util::spawn(program.as_str())?;
+        s.add_one_shot_manage_hook(
+            SharedQuery(query.clone()),
+            |id: Xid, s: &mut State<X>, _: &X| {
+                s.client_set.focus_client(&id);
+                Ok(())
+            },
+            SPAWN_AND_FOCUS_TIMEOUT,
+        );
+
+        Ok(())
+    })
+}
+
+// Allows a single query to be shared between the hooks registered by each call to a handler
+struct SharedQuery<X: XConn>(Rc<dyn Query<X>>);
+
+impl<X: XConn> Query<X> for SharedQuery<X> {
+    fn run(&self, id: Xid, x: &X) -> Result<bool> {
+        self.0.run(id, x)
+    }
+}
+
+/// Focus the client that has been urgent for the longest, switching to its workspace if
+/// required. The urgency of the client is cleared once it has been focused.
+///
+/// This is a no-op if there are no urgent clients.
+pub fn focus_urgent<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    key_handler(|state: &mut State<X>, x: &X| {
+        let id = match state.urgent_clients().first() {
+            Some(&id) => id,
+            None => return Ok(()),
+        };
+
+        x.modify_and_refresh(state, |cs| cs.focus_client(&id))
+    })
+}
+
+/// Swap the positions of the focused client and the previously focused client, keeping focus
+/// on the currently focused client.
+///
+/// The previously focused client is taken from [State::focus_history]. This is a no-op if
+/// there is no previously focused client or if it is on a different workspace.
+pub fn swap_focus_and_previous<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    key_handler(|state: &mut State<X>, x: &X| {
+        let prev = match state.focus_history() {
+            [_, prev, ..] => *prev,
+            _ => return Ok(()),
+        };
+
+        let cs = &state.client_set;
+        if cs.current_stack().map(|s| s.contains(&prev)) != Some(true) {
+            return Ok(());
+        }
+
+        x.modify_and_refresh(state, |cs| {
+            cs.modify_occupied(|mut s| {
+                s.swap_focus_with(&prev);
+                s
+            })
+        })
+    })
+}
+
+/// Focus the nearest visible client in the given [Direction] from the focused client.
+///
+/// Client positions are taken from the last refresh so clients on all visible screens are
+/// considered. Clients that overlap the focused client along the axis perpendicular to
+/// `dir` are preferred, followed by the client whose center is closest in the direction of
+/// travel and then the client whose center is best aligned with the focused client. Any
+/// remaining ties are broken in favour of the client that is stacked highest.
+///
+/// This is a no-op if there is no focused client or no client in the requested direction.
+pub fn focus_direction<X: XConn>(dir: Direction) -> Box<dyn KeyEventHandler<X>> {
+    key_handler(move |state: &mut State<X>, x: &X| {
+        match client_in_direction(state, dir, |_| true) {
+            Some(id) => x.modify_and_refresh(state, |cs| cs.focus_client(&id)),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Swap the position in the stack of the focused client with that of the nearest tiled client
+/// in the given [Direction], keeping focus on the currently focused client.
+///
+/// Neighbouring clients are selected in the same way as for [focus_direction] but only tiled
+/// clients on the current workspace are considered. This is a no-op if the focused client is
+/// floating or if there is no such client in the requested direction.
+pub fn swap_direction<X: XConn>(dir: Direction) -> Box<dyn KeyEventHandler<X>> {
+    key_handler(move |state: &mut State<X>, x: &X| {
+        let cs = &state.client_set;
+        let is_tiled = |id: &Xid| {
+            cs.current_stack().map(|s| s.contains(id)) == Some(true) && !cs.is_floating(id)
+        };
+
+        match cs.current_client() {
+            Some(focused) if is_tiled(focused) => (),
+            _ => return Ok(()),
+        }
+
+        match client_in_direction(state, dir, is_tiled) {
+            Some(id) => x.modify_and_refresh(state, |cs| {
+                cs.modify_occupied(|mut s| {
+                    s.swap_focus_with(&id);
+                    s
+                })
+            }),
+            None => Ok(()),
+        }
+    })
+}
+
+// The nearest visible client to the focused client in the given direction out of those
+// passing the provided filter.
+fn client_in_direction<X, F>(state: &State<X>, dir: Direction, f: F) -> Option<Xid>
+where
+    X: XConn,
+    F: Fn(&Xid) -> bool,
+{
+    let &focused = state.client_set.current_client()?;
+    let r = state.client_geometry(focused)?;
+    let mut candidates: Vec<(Xid, Rect)> = state
+        .clients_in_stacking_order()
+        .filter(|id| *id != focused && f(id))
+        .flat_map(|id| state.client_geometry(id).map(|r| (id, r)))
+        .collect();
+    candidates.reverse(); // topmost first so that it wins ties
+
+    nearest_in_direction(r, dir, candidates)
+}
+
+fn nearest_in_direction(
+    r: Rect,
+    dir: Direction,
+    candidates: impl IntoIterator<Item = (Xid, Rect)>,
+) -> Option<Xid> {
+    let center = |r: Rect| (r.x as i64 * 2 + r.w as i64, r.y as i64 * 2 + r.h as i64);
+    let overlaps = |a: (u32, u32), b: (u32, u32)| a.0 < b.0 + b.1 && b.0 < a.0 + a.1;
+    let (cx, cy) = center(r);
+
+    candidates
+        .into_iter()
+        .filter_map(|(id, other)| {
+            let (ox, oy) = center(other);
+            let (travel, offset, aligned) = match dir {
+                Direction::Left => (cx - ox, oy - cy, overlaps((r.y, r.h), (other.y, other.h))),
+                Direction::Right => (ox - cx, oy - cy, overlaps((r.y, r.h), (other.y, other.h))),
+                Direction::Up => (cy - oy, ox - cx, overlaps((r.x, r.w), (other.x, other.w))),
+                Direction::Down => (oy - cy, ox - cx, overlaps((r.x, r.w), (other.x, other.w))),
+            };
+
+            (travel > 0).then_some(((!aligned, travel, offset.abs()), id))
+        })
+        .min_by_key(|&(key, _)| key)
+        .map(|(_, id)| id)
+}
+
+/// Focus the workspace with the given tag, creating it first if it does not already exist.
+///
+/// See [ClientSet::add_tag] for details of how new workspaces are created.
+pub fn add_and_focus_tag<X: XConn>(tag: impl Into<String>) -> Box<dyn KeyEventHandler<X>> {
+    let tag = tag.into();
+
+    modify_with(move |cs| {
+        cs.ensure_tag(tag.as_str());
+        cs.focus_tag(&tag);
+    })
+}
+
+/// Minimize the currently focused client, hiding it until it is restored using
+/// [restore_last_minimized].
+///
+/// See [ClientSet::minimize] for details.
+pub fn minimize_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    modify_with(|cs| cs.minimize_focused())
+}
+
+/// Restore the most recently minimized client from the current workspace.
+///
+/// This is a no-op if no clients have been minimized from the current workspace.
+pub fn restore_last_minimized<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    modify_with(|cs| {
+        cs.restore_last_minimized();
+    })
+}
+
+/// Toggle the focused client between its current state and filling the usable region of its
+/// screen.
+///
+/// Unlike [toggle_fullscreen][fullscreen::toggle_fullscreen], maximized clients respect the
+/// space reserved for status bars and the gaps of the active layout and `_NET_WM_STATE` is
+/// left unchanged. The maximized client is floated and repeating the action returns it to
+/// its previous state: either tiled or floating in its previous position. Clients that are sunk
+/// or moved after being maximized are maximized again by the next toggle.
+pub fn toggle_maximize_focused<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    key_handler(|state: &mut State<X>, x: &X| {
+        let id = match state.client_set.current_client() {
+            Some(&id) => id,
+            None => return Ok(()),
+        };
+
+        // Clients that have been sunk or moved since they were maximized are no longer
+        // maximized, so toggling maximizes them again rather than restoring them.
+        let cs = &state.client_set;
+        state
+            .maximized
+            .retain(|id, (max, _)| cs.floating.get(id) == Some(max));
+
+        if let Some((_, prev)) = state.maximized.remove(&id) {
+            return x.modify_and_refresh(state, |cs| match prev {
+                Some(rr) => {
+                    cs.floating.insert(id, rr);
+                }
+                None => {
+                    cs.floating.remove(&id);
+                }
+            });
+        }
+
+        let r = match maximized_region(state, id) {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+        let prev = state.client_set.floating.get(&id).copied();
+
+        x.modify_and_refresh(state, |cs| {
+            if let Err(e) = cs.float(id, r) {
+                error!(%e, %id, "unable to maximize client");
+            }
+        })?;
+
+        if let Some(&max) = state.client_set.floating.get(&id) {
+            state.maximized.insert(id, (max, prev));
+        }
+
+        Ok(())
+    })
+}
+
+// The position the active layout gives to a single client within the usable region of the
+// screen containing the given client, so that any reserved space and gaps are respected.
+fn maximized_region<X: XConn>(state: &State<X>, id: Xid) -> Option<Rect> {
+    let s = state.client_set.screen_for_client(&id)?;
+    let r = state.usable_screen_region(s.r);
+    let mut layout = s.workspace.layouts.focused().boxed_clone();
+    let (_, positions) = layout.layout(&Stack::new([], id, []), r);
+
+    let maximized = positions
+        .into_iter()
+        .find(|&(c, _)| c == id)
+        .map(|(_, r)| r)
+        .unwrap_or(r);
+
+    Some(maximized)
+}
+
+/// Remove the currently focused workspace, moving any clients it contains to a fallback
+/// workspace.
+///
+/// See [ClientSet::remove_tag] for details of how the fallback workspace is selected.
+pub fn remove_focused_tag<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
+    key_handler(|s: &mut State<X>, x: &X| {
+        let tag = s.client_set.current_tag().to_owned();
+        s.client_set.remove_tag(&tag)?;
+
+        x.refresh(s)
+    })
+}
+
+/// Paint the background of every screen with the given [Color].
+///
+/// This is useful for setting a plain background before starting a compositor or a program
+/// for setting your wallpaper (which will replace it).
+pub fn set_background_color<X: XConn>(color: impl Into<Color>) -> Box<dyn KeyEventHandler<X>> {
+    set_background_per_screen(vec![color.into()])
+}
+
+/// Paint the background of each screen with its own [Color], ordered by screen index.
+///
+/// If there are more screens than colors then the remaining screens use the last color given.
+/// Nothing is painted if `colors` is empty.
+pub fn set_background_per_screen<X: XConn>(colors: Vec<Color>) -> Box<dyn KeyEventHandler<X>> {
+    key_handler(move |s: &mut State<X>, x: &X| {
+        let last = match colors.last() {
+            Some(&c) => c,
+            None => return Ok(()),
+        };
+
+        let mut screens: Vec<_> = s.client_set.screens().map(|s| (s.index(), s.r)).collect();
+        screens.sort_by_key(|&(ix, _)| ix);
+
+        let fills: Vec<(Rect, Color)> = screens
+            .into_iter()
+            .map(|(ix, r)| (r, colors.get(ix).copied().unwrap_or(last)))
+            .collect();
+
+        x.set_root_background(&fills)
+    })
+}
+
+/// Reload the config and key bindings of the running window manager.
+///
+/// `load` is called each time the action runs and the config and key bindings it returns are
+/// applied once the current key press has been handled: see
+/// [WindowManager::reload][crate::core::WindowManager::reload] for details of what is updated.
+/// If `load` returns an error then the current config and key bindings are left in place.
+pub fn reload<F, X>(mut load: F) -> Box<dyn KeyEventHandler<X>>
+where
+    F: FnMut() -> Result<(Config<X>, KeyBindings<X>)> + 'static,
+    X: XConn,
+{
+    key_handler(move |s: &mut State<X>, _: &X| {
+        let (config, key_bindings) = load()?;
+        s.request_reload(config, key_bindings);
+
+        Ok(())
+    })
+}
+
 /// Exit penrose
 ///
 /// Signal the `WindowManager` to exit it's main event loop.
@@ -102,3 +514,472 @@ pub fn remove_and_unmap_focused_client<X: XConn>() -> Box<dyn KeyEventHandler<X>
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builtin::layout::{transformers::Gaps, Grid, MainAndStack, Monocle},
+        core::{layout::SkipInCycle, Config},
+        stack,
+        x::{
+            property::{Strut, WmState},
+            test::TestXConn,
+            Atom, Prop,
+        },
+    };
+    use simple_test_case::test_case;
+
+    fn action_xconn() -> TestXConn {
+        let r = Rect::new(0, 0, 1000, 800);
+        let class = |c: &str| Prop::UTF8String(vec![c.to_lowercase(), c.to_owned()]);
+
+        (1..=3).fold(TestXConn::new(vec![r]), |x, id| {
+            let c = if id == 1 { "Firefox" } else { "Alacritty" };
+            x.with_client(Xid(id), r)
+                .with_prop(Xid(id), Atom::WmClass.as_ref(), class(c))
+        })
+    }
+
+    #[test]
+    fn modify_with_conn_can_query_the_x_server() {
+        let x = action_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+
+        let mut handler = modify_with_conn(|cs: &mut ClientSet, x: &TestXConn| {
+            let clients: Vec<Xid> = cs.current_workspace().clients().copied().collect();
+            for id in clients {
+                if let Some(Prop::UTF8String(strs)) = x.get_prop(id, Atom::WmClass.as_ref())? {
+                    if strs[1] == "Firefox" {
+                        cs.move_client_to_tag(&id, "2");
+                    }
+                }
+            }
+
+            Ok(())
+        });
+
+        handler.call(&mut state, &x).unwrap();
+
+        assert_eq!(state.client_set.tag_for_client(&Xid(1)), Some("2"));
+        assert_eq!(state.client_set.tag_for_client(&Xid(2)), Some("1"));
+    }
+
+    fn positioned_clients(state: &State<TestXConn>) -> Vec<Xid> {
+        let mut ids: Vec<Xid> = state
+            .diff
+            .after
+            .positions
+            .iter()
+            .map(|&(id, _)| id)
+            .collect();
+        ids.sort();
+
+        ids
+    }
+
+    #[test]
+    fn minimized_clients_are_hidden_until_restored() {
+        let x = action_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+
+        minimize_focused().call(&mut state, &x).unwrap();
+
+        assert_eq!(positioned_clients(&state), vec![Xid(1)]);
+        assert_eq!(x.wm_state(Xid(2)), Some(WmState::Iconic));
+        assert!(state.client_set.is_minimized(&Xid(2)));
+
+        restore_last_minimized().call(&mut state, &x).unwrap();
+
+        assert_eq!(positioned_clients(&state), vec![Xid(1), Xid(2)]);
+        assert_eq!(x.wm_state(Xid(2)), Some(WmState::Normal));
+        assert_eq!(state.client_set.current_client(), Some(&Xid(2)));
+        assert!(!state.client_set.is_minimized(&Xid(2)));
+    }
+
+    fn stack_order(state: &State<TestXConn>) -> Vec<Xid> {
+        state
+            .client_set
+            .current_workspace()
+            .clients()
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn swap_focus_and_previous_swaps_stack_positions() {
+        let x = action_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        for id in 1..=3 {
+            x.modify_and_refresh(&mut state, |cs| cs.insert(Xid(id)))
+                .unwrap();
+        }
+        x.modify_and_refresh(&mut state, |cs| cs.focus_client(&Xid(1)))
+            .unwrap();
+
+        assert_eq!(state.focus_history(), &[Xid(1), Xid(3), Xid(2)]);
+        assert_eq!(stack_order(&state), vec![Xid(3), Xid(2), Xid(1)]);
+
+        swap_focus_and_previous().call(&mut state, &x).unwrap();
+
+        assert_eq!(stack_order(&state), vec![Xid(1), Xid(2), Xid(3)]);
+        assert_eq!(state.client_set.current_client(), Some(&Xid(1)));
+    }
+
+    #[test]
+    fn swap_focus_and_previous_is_a_no_op_across_workspaces() {
+        let x = action_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        for id in 1..=3 {
+            x.modify_and_refresh(&mut state, |cs| cs.insert(Xid(id)))
+                .unwrap();
+        }
+        x.modify_and_refresh(&mut state, |cs| cs.move_client_to_tag(&Xid(3), "2"))
+            .unwrap();
+        assert_eq!(state.focus_history()[1], Xid(3));
+        let before = stack_order(&state);
+
+        swap_focus_and_previous().call(&mut state, &x).unwrap();
+
+        assert_eq!(stack_order(&state), before);
+    }
+
+    fn maximize_test_state(x: &TestXConn) -> State<TestXConn> {
+        let config = Config {
+            default_layouts: Stack::new([], Gaps::wrap(MainAndStack::boxed_default(), 5, 5), []),
+            ..Config::default()
+        };
+        let mut state = x.new_state(config).expect("valid test state");
+        state.struts.insert(Xid(42), Strut::new(0, 0, 20, 0));
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+
+        state
+    }
+
+    #[test]
+    fn toggle_maximize_focused_fills_the_usable_region() {
+        let x = TestXConn::default();
+        let mut state = maximize_test_state(&x);
+        let tiled = state.client_geometry(Xid(2)).unwrap();
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert!(state.client_set.is_floating(&Xid(2)));
+        assert_eq!(
+            state.client_geometry(Xid(2)),
+            Some(Rect::new(10, 30, 1900, 1040))
+        );
+        assert_eq!(x.prop(Xid(2), Atom::NetWmState.as_ref()), None);
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert!(!state.client_set.is_floating(&Xid(2)));
+        assert_eq!(state.client_geometry(Xid(2)), Some(tiled));
+    }
+
+    #[test]
+    fn toggle_maximize_focused_restores_floating_positions() {
+        let x = TestXConn::default();
+        let mut state = maximize_test_state(&x);
+        let r = Rect::new(100, 100, 400, 300);
+        x.modify_and_refresh(&mut state, |cs| cs.float(Xid(2), r).unwrap())
+            .unwrap();
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert!(state.client_set.is_floating(&Xid(2)));
+        assert_eq!(state.client_geometry(Xid(2)), Some(r));
+    }
+
+    #[test]
+    fn toggle_maximize_focused_maximizes_clients_sunk_since_they_were_maximized() {
+        let x = TestXConn::default();
+        let mut state = maximize_test_state(&x);
+        let tiled = state.client_geometry(Xid(2)).unwrap();
+        let r = Rect::new(100, 100, 400, 300);
+        x.modify_and_refresh(&mut state, |cs| cs.float(Xid(2), r).unwrap())
+            .unwrap();
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.sink(&Xid(2));
+        })
+        .unwrap();
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert!(state.client_set.is_floating(&Xid(2)));
+        assert_eq!(
+            state.client_geometry(Xid(2)),
+            Some(Rect::new(10, 30, 1900, 1040))
+        );
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert!(!state.client_set.is_floating(&Xid(2)));
+        assert_eq!(state.client_geometry(Xid(2)), Some(tiled));
+    }
+
+    #[test]
+    fn toggle_maximize_focused_maximizes_clients_moved_since_they_were_maximized() {
+        let x = TestXConn::default();
+        let mut state = maximize_test_state(&x);
+        let r = Rect::new(100, 100, 400, 300);
+        x.modify_and_refresh(&mut state, |cs| cs.float(Xid(2), r).unwrap())
+            .unwrap();
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+        let moved = Rect::new(200, 200, 400, 300);
+        x.modify_and_refresh(&mut state, |cs| cs.float(Xid(2), moved).unwrap())
+            .unwrap();
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert_eq!(
+            state.client_geometry(Xid(2)),
+            Some(Rect::new(10, 30, 1900, 1040))
+        );
+
+        toggle_maximize_focused().call(&mut state, &x).unwrap();
+
+        assert_eq!(state.client_geometry(Xid(2)), Some(moved));
+    }
+
+    fn two_screen_conn() -> TestXConn {
+        TestXConn::new(vec![
+            Rect::new(0, 0, 1920, 1080),
+            Rect::new(1920, 0, 1280, 1024),
+        ])
+    }
+
+    #[test]
+    fn set_background_color_fills_every_screen() {
+        let x = two_screen_conn();
+        let mut state = x.new_state(Config::default()).unwrap();
+
+        set_background_color(0x282828ff)
+            .call(&mut state, &x)
+            .unwrap();
+
+        let expected = vec![
+            (Rect::new(0, 0, 1920, 1080), Color::from(0x282828ff)),
+            (Rect::new(1920, 0, 1280, 1024), Color::from(0x282828ff)),
+        ];
+        assert_eq!(x.root_background(), expected);
+    }
+
+    #[test_case(vec![], vec![]; "no colors")]
+    #[test_case(vec![0x111111ff], vec![0x111111ff, 0x111111ff]; "last color repeats")]
+    #[test_case(vec![0x111111ff, 0x222222ff], vec![0x111111ff, 0x222222ff]; "one per screen")]
+    #[test_case(
+        vec![0x111111ff, 0x222222ff, 0x333333ff],
+        vec![0x111111ff, 0x222222ff];
+        "extra colors are ignored"
+    )]
+    #[test]
+    fn set_background_per_screen_fills_screens_in_order(colors: Vec<u32>, expected: Vec<u32>) {
+        let x = two_screen_conn();
+        let mut state = x.new_state(Config::default()).unwrap();
+        let colors = colors.into_iter().map(Color::from).collect();
+
+        set_background_per_screen(colors)
+            .call(&mut state, &x)
+            .unwrap();
+
+        let filled: Vec<u32> = x
+            .root_background()
+            .into_iter()
+            .map(|(_, c)| c.rgba_u32())
+            .collect();
+        assert_eq!(filled, expected);
+    }
+
+    fn skipping_layouts_config() -> Config<TestXConn> {
+        Config {
+            default_layouts: stack!(
+                MainAndStack::boxed_default(),
+                SkipInCycle::wrap(Grid::boxed()),
+                Monocle::boxed()
+            ),
+            ..Config::default()
+        }
+    }
+
+    #[test_case("Grid", "Grid"; "skipped layout")]
+    #[test_case("Mono", "Mono"; "unskipped layout")]
+    #[test_case("Unknown", "Side"; "unknown layout is a no-op")]
+    #[test]
+    fn jump_to_layout_selects_by_name(name: &str, expected: &str) {
+        let x = TestXConn::default();
+        let mut state = x.new_state(skipping_layouts_config()).unwrap();
+
+        jump_to_layout(name).call(&mut state, &x).unwrap();
+
+        assert_eq!(state.client_set.current_workspace().layout_name(), expected);
+    }
+
+    #[test]
+    fn next_layout_skips_marked_layouts() {
+        let x = TestXConn::default();
+        let mut state = x.new_state(skipping_layouts_config()).unwrap();
+
+        state.client_set.next_layout();
+        assert_eq!(state.client_set.current_workspace().layout_name(), "Mono");
+
+        state.client_set.previous_layout();
+        assert_eq!(state.client_set.current_workspace().layout_name(), "Side");
+    }
+
+    // 1 | 2 | 3
+    // --+---+--
+    // 4 | 5 | 6
+    //   |---+--
+    //   | 7 | 8
+    fn grid_positions() -> Vec<(Xid, Rect)> {
+        vec![
+            (Xid(1), Rect::new(0, 0, 100, 100)),
+            (Xid(2), Rect::new(100, 0, 100, 100)),
+            (Xid(3), Rect::new(200, 0, 100, 100)),
+            (Xid(4), Rect::new(0, 100, 100, 200)),
+            (Xid(5), Rect::new(100, 100, 100, 100)),
+            (Xid(6), Rect::new(200, 100, 100, 100)),
+            (Xid(7), Rect::new(100, 200, 100, 100)),
+            (Xid(8), Rect::new(200, 200, 100, 100)),
+        ]
+    }
+
+    #[test_case(5, Direction::Left, Some(4); "left")]
+    #[test_case(5, Direction::Right, Some(6); "right")]
+    #[test_case(5, Direction::Up, Some(2); "up")]
+    #[test_case(5, Direction::Down, Some(7); "down")]
+    #[test_case(4, Direction::Right, Some(5); "tie broken by first candidate")]
+    #[test_case(4, Direction::Up, Some(1); "up from tall client")]
+    #[test_case(1, Direction::Down, Some(4); "down to tall client")]
+    #[test_case(8, Direction::Left, Some(7); "left prefers aligned client")]
+    #[test_case(1, Direction::Left, None; "no candidate left")]
+    #[test_case(3, Direction::Up, None; "no candidate up")]
+    #[test]
+    fn nearest_in_direction_picks_expected_neighbour(
+        focused: u32,
+        dir: Direction,
+        expected: Option<u32>,
+    ) {
+        let positions = grid_positions();
+        let r = positions
+            .iter()
+            .find(|(id, _)| *id == Xid(focused))
+            .unwrap()
+            .1;
+        let candidates = positions.into_iter().filter(|(id, _)| *id != Xid(focused));
+
+        let chosen = nearest_in_direction(r, dir, candidates);
+
+        assert_eq!(chosen, expected.map(Xid));
+    }
+
+    #[test]
+    fn nearest_in_direction_prefers_overlapping_clients() {
+        let r = Rect::new(400, 300, 200, 200);
+        let candidates = vec![
+            (Xid(1), Rect::new(300, 0, 50, 250)),
+            (Xid(2), Rect::new(0, 450, 300, 200)),
+        ];
+
+        assert_eq!(
+            nearest_in_direction(r, Direction::Left, candidates),
+            Some(Xid(2))
+        );
+    }
+
+    #[test_case(Direction::Left, 1; "left")]
+    #[test_case(Direction::Right, 2; "right")]
+    #[test]
+    fn focus_direction_focuses_the_neighbouring_client(dir: Direction, expected: u32) {
+        let x = TestXConn::default();
+        let mut state = x.new_state(Config::default()).unwrap();
+        x.modify_and_refresh(&mut state, |cs| {
+            for id in 1..=3 {
+                cs.insert(Xid(id));
+            }
+            cs.float(Xid(1), Rect::new(0, 0, 100, 100)).unwrap();
+            cs.float(Xid(2), Rect::new(600, 0, 100, 100)).unwrap();
+            cs.float(Xid(3), Rect::new(300, 0, 100, 100)).unwrap();
+        })
+        .unwrap();
+
+        focus_direction(dir).call(&mut state, &x).unwrap();
+
+        assert_eq!(state.client_set.current_client(), Some(&Xid(expected)));
+    }
+
+    fn tiled_order(state: &State<TestXConn>) -> Vec<u32> {
+        state
+            .client_set
+            .current_workspace()
+            .clients()
+            .map(|id| **id)
+            .collect()
+    }
+
+    // With the default MainAndStack layout and four clients:
+    //   1 | 2
+    //     |---
+    //     | 3
+    //     |---
+    //     | 4
+    #[test_case(3, Direction::Left, vec![3, 2, 1, 4]; "left to main")]
+    #[test_case(1, Direction::Right, vec![3, 2, 1, 4]; "right to aligned stack client")]
+    #[test_case(3, Direction::Up, vec![1, 3, 2, 4]; "up")]
+    #[test_case(3, Direction::Down, vec![1, 2, 4, 3]; "down")]
+    #[test_case(2, Direction::Up, vec![1, 2, 3, 4]; "no neighbour up")]
+    #[test_case(1, Direction::Left, vec![1, 2, 3, 4]; "no neighbour left")]
+    #[test]
+    fn swap_direction_swaps_with_the_visual_neighbour(
+        focused: u32,
+        dir: Direction,
+        expected: Vec<u32>,
+    ) {
+        let x = TestXConn::default();
+        let mut state = x.new_state(Config::default()).unwrap();
+        x.modify_and_refresh(&mut state, |cs| {
+            for id in (1..=4).rev() {
+                cs.insert(Xid(id));
+            }
+            cs.focus_client(&Xid(focused));
+        })
+        .unwrap();
+
+        swap_direction(dir).call(&mut state, &x).unwrap();
+
+        assert_eq!(tiled_order(&state), expected);
+        assert_eq!(state.client_set.current_client(), Some(&Xid(focused)));
+    }
+
+    #[test]
+    fn swap_direction_ignores_floating_clients() {
+        let x = TestXConn::default();
+        let mut state = x.new_state(Config::default()).unwrap();
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert(Xid(2));
+            cs.insert(Xid(1));
+            cs.float(Xid(2), Rect::new(900, 0, 100, 100)).unwrap();
+        })
+        .unwrap();
+
+        swap_direction(Direction::Right)
+            .call(&mut state, &x)
+            .unwrap();
+
+        assert_eq!(tiled_order(&state), vec![1, 2]);
+    }
+}
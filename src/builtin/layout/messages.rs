@@ -62,6 +62,28 @@ impl_message!(Rotate);
 pub struct Mirror;
 impl_message!(Mirror);
 
+/// Increase the inner and outer gap sizes of a [Gaps][0] transformer by the given number of pixels.
+///
+///   [0]: crate::builtin::layout::transformers::Gaps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IncGaps(pub u32);
+impl_message!(IncGaps);
+
+/// Decrease the inner and outer gap sizes of a [Gaps][0] transformer by the given number of
+/// pixels. Gap sizes will not be reduced below zero.
+///
+///   [0]: crate::builtin::layout::transformers::Gaps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DecGaps(pub u32);
+impl_message!(DecGaps);
+
+/// Toggle a [Gaps][0] transformer between its current gap sizes and having no gaps.
+///
+///   [0]: crate::builtin::layout::transformers::Gaps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ToggleGaps;
+impl_message!(ToggleGaps);
+
 /// Unwrap a [LayoutTransformer][0] to return the underlying [Layout][1].
 ///
 /// Handling of this message is provided automatically by the [LayoutTransformer][0] trait.
@@ -5,6 +5,7 @@ use crate::{
     pure::{geometry::Rect, Stack},
     Xid,
 };
+use tracing::warn;
 
 pub mod messages;
 pub mod transformers;
@@ -14,6 +15,33 @@ pub mod transformers;
 #[cfg(test)]
 pub mod quickcheck_tests;
 
+const DEFAULT_RATIO: f32 = 0.6;
+const DEFAULT_RATIO_STEP: f32 = 0.1;
+
+// Ensure that user provided ratio parameters are usable: ratios are clamped to 0.0..=1.0
+// and a step that is not positive (which would make ExpandMain and ShrinkMain either
+// do nothing or behave the wrong way round) is replaced with the default step.
+fn validated_ratios(ratio: f32, ratio_step: f32) -> (f32, f32) {
+    let valid_ratio = if ratio.is_nan() {
+        DEFAULT_RATIO
+    } else {
+        ratio.clamp(0.0, 1.0)
+    };
+
+    if valid_ratio != ratio {
+        warn!(%ratio, %valid_ratio, "layout ratio must be between 0.0 and 1.0");
+    }
+
+    let valid_step = if ratio_step > 0.0 && ratio_step.is_finite() {
+        ratio_step
+    } else {
+        warn!(%ratio_step, default = %DEFAULT_RATIO_STEP, "layout ratio_step must be positive");
+        DEFAULT_RATIO_STEP
+    };
+
+    (valid_ratio, valid_step)
+}
+
 #[derive(Debug, Clone, Copy)]
 enum StackPosition {
     Side,
@@ -39,6 +67,10 @@ impl StackPosition {
 /// increased or decreased by sending an [IncMain] message. To flip between the side and bottom
 /// behaviours you can send a [Rotate] message.
 ///
+/// Ratios passed to the constructors of this layout are clamped to the range `0.0..=1.0` and a
+/// `ratio_step` that is not positive is replaced with the default step of `0.1`. A warning is
+/// logged in either case.
+///
 /// ```text
 /// ..................................
 /// .                  .             .
@@ -68,8 +100,8 @@ impl Default for MainAndStack {
         Self {
             pos: StackPosition::Side,
             max_main: 1,
-            ratio: 0.6,
-            ratio_step: 0.1,
+            ratio: DEFAULT_RATIO,
+            ratio_step: DEFAULT_RATIO_STEP,
             mirrored: false,
         }
     }
@@ -106,6 +138,8 @@ impl MainAndStack {
     /// Create a new [MainAndStack] [Layout] with the main area and remaining windows
     /// stacked to the side.
     pub fn side_unboxed(max_main: u32, ratio: f32, ratio_step: f32, mirrored: bool) -> Self {
+        let (ratio, ratio_step) = validated_ratios(ratio, ratio_step);
+
         Self {
             pos: StackPosition::Side,
             max_main,
@@ -130,6 +164,8 @@ impl MainAndStack {
     /// Create a new [MainAndStack] [Layout] with a main area and the remaining windows
     /// stacked either on the top or the bottom.
     pub fn bottom_unboxed(max_main: u32, ratio: f32, ratio_step: f32, mirrored: bool) -> Self {
+        let (ratio, ratio_step) = validated_ratios(ratio, ratio_step);
+
         Self {
             pos: StackPosition::Bottom,
             max_main,
@@ -268,6 +304,8 @@ impl Layout for MainAndStack {
 /// increased or decreased by sending an [IncMain] message. To flip between the vertical and
 /// horizontal behaviours you can send a [Rotate] message.
 ///
+/// Ratios are validated in the same way as for [MainAndStack].
+///
 /// ```text
 /// ...................................
 /// .                .                .
@@ -298,8 +336,8 @@ impl Default for CenteredMain {
         Self {
             pos: StackPosition::Side,
             max_main: 1,
-            ratio: 0.6,
-            ratio_step: 0.1,
+            ratio: DEFAULT_RATIO,
+            ratio_step: DEFAULT_RATIO_STEP,
         }
     }
 }
@@ -329,6 +367,8 @@ impl CenteredMain {
     /// Create a new [CenteredMain] [Layout] with a vertical main area and remaining windows
     /// tiled to the left and right.
     pub fn vertical_unboxed(max_main: u32, ratio: f32, ratio_step: f32) -> Self {
+        let (ratio, ratio_step) = validated_ratios(ratio, ratio_step);
+
         Self {
             pos: StackPosition::Side,
             max_main,
@@ -346,6 +386,8 @@ impl CenteredMain {
     /// Create a new [CenteredMain] [Layout] with a horizontal main area and remaining windows
     /// tiled above and below.
     pub fn horizontal_unboxed(max_main: u32, ratio: f32, ratio_step: f32) -> Self {
+        let (ratio, ratio_step) = validated_ratios(ratio, ratio_step);
+
         Self {
             pos: StackPosition::Bottom,
             max_main,
@@ -490,6 +532,111 @@ impl Layout for CenteredMain {
     }
 }
 
+/// A [Layout] with a main region of a fixed width in pixels and remaining windows stacked
+/// to the right.
+///
+/// The width of the main region can be adjusted by `px_step` pixels at a time by sending
+/// [ShrinkMain] and [ExpandMain] messages to this layout. The main region is never allowed
+/// to grow so large that less than `min_stack_px` pixels would remain for the stack.
+///
+/// ```text
+/// ..................................
+/// .              .                 .
+/// .              .                 .
+/// .              .                 .
+/// .              ...................
+/// .              .                 .
+/// .              .                 .
+/// .              .                 .
+/// .              ...................
+/// .              .                 .
+/// .              .                 .
+/// .              .                 .
+/// ..................................
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FixedMain {
+    px: u32,
+    px_step: u32,
+    min_stack_px: u32,
+    max_px: Option<u32>,
+}
+
+impl FixedMain {
+    /// Create a new [FixedMain] [Layout] as a trait object ready to be added to your
+    /// [LayoutStack][crate::core::layout::LayoutStack].
+    pub fn boxed(px: u32, px_step: u32, min_stack_px: u32) -> Box<dyn Layout> {
+        Box::new(Self::new(px, px_step, min_stack_px))
+    }
+
+    /// Create a new [FixedMain] [Layout] with a main region that is `px` pixels wide.
+    pub fn new(px: u32, px_step: u32, min_stack_px: u32) -> Self {
+        Self {
+            px,
+            px_step,
+            min_stack_px,
+            max_px: None,
+        }
+    }
+
+    /// The current width of the main region in pixels.
+    ///
+    /// The width used when laying out a screen narrower than `px + min_stack_px` will be
+    /// smaller than this value.
+    pub fn px(&self) -> u32 {
+        self.px
+    }
+
+    fn max_px_for(&self, r: Rect) -> u32 {
+        r.w.saturating_sub(self.min_stack_px)
+    }
+}
+
+impl Layout for FixedMain {
+    fn name(&self) -> String {
+        "Fixed".to_owned()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Layout> {
+        Box::new(*self)
+    }
+
+    fn layout(&mut self, s: &Stack<Xid>, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let n = s.len() as u32;
+        let max_px = self.max_px_for(r);
+        self.max_px = Some(max_px);
+
+        let split = match self.px.min(max_px) {
+            0 => None,
+            px => r.split_at_width(px),
+        };
+
+        let positions = match split {
+            Some((main, stack)) if n > 1 => std::iter::once(main)
+                .chain(stack.as_rows(n - 1))
+                .zip(s)
+                .map(|(r, c)| (*c, r))
+                .collect(),
+            _ => r.as_rows(n).iter().zip(s).map(|(r, c)| (*c, *r)).collect(),
+        };
+
+        (None, positions)
+    }
+
+    fn handle_message(&mut self, m: &Message) -> Option<Box<dyn Layout>> {
+        if let Some(&ExpandMain) = m.downcast_ref() {
+            self.px = self.px.saturating_add(self.px_step);
+            if let Some(max_px) = self.max_px {
+                self.px = self.px.min(max_px);
+            }
+        } else if let Some(&ShrinkMain) = m.downcast_ref() {
+            self.px = self.px.saturating_sub(self.px_step);
+        }
+
+        None
+    }
+}
+
 /// A simple monolce layout that gives the maximum available space to the currently
 /// focused client and unmaps all other windows.
 ///
@@ -622,6 +769,7 @@ mod tests {
         builtin::layout::{messages::IncMain, *},
         core::layout::IntoMessage,
     };
+    use simple_test_case::test_case;
 
     #[test]
     fn message_handling() {
@@ -631,4 +779,98 @@ mod tests {
 
         assert_eq!(l.max_main, 3);
     }
+
+    #[test_case(0.6, 0.1, 0.6, 0.1; "valid")]
+    #[test_case(1.5, 0.1, 1.0, 0.1; "ratio too large")]
+    #[test_case(-0.5, 0.1, 0.0, 0.1; "ratio negative")]
+    #[test_case(f32::NAN, 0.1, 0.6, 0.1; "ratio nan")]
+    #[test_case(0.6, 0.0, 0.6, 0.1; "step zero")]
+    #[test_case(0.6, -0.2, 0.6, 0.1; "step negative")]
+    #[test_case(0.6, f32::INFINITY, 0.6, 0.1; "step infinite")]
+    #[test]
+    fn ratios_are_validated(ratio: f32, step: f32, expected_ratio: f32, expected_step: f32) {
+        let l = MainAndStack::side_unboxed(1, ratio, step, false);
+        assert_eq!((l.ratio, l.ratio_step), (expected_ratio, expected_step));
+
+        let l = CenteredMain::vertical_unboxed(1, ratio, step);
+        assert_eq!((l.ratio, l.ratio_step), (expected_ratio, expected_step));
+    }
+
+    #[test_case(ExpandMain.into_message(), 0.75; "expand")]
+    #[test_case(ShrinkMain.into_message(), 0.25; "shrink")]
+    #[test]
+    fn a_valid_step_changes_the_ratio(m: Message, expected: f32) {
+        let mut l = MainAndStack::side_unboxed(1, 0.5, 0.25, false);
+
+        l.handle_message(&m);
+
+        assert_eq!(l.ratio, expected);
+    }
+
+    #[test]
+    fn an_invalid_step_is_replaced_by_the_default() {
+        let mut l = MainAndStack::side_unboxed(1, 0.5, 0.0, false);
+
+        l.handle_message(&ExpandMain.into_message());
+
+        assert_eq!(l.ratio, 0.6);
+    }
+
+    fn fixed_main_positions(l: &mut FixedMain, n: u32) -> Vec<Rect> {
+        let ids: Vec<Xid> = (2..=n).map(Xid).collect();
+        let s = Stack::new([], Xid(1), ids);
+        let (_, positions) = l.layout(&s, Rect::new(0, 0, 1000, 600));
+
+        positions.into_iter().map(|(_, r)| r).collect()
+    }
+
+    #[test_case(1, vec![Rect::new(0, 0, 1000, 600)]; "single client")]
+    #[test_case(2, vec![Rect::new(0, 0, 300, 600), Rect::new(300, 0, 700, 600)]; "two clients")]
+    #[test_case(
+        3,
+        vec![
+            Rect::new(0, 0, 300, 600),
+            Rect::new(300, 0, 700, 300),
+            Rect::new(300, 300, 700, 300)
+        ];
+        "three clients"
+    )]
+    #[test]
+    fn fixed_main_uses_an_absolute_width(n: u32, expected: Vec<Rect>) {
+        let mut l = FixedMain::new(300, 50, 200);
+
+        assert_eq!(fixed_main_positions(&mut l, n), expected);
+    }
+
+    #[test]
+    fn fixed_main_width_is_adjusted_by_messages() {
+        let mut l = FixedMain::new(300, 50, 200);
+
+        l.handle_message(&ExpandMain.into_message());
+        assert_eq!(
+            fixed_main_positions(&mut l, 2)[0],
+            Rect::new(0, 0, 350, 600)
+        );
+
+        l.handle_message(&ShrinkMain.into_message());
+        l.handle_message(&ShrinkMain.into_message());
+        assert_eq!(
+            fixed_main_positions(&mut l, 2)[0],
+            Rect::new(0, 0, 250, 600)
+        );
+    }
+
+    #[test]
+    fn fixed_main_leaves_a_minimum_stack_width() {
+        let mut l = FixedMain::new(900, 50, 200);
+        assert_eq!(
+            fixed_main_positions(&mut l, 2)[0],
+            Rect::new(0, 0, 800, 600)
+        );
+
+        for _ in 0..10 {
+            l.handle_message(&ExpandMain.into_message());
+        }
+        assert_eq!(l.px(), 800, "expanding is clamped to the last screen size");
+    }
 }
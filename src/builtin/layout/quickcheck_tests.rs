@@ -6,7 +6,7 @@
 use crate::{
     builtin::layout::{
         transformers::{ReflectHorizontal, ReflectVertical},
-        CenteredMain, Grid, MainAndStack, Monocle,
+        CenteredMain, FixedMain, Grid, MainAndStack, Monocle,
     },
     core::layout::Layout,
     pure::{geometry::Rect, Stack},
@@ -66,6 +66,14 @@ fn grid_doesnt_panic(r: Rect, stack: Stack<Xid>) -> bool {
     !positions.is_empty()
 }
 
+#[quickcheck]
+fn fixed_main_doesnt_panic(r: Rect, stack: Stack<Xid>, px: u16, min_stack_px: u16) -> bool {
+    let mut l = FixedMain::new(px as u32, 10, min_stack_px as u32);
+    let (_, positions) = l.layout(&stack, r);
+
+    !positions.is_empty()
+}
+
 mod main_and_stack {
     use super::*;
 
@@ -1,9 +1,14 @@
 //! Built-in layout transformers.
 use crate::{
-    core::layout::{Layout, LayoutTransformer},
-    pure::geometry::Rect,
+    builtin::layout::{
+        messages::{DecGaps, IncGaps, ToggleGaps, UnwrapTransformer},
+        Monocle,
+    },
+    core::layout::{Layout, LayoutTransformer, Message},
+    pure::{geometry::Rect, Stack},
     simple_transformer, Xid,
 };
+use std::mem::replace;
 
 simple_transformer!(
     /// Wrap an existing layout and reflect its window positions horizontally.
@@ -48,6 +53,12 @@ fn reflect_vertical(r: Rect, positions: Vec<(Xid, Rect)>) -> Vec<(Xid, Rect)> {
 /// `outer_px` controls the width of the gap around the edge of the screen and `inner_px`
 /// controls the gap around each individual window. Set both equal to one another to have
 /// a consistant gap size in all places.
+///
+/// Gap sizes can be adjusted while penrose is running by sending [IncGaps], [DecGaps] and
+/// [ToggleGaps] messages to the layout.
+///
+/// > **NOTE**: `Gaps` can no longer be constructed using a struct literal as whether or not
+/// > gaps are currently applied is tracked internally. Use [Gaps::wrap] instead.
 #[derive(Debug, Clone)]
 pub struct Gaps {
     /// The inner [Layout] having gaps applied to it.
@@ -56,6 +67,8 @@ pub struct Gaps {
     pub outer_px: u32,
     /// The desired inner gap size in pixels
     pub inner_px: u32,
+    // Gap sizes are retained while gaps are disabled so that they can be restored
+    enabled: bool,
 }
 
 impl Gaps {
@@ -65,8 +78,35 @@ impl Gaps {
             layout,
             outer_px,
             inner_px,
+            enabled: true,
         })
     }
+
+    /// Whether or not gaps are currently being applied.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Toggle whether or not gaps are applied, retaining the current gap sizes.
+    ///
+    /// This is the same as sending a [ToggleGaps] message to the layout.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    fn adjust(&mut self, f: impl Fn(u32) -> u32) {
+        self.outer_px = f(self.outer_px);
+        self.inner_px = f(self.inner_px);
+    }
+
+    // The (outer, inner) gap sizes to apply given whether or not gaps are enabled
+    fn active_px(&self) -> (u32, u32) {
+        if self.enabled {
+            (self.outer_px, self.inner_px)
+        } else {
+            (0, 0)
+        }
+    }
 }
 
 fn shrink(r: Rect, px: u32) -> Rect {
@@ -74,6 +114,9 @@ fn shrink(r: Rect, px: u32) -> Rect {
         return r;
     }
 
+    // Gap sizes can be increased at runtime so make sure we never shrink past zero
+    let px = px.min(r.w.min(r.h) / 2);
+
     Rect {
         x: r.x + px,
         y: r.y + px,
@@ -87,25 +130,48 @@ impl LayoutTransformer for Gaps {
         self.layout.name()
     }
 
+    fn inner(&self) -> Option<&dyn Layout> {
+        Some(&*self.layout)
+    }
+
     fn inner_mut(&mut self) -> &mut Box<dyn Layout> {
         &mut self.layout
     }
 
     fn transform_initial(&self, r: Rect) -> Rect {
-        shrink(r, self.outer_px)
+        shrink(r, self.active_px().0)
     }
 
     fn transform_positions(&mut self, _: Rect, positions: Vec<(Xid, Rect)>) -> Vec<(Xid, Rect)> {
+        let inner_px = self.active_px().1;
+
         positions
             .into_iter()
-            .map(|(id, r)| (id, shrink(r, self.inner_px)))
+            .map(|(id, r)| (id, shrink(r, inner_px)))
             .collect()
     }
+
+    fn passthrough_message(&mut self, m: &Message) -> Option<Box<dyn Layout>> {
+        if let Some(&IncGaps(px)) = m.downcast_ref() {
+            self.adjust(|n| n.saturating_add(px));
+        } else if let Some(&DecGaps(px)) = m.downcast_ref() {
+            self.adjust(|n| n.saturating_sub(px));
+        } else if let Some(&ToggleGaps) = m.downcast_ref() {
+            self.toggle();
+        } else if let Some(new) = self.layout.handle_message(m) {
+            self.swap_inner(new);
+        }
+
+        None
+    }
 }
 
 /// Reserve `px` pixels at the top of the screen.
 ///
 /// Typically used for providing space for a status bar.
+///
+/// > Status bars that set _NET_WM_STRUT or _NET_WM_STRUT_PARTIAL will have space reserved
+/// > for them automatically so there is no need to wrap your layouts in this transformer.
 #[derive(Debug, Clone)]
 pub struct ReserveTop {
     /// The wrapped inner layout
@@ -126,6 +192,10 @@ impl LayoutTransformer for ReserveTop {
         self.layout.name()
     }
 
+    fn inner(&self) -> Option<&dyn Layout> {
+        Some(&*self.layout)
+    }
+
     fn inner_mut(&mut self) -> &mut Box<dyn Layout> {
         &mut self.layout
     }
@@ -142,9 +212,267 @@ impl LayoutTransformer for ReserveTop {
     }
 }
 
+/// Reserve `px` pixels at the bottom of the screen.
+///
+/// Typically used for providing space for a status bar.
+///
+/// > Status bars that set _NET_WM_STRUT or _NET_WM_STRUT_PARTIAL will have space reserved
+/// > for them automatically so there is no need to wrap your layouts in this transformer.
+#[derive(Debug, Clone)]
+pub struct ReserveBottom {
+    /// The wrapped inner layout
+    pub layout: Box<dyn Layout>,
+    /// The number of pixels to reserve at the bottom of the screen
+    pub px: u32,
+}
+
+impl ReserveBottom {
+    /// Wrap an existing [Layout] with the given reserved area.
+    pub fn wrap(layout: Box<dyn Layout>, px: u32) -> Box<dyn Layout> {
+        Box::new(Self { layout, px })
+    }
+}
+
+impl LayoutTransformer for ReserveBottom {
+    fn transformed_name(&self) -> String {
+        self.layout.name()
+    }
+
+    fn inner(&self) -> Option<&dyn Layout> {
+        Some(&*self.layout)
+    }
+
+    fn inner_mut(&mut self) -> &mut Box<dyn Layout> {
+        &mut self.layout
+    }
+
+    fn transform_initial(&self, mut r: Rect) -> Rect {
+        if r.w == 0 || r.h == 0 {
+            return r;
+        }
+
+        r.h = r.h.saturating_sub(self.px);
+
+        r
+    }
+}
+
+/// Enlarge the focused client beyond the position given to it by the enclosed [Layout].
+///
+/// The position of the focused client is scaled by `scale` about its center, clamped to fit
+/// on the screen and then stacked above all other clients. The positions of all other clients
+/// are left unchanged.
+#[derive(Debug, Clone)]
+pub struct Magnify {
+    /// The wrapped inner layout
+    pub layout: Box<dyn Layout>,
+    /// The factor to scale the focused client's position by
+    pub scale: f32,
+}
+
+impl Magnify {
+    /// Wrap an existing [Layout] with the given scale factor for the focused client.
+    pub fn wrap(layout: Box<dyn Layout>, scale: f32) -> Box<dyn Layout> {
+        Box::new(Self { layout, scale })
+    }
+
+    fn magnify(&self, focus: Xid, r_s: Rect, mut positions: Vec<(Xid, Rect)>) -> Vec<(Xid, Rect)> {
+        let ix = match positions.iter().position(|&(id, _)| id == focus) {
+            Some(ix) => ix,
+            None => return positions,
+        };
+
+        let (id, r) = positions.remove(ix);
+        let w = ((r.w as f32 * self.scale) as u32).min(r_s.w);
+        let h = ((r.h as f32 * self.scale) as u32).min(r_s.h);
+        let x = (r.x + r.w / 2)
+            .saturating_sub(w / 2)
+            .clamp(r_s.x, r_s.x + r_s.w - w);
+        let y = (r.y + r.h / 2)
+            .saturating_sub(h / 2)
+            .clamp(r_s.y, r_s.y + r_s.h - h);
+
+        // Clients are stacked in the order they are returned so the focused client goes last
+        positions.push((id, Rect::new(x, y, w, h)));
+
+        positions
+    }
+
+    fn swap_if_some(&mut self, new: Option<Box<dyn Layout>>) {
+        if let Some(l) = new {
+            self.layout = l;
+        }
+    }
+}
+
+impl Layout for Magnify {
+    fn name(&self) -> String {
+        self.layout.name()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn layout_workspace(
+        &mut self,
+        tag: &str,
+        stack: &Option<Stack<Xid>>,
+        r: Rect,
+    ) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.layout.layout_workspace(tag, stack, r);
+        self.swap_if_some(new);
+
+        match stack {
+            Some(s) => (None, self.magnify(s.focus, r, positions)),
+            None => (None, positions),
+        }
+    }
+
+    fn layout(&mut self, s: &Stack<Xid>, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.layout.layout(s, r);
+        self.swap_if_some(new);
+
+        (None, self.magnify(s.focus, r, positions))
+    }
+
+    fn layout_empty(&mut self, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.layout.layout_empty(r);
+        self.swap_if_some(new);
+
+        (None, positions)
+    }
+
+    fn handle_message(&mut self, m: &Message) -> Option<Box<dyn Layout>> {
+        if let Some(&UnwrapTransformer) = m.downcast_ref() {
+            return Some(replace(&mut self.layout, Box::new(Monocle)));
+        }
+
+        let new = self.layout.handle_message(m);
+        self.swap_if_some(new);
+
+        None
+    }
+}
+
+/// Stop tiling additional clients once the enclosed [Layout] would give them a position
+/// smaller than a minimum size.
+///
+/// The enclosed layout is run with as many clients as possible (always at least one) without
+/// any client being positioned smaller than `min_w` x `min_h`. Any remaining clients overflow
+/// and are stacked beneath the last tiled client, sharing its position. If the focused client
+/// would overflow then it takes the place of the last tiled client so that it remains visible.
+#[derive(Debug, Clone)]
+pub struct MinSize {
+    /// The wrapped inner layout
+    pub layout: Box<dyn Layout>,
+    /// The minimum width of a tiled client
+    pub min_w: u32,
+    /// The minimum height of a tiled client
+    pub min_h: u32,
+}
+
+impl MinSize {
+    /// Wrap an existing [Layout] with the given minimum client size.
+    pub fn wrap(layout: Box<dyn Layout>, min_w: u32, min_h: u32) -> Box<dyn Layout> {
+        Box::new(Self {
+            layout,
+            min_w,
+            min_h,
+        })
+    }
+
+    fn fits(&self, positions: &[(Xid, Rect)]) -> bool {
+        positions
+            .iter()
+            .all(|(_, r)| r.w >= self.min_w && r.h >= self.min_h)
+    }
+
+    fn swap_if_some(&mut self, new: Option<Box<dyn Layout>>) {
+        if let Some(l) = new {
+            self.layout = l;
+        }
+    }
+}
+
+// Split the clients of a stack into the first n to be tiled (keeping the focused client) and
+// the clients that overflow.
+fn split_tiled(s: &Stack<Xid>, n: usize) -> (Stack<Xid>, Vec<Xid>) {
+    let mut clients: Vec<Xid> = s.iter().copied().collect();
+    let focus = s.focus;
+
+    let ix = clients.iter().position(|&id| id == focus).unwrap_or(0);
+    if ix >= n {
+        clients.swap(ix, n - 1);
+    }
+
+    let overflow = clients.split_off(n);
+    let ix = ix.min(n - 1);
+    let down = clients.split_off(ix + 1);
+    clients.pop();
+
+    (Stack::new(clients, focus, down), overflow)
+}
+
+impl Layout for MinSize {
+    fn name(&self) -> String {
+        self.layout.name()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn layout(&mut self, s: &Stack<Xid>, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        for n in (1..=s.len()).rev() {
+            let (tiled, overflow) = split_tiled(s, n);
+            let (new, positions) = self.layout.layout(&tiled, r);
+            self.swap_if_some(new);
+
+            if n > 1 && !self.fits(&positions) {
+                continue;
+            }
+
+            let last = tiled.iter().last().copied();
+            let shared = positions.iter().find(|&&(id, _)| Some(id) == last);
+            let mut stacked: Vec<(Xid, Rect)> = match shared {
+                Some(&(_, r)) => overflow.into_iter().map(|id| (id, r)).collect(),
+                None => Vec::new(),
+            };
+            stacked.extend(positions);
+
+            return (None, stacked);
+        }
+
+        (None, Vec::new())
+    }
+
+    fn layout_empty(&mut self, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.layout.layout_empty(r);
+        self.swap_if_some(new);
+
+        (None, positions)
+    }
+
+    fn handle_message(&mut self, m: &Message) -> Option<Box<dyn Layout>> {
+        if let Some(&UnwrapTransformer) = m.downcast_ref() {
+            return Some(replace(&mut self.layout, Box::new(Monocle)));
+        }
+
+        let new = self.layout.handle_message(m);
+        self.swap_if_some(new);
+
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        builtin::layout::{messages::IncMain, MainAndStack},
+        core::layout::IntoMessage,
+    };
     use simple_test_case::test_case;
 
     #[test_case(Rect::new(0, 0, 100, 200), Rect::new(0, 0, 100, 200); "fullscreen is idempotent")]
@@ -172,4 +500,205 @@ mod tests {
 
         assert_eq!(transformed, vec![(Xid(1), expected)]);
     }
+
+    fn focused_on(focus: u32) -> Stack<Xid> {
+        let mut s = crate::stack!([Xid(1)], Xid(2), [Xid(3)]);
+        s.focus_element(&Xid(focus));
+
+        s
+    }
+
+    // The focused client should be moved to the end of the positions returned by the inner
+    // layout with all other positions left unchanged.
+    fn assert_magnified(
+        mut inner: Box<dyn Layout>,
+        mut magnify: Box<dyn Layout>,
+        focus: u32,
+        expected: Rect,
+    ) {
+        let s = focused_on(focus);
+        let r = Rect::new(0, 0, 1000, 600);
+        let (_, original) = inner.layout(&s, r);
+        let (_, magnified) = magnify.layout(&s, r);
+
+        let (last, rest) = magnified.split_last().unwrap();
+        assert_eq!(
+            *last,
+            (Xid(focus), expected),
+            "focused client is stacked on top"
+        );
+
+        let unchanged: Vec<_> = original
+            .into_iter()
+            .filter(|&(id, _)| id != Xid(focus))
+            .collect();
+        assert_eq!(rest, unchanged);
+    }
+
+    #[test_case(1.5, 2, Rect::new(400, 0, 600, 450); "stack client clamped to screen")]
+    #[test_case(1.5, 1, Rect::new(0, 0, 900, 600); "main client clamped to screen")]
+    #[test_case(1.0, 3, Rect::new(600, 300, 400, 300); "unit scale")]
+    #[test]
+    fn magnify_enlarges_only_the_focused_client(scale: f32, focus: u32, expected: Rect) {
+        assert_magnified(
+            MainAndStack::boxed_default(),
+            Magnify::wrap(MainAndStack::boxed_default(), scale),
+            focus,
+            expected,
+        );
+    }
+
+    #[test]
+    fn magnify_scales_about_the_center_of_the_client() {
+        let m = Magnify {
+            layout: Monocle::boxed(),
+            scale: 1.5,
+        };
+        let positions = vec![
+            (Xid(1), Rect::new(200, 100, 200, 100)),
+            (Xid(2), Rect::new(0, 0, 100, 100)),
+        ];
+
+        let magnified = m.magnify(Xid(1), Rect::new(0, 0, 1000, 600), positions);
+
+        assert_eq!(
+            magnified,
+            vec![
+                (Xid(2), Rect::new(0, 0, 100, 100)),
+                (Xid(1), Rect::new(150, 75, 300, 150)),
+            ]
+        );
+    }
+
+    #[test]
+    fn magnify_passes_messages_to_the_inner_layout() {
+        let mut inner = MainAndStack::boxed_default();
+        let mut magnify = Magnify::wrap(MainAndStack::boxed_default(), 1.5);
+        inner.handle_message(&IncMain(1).into_message());
+        magnify.handle_message(&IncMain(1).into_message());
+
+        assert_magnified(inner, magnify, 3, Rect::new(400, 0, 600, 600));
+    }
+
+    fn gap_positions(gaps: &mut Box<dyn Layout>) -> Rect {
+        let s = crate::stack!(Xid(1));
+        let (_, positions) = gaps.layout(&s, Rect::new(0, 0, 100, 100));
+
+        positions[0].1
+    }
+
+    #[test_case(IncGaps(5).into_message(), Rect::new(20, 20, 60, 60); "inc gaps")]
+    #[test_case(DecGaps(3).into_message(), Rect::new(4, 4, 92, 92); "dec gaps")]
+    #[test_case(DecGaps(10).into_message(), Rect::new(0, 0, 100, 100); "dec gaps clamps to zero")]
+    #[test_case(ToggleGaps.into_message(), Rect::new(0, 0, 100, 100); "toggle gaps")]
+    #[test]
+    fn gap_messages_adjust_the_gap_size(m: Message, expected: Rect) {
+        let mut gaps = Gaps::wrap(Monocle::boxed(), 5, 5);
+        assert_eq!(gap_positions(&mut gaps), Rect::new(10, 10, 80, 80));
+
+        gaps.handle_message(&m);
+
+        assert_eq!(gap_positions(&mut gaps), expected);
+    }
+
+    #[test]
+    fn toggle_gaps_restores_the_original_gap_sizes() {
+        let mut gaps = Gaps::wrap(Monocle::boxed(), 5, 3);
+        let original = gap_positions(&mut gaps);
+
+        gaps.handle_message(&ToggleGaps.into_message());
+        assert_eq!(gap_positions(&mut gaps), Rect::new(0, 0, 100, 100));
+
+        gaps.handle_message(&ToggleGaps.into_message());
+        assert_eq!(gap_positions(&mut gaps), original);
+    }
+
+    #[test]
+    fn adjusting_gaps_while_toggled_off_updates_the_restored_sizes() {
+        let mut gaps = Gaps::wrap(Monocle::boxed(), 5, 5);
+
+        gaps.handle_message(&ToggleGaps.into_message());
+        gaps.handle_message(&IncGaps(5).into_message());
+        assert_eq!(gap_positions(&mut gaps), Rect::new(0, 0, 100, 100));
+
+        gaps.handle_message(&ToggleGaps.into_message());
+        assert_eq!(gap_positions(&mut gaps), Rect::new(20, 20, 60, 60));
+
+        gaps.handle_message(&ToggleGaps.into_message());
+        gaps.handle_message(&DecGaps(10).into_message());
+        gaps.handle_message(&ToggleGaps.into_message());
+        assert_eq!(gap_positions(&mut gaps), Rect::new(0, 0, 100, 100));
+    }
+
+    #[test]
+    fn gap_sizes_never_exceed_the_available_space() {
+        let mut gaps = Gaps::wrap(Monocle::boxed(), 5, 5);
+        gaps.handle_message(&IncGaps(100).into_message());
+
+        assert_eq!(gap_positions(&mut gaps), Rect::new(50, 50, 0, 0));
+    }
+
+    #[test_case(ReserveTop::wrap(Monocle::boxed(), 20), Rect::new(0, 20, 1000, 580); "top")]
+    #[test_case(ReserveBottom::wrap(Monocle::boxed(), 20), Rect::new(0, 0, 1000, 580); "bottom")]
+    #[test]
+    fn reserved_space_is_excluded_from_the_layout(mut layout: Box<dyn Layout>, expected: Rect) {
+        let (_, positions) = layout.layout(&crate::stack!(Xid(1)), Rect::new(0, 0, 1000, 600));
+
+        assert_eq!(positions, vec![(Xid(1), expected)]);
+    }
+
+    fn min_size_positions(focus: u32) -> Vec<(Xid, Rect)> {
+        let mut s = crate::stack!(Xid(1), Xid(2), Xid(3), Xid(4), Xid(5));
+        s.focus_element(&Xid(focus));
+        let mut layout = MinSize::wrap(MainAndStack::boxed_default(), 100, 200);
+
+        let (_, positions) = layout.layout(&s, Rect::new(0, 0, 1000, 600));
+
+        positions
+    }
+
+    #[test]
+    fn min_size_is_a_no_op_when_clients_are_large_enough() {
+        let s = crate::stack!(Xid(1), Xid(2), Xid(3));
+        let r = Rect::new(0, 0, 1000, 600);
+        let (_, expected) = MainAndStack::boxed_default().layout(&s, r);
+        let (_, positions) = MinSize::wrap(MainAndStack::boxed_default(), 100, 200).layout(&s, r);
+
+        assert_eq!(positions, expected);
+    }
+
+    #[test]
+    fn min_size_overflow_is_stacked_below_the_last_tiled_client() {
+        let positions = min_size_positions(1);
+        let ids: Vec<Xid> = positions.iter().map(|&(id, _)| id).collect();
+
+        assert_eq!(ids, vec![Xid(5), Xid(1), Xid(2), Xid(3), Xid(4)]);
+        assert!(positions.iter().all(|(_, r)| r.h >= 200));
+        assert_eq!(positions[0].1, positions[4].1, "overflow shares a position");
+    }
+
+    #[test]
+    fn min_size_keeps_an_overflowing_focused_client_visible() {
+        let positions = min_size_positions(5);
+        let ids: Vec<Xid> = positions.iter().map(|&(id, _)| id).collect();
+
+        assert_eq!(ids, vec![Xid(4), Xid(1), Xid(2), Xid(3), Xid(5)]);
+        assert_eq!(positions[0].1, positions[4].1, "overflow shares a position");
+    }
+
+    #[test]
+    fn min_size_always_tiles_at_least_one_client() {
+        let s = crate::stack!(Xid(1), Xid(2));
+        let mut layout = MinSize::wrap(Monocle::boxed(), 2000, 2000);
+
+        let (_, positions) = layout.layout(&s, Rect::new(0, 0, 1000, 600));
+
+        assert_eq!(
+            positions,
+            vec![
+                (Xid(2), Rect::new(0, 0, 1000, 600)),
+                (Xid(1), Rect::new(0, 0, 1000, 600)),
+            ]
+        );
+    }
 }
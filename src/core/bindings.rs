@@ -9,7 +9,15 @@ use crate::{
 use penrose_keysyms::XKeySym;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, convert::TryFrom, fmt, process::Command};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    process::Command,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 use strum::{EnumIter, IntoEnumIterator};
 use tracing::trace;
 
@@ -24,50 +32,172 @@ use tracing::trace;
 /// This function will panic if it is unable to fetch keycodes using the xmodmap
 /// binary on your system or if the output of `xmodmap -pke` is not valid
 pub fn keycodes_from_xmodmap() -> Result<HashMap<String, u8>> {
+    Ok(primary_keycodes(&all_keycodes_from_xmodmap()?))
+}
+
+/// Run the xmodmap command to dump the system keymap table, returning every key code that
+/// produces each key name in any keyboard group or shift level.
+///
+/// The mask of each returned [KeyCode] holds the [XKB_GROUP_MASK] bits for the keyboard group
+/// that the name was found in. The last key code for each name is the one returned by
+/// [keycodes_from_xmodmap].
+///
+/// # Panics
+/// This function will panic if it is unable to fetch keycodes using the xmodmap
+/// binary on your system or if the output of `xmodmap -pke` is not valid
+pub fn all_keycodes_from_xmodmap() -> Result<HashMap<String, Vec<KeyCode>>> {
     let output = Command::new("xmodmap").arg("-pke").output()?;
-    let m = String::from_utf8(output.stdout)?
-        .lines()
-        .flat_map(|l| {
-            let mut words = l.split_whitespace(); // keycode <code> = <names ...>
-            let key_code: u8 = match words.nth(1) {
-                Some(word) => match word.parse() {
-                    Ok(val) => val,
-                    Err(e) => panic!("{}", e),
-                },
-                None => panic!("unexpected output format from xmodmap -pke"),
-            };
-            words.skip(1).map(move |name| (name.into(), key_code))
-        })
-        .collect();
+
+    Ok(parse_xmodmap_output(&String::from_utf8(output.stdout)?))
+}
+
+pub(crate) fn parse_xmodmap_output(raw: &str) -> HashMap<String, Vec<KeyCode>> {
+    let mut m = HashMap::new();
+
+    for l in raw.lines() {
+        let mut words = l.split_whitespace(); // keycode <code> = <names ...>
+        let key_code: u8 = match words.nth(1) {
+            Some(word) => match word.parse() {
+                Ok(val) => val,
+                Err(e) => panic!("{}", e),
+            },
+            None => panic!("unexpected output format from xmodmap -pke"),
+        };
+
+        for (col, name) in words.skip(1).enumerate() {
+            push_keycode(&mut m, name, key_code, col);
+        }
+    }
+
+    m
+}
+
+// Codes are kept in the order they were last seen so that the final code for each name
+// matches the behaviour of collecting (name, code) pairs into a map.
+fn push_keycode(m: &mut HashMap<String, Vec<KeyCode>>, name: &str, code: u8, col: usize) {
+    let k = KeyCode {
+        mask: group_mask(col),
+        code,
+    };
+    let codes = m.entry(name.to_owned()).or_default();
+    codes.retain(|&c| c != k);
+    codes.push(k);
+}
+
+// The core keyboard mapping lists the first two shift levels of the first and second keyboard
+// groups before any additional shift levels. Everything after the first four columns is
+// treated as belonging to the first group.
+fn group_mask(col: usize) -> KeyCodeMask {
+    match col {
+        2 | 3 => 1 << 13,
+        _ => 0,
+    }
+}
+
+fn primary_keycodes(all_codes: &HashMap<String, Vec<KeyCode>>) -> HashMap<String, u8> {
+    all_codes
+        .iter()
+        .flat_map(|(name, codes)| codes.last().map(|k| (name.clone(), k.code)))
+        .collect()
+}
+
+/// Query the current keyboard mapping from the X server in order to map key names to
+/// key codes in the same way as [keycodes_from_xmodmap].
+///
+/// Unlike [keycodes_from_xmodmap] this does not require the `xmodmap` program to be
+/// installed. All known names for each keysym are included.
+#[cfg(feature = "keysyms")]
+pub fn keycodes_from_xkb<X: XConn>(x: &X) -> Result<HashMap<String, u8>> {
+    Ok(primary_keycodes(&all_keycodes_from_xkb(x)?))
+}
+
+/// Query the current keyboard mapping from the X server, returning every key code that
+/// produces each key name in any keyboard group or shift level.
+///
+/// As with [all_keycodes_from_xmodmap], the mask of each returned [KeyCode] holds the keyboard
+/// group that the name was found in. The last key code for each name is the one returned by
+/// [keycodes_from_xkb].
+#[cfg(feature = "keysyms")]
+pub fn all_keycodes_from_xkb<X: XConn>(x: &X) -> Result<HashMap<String, Vec<KeyCode>>> {
+    let mut names: HashMap<u32, Vec<XKeySym>> = HashMap::new();
+    for sym in XKeySym::iter() {
+        names.entry(sym.as_u32()).or_default().push(sym);
+    }
+
+    // Sorted so that names bound to multiple key codes resolve in the same way as xmodmap
+    let mut mapping: Vec<(u8, Vec<u32>)> = x.keyboard_mapping()?.into_iter().collect();
+    mapping.sort_by_key(|&(code, _)| code);
+
+    let mut m = HashMap::new();
+    for (code, syms) in mapping {
+        for (col, sym) in syms.into_iter().enumerate() {
+            for name in names.get(&sym).into_iter().flatten() {
+                push_keycode(&mut m, name.as_ref(), code, col);
+            }
+        }
+    }
 
     Ok(m)
 }
 
-fn parse_binding(pattern: &str, known_codes: &HashMap<String, u8>) -> Result<KeyCode> {
+// Returns the primary key code for the binding along with any other key codes that produce
+// the same key name, each tagged with the keyboard group they belong to. The primary key code
+// is taken from the first keyboard group where possible and is bound in every group.
+fn parse_binding(
+    pattern: &str,
+    all_codes: &HashMap<String, Vec<KeyCode>>,
+) -> Result<(KeyCode, Vec<KeyCode>)> {
     let mut parts: Vec<&str> = pattern.split('-').collect();
     let name = parts.remove(parts.len() - 1);
 
-    match known_codes.get(name) {
-        Some(code) => {
-            let mask = parts
-                .iter()
-                .map(|&s| ModifierKey::try_from(s))
-                .try_fold(0, |acc, v| v.map(|inner| acc | u16::from(inner)))?;
-
-            trace!(?pattern, mask, code, "parsed keybinding");
-            Ok(KeyCode { mask, code: *code })
+    let codes = match all_codes.get(name) {
+        Some(codes) if !codes.is_empty() => codes,
+        _ => {
+            return Err(Error::UnknownKeyName {
+                name: name.to_owned(),
+            })
         }
+    };
 
-        None => Err(Error::UnknownKeyName {
-            name: name.to_owned(),
-        }),
-    }
+    let ix = codes
+        .iter()
+        .rposition(|k| k.mask & XKB_GROUP_MASK == 0)
+        .unwrap_or(codes.len() - 1);
+    let code = codes[ix].code;
+
+    // Codes from other groups that also produce this name in the first group are already
+    // covered by the binding for the first group.
+    let in_first_group = |c: u8| codes.iter().any(|k| k.code == c && k.mask == 0);
+    let alternates: Vec<KeyCode> = codes
+        .iter()
+        .enumerate()
+        .filter(|&(i, k)| i != ix && (k.mask == 0 || !in_first_group(k.code)))
+        .map(|(_, &k)| k)
+        .collect();
+
+    let mask = parts
+        .iter()
+        .map(|&s| ModifierKey::try_from(s))
+        .try_fold(0, |acc, v| v.map(|inner| acc | u16::from(inner)))?;
+
+    trace!(?pattern, mask, code, ?alternates, "parsed keybinding");
+    Ok((KeyCode { mask, code }, alternates))
 }
 
 /// Parse string format key bindings into [KeyCode] based [KeyBindings] using
 /// the command line `xmodmap` utility.
 ///
-/// See [keycodes_from_xmodmap] for details of how `xmodmap` is used.
+/// See [keycodes_from_xmodmap] for details of how `xmodmap` is used. Each binding is a single
+/// key press (such as `"M-a"`) that is bound using the primary key code for its key name.
+///
+/// Key chords and bindings for keys in other keyboard groups share their handlers between
+/// multiple key codes, which requires the [XConn] to be `'static`: use
+/// [parse_keybindings_with_xkb] if you need either of these.
+///
+/// # Errors
+/// Returns `Error::ConflictingKeyBindings` if multiple bindings resolve to the same key
+/// presses, such as `"M-S-a"` and `"S-M-a"`. Bindings made up of multiple key presses are
+/// returned as an `Error::UnknownKeyName`.
 pub fn parse_keybindings_with_xmodmap<S, X>(
     str_bindings: HashMap<S, Box<dyn KeyEventHandler<X>>>,
 ) -> Result<KeyBindings<X>>
@@ -75,12 +205,217 @@ where
     S: AsRef<str>,
     X: XConn,
 {
-    let m = keycodes_from_xmodmap()?;
+    let m = all_keycodes_from_xmodmap()?;
 
-    str_bindings
-        .into_iter()
-        .map(|(s, v)| parse_binding(s.as_ref(), &m).map(|k| (k, v)))
-        .collect()
+    parse_single_keybindings(str_bindings, &m)
+}
+
+/// Parse string format key bindings into [KeyCode] based [KeyBindings] using the keyboard
+/// mapping reported by the X server.
+///
+/// If the keyboard mapping can not be obtained (for example, if the X server does not support
+/// the XKB extension) then this falls back to using the output of `xmodmap`: see
+/// [all_keycodes_from_xmodmap]. See [keycodes_from_xkb] for details of how key names are
+/// resolved. Keys that produce the same key name in other keyboard groups are also bound while
+/// that group is active so that bindings continue to work when switching between keyboard
+/// layouts.
+///
+/// Bindings made up of multiple whitespace separated key presses (e.g. `"M-space t"`) are
+/// run as key chords: see [key_chord] for details.
+///
+/// # Errors
+/// Returns `Error::ConflictingKeyBindings` if multiple bindings resolve to the same key
+/// presses, such as `"M-S-a"` and `"S-M-a"`.
+#[cfg(feature = "keysyms")]
+pub fn parse_keybindings_with_xkb<S, X>(
+    str_bindings: HashMap<S, Box<dyn KeyEventHandler<X>>>,
+    x: &X,
+) -> Result<KeyBindings<X>>
+where
+    S: AsRef<str>,
+    X: XConn + 'static,
+{
+    let m = all_keycodes(x)?;
+
+    parse_keybindings(str_bindings, &m)
+}
+
+// Key codes are taken from the keyboard mapping reported by the X server where possible,
+// falling back to the output of xmodmap if the mapping can not be obtained.
+#[cfg(feature = "keysyms")]
+pub(crate) fn all_keycodes<X: XConn>(x: &X) -> Result<HashMap<String, Vec<KeyCode>>> {
+    match all_keycodes_from_xkb(x) {
+        Ok(m) => Ok(m),
+        Err(e) => {
+            tracing::warn!(%e, "unable to query keyboard mapping: falling back to xmodmap");
+            all_keycodes_from_xmodmap()
+        }
+    }
+}
+
+#[cfg(not(feature = "keysyms"))]
+pub(crate) fn all_keycodes<X: XConn>(_: &X) -> Result<HashMap<String, Vec<KeyCode>>> {
+    all_keycodes_from_xmodmap()
+}
+
+// Key chords and alternate key codes share boxed handlers, which requires the XConn to be
+// 'static. Without that bound, bindings are limited to a single key press using the primary
+// key code for each key name.
+pub(crate) fn parse_single_keybindings<S, X>(
+    str_bindings: HashMap<S, Box<dyn KeyEventHandler<X>>>,
+    all_codes: &HashMap<String, Vec<KeyCode>>,
+) -> Result<KeyBindings<X>>
+where
+    S: AsRef<str>,
+    X: XConn,
+{
+    let mut bindings = KeyBindings::new();
+    let mut patterns: HashMap<KeyCode, String> = HashMap::new();
+
+    for (s, handler) in str_bindings.into_iter() {
+        let pattern = s.as_ref();
+        let k = match pattern.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [p] => parse_binding(p, all_codes)?.0,
+            _ => {
+                return Err(Error::UnknownKeyName {
+                    name: pattern.to_owned(),
+                })
+            }
+        };
+
+        if let Some(existing) = patterns.insert(k, pattern.to_owned()) {
+            return Err(conflict(&existing, pattern));
+        }
+        bindings.insert(k, handler);
+    }
+
+    Ok(bindings)
+}
+
+// Bindings are parsed using the primary key code for each key name and then also bound to
+// any other key codes that produce the same name, within the keyboard group that they belong
+// to.
+pub(crate) fn parse_keybindings<S, X>(
+    str_bindings: HashMap<S, Box<dyn KeyEventHandler<X>>>,
+    all_codes: &HashMap<String, Vec<KeyCode>>,
+) -> Result<KeyBindings<X>>
+where
+    S: AsRef<str>,
+    X: XConn + 'static,
+{
+    let mut tree = HashMap::new();
+
+    for (s, handler) in str_bindings.into_iter() {
+        let pattern = s.as_ref();
+        let codes = pattern
+            .split_whitespace()
+            .map(|p| parse_binding(p, all_codes))
+            .collect::<Result<Vec<_>>>()?;
+
+        let ((last, last_alternates), prefix) = match codes.split_last() {
+            Some(split) => split,
+            None => {
+                return Err(Error::UnknownKeyName {
+                    name: pattern.to_owned(),
+                })
+            }
+        };
+
+        let mut level = &mut tree;
+        for (code, alternates) in prefix {
+            level = match level.entry(*code).or_insert_with(|| {
+                BindingTree::Chord(pattern.to_owned(), Vec::new(), HashMap::new())
+            }) {
+                BindingTree::Chord(_, codes, sub_tree) => {
+                    codes.extend(alternates);
+                    codes.sort_by_key(|k| (k.mask, k.code));
+                    codes.dedup();
+                    sub_tree
+                }
+                BindingTree::Action(existing, _, _) => return Err(conflict(existing, pattern)),
+            };
+        }
+
+        match level.get(last) {
+            Some(BindingTree::Action(existing, _, _) | BindingTree::Chord(existing, _, _)) => {
+                return Err(conflict(existing, pattern))
+            }
+            None => {
+                let node =
+                    BindingTree::Action(pattern.to_owned(), last_alternates.clone(), handler);
+                level.insert(*last, node);
+            }
+        }
+    }
+
+    Ok(BindingTree::into_key_bindings(tree))
+}
+
+fn conflict(a: &str, b: &str) -> Error {
+    let mut bindings = vec![a.to_owned(), b.to_owned()];
+    bindings.sort();
+
+    Error::ConflictingKeyBindings { bindings }
+}
+
+// Intermediate representation of parsed bindings, grouping key chords by their prefix. The
+// original string binding is kept for reporting conflicts along with any alternate key codes
+// for the key (with the mask holding the keyboard group that each alternate belongs to).
+enum BindingTree<X: XConn> {
+    Action(String, Vec<KeyCode>, Box<dyn KeyEventHandler<X>>),
+    Chord(String, Vec<KeyCode>, HashMap<KeyCode, BindingTree<X>>),
+}
+
+impl<X: XConn + 'static> BindingTree<X> {
+    // Alternate key codes share the handler of their primary binding but never replace an
+    // existing binding.
+    fn into_key_bindings(tree: HashMap<KeyCode, Self>) -> KeyBindings<X> {
+        let mut bindings = KeyBindings::new();
+        let mut alternates = Vec::new();
+
+        // Sorted so that conflicting alternates are resolved consistently
+        let mut nodes: Vec<_> = tree.into_iter().collect();
+        nodes.sort_by_key(|&(k, _)| (k.mask, k.code));
+
+        for (k, node) in nodes {
+            let (codes, handler) = match node {
+                Self::Action(_, codes, handler) => (codes, handler),
+                Self::Chord(_, codes, sub_tree) => {
+                    (codes, key_chord(Self::into_key_bindings(sub_tree)))
+                }
+            };
+
+            if codes.is_empty() {
+                bindings.insert(k, handler);
+                continue;
+            }
+
+            let shared = Rc::new(RefCell::new(handler));
+            alternates.extend(codes.into_iter().map(|alt| {
+                let alt = KeyCode {
+                    mask: k.mask | alt.mask,
+                    code: alt.code,
+                };
+                (alt, SharedHandler(Rc::clone(&shared)))
+            }));
+            bindings.insert(k, Box::new(SharedHandler(shared)));
+        }
+
+        for (k, handler) in alternates {
+            bindings.entry(k).or_insert_with(|| Box::new(handler));
+        }
+
+        bindings
+    }
+}
+
+// A handler bound to multiple key codes
+struct SharedHandler<X: XConn>(Rc<RefCell<Box<dyn KeyEventHandler<X>>>>);
+
+impl<X: XConn> KeyEventHandler<X> for SharedHandler<X> {
+    fn call(&mut self, state: &mut State<X>, x: &X) -> Result<()> {
+        self.0.borrow_mut().call(state, x)
+    }
 }
 
 /// Some action to be run by a user key binding
@@ -111,6 +446,170 @@ where
 /// User defined key bindings
 pub type KeyBindings<X> = HashMap<KeyCode, Box<dyn KeyEventHandler<X>>>;
 
+/// Create a [KeyEventHandler] that waits for a further key press and then runs the matching
+/// binding from `bindings`.
+///
+/// While waiting for the next key press, the keys in `bindings` are grabbed in addition to
+/// your top level key bindings. If the next key press does not match any of the bindings
+/// in `bindings` then the chord is cancelled. If no key is pressed within the `chord_timeout`
+/// set in your [Config][crate::core::Config] then the chord is cancelled and its keys are
+/// released so that they are once again sent to the focused client.
+///
+/// Key chords are typically created by using whitespace separated key presses when
+/// parsing string key bindings, such as `"M-space t"`, rather than calling this directly.
+pub fn key_chord<X: XConn + 'static>(bindings: KeyBindings<X>) -> Box<dyn KeyEventHandler<X>> {
+    Box::new(KeyChord {
+        bindings: Rc::new(RefCell::new(bindings)),
+    })
+}
+
+struct KeyChord<X: XConn> {
+    bindings: Rc<RefCell<KeyBindings<X>>>,
+}
+
+impl<X: XConn> KeyEventHandler<X> for KeyChord<X> {
+    fn call(&mut self, state: &mut State<X>, x: &X) -> Result<()> {
+        trace!("awaiting next key in key chord");
+        state.pending_chord = Some(PendingChord {
+            bindings: Rc::clone(&self.bindings),
+            started: Instant::now(),
+        });
+
+        // Wake the event loop once the chord has expired so that its keys can be released
+        match state.config.chord_timeout {
+            Some(timeout) => x.wake_after(timeout),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A key chord that is waiting for its next key press
+pub(crate) struct PendingChord<X: XConn> {
+    pub(crate) bindings: Rc<RefCell<KeyBindings<X>>>,
+    started: Instant,
+}
+
+impl<X: XConn> PendingChord<X> {
+    pub(crate) fn has_expired(&self, timeout: Option<Duration>) -> bool {
+        match timeout {
+            Some(t) => self.started.elapsed() >= t,
+            None => false,
+        }
+    }
+}
+
+impl<X: XConn> fmt::Debug for PendingChord<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingChord")
+            .field("keys", &self.bindings.borrow().keys())
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+/// A named set of [KeyBindings] that take precedence over your top level key bindings while
+/// the mode is active.
+///
+/// Modes are added to the window manager using [WindowManager::add_key_mode][0] and are
+/// entered and exited using the [enter_mode][1] and [exit_mode][2] actions. Modes can be
+/// nested: exiting a mode returns to the mode that was active when it was entered.
+///
+/// Key presses that are not bound in the active mode are handled by your top level key
+/// bindings. By default, only the keys bound in the mode are grabbed while it is active.
+/// If `grab_keyboard` is set then all key presses are sent to the window manager while the
+/// mode is active and any key presses that are not bound are ignored.
+///
+///   [0]: crate::core::WindowManager::add_key_mode
+///   [1]: crate::builtin::actions::enter_mode
+///   [2]: crate::builtin::actions::exit_mode
+pub struct KeyMode<X: XConn> {
+    name: String,
+    bindings: Rc<RefCell<KeyBindings<X>>>,
+    grab_keyboard: bool,
+}
+
+impl<X: XConn> KeyMode<X> {
+    /// Create a new named KeyMode.
+    pub fn new(name: impl Into<String>, bindings: KeyBindings<X>, grab_keyboard: bool) -> Self {
+        Self {
+            name: name.into(),
+            bindings: Rc::new(RefCell::new(bindings)),
+            grab_keyboard,
+        }
+    }
+
+    /// The name of this mode.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<X: XConn> fmt::Debug for KeyMode<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyMode")
+            .field("name", &self.name)
+            .field("keys", &self.bindings.borrow().keys())
+            .field("grab_keyboard", &self.grab_keyboard)
+            .finish()
+    }
+}
+
+/// The known [KeyMode]s and the stack of currently active modes
+#[derive(Debug)]
+pub(crate) struct KeyModes<X: XConn> {
+    modes: HashMap<String, KeyMode<X>>,
+    active: Vec<String>,
+    pub(crate) keyboard_grabbed: bool,
+}
+
+impl<X: XConn> Default for KeyModes<X> {
+    fn default() -> Self {
+        Self {
+            modes: HashMap::new(),
+            active: Vec::new(),
+            keyboard_grabbed: false,
+        }
+    }
+}
+
+impl<X: XConn> KeyModes<X> {
+    pub(crate) fn add(&mut self, mode: KeyMode<X>) {
+        self.modes.insert(mode.name.clone(), mode);
+    }
+
+    pub(crate) fn active(&self) -> Option<&str> {
+        self.active.last().map(|name| name.as_str())
+    }
+
+    pub(crate) fn active_bindings(&self) -> Option<Rc<RefCell<KeyBindings<X>>>> {
+        self.active_mode().map(|m| Rc::clone(&m.bindings))
+    }
+
+    pub(crate) fn should_grab_keyboard(&self) -> bool {
+        self.active_mode().map(|m| m.grab_keyboard).unwrap_or(false)
+    }
+
+    pub(crate) fn enter(&mut self, name: &str) -> Result<()> {
+        if !self.modes.contains_key(name) {
+            return Err(Error::UnknownKeyMode {
+                name: name.to_owned(),
+            });
+        }
+
+        self.active.push(name.to_owned());
+
+        Ok(())
+    }
+
+    pub(crate) fn exit(&mut self) -> Option<String> {
+        self.active.pop()
+    }
+
+    fn active_mode(&self) -> Option<&KeyMode<X>> {
+        self.active().and_then(|name| self.modes.get(name))
+    }
+}
+
 /// An action to be run in response to a mouse event
 pub trait MouseEventHandler<X>
 where
@@ -246,6 +745,12 @@ pub type KeyCodeMask = u16;
 /// A u8 X key-code enum value
 pub type KeyCodeValue = u8;
 
+/// The bits of a [KeyCodeMask] used by XKB to report the active keyboard group.
+///
+/// Key presses are first matched against bindings for the active keyboard group and then
+/// against bindings that apply to all groups. These bits are never included in key grabs.
+pub const XKB_GROUP_MASK: KeyCodeMask = 0x6000;
+
 /// A key press and held modifiers
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -281,6 +786,21 @@ pub enum MouseButton {
     ScrollUp,
     /// 5
     ScrollDown,
+    /// 6
+    ScrollLeft,
+    /// 7
+    ScrollRight,
+}
+
+impl MouseButton {
+    /// Whether or not this button is a scroll wheel event rather than a button that can
+    /// be held down.
+    pub fn is_scroll(&self) -> bool {
+        matches!(
+            self,
+            Self::ScrollUp | Self::ScrollDown | Self::ScrollLeft | Self::ScrollRight
+        )
+    }
 }
 
 impl From<MouseButton> for u8 {
@@ -291,6 +811,8 @@ impl From<MouseButton> for u8 {
             MouseButton::Right => 3,
             MouseButton::ScrollUp => 4,
             MouseButton::ScrollDown => 5,
+            MouseButton::ScrollLeft => 6,
+            MouseButton::ScrollRight => 7,
         }
     }
 }
@@ -305,6 +827,8 @@ impl TryFrom<u8> for MouseButton {
             3 => Ok(Self::Right),
             4 => Ok(Self::ScrollUp),
             5 => Ok(Self::ScrollDown),
+            6 => Ok(Self::ScrollLeft),
+            7 => Ok(Self::ScrollRight),
             _ => Err(Error::UnknownMouseButton { button: n }),
         }
     }
@@ -473,3 +997,99 @@ impl MotionNotifyEvent {
         }
     }
 }
+
+#[cfg(all(test, feature = "keysyms"))]
+mod tests {
+    use super::*;
+    use crate::x::{MockXConn, StubXConn};
+
+    const XMODMAP_OUTPUT: &str = "\
+keycode  24 = q Q q Q
+keycode  36 = Return NoSymbol Return
+keycode  38 = a A a A
+keycode  65 = space NoSymbol space
+keycode 133 = Super_L NoSymbol Super_L
+keycode 206 = NoSymbol Super_L NoSymbol Super_L
+";
+
+    struct KeymapXConn;
+
+    impl MockXConn for KeymapXConn {
+        fn mock_keyboard_mapping(&self) -> Result<HashMap<u8, Vec<u32>>> {
+            Ok([
+                (24, vec![0x71, 0x51]),
+                (36, vec![0xff0d]),
+                (38, vec![0x61, 0x41]),
+                (65, vec![0x20]),
+                (133, vec![0xffeb]),
+                (206, vec![0, 0xffeb]),
+            ]
+            .into_iter()
+            .collect())
+        }
+    }
+
+    fn bindings(keys: &[&str]) -> HashMap<String, Box<dyn KeyEventHandler<StubXConn>>> {
+        keys.iter()
+            .map(|&k| {
+                let handler: Box<dyn KeyEventHandler<StubXConn>> =
+                    Box::new(|_: &mut State<StubXConn>, _: &StubXConn| Ok(()));
+                (k.to_owned(), handler)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_keybindings_use_the_primary_key_code() {
+        let codes = parse_xmodmap_output(XMODMAP_OUTPUT);
+
+        let parsed = parse_single_keybindings(bindings(&["M-a", "C-Super_L"]), &codes).unwrap();
+        let mut keys: Vec<KeyCode> = parsed.into_keys().collect();
+        keys.sort_by_key(|k| (k.code, k.mask));
+
+        let expected = vec![
+            KeyCode {
+                mask: 0x40,
+                code: 38,
+            },
+            KeyCode {
+                mask: 0x04,
+                code: 206,
+            },
+        ];
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn single_keybindings_reject_chords_and_conflicts() {
+        let codes = parse_xmodmap_output(XMODMAP_OUTPUT);
+
+        let res = parse_single_keybindings(bindings(&["M-space a"]), &codes);
+        assert!(matches!(res, Err(Error::UnknownKeyName { name }) if name == "M-space a"));
+
+        let res = parse_single_keybindings(bindings(&["M-S-a", "S-M-a"]), &codes);
+        assert!(matches!(res, Err(Error::ConflictingKeyBindings { .. })));
+    }
+
+    #[test]
+    fn xkb_and_xmodmap_keycodes_agree() {
+        let keys = ["M-q", "M-S-Return", "A-space", "M-a", "C-Super_L"];
+        let from_xmodmap = parse_xmodmap_output(XMODMAP_OUTPUT);
+        let from_xkb = all_keycodes_from_xkb(&KeymapXConn).unwrap();
+
+        let mut expected: Vec<KeyCode> = parse_keybindings(bindings(&keys), &from_xmodmap)
+            .unwrap()
+            .into_keys()
+            .collect();
+        let mut parsed: Vec<KeyCode> = parse_keybindings(bindings(&keys), &from_xkb)
+            .unwrap()
+            .into_keys()
+            .collect();
+
+        expected.sort_by_key(|k| (k.code, k.mask));
+        parsed.sort_by_key(|k| (k.code, k.mask));
+
+        assert_eq!(parsed, expected);
+        assert_eq!(keycodes_from_xkb(&KeymapXConn).unwrap()["Super_L"], 206);
+    }
+}
@@ -0,0 +1,168 @@
+//! Typed events describing changes to the window manager state.
+//!
+//! Subscribing to events using [WindowManager::subscribe_events][0] provides a [Receiver][1]
+//! of [WmEvent]s that can be consumed from another thread, allowing for integrations such as
+//! loggers or IPC clients to react to changes without needing to implement any hooks. Events
+//! are generated by comparing the state after each refresh against the state from the previous
+//! refresh so multiple changes made by a single action are delivered together.
+//!
+//!   [0]: crate::core::WindowManager::subscribe_events
+//!   [1]: std::sync::mpsc::Receiver
+use crate::{core::State, x::XConn, Xid};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A change to the window manager state.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WmEvent {
+    /// A new client is now being managed
+    ClientAdded(Xid),
+    /// A client is no longer being managed
+    ClientRemoved(Xid),
+    /// The workspace with the given tag is now focused
+    TagFocused(String),
+    /// The active layout of the focused workspace is now the layout with the given name
+    LayoutChanged(String),
+    /// The given client is now focused, or no client is focused if this is `None`
+    FocusChanged(Option<Xid>),
+}
+
+/// The parts of [State] that are compared between refreshes in order to generate [WmEvent]s.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct EventState {
+    clients: HashSet<Xid>,
+    tag: String,
+    layout: String,
+    focused_client: Option<Xid>,
+}
+
+impl EventState {
+    pub(crate) fn from_state<X: XConn>(state: &State<X>) -> Self {
+        let cs = &state.client_set;
+
+        Self {
+            clients: cs.clients().copied().collect(),
+            tag: cs.current_tag().to_owned(),
+            layout: cs.current_workspace().layout_name(),
+            focused_client: cs.current_client().copied(),
+        }
+    }
+
+    /// The events required to move from this state to `next`.
+    pub(crate) fn events_until(&self, next: &Self) -> Vec<WmEvent> {
+        let mut removed: Vec<Xid> = self.clients.difference(&next.clients).copied().collect();
+        let mut added: Vec<Xid> = next.clients.difference(&self.clients).copied().collect();
+        removed.sort();
+        added.sort();
+
+        let mut events: Vec<WmEvent> = removed
+            .into_iter()
+            .map(WmEvent::ClientRemoved)
+            .chain(added.into_iter().map(WmEvent::ClientAdded))
+            .collect();
+
+        if self.tag != next.tag {
+            events.push(WmEvent::TagFocused(next.tag.clone()));
+        }
+
+        if self.layout != next.layout {
+            events.push(WmEvent::LayoutChanged(next.layout.clone()));
+        }
+
+        if self.focused_client != next.focused_client {
+            events.push(WmEvent::FocusChanged(next.focused_client));
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{Config, WindowManager},
+        pure::geometry::Rect,
+        x::{test::TestXConn, XConnExt},
+        Xid,
+    };
+    use simple_test_case::test_case;
+    use std::collections::HashMap;
+
+    fn event_state(clients: &[u32], tag: &str, layout: &str, focus: Option<u32>) -> EventState {
+        EventState {
+            clients: clients.iter().map(|&c| Xid(c)).collect(),
+            tag: tag.to_owned(),
+            layout: layout.to_owned(),
+            focused_client: focus.map(Xid),
+        }
+    }
+
+    #[test_case(
+        event_state(&[1], "1", "Side", Some(1)),
+        vec![];
+        "no change"
+    )]
+    #[test_case(
+        event_state(&[1, 2], "1", "Side", Some(2)),
+        vec![WmEvent::ClientAdded(Xid(2)), WmEvent::FocusChanged(Some(Xid(2)))];
+        "client added"
+    )]
+    #[test_case(
+        event_state(&[], "1", "Side", None),
+        vec![WmEvent::ClientRemoved(Xid(1)), WmEvent::FocusChanged(None)];
+        "client removed"
+    )]
+    #[test_case(
+        event_state(&[1], "2", "Mono", None),
+        vec![
+            WmEvent::TagFocused("2".to_owned()),
+            WmEvent::LayoutChanged("Mono".to_owned()),
+            WmEvent::FocusChanged(None),
+        ];
+        "tag focused"
+    )]
+    #[test]
+    fn events_until(next: EventState, expected: Vec<WmEvent>) {
+        let prev = event_state(&[1], "1", "Side", Some(1));
+
+        assert_eq!(prev.events_until(&next), expected);
+    }
+
+    #[test]
+    fn focus_changes_are_delivered_to_subscribers() {
+        let x = TestXConn::new(vec![Rect::new(0, 0, 1000, 800)]);
+        let config = Config::default();
+        let mut wm = WindowManager::new(config, HashMap::new(), HashMap::new(), x).unwrap();
+
+        wm.state.client_set.insert(Xid(1));
+        wm.state.client_set.insert(Xid(2));
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        let rx = wm.subscribe_events();
+        wm.state.client_set.focus_client(&Xid(1));
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        let events: Vec<WmEvent> = rx.try_iter().collect();
+        assert_eq!(events, vec![WmEvent::FocusChanged(Some(Xid(1)))]);
+    }
+
+    #[test]
+    fn subscriptions_end_once_the_receiver_is_dropped() {
+        let x = TestXConn::new(vec![Rect::new(0, 0, 1000, 800)]);
+        let config = Config::default();
+        let mut wm = WindowManager::new(config, HashMap::new(), HashMap::new(), x).unwrap();
+
+        let rx = wm.subscribe_events();
+        let _rx = wm.subscribe_events();
+        drop(rx);
+        assert_eq!(wm.state.subscribers.len(), 2);
+
+        wm.state.client_set.insert(Xid(1));
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        assert_eq!(wm.state.subscribers.len(), 1);
+    }
+}
@@ -1,55 +1,293 @@
 //! XEvent handlers for use in the main event loop;
 use crate::{
+    builtin::actions::fullscreen::{net_wm_state, set_fullscreen_state, FullScreenAction},
     core::{
         bindings::{
-            KeyBindings, KeyCode, MotionNotifyEvent, MouseBindings, MouseEvent, MouseEventKind,
+            KeyBindings, KeyCode, KeyEventHandler, MotionNotifyEvent, MouseBindings, MouseEvent,
+            MouseEventKind, XKB_GROUP_MASK,
         },
         State, Xid,
     },
-    pure::geometry::Point,
+    pure::geometry::{Point, RelativeTo},
     x::{
         atom::Atom,
-        event::{ClientMessage, ClientMessageKind, ConfigureEvent, PointerChange},
-        property::{Prop, WmHints},
+        event::{
+            ClientMessage, ClientMessageData, ClientMessageKind, ConfigureEvent, PointerChange,
+            PropertyEvent,
+        },
+        property::{Prop, WmHints, WmHintsFlags},
         ClientConfig, XConn, XConnExt,
     },
     Result,
 };
-use tracing::{error, info, trace};
+use std::time::Instant;
+use tracing::{error, info, trace, warn};
 
-// Currently no client messages are handled by default (see the ewmh extension for some examples of messages
-// that are handled when that is enabled)
-pub(crate) fn client_message<X: XConn>(msg: ClientMessage, _: &mut State<X>, _: &X) -> Result<()> {
+// Only requests to modify _NET_WM_STATE are handled by default (see the ewmh extension for some
+// examples of other messages that are handled when that is enabled)
+pub(crate) fn client_message<X: XConn>(
+    msg: ClientMessage,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
     let data = &msg.data;
     trace!(id = msg.id.0, dtype = ?msg.dtype, ?data, "got client message");
 
+    if msg.dtype == Atom::NetWmState.as_ref() && state.client_set.contains(&msg.id) {
+        net_wm_state_message(msg.id, data, state, x)?;
+    }
+
+    Ok(())
+}
+
+// Clients are able to request that we add, remove or toggle up to two properties in their
+// _NET_WM_STATE at a time. Currently we support fullscreen, sticky and demanding attention.
+fn net_wm_state_message<X: XConn>(
+    id: Xid,
+    data: &ClientMessageData,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    let data32 = data.as_u32();
+    if data32.len() < 2 {
+        warn!(?data, "malformed data in _NET_WM_STATE message");
+        return Ok(());
+    }
+
+    let action = match FullScreenAction::try_from_u32(data32[0]) {
+        Some(action) => action,
+        None => {
+            warn!(
+                action = data32[0],
+                "invalid _NET_WM_STATE action: expected 0, 1 or 2"
+            );
+            return Ok(());
+        }
+    };
+
+    let props = &data32[1..data32.len().min(3)];
+    let full_screen = *x.intern_atom(Atom::NetWmStateFullscreen.as_ref())?;
+    let demands_attention = *x.intern_atom(Atom::NetWmStateDemandsAttention.as_ref())?;
+    let sticky = *x.intern_atom(Atom::NetWmStateSticky.as_ref())?;
+
+    if props.contains(&full_screen) {
+        set_fullscreen_state(id, action, state, x)?;
+    }
+
+    if props.contains(&sticky) {
+        set_sticky_state(id, sticky, action, state, x)?;
+    }
+
+    if props.contains(&demands_attention) {
+        let urgent = match action {
+            FullScreenAction::Remove => false,
+            FullScreenAction::Add => true,
+            FullScreenAction::Toggle => !state.urgent_clients().contains(&id),
+        };
+
+        if set_urgent(id, urgent, state, x)? {
+            x.refresh(state)?;
+        }
+    }
+
     Ok(())
 }
 
+// The sticky state is tracked in the client set and reflected back in _NET_WM_STATE
+fn set_sticky_state<X: XConn>(
+    id: Xid,
+    sticky: u32,
+    action: FullScreenAction,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    let is_sticky = match action {
+        FullScreenAction::Remove => false,
+        FullScreenAction::Add => true,
+        FullScreenAction::Toggle => !state.client_set.is_sticky(&id),
+    };
+    state.client_set.set_sticky(&id, is_sticky);
+
+    let mut wstate = net_wm_state(id, x).unwrap_or_default();
+    wstate.retain(|&val| val != sticky);
+    if is_sticky {
+        wstate.push(sticky);
+    }
+    x.set_prop(id, Atom::NetWmState.as_ref(), Prop::Cardinal(wstate))?;
+
+    x.refresh(state)
+}
+
 pub(crate) fn mapping_notify<X: XConn>(
     key_bindings: &KeyBindings<X>,
     mouse_bindings: &MouseBindings<X>,
     x: &X,
 ) -> Result<()> {
     trace!("grabbing key and mouse bindings");
-    let key_codes: Vec<_> = key_bindings.keys().copied().collect();
+    let key_codes = grab_codes(key_bindings.keys());
     let mouse_states: Vec<_> = mouse_bindings.keys().cloned().collect();
 
     x.grab(&key_codes, &mouse_states)
 }
 
+// Only the key codes that differ between the old and new bindings are released or grabbed
+// so that reloading a large set of bindings doesn't require grabbing all of them again. Keys
+// for the active key mode and pending key chord remain grabbed.
+pub(crate) fn update_key_grabs<X: XConn>(
+    old: &KeyBindings<X>,
+    new: &KeyBindings<X>,
+    state: &State<X>,
+    x: &X,
+) -> Result<()> {
+    let old = active_key_codes(old, state);
+    let new = active_key_codes(new, state);
+    let removed: Vec<_> = old.iter().filter(|k| !new.contains(k)).copied().collect();
+    let added: Vec<_> = new.iter().filter(|k| !old.contains(k)).copied().collect();
+    trace!(?removed, ?added, "updating key grabs");
+
+    if !removed.is_empty() {
+        x.ungrab_keys(&removed)?;
+    }
+    if !added.is_empty() {
+        x.grab_keys(&added)?;
+    }
+
+    Ok(())
+}
+
+/// Cancel the pending key chord if it has expired, releasing the keys it grabbed.
+pub(crate) fn expire_pending_chord<X: XConn>(
+    key_bindings: &KeyBindings<X>,
+    mouse_bindings: &MouseBindings<X>,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    let timeout = state.config.chord_timeout;
+    if !matches!(&state.pending_chord, Some(chord) if chord.has_expired(timeout)) {
+        return Ok(());
+    }
+
+    trace!("pending key chord has expired: cancelling");
+    state.pending_chord = None;
+
+    grab_active_keys(key_bindings, mouse_bindings, state, x)
+}
+
 pub(crate) fn keypress<X: XConn>(
     key: KeyCode,
+    key_bindings: &mut KeyBindings<X>,
+    mouse_bindings: &MouseBindings<X>,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    let had_chord = state.pending_chord.is_some();
+    let mode = state.key_modes.active().map(|m| m.to_owned());
+
+    let res = match state.pending_chord.take() {
+        Some(chord) if !chord.has_expired(state.config.chord_timeout) => {
+            match binding_for(&mut chord.bindings.borrow_mut(), key) {
+                Some(action) => run_key_binding(key, action, state, x),
+                None => {
+                    trace!(?key, "key did not match pending key chord: cancelling");
+                    Ok(())
+                }
+            }
+        }
+
+        // Bindings in the active key mode take precedence over the top level bindings
+        _ => {
+            let mode_bindings = state.key_modes.active_bindings();
+            let mut mode_bindings = mode_bindings.as_ref().map(|b| b.borrow_mut());
+
+            match mode_bindings
+                .as_mut()
+                .and_then(|b| binding_for(b, key))
+                .or_else(|| binding_for(key_bindings, key))
+            {
+                Some(action) => run_key_binding(key, action, state, x),
+                None => Ok(()),
+            }
+        }
+    };
+
+    let mode_changed = state.key_modes.active() != mode.as_deref();
+    if had_chord || state.pending_chord.is_some() || mode_changed {
+        grab_active_keys(key_bindings, mouse_bindings, state, x)?;
+    }
+
+    res
+}
+
+// Bindings for the active keyboard group take precedence over those that apply to all groups.
+fn binding_for<X: XConn>(
     bindings: &mut KeyBindings<X>,
+    key: KeyCode,
+) -> Option<&mut Box<dyn KeyEventHandler<X>>> {
+    let key = if bindings.contains_key(&key) {
+        key
+    } else {
+        key.ignoring_modifier(XKB_GROUP_MASK)
+    };
+
+    bindings.get_mut(&key)
+}
+
+fn run_key_binding<X: XConn>(
+    key: KeyCode,
+    action: &mut Box<dyn KeyEventHandler<X>>,
     state: &mut State<X>,
     x: &X,
 ) -> Result<()> {
-    if let Some(action) = bindings.get_mut(&key) {
-        trace!(?key, "running user keybinding");
-        if let Err(error) = action.call(state, x) {
-            error!(%error, ?key, "error running user keybinding");
-            return Err(error);
+    trace!(?key, "running user keybinding");
+    if let Err(error) = action.call(state, x) {
+        error!(%error, ?key, "error running user keybinding");
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+// The key codes for the active key mode and pending key chord (if there are any) along with
+// the top level bindings.
+fn active_key_codes<X: XConn>(key_bindings: &KeyBindings<X>, state: &State<X>) -> Vec<KeyCode> {
+    let mut key_codes: Vec<_> = key_bindings.keys().copied().collect();
+    if let Some(bindings) = state.key_modes.active_bindings() {
+        key_codes.extend(bindings.borrow().keys().copied());
+    }
+    if let Some(chord) = &state.pending_chord {
+        key_codes.extend(chord.bindings.borrow().keys().copied());
+    }
+
+    grab_codes(key_codes.iter())
+}
+
+// Passive grabs can not be made for a specific keyboard group so bindings for each group are
+// grabbed using their modifiers alone.
+fn grab_codes<'a>(keys: impl Iterator<Item = &'a KeyCode>) -> Vec<KeyCode> {
+    keys.map(|k| k.ignoring_modifier(XKB_GROUP_MASK)).collect()
+}
+
+// Grab the keys returned by active_key_codes, grabbing or releasing the keyboard if required
+// by the active mode.
+fn grab_active_keys<X: XConn>(
+    key_bindings: &KeyBindings<X>,
+    mouse_bindings: &MouseBindings<X>,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    let key_codes = active_key_codes(key_bindings, state);
+    let mouse_states: Vec<_> = mouse_bindings.keys().cloned().collect();
+
+    x.grab(&key_codes, &mouse_states)?;
+
+    let grab_keyboard = state.key_modes.should_grab_keyboard();
+    if grab_keyboard != state.key_modes.keyboard_grabbed {
+        if grab_keyboard {
+            x.grab_keyboard()?;
+        } else {
+            x.ungrab_keyboard()?;
         }
+        state.key_modes.keyboard_grabbed = grab_keyboard;
     }
 
     Ok(())
@@ -67,7 +305,9 @@ pub(crate) fn mouse_event<X: XConn>(
             return Err(error);
         }
 
+        // Scroll events can't be held so they never result in motion events for the binding
         match e.kind {
+            _ if e.state.button.is_scroll() => (),
             MouseEventKind::Press => state.held_mouse_state = Some(e.state),
             MouseEventKind::Release => state.held_mouse_state = None,
         }
@@ -102,11 +342,32 @@ pub(crate) fn configure_request<X: XConn>(
     state: &mut State<X>,
     x: &X,
 ) -> Result<()> {
-    if state.client_set.contains(id) && !state.client_set.floating.contains_key(id) {
+    let cs = &mut state.client_set;
+    let is_floating = cs.floating.contains_key(id);
+    if cs.contains(id) && !is_floating {
         return Ok(()); // Managed tiled clients aren't allowed to configure themselves
     }
 
-    x.set_client_config(*id, &[ClientConfig::Position(*r)])
+    // Floating clients are kept on the screen they are currently displayed on while unmanaged
+    // windows are kept on the screen they are requesting to be placed on.
+    let screen = match cs.screen_for_client(id) {
+        Some(s) => s.r,
+        None => {
+            cs.screens()
+                .find(|s| s.r.contains_point(r.midpoint()))
+                .unwrap_or_else(|| cs.current_screen())
+                .r
+        }
+    };
+    let r = r.clamped_to(&screen);
+
+    if is_floating {
+        trace!(%id, ?r, "updating floating position from configure request");
+        cs.floating.insert(*id, r.relative_to(&screen));
+    }
+
+    state.applied_positions.borrow_mut().remove(id);
+    x.set_client_config(*id, &[ClientConfig::Position(r)])
 }
 
 pub(crate) fn map_request<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
@@ -116,13 +377,123 @@ pub(crate) fn map_request<X: XConn>(client: Xid, state: &mut State<X>, x: &X) ->
     if !state.client_set.contains(&client) && !attrs.override_redirect {
         trace!(?client, "managing client");
         x.manage(client, state)?;
+
+        // Clients may request to be fullscreen before they are mapped
+        let full_screen = *x.intern_atom(Atom::NetWmStateFullscreen.as_ref())?;
+        if net_wm_state(client, x)?.contains(&full_screen) && state.client_set.contains(&client) {
+            set_fullscreen_state(client, FullScreenAction::Add, state, x)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Windows are checked for struts when they are mapped regardless of whether or not we are
+// managing them, as status bars are frequently override-redirect windows.
+pub(crate) fn map_notify<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
+    if update_strut(client, state, x)? {
+        x.refresh(state)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn property_notify<X: XConn>(
+    PropertyEvent { id, atom, .. }: &PropertyEvent,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    let is_strut = atom == Atom::NetWmStrut.as_ref() || atom == Atom::NetWmStrutPartial.as_ref();
+
+    // Title changes do not modify anything on screen so subscribers are notified directly
+    // rather than running a full refresh.
+    let changed = if is_strut {
+        update_strut(*id, state, x)?
+    } else if atom == Atom::WmHints.as_ref() {
+        check_urgency_hint(*id, state, x)?
+    } else if atom == Atom::WmName.as_ref() || atom == Atom::NetWmName.as_ref() {
+        if update_title(*id, state, x)? {
+            state.notify_subscribers();
+        }
+        false
+    } else {
+        false
+    };
+
+    if changed {
+        x.refresh(state)?;
     }
 
     Ok(())
 }
 
+/// Update the cached title of a managed client, returning whether or not the title of the
+/// focused client has changed.
+pub(crate) fn update_title<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Result<bool> {
+    if !state.client_set.contains(&client) {
+        return Ok(false);
+    }
+
+    let title = x.window_title(client)?;
+    let prev = state.titles.insert(client, title.clone());
+    let focused = state.client_set.current_client() == Some(&client);
+
+    Ok(focused && prev.as_ref() != Some(&title))
+}
+
+/// Update the urgency of a managed client based on the urgency flag in its WM_HINTS,
+/// returning whether or not its urgency has changed.
+pub(crate) fn check_urgency_hint<X: XConn>(
+    client: Xid,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<bool> {
+    if !state.client_set.contains(&client) {
+        return Ok(false);
+    }
+
+    let urgent = match x.get_prop(client, Atom::WmHints.as_ref())? {
+        Some(Prop::WmHints(WmHints { flags, .. })) => flags.contains(WmHintsFlags::URGENCY_HINT),
+        _ => false,
+    };
+
+    set_urgent(client, urgent, state, x)
+}
+
+// The focused client is never marked as urgent as it already has the user's attention
+fn set_urgent<X: XConn>(client: Xid, urgent: bool, state: &mut State<X>, x: &X) -> Result<bool> {
+    let focused = state.client_set.current_client() == Some(&client);
+
+    state.set_urgent(client, urgent && !focused, x)
+}
+
+/// Update the strut we are tracking for the given window, returning whether or not
+/// the reserved space has changed.
+pub(crate) fn update_strut<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Result<bool> {
+    let strut = x.window_strut(client)?;
+
+    let prev = match strut {
+        Some(s) => {
+            // Managed clients already report property changes to us but anything else
+            // needs to be asked to do so in order for us to see updates to the strut.
+            let known = state.struts.contains_key(&client) || state.client_set.contains(&client);
+            if !known {
+                x.select_property_events(client)?;
+            }
+
+            trace!(?client, ?s, "tracking strut");
+            state.struts.insert(client, s)
+        }
+
+        None => state.struts.remove(&client),
+    };
+
+    Ok(prev != strut)
+}
+
 pub(crate) fn destroy<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
     trace!(?client, "destroying client");
+    state.struts.remove(&client);
     x.unmanage(client, state)?;
     state.mapped.remove(&client);
     state.pending_unmap.remove(&client);
@@ -133,9 +504,10 @@ pub(crate) fn destroy<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Res
 // Expected unmap events are tracked in pending_unmap. We ignore expected unmaps.
 pub(crate) fn unmap_notify<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
     let expected = *state.pending_unmap.get(&client).unwrap_or(&0);
+    let had_strut = state.struts.remove(&client).is_some();
 
     if expected == 0 {
-        x.unmanage(client, state)?;
+        return x.unmanage(client, state);
     } else if expected == 1 {
         state.pending_unmap.remove(&client);
     } else {
@@ -145,6 +517,10 @@ pub(crate) fn unmap_notify<X: XConn>(client: Xid, state: &mut State<X>, x: &X) -
             .and_modify(|count| *count -= 1);
     }
 
+    if had_strut {
+        x.refresh(state)?;
+    }
+
     Ok(())
 }
 
@@ -189,12 +565,47 @@ pub(crate) fn leave<X: XConn>(p: PointerChange, state: &mut State<X>, x: &X) ->
     Ok(())
 }
 
-pub(crate) fn detect_screens<X: XConn>(state: &mut State<X>, x: &X) -> Result<()> {
+// The current time is passed in so that the debounce can be tested without waiting on the clock
+pub(crate) fn detect_screens<X: XConn>(now: Instant, state: &mut State<X>, x: &X) -> Result<()> {
+    // Hotplugging a monitor results in a burst of events so we wait for the X server to finish
+    // reconfiguring outputs: each event in the burst pushes back the time at which screens
+    // are re-detected and the event loop is woken once the final deadline has passed.
+    match state.config.screen_update_debounce {
+        Some(debounce) => {
+            trace!(?debounce, "deferring screen detection");
+            state.pending_screen_update = Some(now + debounce);
+            x.wake_after(debounce)
+        }
+
+        None => update_screens(state, x),
+    }
+}
+
+/// Re-detect screens if a deferred screen update is due at `now`, refreshing the X state to match.
+pub(crate) fn run_pending_screen_update<X: XConn>(
+    now: Instant,
+    state: &mut State<X>,
+    x: &X,
+) -> Result<()> {
+    if !matches!(state.pending_screen_update, Some(t) if now >= t) {
+        return Ok(());
+    }
+
+    state.pending_screen_update = None;
+    update_screens(state, x)?;
+
+    x.refresh(state)
+}
+
+fn update_screens<X: XConn>(state: &mut State<X>, x: &X) -> Result<()> {
     info!("re-detecting screens");
-    let rects = x.screen_details()?;
-    info!(?rects, "found screens");
+    let outputs = x.output_details()?;
+    info!(?outputs, "found screens");
 
-    state.client_set.update_screens(rects)
+    state.client_set.update_outputs(outputs)?;
+    state.force_reposition();
+
+    Ok(())
 }
 
 pub(crate) fn screen_change<X: XConn>(state: &mut State<X>, x: &X) -> Result<()> {
@@ -21,6 +21,14 @@
 //!
 //! > **NOTE**: Startup hooks are run to completion before entering the main event loop.
 //!
+//! ### Post Startup Hooks
+//!
+//! If you need to run something once the window manager has fully initialised, such as
+//! spawning the programs you want running in your session, you can use a [`PostStartupHook`].
+//! These are run exactly once, after any existing clients have been managed and the first
+//! layout has been applied, and are given access to the full [`WindowManager`]. Post startup
+//! hooks are not run again when reloading your config.
+//!
 //! ### Event Hooks
 //!
 //! The [`EventHook`] trait allows you to pre-process incoming [`XEvent`]s as they
@@ -54,6 +62,14 @@
 //! >           triggering a refresh directly will do is run the refresh twice: once with
 //! >           the initial state of the client before your hook was applied and once after.
 //!
+//! ### Urgent Hooks
+//!
+//! [`UrgentHook`]s are run whenever a client requests attention (by setting the urgency flag
+//! in its `WM_HINTS` or via `_NET_WM_STATE_DEMANDS_ATTENTION`) and again when that request is
+//! cleared. Clients have their urgency cleared when they are focused or removed from the
+//! window manager state. The set of currently urgent clients can be inspected using
+//! [`State::urgent_clients`] which is useful for highlighting workspaces in a status bar.
+//!
 //! ### Layout Hooks
 //!
 //! Next we have [`LayoutHook`]s which operate a little differently, in that they have
@@ -88,12 +104,12 @@
 //!   [2]: crate::core::Config
 
 use crate::{
-    core::{layout::LayoutTransformer, State},
+    core::{layout::LayoutTransformer, State, WindowManager},
     pure::geometry::Rect,
-    x::{XConn, XEvent},
+    x::{Query, XConn, XEvent},
     Result, Xid,
 };
-use std::fmt;
+use std::{fmt, time::Instant};
 
 /// Handle an [XEvent], return `true` if default event handling should be run afterwards.
 ///
@@ -290,6 +306,167 @@ where
     }
 }
 
+/// A [ManageHook] that is run at most once: for the first newly managed client matching its
+/// [Query], provided that the client appears before the hook expires.
+///
+/// One shot hooks are added at runtime using [State::add_one_shot_manage_hook].
+pub(crate) struct OneShotManageHook<X>
+where
+    X: XConn,
+{
+    pub(crate) query: Box<dyn Query<X>>,
+    pub(crate) hook: Box<dyn ManageHook<X>>,
+    pub(crate) expires_at: Instant,
+}
+
+impl<X: XConn> OneShotManageHook<X> {
+    pub(crate) fn has_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    // Errors from the query are treated as the client not matching
+    pub(crate) fn matches(&self, client: Xid, x: &X) -> bool {
+        self.query.run(client, x).unwrap_or(false)
+    }
+}
+
+impl<X: XConn> fmt::Debug for OneShotManageHook<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OneShotManageHook")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+/// A callback to run once, after the requests made while processing the current event have
+/// been flushed to the X server.
+///
+/// Callbacks are added at runtime using [State::after_flush].
+#[allow(clippy::type_complexity)]
+pub(crate) struct AfterFlush<X>(pub(crate) Box<dyn FnOnce(&mut State<X>, &X) -> Result<()>>)
+where
+    X: XConn;
+
+impl<X: XConn> fmt::Debug for AfterFlush<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AfterFlush").finish()
+    }
+}
+
+/// A callback that is notified each time the window manager state may have changed: after
+/// each refresh and when the title of the focused client changes.
+///
+/// Subscribers are added using [WindowManager::subscribe_events][0] and
+/// [WindowManager::subscribe_state_json][1]. A subscriber returns `false` once it should no
+/// longer be notified.
+///
+///   [0]: crate::core::WindowManager::subscribe_events
+///   [1]: crate::core::WindowManager::subscribe_state_json
+#[allow(clippy::type_complexity)]
+pub(crate) struct Subscriber<X>(pub(crate) Box<dyn FnMut(&State<X>) -> bool>)
+where
+    X: XConn;
+
+impl<X: XConn> fmt::Debug for Subscriber<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscriber").finish()
+    }
+}
+
+/// Action to run when a client is marked as urgent or has its urgency cleared.
+///
+/// `urgent` is `true` when the client has requested attention and `false` when that request
+/// has been cleared, either by the client itself, by it being focused or by it being closed.
+/// As with [ManageHook]s, urgent hooks should _not_ trigger refreshes of state directly.
+pub trait UrgentHook<X>
+where
+    X: XConn,
+{
+    /// Run this hook
+    fn call(&mut self, client: Xid, urgent: bool, state: &mut State<X>, x: &X) -> Result<()>;
+
+    /// Convert to a trait object
+    fn boxed(self) -> Box<dyn UrgentHook<X>>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+
+    /// Compose this hook with another [UrgentHook].
+    fn then<H>(self, next: H) -> ComposedUrgentHook<X>
+    where
+        H: UrgentHook<X> + 'static,
+        Self: Sized + 'static,
+    {
+        ComposedUrgentHook {
+            first: Box::new(self),
+            second: Box::new(next),
+        }
+    }
+
+    /// Compose this hook with a boxed [UrgentHook].
+    fn then_boxed(self, next: Box<dyn UrgentHook<X>>) -> Box<dyn UrgentHook<X>>
+    where
+        Self: Sized + 'static,
+        X: 'static,
+    {
+        Box::new(ComposedUrgentHook {
+            first: Box::new(self),
+            second: next,
+        })
+    }
+}
+
+impl<X> UrgentHook<X> for Vec<Box<dyn UrgentHook<X>>>
+where
+    X: XConn,
+{
+    fn call(&mut self, id: Xid, urgent: bool, state: &mut State<X>, x: &X) -> Result<()> {
+        for hook in self.iter_mut() {
+            hook.call(id, urgent, state, x)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<X: XConn> fmt::Debug for Box<dyn UrgentHook<X>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrgentHook").finish()
+    }
+}
+
+/// The result of composing two urgent hooks using `then`
+#[derive(Debug)]
+pub struct ComposedUrgentHook<X>
+where
+    X: XConn,
+{
+    first: Box<dyn UrgentHook<X>>,
+    second: Box<dyn UrgentHook<X>>,
+}
+
+impl<X> UrgentHook<X> for ComposedUrgentHook<X>
+where
+    X: XConn,
+{
+    fn call(&mut self, client: Xid, urgent: bool, state: &mut State<X>, x: &X) -> Result<()> {
+        self.first.call(client, urgent, state, x)?;
+        self.second.call(client, urgent, state, x)
+    }
+}
+
+impl<F, X> UrgentHook<X> for F
+where
+    F: FnMut(Xid, bool, &mut State<X>, &X) -> Result<()>,
+    X: XConn,
+{
+    fn call(&mut self, client: Xid, urgent: bool, state: &mut State<X>, x: &X) -> Result<()> {
+        (self)(client, urgent, state, x)
+    }
+}
+
 /// An arbitrary action that can be run and modify [State]
 pub trait StateHook<X>
 where
@@ -380,6 +557,99 @@ where
     }
 }
 
+/// Action to run once with access to the [WindowManager] after startup has completed.
+///
+/// Post startup hooks are run after existing clients have been managed and the initial layout
+/// has been applied, immediately before entering the main event loop.
+pub trait PostStartupHook<X>
+where
+    X: XConn,
+{
+    /// Run this hook
+    fn call(&mut self, wm: &mut WindowManager<X>) -> Result<()>;
+
+    /// Convert to a trait object
+    fn boxed(self) -> Box<dyn PostStartupHook<X>>
+    where
+        Self: Sized + 'static,
+    {
+        Box::new(self)
+    }
+
+    /// Compose this hook with another [PostStartupHook].
+    fn then<H>(self, next: H) -> ComposedPostStartupHook<X>
+    where
+        H: PostStartupHook<X> + 'static,
+        Self: Sized + 'static,
+    {
+        ComposedPostStartupHook {
+            first: Box::new(self),
+            second: Box::new(next),
+        }
+    }
+
+    /// Compose this hook with a boxed [PostStartupHook].
+    fn then_boxed(self, next: Box<dyn PostStartupHook<X>>) -> Box<dyn PostStartupHook<X>>
+    where
+        Self: Sized + 'static,
+        X: 'static,
+    {
+        Box::new(ComposedPostStartupHook {
+            first: Box::new(self),
+            second: next,
+        })
+    }
+}
+
+impl<X> PostStartupHook<X> for Vec<Box<dyn PostStartupHook<X>>>
+where
+    X: XConn,
+{
+    fn call(&mut self, wm: &mut WindowManager<X>) -> Result<()> {
+        for hook in self.iter_mut() {
+            hook.call(wm)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<X: XConn> fmt::Debug for Box<dyn PostStartupHook<X>> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PostStartupHook").finish()
+    }
+}
+
+/// The result of composing two post startup hooks using `then`
+#[derive(Debug)]
+pub struct ComposedPostStartupHook<X>
+where
+    X: XConn,
+{
+    first: Box<dyn PostStartupHook<X>>,
+    second: Box<dyn PostStartupHook<X>>,
+}
+
+impl<X> PostStartupHook<X> for ComposedPostStartupHook<X>
+where
+    X: XConn,
+{
+    fn call(&mut self, wm: &mut WindowManager<X>) -> Result<()> {
+        self.first.call(wm)?;
+        self.second.call(wm)
+    }
+}
+
+impl<F, X> PostStartupHook<X> for F
+where
+    F: FnMut(&mut WindowManager<X>) -> Result<()>,
+    X: XConn,
+{
+    fn call(&mut self, wm: &mut WindowManager<X>) -> Result<()> {
+        (self)(wm)
+    }
+}
+
 /// Logic to run before and after laying out clients
 pub trait LayoutHook<X>
 where
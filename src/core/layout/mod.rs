@@ -95,6 +95,16 @@ pub trait Layout {
     ///
     /// See the trait level docs for details on what is possible with messages.
     fn handle_message(&mut self, m: &Message) -> Option<Box<dyn Layout>>;
+
+    /// Whether or not this layout should be skipped when cycling through the layouts of a
+    /// [LayoutStack] using [LayoutStack::focus_next_in_cycle] or
+    /// [LayoutStack::focus_previous_in_cycle].
+    ///
+    /// The default implementation returns `false`. To mark an existing layout as being skipped
+    /// you can wrap it using [SkipInCycle].
+    fn skip_in_cycle(&self) -> bool {
+        false
+    }
 }
 
 impl Clone for Box<dyn Layout> {
@@ -147,6 +157,29 @@ impl LayoutStack {
         rs
     }
 
+    /// Apply a function to only the currently active [Layout], leaving all others unchanged.
+    ///
+    /// This is useful for wrapping a single layout in a [LayoutTransformer]. To apply a
+    /// function to every layout in the stack, use [Stack::map]. In both cases the position of
+    /// each layout and the layout that is active are preserved.
+    ///
+    /// # Example
+    /// ```
+    /// # use penrose::{builtin::layout::{transformers::ReflectHorizontal, Grid, Monocle}, stack};
+    /// # use penrose::core::layout::Layout;
+    /// let layouts = stack!(Monocle::boxed(), Grid::boxed()).map_focused(ReflectHorizontal::wrap);
+    ///
+    /// assert_eq!(layouts.name(), "Reflected<Mono>");
+    /// ```
+    pub fn map_focused<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Box<dyn Layout>) -> Box<dyn Layout>,
+    {
+        self.focus = f(self.focus);
+
+        self
+    }
+
     /// Send the given [Message] to the currently active [Layout].
     pub fn handle_message<M>(&mut self, m: M)
     where
@@ -173,6 +206,121 @@ impl LayoutStack {
             }
         }
     }
+
+    /// Move focus to the next [Layout] in the stack that is not marked as being skipped when
+    /// cycling, wrapping from bottom to top.
+    ///
+    /// If every other layout is marked as skipped then the focus is left unchanged.
+    pub fn focus_next_in_cycle(&mut self) {
+        self.cycle_focus(Self::focus_down);
+    }
+
+    /// Move focus to the previous [Layout] in the stack that is not marked as being skipped
+    /// when cycling, wrapping from top to bottom.
+    ///
+    /// If every other layout is marked as skipped then the focus is left unchanged.
+    pub fn focus_previous_in_cycle(&mut self) {
+        self.cycle_focus(Self::focus_up);
+    }
+
+    fn cycle_focus(&mut self, step: fn(&mut Self) -> &mut Self) {
+        for _ in 1..self.len() {
+            step(self);
+            if !self.focus.skip_in_cycle() {
+                return;
+            }
+        }
+
+        // Every other layout is skipped so we are back where we started
+        step(self);
+    }
+}
+
+/// A wrapper around a [Layout] that marks it as being skipped when cycling through the
+/// layouts of a [LayoutStack] with [LayoutStack::focus_next_in_cycle] and
+/// [LayoutStack::focus_previous_in_cycle].
+///
+/// The wrapped layout can still be selected directly by name, allowing for layouts that are
+/// only ever jumped to using a dedicated key binding. The name of the wrapped layout is left
+/// unchanged and all messages are passed through to it.
+///
+/// Layouts wrapped by a [LayoutTransformer] are also skipped if the inner layout is, provided
+/// that the transformer implements [LayoutTransformer::inner].
+///
+/// # Example
+/// ```
+/// # use penrose::{builtin::layout::{Grid, MainAndStack, Monocle}, stack};
+/// # use penrose::core::layout::{Layout, SkipInCycle};
+/// let mut layouts = stack!(
+///     Monocle::boxed(),
+///     SkipInCycle::wrap(Grid::boxed()),
+///     MainAndStack::boxed_default()
+/// );
+/// layouts.focus_next_in_cycle();
+///
+/// assert_eq!(layouts.name(), "Side");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkipInCycle(Box<dyn Layout>);
+
+impl SkipInCycle {
+    /// Mark the given layout as being skipped when cycling through layouts.
+    pub fn wrap(layout: Box<dyn Layout>) -> Box<dyn Layout> {
+        Box::new(Self(layout))
+    }
+
+    fn replace_inner(&mut self, new: Option<Box<dyn Layout>>) {
+        if let Some(l) = new {
+            self.0 = l;
+        }
+    }
+}
+
+impl Layout for SkipInCycle {
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Layout> {
+        Box::new(self.clone())
+    }
+
+    fn layout_workspace(
+        &mut self,
+        tag: &str,
+        stack: &Option<Stack<Xid>>,
+        r: Rect,
+    ) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.0.layout_workspace(tag, stack, r);
+        self.replace_inner(new);
+
+        (None, positions)
+    }
+
+    fn layout(&mut self, s: &Stack<Xid>, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.0.layout(s, r);
+        self.replace_inner(new);
+
+        (None, positions)
+    }
+
+    fn layout_empty(&mut self, r: Rect) -> (Option<Box<dyn Layout>>, Vec<(Xid, Rect)>) {
+        let (new, positions) = self.0.layout_empty(r);
+        self.replace_inner(new);
+
+        (None, positions)
+    }
+
+    fn handle_message(&mut self, m: &Message) -> Option<Box<dyn Layout>> {
+        let new = self.0.handle_message(m);
+        self.replace_inner(new);
+
+        None
+    }
+
+    fn skip_in_cycle(&self) -> bool {
+        true
+    }
 }
 
 impl Layout for LayoutStack {
@@ -214,3 +362,114 @@ impl Layout for LayoutStack {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builtin::layout::{transformers::ReflectHorizontal, Grid, Monocle};
+    use simple_test_case::test_case;
+
+    fn names(s: &LayoutStack) -> Vec<String> {
+        s.iter().map(|l| l.name()).collect()
+    }
+
+    #[test]
+    fn map_focused_only_wraps_the_active_layout() {
+        let mut s = stack!(
+            Monocle::boxed(),
+            Grid::boxed(),
+            MainAndStack::boxed_default()
+        );
+        s.focus_down();
+
+        let s = s.map_focused(ReflectHorizontal::wrap);
+
+        assert_eq!(names(&s), vec!["Mono", "Reflected<Grid>", "Side"]);
+        assert_eq!(s.focus.name(), "Reflected<Grid>");
+    }
+
+    #[test]
+    fn map_wraps_every_layout_and_preserves_focus() {
+        let mut s = stack!(
+            Monocle::boxed(),
+            Grid::boxed(),
+            MainAndStack::boxed_default()
+        );
+        s.focus_down();
+
+        let s = s.map(ReflectHorizontal::wrap);
+
+        assert_eq!(
+            names(&s),
+            vec!["Reflected<Mono>", "Reflected<Grid>", "Reflected<Side>"]
+        );
+        assert_eq!(s.focus.name(), "Reflected<Grid>");
+    }
+
+    fn skipping_stack() -> LayoutStack {
+        stack!(
+            Monocle::boxed(),
+            SkipInCycle::wrap(Grid::boxed()),
+            MainAndStack::boxed_default(),
+            SkipInCycle::wrap(ReflectHorizontal::wrap(Monocle::boxed()))
+        )
+    }
+
+    #[test_case(true, &["Side", "Mono", "Side"]; "next")]
+    #[test_case(false, &["Side", "Mono", "Side"]; "previous")]
+    #[test]
+    fn cycling_skips_marked_layouts(next: bool, expected: &[&str]) {
+        let mut s = skipping_stack();
+        let mut seen = Vec::new();
+
+        for _ in 0..3 {
+            if next {
+                s.focus_next_in_cycle();
+            } else {
+                s.focus_previous_in_cycle();
+            }
+            seen.push(s.focus.name());
+        }
+
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn cycling_from_a_skipped_layout_moves_to_the_next_unskipped_one() {
+        let mut s = skipping_stack();
+        s.focus_element_by(|l| l.name() == "Grid");
+        s.focus_next_in_cycle();
+
+        assert_eq!(s.focus.name(), "Side");
+    }
+
+    #[test]
+    fn cycling_is_a_no_op_if_all_other_layouts_are_skipped() {
+        let mut s = stack!(Monocle::boxed(), SkipInCycle::wrap(Grid::boxed()));
+        s.focus_next_in_cycle();
+
+        assert_eq!(s.focus.name(), "Mono");
+    }
+
+    #[test]
+    fn transformers_wrapping_a_skipped_layout_are_skipped() {
+        let mut s = stack!(
+            Monocle::boxed(),
+            ReflectHorizontal::wrap(SkipInCycle::wrap(Grid::boxed())),
+            MainAndStack::boxed_default()
+        )
+        .map(ReflectHorizontal::wrap);
+
+        s.focus_next_in_cycle();
+
+        assert_eq!(s.focus.name(), "Reflected<Side>");
+    }
+
+    #[test]
+    fn skip_in_cycle_preserves_the_inner_name() {
+        assert_eq!(
+            names(&skipping_stack()),
+            vec!["Mono", "Grid", "Side", "Reflected<Mono>"]
+        );
+    }
+}
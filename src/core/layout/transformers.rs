@@ -12,6 +12,15 @@ pub trait LayoutTransformer: Clone + Sized + 'static {
     /// The same as [Layout::name] but for [LayoutTransformer] itself.
     fn transformed_name(&self) -> String;
 
+    /// Provide a reference to the [Layout] wrapped by this transformer if possible.
+    ///
+    /// This is used to check whether the wrapped layout should be skipped when cycling through
+    /// layouts (see [Layout::skip_in_cycle]). The default implementation returns `None`, in
+    /// which case the transformer is never skipped.
+    fn inner(&self) -> Option<&dyn Layout> {
+        None
+    }
+
     /// Provide a mutable reference to the [Layout] wrapped by this transformer.
     fn inner_mut(&mut self) -> &mut Box<dyn Layout>;
 
@@ -116,6 +125,10 @@ where
 
         self.passthrough_message(m)
     }
+
+    fn skip_in_cycle(&self) -> bool {
+        self.inner().is_some_and(|l| l.skip_in_cycle())
+    }
 }
 
 /// Quickly define a [LayoutTransformer] from a single element tuple struct and a
@@ -159,6 +172,10 @@ macro_rules! simple_transformer {
                 format!("{}<{}>", $prefix, self.0.name())
             }
 
+            fn inner(&self) -> Option<&dyn $crate::core::layout::Layout> {
+                Some(&*self.0)
+            }
+
             fn inner_mut(&mut self) -> &mut Box<dyn $crate::core::layout::Layout> {
                 &mut self.0
             }
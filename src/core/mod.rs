@@ -1,10 +1,13 @@
 //! Core data structures and user facing functionality for the window manager
 use crate::{
-    pure::{geometry::Rect, Diff, ScreenClients, Snapshot, StackSet, Workspace},
+    pure::{
+        geometry::{Rect, RelativeRect},
+        Diff, ScreenClients, Snapshot, StackSet, Workspace,
+    },
     x::{
         manage_without_refresh,
-        property::{MapState, WmState},
-        Atom, Prop, WindowAttributes, XConn, XConnExt, XEvent,
+        property::{MapState, Strut, WmState},
+        Atom, ClientConfig, Prop, Query, WindowAttributes, XConn, XConnExt, XEvent,
     },
     Color, Error, Result,
 };
@@ -18,18 +21,32 @@ use std::{
     collections::{HashMap, HashSet},
     fmt,
     ops::Deref,
-    sync::Arc,
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 use tracing::{debug, error, info, span, trace, warn, Level};
 
 pub mod bindings;
+pub mod events;
 pub(crate) mod handle;
 pub mod hooks;
 pub mod layout;
+pub mod summary;
 
-use bindings::{KeyBindings, MouseBindings, MouseState};
-use hooks::{EventHook, LayoutHook, ManageHook, StateHook};
+use bindings::{
+    all_keycodes, parse_keybindings, KeyBindings, KeyCode, KeyEventHandler, KeyMode, KeyModes,
+    MouseBindings, MouseState, PendingChord,
+};
+use events::{EventState, WmEvent};
+use hooks::{
+    AfterFlush, EventHook, LayoutHook, ManageHook, OneShotManageHook, PostStartupHook, StateHook,
+    Subscriber, UrgentHook,
+};
 use layout::{Layout, LayoutStack};
+use summary::StateSummary;
 
 /// An X11 ID for a given resource
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -82,10 +99,25 @@ where
     pub(crate) root: Xid,
     pub(crate) mapped: HashSet<Xid>,
     pub(crate) pending_unmap: HashMap<Xid, usize>,
+    pub(crate) struts: HashMap<Xid, Strut>,
+    pub(crate) urgent: Vec<Xid>,
+    pub(crate) titles: HashMap<Xid, String>,
     pub(crate) current_event: Option<XEvent>,
     pub(crate) diff: Diff<Xid>,
     pub(crate) running: bool,
     pub(crate) held_mouse_state: Option<MouseState>,
+    pub(crate) pending_chord: Option<PendingChord<X>>,
+    pub(crate) key_modes: KeyModes<X>,
+    pub(crate) one_shot_manage_hooks: Vec<OneShotManageHook<X>>,
+    pub(crate) after_flush: Vec<AfterFlush<X>>,
+    pub(crate) subscribers: Vec<Subscriber<X>>,
+    pub(crate) pending_screen_update: Option<Instant>,
+    // Updated while positioning clients during a refresh, which only has shared access to state
+    pub(crate) applied_positions: RefCell<HashMap<Xid, Rect>>,
+    pub(crate) focus_history: Vec<Xid>,
+    // The floating position given to each maximized client along with its previous position
+    pub(crate) maximized: HashMap<Xid, (RelativeRect, Option<RelativeRect>)>,
+    pub(crate) pending_reload: Option<(Config<X>, KeyBindings<X>)>,
 }
 
 impl<X> State<X>
@@ -93,11 +125,13 @@ where
     X: XConn,
 {
     pub(crate) fn try_new(config: Config<X>, x: &X) -> Result<Self> {
+        let outputs = x.output_details()?;
         let mut client_set = StackSet::try_new(
             config.default_layouts.clone(),
             config.tags.iter(),
-            x.screen_details()?,
+            outputs.iter().map(|(_, r)| *r),
         )?;
+        client_set.update_outputs(outputs)?;
 
         let ss = client_set.snapshot(vec![]);
         let diff = Diff::new(ss.clone(), ss);
@@ -109,10 +143,23 @@ where
             root: x.root(),
             mapped: HashSet::new(),
             pending_unmap: HashMap::new(),
+            struts: HashMap::new(),
+            urgent: Vec::new(),
+            titles: HashMap::new(),
             current_event: None,
             diff,
             running: false,
             held_mouse_state: None,
+            pending_chord: None,
+            key_modes: KeyModes::default(),
+            one_shot_manage_hooks: Vec::new(),
+            after_flush: Vec::new(),
+            subscribers: Vec::new(),
+            pending_screen_update: None,
+            applied_positions: Default::default(),
+            focus_history: Vec::new(),
+            maximized: HashMap::new(),
+            pending_reload: None,
         })
     }
 
@@ -126,11 +173,248 @@ where
         &self.mapped
     }
 
+    /// The set of clients that are currently requesting attention, in the order that they
+    /// were marked as urgent.
+    pub fn urgent_clients(&self) -> &[Xid] {
+        &self.urgent
+    }
+
+    /// Whether or not the [Workspace] with the given tag contains any urgent clients.
+    pub fn workspace_is_urgent(&self, tag: &str) -> bool {
+        self.client_set
+            .workspace(tag)
+            .map(|w| w.clients().any(|c| self.urgent.contains(c)))
+            .unwrap_or(false)
+    }
+
+    /// Mark a client as urgent or clear its urgency, running the user provided [UrgentHook]
+    /// if its urgency has changed.
+    ///
+    /// Returns whether or not the urgency of the client was changed.
+    pub(crate) fn set_urgent(&mut self, id: Xid, urgent: bool, x: &X) -> Result<bool> {
+        if self.urgent.contains(&id) == urgent {
+            return Ok(false);
+        }
+
+        if urgent {
+            self.urgent.push(id);
+        } else {
+            self.urgent.retain(|&c| c != id);
+        }
+
+        // Clients are removed from the urgent set when they are closed so we may not be
+        // able to update their border
+        if self.client_set.contains(&id) {
+            x.set_client_border_color(id, self.border_color(id))?;
+        }
+
+        let mut hook = self.config.urgent_hook.take();
+        if let Some(ref mut h) = hook {
+            trace!(%id, %urgent, "running user urgent hook");
+            if let Err(e) = h.call(id, urgent, self, x) {
+                error!(%e, "error returned from user urgent hook");
+            }
+        }
+        self.config.urgent_hook = hook;
+
+        Ok(true)
+    }
+
+    /// The border color that should be used for the given client based on its current state.
+    ///
+    /// The focused client always uses the focused border color, unfocused clients use the
+    /// urgent border color if they are urgent and the normal border color if not.
+    pub fn border_color(&self, id: Xid) -> Color {
+        if self.client_set.current_client() == Some(&id) {
+            self.config.focused_border
+        } else if self.urgent.contains(&id) {
+            self.config.urgent_border
+        } else {
+            self.config.normal_border
+        }
+    }
+
+    /// The title of the given client as of the last time it was updated.
+    ///
+    /// Titles are cached when clients are managed and updated when they change their
+    /// WM_NAME or _NET_WM_NAME properties so this does not require querying the X server.
+    pub fn client_title(&self, id: Xid) -> Option<&str> {
+        self.titles.get(&id).map(|s| s.as_str())
+    }
+
+    /// Clients in the order they were most recently focused, starting with the currently
+    /// focused client.
+    ///
+    /// This history is updated each time the X state is refreshed and clients are removed
+    /// from it once they are no longer managed.
+    pub fn focus_history(&self) -> &[Xid] {
+        &self.focus_history
+    }
+
+    pub(crate) fn update_focus_history(&mut self) {
+        let cs = &self.client_set;
+        self.focus_history.retain(|id| cs.contains(id));
+
+        if let Some(&id) = cs.current_client() {
+            self.focus_history.retain(|&other| other != id);
+            self.focus_history.insert(0, id);
+        }
+    }
+
+    /// A [StateSummary] of the current state that can be shared with external tools.
+    pub fn summary(&self) -> StateSummary {
+        StateSummary::from_state(self)
+    }
+
+    /// The current [StateSummary] serialized as JSON.
+    #[cfg(feature = "serde")]
+    pub fn state_json(&self) -> String {
+        self.summary().to_json()
+    }
+
+    /// The name of the currently active [KeyMode], if there is one.
+    pub fn active_key_mode(&self) -> Option<&str> {
+        self.key_modes.active()
+    }
+
+    /// Enter the named [KeyMode], making its bindings take precedence over the top level
+    /// key bindings.
+    ///
+    /// See [enter_mode][crate::builtin::actions::enter_mode] for a key binding action that
+    /// calls this method.
+    ///
+    /// # Errors
+    /// Returns `Error::UnknownKeyMode` if no mode with the given name has been added using
+    /// [WindowManager::add_key_mode].
+    pub fn enter_key_mode(&mut self, name: &str) -> Result<()> {
+        self.key_modes.enter(name)
+    }
+
+    /// Exit the currently active [KeyMode], returning its name.
+    pub fn exit_key_mode(&mut self) -> Option<String> {
+        self.key_modes.exit()
+    }
+
+    /// Run a [ManageHook] for the next newly managed client matching `query`, provided that
+    /// it appears within `timeout`.
+    ///
+    /// The hook is run after the `manage_hook` set in your [Config] and is then discarded. If
+    /// multiple one shot hooks match the same client then only the one that was added first
+    /// is run. See [spawn_and_focus][crate::builtin::actions::spawn_and_focus] for an action
+    /// that uses this to focus the client created by a spawned program.
+    pub fn add_one_shot_manage_hook<Q, H>(&mut self, query: Q, hook: H, timeout: Duration)
+    where
+        Q: Query<X> + 'static,
+        H: ManageHook<X> + 'static,
+    {
+        self.one_shot_manage_hooks.push(OneShotManageHook {
+            query: Box::new(query),
+            hook: Box::new(hook),
+            expires_at: Instant::now() + timeout,
+        });
+    }
+
+    /// Run a callback once the requests made while processing the current event have been
+    /// flushed to the X server.
+    ///
+    /// This allows hooks and actions to sequence operations that would otherwise race with
+    /// pending requests, such as warping the cursor to a client that is still being mapped.
+    /// Callbacks are run in the order they were added and requests made by them are flushed
+    /// once they have all completed. Callbacks added while running these callbacks are run
+    /// after the next flush.
+    pub fn after_flush<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut State<X>, &X) -> Result<()> + 'static,
+    {
+        self.after_flush.push(AfterFlush(Box::new(f)));
+    }
+
+    // Subscribers added while notifying are kept but not run until the next notification.
+    // Subscribers that report they are finished are dropped.
+    pub(crate) fn notify_subscribers(&mut self) {
+        let mut subscribers = std::mem::take(&mut self.subscribers);
+        subscribers.retain_mut(|Subscriber(f)| f(self));
+        subscribers.append(&mut self.subscribers);
+        self.subscribers = subscribers;
+    }
+
+    /// Request that the running [WindowManager] reloads its config and key bindings once the
+    /// current event has been handled.
+    ///
+    /// See [WindowManager::reload] for details of what is updated. Only the most recent request
+    /// made while handling an event is applied. See [reload][crate::builtin::actions::reload]
+    /// for a key binding action that calls this method.
+    pub fn request_reload(&mut self, config: Config<X>, key_bindings: KeyBindings<X>) {
+        self.pending_reload = Some((config, key_bindings));
+    }
+
+    /// Force every visible client to be repositioned on the next refresh.
+    ///
+    /// By default, clients are only repositioned when the position assigned to them during a
+    /// refresh differs from the one applied during the previous refresh.
+    pub fn force_reposition(&mut self) {
+        self.applied_positions.borrow_mut().clear();
+    }
+
     /// The event currently being processed.
     pub fn current_event(&self) -> Option<&XEvent> {
         self.current_event.as_ref()
     }
 
+    /// The screen position assigned to the given client during the last refresh.
+    ///
+    /// For tiled clients this is the position returned by the active layout and for floating
+    /// clients it is their current floating position. This is read from the cached output of
+    /// the last layout pass rather than querying the X server so it may not reflect any changes
+    /// the client has made to its own geometry since then.
+    ///
+    /// Returns `None` if the client is unknown or was not visible on a screen.
+    pub fn client_geometry(&self, id: Xid) -> Option<Rect> {
+        self.diff
+            .after
+            .positions
+            .iter()
+            .find(|&&(c, _)| c == id)
+            .map(|&(_, r)| r)
+    }
+
+    /// The visible clients in the order they are stacked on screen, from bottom to top.
+    ///
+    /// Clients are restacked in the order they were positioned during the last refresh so
+    /// this is read from the cached output of the last layout pass rather than querying the
+    /// X server. This is the order used for the visible clients in _NET_CLIENT_LIST_STACKING
+    /// when the ewmh hooks are enabled.
+    pub fn clients_in_stacking_order(&self) -> impl Iterator<Item = Xid> + '_ {
+        self.diff
+            .after
+            .positions
+            .iter()
+            .map(|&(id, _)| id)
+            .filter(|id| self.client_set.contains(id))
+    }
+
+    /// The region of the given screen [Rect] that remains available for tiling once the
+    /// space reserved by windows setting _NET_WM_STRUT or _NET_WM_STRUT_PARTIAL (such as
+    /// external status bars) has been removed.
+    pub fn usable_screen_region(&self, r: Rect) -> Rect {
+        let r_root = self
+            .client_set
+            .screens()
+            .map(|s| s.r)
+            .reduce(|a, b| {
+                let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+                let w = (a.x + a.w).max(b.x + b.w) - x;
+                let h = (a.y + a.h).max(b.y + b.h) - y;
+
+                Rect::new(x, y, w, h)
+            })
+            .unwrap_or(r);
+
+        self.struts
+            .values()
+            .fold(r, |r, strut| strut.apply_to(r, r_root))
+    }
+
     /// Get access to a shared state extension.
     ///
     /// To add an extension to [State] before starting the Window Manager, see the
@@ -177,6 +461,11 @@ where
     }
 
     /// Add a typed [State] extension to this State.
+    ///
+    /// If your extension stores the [Xid]s of client windows then you will need to remove
+    /// them when the client is destroyed (for example, using an [EventHook] that watches for
+    /// [XEvent::Destroy]) as the X server is free to reuse IDs for new windows. See the
+    /// [NamedScratchPad][crate::extensions::hooks::NamedScratchPad] extension for an example.
     pub fn add_extension<E: Any>(&mut self, extension: E) {
         self.extensions.insert(Arc::new(RefCell::new(extension)));
     }
@@ -216,7 +505,9 @@ where
                 float_positions.push((*c, r_c.applied_to(&r_s)));
             }
 
-            // Next run layout functions for each workspace on a visible screen
+            // Next run layout functions for each workspace on a visible screen, leaving
+            // space for any struts that have been set by external clients
+            let r_s = self.usable_screen_region(r_s);
             let stack_positions = match hook {
                 Some(ref mut h) => {
                     let r_s = h.transform_initial_for_screen(i, r_s, self, x);
@@ -244,6 +535,26 @@ where
     }
 }
 
+/// Where to place the mouse cursor when warping it to a newly focused client
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WarpPosition {
+    /// The center of the client window
+    #[default]
+    Center,
+    /// The top left corner of the client window
+    TopLeft,
+}
+
+impl WarpPosition {
+    /// The position of the cursor relative to a client window with the given geometry.
+    pub fn relative_to(&self, r: Rect) -> (i16, i16) {
+        match self {
+            Self::Center => (r.w as i16 / 2, r.h as i16 / 2),
+            Self::TopLeft => (0, 0),
+        }
+    }
+}
+
 /// The user specified config options for how the window manager should run
 pub struct Config<X>
 where
@@ -253,18 +564,48 @@ where
     pub normal_border: Color,
     /// The RGBA color to use for the focused window border
     pub focused_border: Color,
+    /// The RGBA color to use for the borders of unfocused windows that are marked as urgent
+    pub urgent_border: Color,
     /// The width in pixels to use for drawing window borders
     pub border_width: u32,
     /// Whether or not the mouse entering a new window should set focus
     pub focus_follow_mouse: bool,
+    /// Whether or not the mouse cursor should be warped to newly focused clients.
+    ///
+    /// The cursor is always warped when `focus_follow_mouse` is set so that the client under
+    /// the cursor remains the focused client. Focus changes caused by the mouse itself never
+    /// result in the cursor being warped.
+    pub warp_cursor_on_focus: bool,
+    /// Where within a client window the mouse cursor should be placed when warping it
+    pub warp_to: WarpPosition,
+    /// Whether or not tiled clients should have their size reduced to honour the resize
+    /// increment and aspect ratio hints set in their `WM_NORMAL_HINTS` property.
+    ///
+    /// This is useful for terminals that resize in whole character cells: snapped clients
+    /// remain positioned at the top left of the region assigned to them by the active layout.
+    pub respect_size_hints: bool,
     /// The stack of layouts to use for each workspace
     pub default_layouts: LayoutStack,
     /// The ordered set of workspace tags to use on window manager startup
     pub tags: Vec<String>,
     /// Window classes that should always be assigned floating positions rather than tiled
     pub floating_classes: Vec<String>,
+    /// How long to wait for the next key press in a key chord before cancelling it.
+    ///
+    /// If this is `None` then key chords will wait indefinitely.
+    pub chord_timeout: Option<Duration>,
+    /// How long to wait for screen changes to settle before re-detecting screens.
+    ///
+    /// Connecting or disconnecting a monitor typically results in a burst of RandR events
+    /// from the X server: screens are re-detected once this period has passed without any
+    /// further events being received. If this is `None` then screens are re-detected
+    /// immediately for every event.
+    pub screen_update_debounce: Option<Duration>,
     /// A [StateHook] to run before entering the main event loop
     pub startup_hook: Option<Box<dyn StateHook<X>>>,
+    /// A [PostStartupHook] to run once after existing clients have been managed and the
+    /// initial layout has been applied
+    pub post_startup_hook: Option<Box<dyn PostStartupHook<X>>>,
     /// A [StateHook] to run before processing each [XEvent]
     pub event_hook: Option<Box<dyn EventHook<X>>>,
     /// A [ManageHook] to run after each new window becomes managed by the window manager
@@ -273,6 +614,8 @@ where
     pub refresh_hook: Option<Box<dyn StateHook<X>>>,
     /// A [LayoutHook] to run when positioning clients on the screen
     pub layout_hook: Option<Box<dyn LayoutHook<X>>>,
+    /// An [UrgentHook] to run each time a client is marked as urgent or has its urgency cleared
+    pub urgent_hook: Option<Box<dyn UrgentHook<X>>>,
 }
 
 impl<X> fmt::Debug for Config<X>
@@ -283,11 +626,17 @@ where
         f.debug_struct("Config")
             .field("normal_border", &self.normal_border)
             .field("focused_border", &self.focused_border)
+            .field("urgent_border", &self.urgent_border)
             .field("border_width", &self.border_width)
             .field("focus_follow_mouse", &self.focus_follow_mouse)
+            .field("warp_cursor_on_focus", &self.warp_cursor_on_focus)
+            .field("warp_to", &self.warp_to)
+            .field("respect_size_hints", &self.respect_size_hints)
             .field("default_layouts", &self.default_layouts)
             .field("tags", &self.tags)
             .field("floating_classes", &self.floating_classes)
+            .field("chord_timeout", &self.chord_timeout)
+            .field("screen_update_debounce", &self.screen_update_debounce)
             .finish()
     }
 }
@@ -302,16 +651,24 @@ where
         Config {
             normal_border: "#3c3836ff".try_into().expect("valid hex code"),
             focused_border: "#cc241dff".try_into().expect("valid hex code"),
+            urgent_border: "#d79921ff".try_into().expect("valid hex code"),
             border_width: 2,
             focus_follow_mouse: true,
+            warp_cursor_on_focus: false,
+            warp_to: WarpPosition::Center,
+            respect_size_hints: false,
             default_layouts: LayoutStack::default(),
             tags: strings(&["1", "2", "3", "4", "5", "6", "7", "8", "9"]),
             floating_classes: strings(&["dmenu", "dunst"]),
+            chord_timeout: Some(Duration::from_secs(2)),
+            screen_update_debounce: Some(Duration::from_millis(100)),
             startup_hook: None,
+            post_startup_hook: None,
             event_hook: None,
             manage_hook: None,
             refresh_hook: None,
             layout_hook: None,
+            urgent_hook: None,
         }
     }
 }
@@ -334,6 +691,20 @@ where
         };
     }
 
+    /// Set the post_startup_hook or compose it with what is already set.
+    ///
+    /// The new hook will run before what was there before.
+    pub fn compose_or_set_post_startup_hook<H>(&mut self, hook: H)
+    where
+        H: PostStartupHook<X> + 'static,
+        X: 'static,
+    {
+        self.post_startup_hook = match self.post_startup_hook.take() {
+            Some(h) => Some(hook.then_boxed(h)),
+            None => Some(hook.boxed()),
+        };
+    }
+
     /// Set the event_hook or compose it with what is already set.
     ///
     /// The new hook will run before what was there before.
@@ -389,6 +760,20 @@ where
             None => Some(hook.boxed()),
         };
     }
+
+    /// Set the urgent_hook or compose it with what is already set.
+    ///
+    /// The new hook will run before what was there before.
+    pub fn compose_or_set_urgent_hook<H>(&mut self, hook: H)
+    where
+        H: UrgentHook<X> + 'static,
+        X: 'static,
+    {
+        self.urgent_hook = match self.urgent_hook.take() {
+            Some(h) => Some(hook.then_boxed(h)),
+            None => Some(hook.boxed()),
+        };
+    }
 }
 
 /// A top level struct holding all of the state required to run as an X11 window manager.
@@ -436,6 +821,292 @@ where
         self.state.add_extension(extension);
     }
 
+    /// Run a callback once the requests made while processing the current event have been
+    /// flushed to the X server.
+    ///
+    /// See [State::after_flush] for details.
+    pub fn after_flush<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut State<X>, &X) -> Result<()> + 'static,
+    {
+        self.state.after_flush(f);
+    }
+
+    /// Add a [KeyMode] that can be entered using [State::enter_key_mode].
+    ///
+    /// Adding a mode with the same name as an existing mode replaces it.
+    pub fn add_key_mode(&mut self, mode: KeyMode<X>) {
+        self.state.key_modes.add(mode);
+    }
+
+    /// The screen position assigned to the given client during the last refresh.
+    ///
+    /// See [State::client_geometry] for details.
+    pub fn client_geometry(&self, id: Xid) -> Option<Rect> {
+        self.state.client_geometry(id)
+    }
+
+    /// The visible clients in the order they are stacked on screen, from bottom to top.
+    ///
+    /// See [State::clients_in_stacking_order] for details.
+    pub fn clients_in_stacking_order(&self) -> impl Iterator<Item = Xid> + '_ {
+        self.state.clients_in_stacking_order()
+    }
+
+    /// The class name (the second string of `WM_CLASS`) of the currently focused client.
+    ///
+    /// Returns `None` if there is no focused client or its class could not be fetched.
+    pub fn focused_client_class(&self) -> Option<String> {
+        let &id = self.state.client_set.current_client()?;
+
+        match self.x.get_prop(id, Atom::WmClass.as_ref()) {
+            Ok(Some(Prop::UTF8String(mut strs))) if strs.len() > 1 => Some(strs.swap_remove(1)),
+            _ => None,
+        }
+    }
+
+    /// The title of the currently focused client.
+    ///
+    /// Returns `None` if there is no focused client or its title could not be fetched. See
+    /// [XConnExt::window_title] for details of how the title is determined.
+    pub fn focused_client_title(&self) -> Option<String> {
+        let &id = self.state.client_set.current_client()?;
+
+        self.x.window_title(id).ok()
+    }
+
+    /// The current state of the window manager serialized as JSON.
+    ///
+    /// See [StateSummary] for details of the fields that are included.
+    #[cfg(feature = "serde")]
+    pub fn state_json(&self) -> String {
+        self.state.state_json()
+    }
+
+    /// Receive the output of [WindowManager::state_json] each time it changes.
+    ///
+    /// The state is checked for changes after each refresh and each time the title of the
+    /// focused client changes. The first check after subscribing will always send the current
+    /// state.
+    #[cfg(feature = "serde")]
+    pub fn subscribe_state_json(&mut self) -> Receiver<String>
+    where
+        X: 'static,
+    {
+        let (tx, rx) = channel();
+        let mut prev = None;
+
+        self.state
+            .subscribers
+            .push(Subscriber(Box::new(move |state: &State<X>| {
+                let json = state.state_json();
+                if prev.as_ref() == Some(&json) {
+                    return true;
+                }
+                prev = Some(json.clone());
+
+                // The subscription ends once the receiver has been dropped
+                tx.send(json).is_ok()
+            })));
+
+        rx
+    }
+
+    /// Receive a [WmEvent] for each change to the window manager state.
+    ///
+    /// The state is checked for changes after each refresh, with the state at the time of
+    /// subscribing used as the starting point. Each call to this method creates a new,
+    /// independent subscription.
+    pub fn subscribe_events(&mut self) -> Receiver<WmEvent>
+    where
+        X: 'static,
+    {
+        let (tx, rx) = channel();
+        let mut prev = EventState::from_state(&self.state);
+
+        self.state
+            .subscribers
+            .push(Subscriber(Box::new(move |state: &State<X>| {
+                let next = EventState::from_state(state);
+                let events = prev.events_until(&next);
+                prev = next;
+
+                // The subscription ends once the receiver has been dropped
+                events.into_iter().all(|event| tx.send(event).is_ok())
+            })));
+
+        rx
+    }
+
+    /// Replace the current [ClientSet] with one that was previously persisted, for example
+    /// to restore the state of the window manager after re-executing the window manager binary.
+    ///
+    /// The restored state is validated against the X server before it is used: every client
+    /// it contains must still exist and the screens it was created with must match those that
+    /// are currently connected. If either of these checks fails then an error is returned and the
+    /// current state is left unchanged.
+    ///
+    /// [LayoutStack]s can not be serialized so each restored workspace is given the layouts of
+    /// the current workspace with the same tag (or the `default_layouts` from your [Config] if
+    /// there is no such workspace).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use penrose::{core::{ClientSet, WindowManager}, x::XConn, Result};
+    /// # fn load_state() -> ClientSet { todo!() }
+    /// # fn example<X: XConn>(mut wm: WindowManager<X>) -> Result<()> {
+    /// // With the `serde` feature enabled, `load_state` can deserialize a ClientSet that was
+    /// // serialized from `wm.state.client_set` before the window manager was restarted.
+    /// let client_set: ClientSet = load_state();
+    /// wm.hydrate(client_set)?;
+    /// wm.run()
+    /// # }
+    /// ```
+    pub fn hydrate(&mut self, mut client_set: ClientSet) -> Result<()> {
+        let existing: HashSet<Xid> = self.x.existing_clients()?.into_iter().collect();
+        let mut missing: Vec<Xid> = client_set
+            .clients()
+            .chain(client_set.floating.keys())
+            .filter(|id| !existing.contains(id))
+            .copied()
+            .collect();
+
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            return Err(Error::InvalidHydrationState {
+                reason: format!("unknown clients: {missing:?}"),
+            });
+        }
+
+        client_set
+            .previous_floating
+            .retain(|id, _| existing.contains(id));
+        client_set.sticky.retain(|id| existing.contains(id));
+        client_set.minimized.retain(|_, ids| {
+            ids.retain(|id| existing.contains(id));
+            !ids.is_empty()
+        });
+
+        let mut current_screens = self.x.screen_details()?;
+        let mut restored_screens: Vec<Rect> = client_set.screens().map(|s| s.r).collect();
+        current_screens.sort_by_key(|r| (r.x, r.y));
+        restored_screens.sort_by_key(|r| (r.x, r.y));
+
+        if current_screens != restored_screens {
+            return Err(Error::InvalidHydrationState {
+                reason: format!(
+                    "screens have changed: {restored_screens:?} != {current_screens:?}"
+                ),
+            });
+        }
+
+        for w in client_set.workspaces_mut() {
+            w.layouts = match self.state.client_set.workspace(&w.tag) {
+                Some(current) => current.layouts.clone(),
+                None => self.state.config.default_layouts.clone(),
+            };
+        }
+
+        info!("restoring window manager state");
+        self.state.client_set = client_set;
+        self.state.force_reposition();
+
+        self.x.refresh(&mut self.state)
+    }
+
+    /// Reload the user config and key bindings of a running WindowManager without losing
+    /// the current [ClientSet].
+    ///
+    /// String key bindings are parsed in the same way as [parse_keybindings_with_xkb][0], using
+    /// the keyboard mapping reported by the X server and falling back to the output of `xmodmap`
+    /// if it can not be obtained. Key chords and keys in other keyboard groups are supported and
+    /// the resulting bindings replace the existing ones. Only keys that are no longer bound or that are newly bound have their grabs updated
+    /// on the X server.
+    /// If any of the bindings fail to parse then an error is returned and the current config
+    /// and bindings are left unchanged.
+    ///
+    /// If the names of the `default_layouts` in the new config differ from the current ones
+    /// then they replace the layouts of every workspace. Otherwise each workspace keeps its
+    /// current layouts along with any changes that have been made to them (such as the active
+    /// layout or an adjusted ratio). Visible workspaces are laid out again in either case.
+    /// Border, focus and floating class settings are also updated but the tags and any hooks
+    /// in the new config are ignored: these are set up on startup (often by extensions) and
+    /// are preserved as they are.
+    ///
+    /// Actions and hooks only have access to the [State] of a running WindowManager: they are
+    /// able to trigger a reload using [State::request_reload].
+    ///
+    ///   [0]: crate::core::bindings::parse_keybindings_with_xkb
+    pub fn reload<S>(
+        &mut self,
+        config: Config<X>,
+        key_bindings: HashMap<S, Box<dyn KeyEventHandler<X>>>,
+    ) -> Result<()>
+    where
+        S: AsRef<str>,
+        X: 'static,
+    {
+        let all_codes = all_keycodes(&self.x)?;
+
+        self.reload_with_keycodes(config, key_bindings, &all_codes)
+    }
+
+    fn reload_with_keycodes<S>(
+        &mut self,
+        config: Config<X>,
+        key_bindings: HashMap<S, Box<dyn KeyEventHandler<X>>>,
+        all_codes: &HashMap<String, Vec<KeyCode>>,
+    ) -> Result<()>
+    where
+        S: AsRef<str>,
+        X: 'static,
+    {
+        let key_bindings = parse_keybindings(key_bindings, all_codes)?;
+
+        self.apply_reload(config, key_bindings)
+    }
+
+    fn apply_reload(&mut self, config: Config<X>, key_bindings: KeyBindings<X>) -> Result<()> {
+        handle::update_key_grabs(&self.key_bindings, &key_bindings, &self.state, &self.x)?;
+        self.key_bindings = key_bindings;
+
+        info!("reloading config");
+        let current = &mut self.state.config;
+        current.normal_border = config.normal_border;
+        current.focused_border = config.focused_border;
+        current.urgent_border = config.urgent_border;
+        current.border_width = config.border_width;
+        current.focus_follow_mouse = config.focus_follow_mouse;
+        current.warp_cursor_on_focus = config.warp_cursor_on_focus;
+        current.warp_to = config.warp_to;
+        current.floating_classes = config.floating_classes;
+        current.chord_timeout = config.chord_timeout;
+        current.screen_update_debounce = config.screen_update_debounce;
+
+        let layout_names = |ls: &LayoutStack| ls.iter().map(|l| l.name()).collect::<Vec<_>>();
+        let layouts_changed =
+            layout_names(&current.default_layouts) != layout_names(&config.default_layouts);
+        current.default_layouts = config.default_layouts;
+
+        if layouts_changed {
+            for w in self.state.client_set.workspaces_mut() {
+                w.layouts = self.state.config.default_layouts.clone();
+            }
+        }
+        self.state.force_reposition();
+
+        // The focused border is set as part of refresh
+        let border = ClientConfig::BorderPx(self.state.config.border_width);
+        for &id in self.state.client_set.clients() {
+            self.x.set_client_config(id, &[border])?;
+            self.x
+                .set_client_border_color(id, self.state.border_color(id))?;
+        }
+
+        self.x.refresh(&mut self.state)
+    }
+
     /// Start the WindowManager and run it until told to exit.
     ///
     /// Any provided startup hooks will be run after setting signal handlers and grabbing
@@ -450,7 +1121,8 @@ where
     /// to your Config so that there is more information available to correctly position your
     /// existing clients.
     /// Startup hooks are run before this takes place so that there is an opportunity to handle
-    /// restoring any state being held outside of the main WindowManager data structures.
+    /// restoring any state being held outside of the main WindowManager data structures. Post
+    /// startup hooks are run once this has completed and the initial layout has been applied.
     ///
     /// > **NOTE**: This is not guaranteed to preserve the stacking order or correctly handle any
     /// > clients that were on invisible workspaces / workspaces that no longer exist and that the
@@ -464,6 +1136,22 @@ where
             panic!("unable to set signal handler: {}", e);
         }
 
+        self.init()?;
+        self.state.running = true;
+
+        while self.state.running {
+            match self.x.next_event() {
+                Ok(event) => self.process_event(event),
+                Err(e) => self.handle_error(e),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Grab bindings, run startup hooks and manage any existing clients. Post startup hooks are
+    // removed from the config when they are run so they will only ever run once.
+    fn init(&mut self) -> Result<()> {
         handle::mapping_notify(&self.key_bindings, &self.mouse_bindings, &self.x)?;
 
         if let Some(mut h) = self.state.config.startup_hook.take() {
@@ -474,29 +1162,53 @@ where
         }
 
         manage_existing_clients(&mut self.state, &self.x)?;
-        self.state.running = true;
 
-        while self.state.running {
-            match self.x.next_event() {
-                Ok(event) => {
-                    let span = span!(target: "penrose", Level::INFO, "XEvent", %event);
-                    let _enter = span.enter();
-                    trace!(details = ?event, "event details");
-                    self.state.current_event = Some(event.clone());
-
-                    if let Err(e) = self.handle_xevent(event) {
-                        error!(%e, "Error handling XEvent");
-                    }
-                    self.x.flush();
+        if let Some(mut h) = self.state.config.post_startup_hook.take() {
+            trace!("running user post startup hook");
+            if let Err(e) = h.call(self) {
+                error!(%e, "error returned from user post startup hook");
+            }
+        }
 
-                    self.state.current_event = None;
-                }
+        Ok(())
+    }
 
-                Err(e) => self.handle_error(e),
+    // Requests made to the X server while handling an event are batched up and flushed
+    // once processing is complete rather than being flushed as they are made.
+    fn process_event(&mut self, event: XEvent) {
+        let span = span!(target: "penrose", Level::INFO, "XEvent", %event);
+        let _enter = span.enter();
+        trace!(details = ?event, "event details");
+        self.state.current_event = Some(event.clone());
+
+        if let Err(e) = self.handle_xevent(event) {
+            error!(%e, "Error handling XEvent");
+        }
+
+        if let Some((config, key_bindings)) = self.state.pending_reload.take() {
+            if let Err(e) = self.apply_reload(config, key_bindings) {
+                error!(%e, "unable to reload config");
             }
         }
+        self.x.flush();
+        self.run_after_flush();
 
-        Ok(())
+        self.state.current_event = None;
+    }
+
+    // Callbacks queued while these callbacks are running are left for the next flush
+    fn run_after_flush(&mut self) {
+        let callbacks = std::mem::take(&mut self.state.after_flush);
+        if callbacks.is_empty() {
+            return;
+        }
+
+        for AfterFlush(f) in callbacks {
+            if let Err(e) = f(&mut self.state, &self.x) {
+                error!(%e, "error returned from after flush callback");
+            }
+        }
+        self.x.flush();
     }
 
     fn handle_xevent(&mut self, event: XEvent) -> Result<()> {
@@ -509,6 +1221,9 @@ where
             mouse_bindings,
         } = self;
 
+        handle::expire_pending_chord(key_bindings, mouse_bindings, state, x)?;
+        handle::run_pending_screen_update(Instant::now(), state, x)?;
+
         let mut hook = state.config.event_hook.take();
         let should_run = match hook {
             Some(ref mut h) => {
@@ -533,21 +1248,22 @@ where
 
         match &event {
             ClientMessage(m) => handle::client_message(m.clone(), state, x)?,
-            ConfigureNotify(e) if e.is_root => handle::detect_screens(state, x)?,
+            ConfigureNotify(e) if e.is_root => handle::detect_screens(Instant::now(), state, x)?,
             ConfigureNotify(_) => (), // Not currently handled
             ConfigureRequest(e) => handle::configure_request(e, state, x)?,
             Enter(p) => handle::enter(*p, state, x)?,
             Expose(_) => (), // Not currently handled
             FocusIn(id) => handle::focus_in(*id, state, x)?,
             Destroy(xid) => handle::destroy(*xid, state, x)?,
-            KeyPress(code) => handle::keypress(*code, key_bindings, state, x)?,
+            KeyPress(code) => handle::keypress(*code, key_bindings, mouse_bindings, state, x)?,
             Leave(p) => handle::leave(*p, state, x)?,
             MappingNotify => handle::mapping_notify(key_bindings, mouse_bindings, x)?,
+            MapNotify(xid) => handle::map_notify(*xid, state, x)?,
             MapRequest(xid) => handle::map_request(*xid, state, x)?,
             MouseEvent(e) => handle::mouse_event(e.clone(), mouse_bindings, state, x)?,
             MotionNotify(e) => handle::motion_event(e.clone(), mouse_bindings, state, x)?,
-            PropertyNotify(_) => (), // Not currently handled
-            RandrNotify => handle::detect_screens(state, x)?,
+            PropertyNotify(e) => handle::property_notify(e, state, x)?,
+            RandrNotify => handle::detect_screens(Instant::now(), state, x)?,
             ScreenChange => handle::screen_change(state, x)?,
             UnmapNotify(xid) => handle::unmap_notify(*xid, state, x)?,
 
@@ -607,6 +1323,21 @@ fn manage_existing_clients<X: XConn>(state: &mut State<X>, x: &X) -> Result<()>
             info!(%id, %title, %tag, "attempting to manage existing client");
             manage_without_refresh(id, Some(tag), state, x)?;
         }
+
+        // Windows such as status bars may already be reserving space on screen
+        let viewable = matches!(
+            x.get_window_attributes(id),
+            Ok(WindowAttributes {
+                map_state: MapState::Viewable,
+                ..
+            })
+        );
+
+        if viewable {
+            if let Err(e) = handle::update_strut(id, state, x) {
+                warn!(%id, %e, "unable to check window for struts");
+            }
+        }
     }
 
     // If EWMH is enabled then we should have this property set to tell us what the previously
@@ -664,37 +1395,407 @@ fn client_should_be_manged<X: XConn>(id: Xid, x: &X) -> bool {
     !override_redirect && (viewable || iconic)
 }
 
+#[cfg(test)]
+impl State<crate::x::test::TestXConn> {
+    /// Create a new [State] holding the given [ClientSet] for use in tests, using the same
+    /// constructor as a running [WindowManager] so that every other field has its default value.
+    pub(crate) fn from_client_set(mut client_set: ClientSet) -> Self {
+        let mut screens: Vec<(usize, Rect)> =
+            client_set.screens().map(|s| (s.index, s.r)).collect();
+        screens.sort_by_key(|&(ix, _)| ix);
+
+        let x = crate::x::test::TestXConn::new(screens.into_iter().map(|(_, r)| r).collect());
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        let ss = client_set.snapshot(vec![]);
+        state.diff = Diff::new(ss.clone(), ss);
+        state.client_set = client_set;
+
+        state
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pure::{test_xid_stack_set, Position};
+    use crate::{
+        builtin::{
+            actions::{enter_mode, exit_mode, focus_urgent, key_handler, reload},
+            layout::{Grid, Monocle},
+        },
+        core::bindings::{
+            parse_xmodmap_output, KeyCode, MouseButton, MouseEvent, MouseEventHandler,
+            MouseEventKind, MouseState,
+        },
+        pure::{geometry::Point, test_xid_stack_set, Position},
+        x::{
+            event::{ClientEventMask, ClientMessage, ClientMessageData, ConfigureEvent},
+            property::{WmHints, WmHintsFlags},
+            test::TestXConn,
+            ClientConfig, WAKE_MESSAGE,
+        },
+    };
+    use simple_test_case::test_case;
+    use std::rc::Rc;
 
-    fn stack_order(cs: &ClientSet) -> Vec<u32> {
-        let positions = cs.visible_client_positions();
-        positions.iter().map(|&(id, _)| *id).collect()
-    }
+    const SCREEN: Rect = Rect::new(0, 0, 1000, 800);
+    const FLOATING: Rect = Rect::new(100, 100, 200, 200);
 
+    #[test_case(1, Some(SCREEN); "tiled")]
+    #[test_case(2, Some(FLOATING); "floating")]
+    #[test_case(3, None; "on hidden workspace")]
+    #[test_case(42, None; "unknown")]
     #[test]
-    fn floating_client_positions_are_respected() {
-        let mut s = test_xid_stack_set(5, 2);
+    fn client_geometry_uses_last_layout_positions(id: u32, expected: Option<Rect>) {
+        let x = TestXConn::new(vec![SCREEN]);
+        let mut state = State::try_new(Config::default(), &x).expect("valid test state");
 
-        for n in 0..4 {
-            s.insert(Xid(n));
-        }
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+        state.client_set.float_unchecked(Xid(2), FLOATING);
+        state.client_set.insert(Xid(3));
+        state.client_set.move_client_to_tag(&Xid(3), "2");
 
-        let r = Rect::new(50, 50, 50, 50);
-        s.float_unchecked(Xid(1), r);
+        assert_eq!(state.client_geometry(Xid(id)), None, "before refresh");
 
-        let positions = s.visible_client_positions();
+        let ss = state.position_and_snapshot(&x);
+        state.diff.update(ss);
 
-        assert!(positions.contains(&(Xid(1), r)), "{positions:?}")
+        assert_eq!(state.client_geometry(Xid(id)), expected, "after refresh");
     }
 
-    #[test]
-    fn floating_clients_stay_on_their_assigned_screen() {
-        let mut s = test_xid_stack_set(5, 2);
+    fn prop_xconn() -> TestXConn {
+        let utf8 = |strs: &[&str]| Prop::UTF8String(strs.iter().map(|s| s.to_string()).collect());
 
-        for n in 0..4 {
+        TestXConn::new(vec![SCREEN])
+            .with_prop(Xid(1), "WM_CLASS", utf8(&["firefox", "Firefox"]))
+            .with_prop(Xid(1), "WM_NAME", utf8(&["Mozilla Firefox"]))
+            .with_prop(Xid(2), "_NET_WM_NAME", utf8(&["htop"]))
+    }
+
+    #[test_case(None, None, None; "no focused client")]
+    #[test_case(Some(1), Some("Firefox"), Some("Mozilla Firefox"); "class and title")]
+    #[test_case(Some(2), None, Some("htop"); "title only")]
+    #[test]
+    fn focused_client_accessors(focus: Option<u32>, class: Option<&str>, title: Option<&str>) {
+        let x = prop_xconn();
+        let mut wm =
+            WindowManager::new(Config::default(), HashMap::new(), HashMap::new(), x).unwrap();
+
+        if let Some(focus) = focus {
+            wm.state.client_set.insert(Xid(1));
+            wm.state.client_set.insert(Xid(2));
+            wm.state.client_set.focus_client(&Xid(focus));
+        }
+
+        assert_eq!(wm.focused_client_class().as_deref(), class);
+        assert_eq!(wm.focused_client_title().as_deref(), title);
+    }
+
+    #[test_case(None, SCREEN; "no strut")]
+    #[test_case(Some(vec![0, 0, 30, 0, 0, 0, 0, 0, 0, 999, 0, 0]), Rect::new(0, 30, 1000, 770); "top")]
+    #[test_case(Some(vec![0, 0, 0, 20, 0, 0, 0, 0, 0, 0, 0, 999]), Rect::new(0, 0, 1000, 780); "bottom")]
+    #[test_case(Some(vec![0, 0, 30, 0, 0, 0, 0, 0, 2000, 2999, 0, 0]), SCREEN; "top on other screen")]
+    #[test]
+    fn struts_reduce_the_laid_out_region(strut: Option<Vec<u32>>, expected: Rect) {
+        let has_strut = strut.is_some();
+        let x = match strut {
+            Some(strut) => TestXConn::new(vec![SCREEN]).with_prop(
+                Xid(42),
+                Atom::NetWmStrutPartial.as_ref(),
+                Prop::Cardinal(strut),
+            ),
+            None => TestXConn::new(vec![SCREEN]),
+        };
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        state.client_set.insert(Xid(1));
+
+        let changed = handle::update_strut(Xid(42), &mut state, &x).expect("to parse strut");
+        assert_eq!(changed, has_strut);
+
+        let positions = state.visible_client_positions(&x);
+
+        assert_eq!(positions, vec![(Xid(1), expected)]);
+    }
+
+    #[test]
+    fn tracking_a_strut_keeps_existing_event_masks() {
+        let strut = Prop::Cardinal(vec![0, 0, 30, 0, 0, 0, 0, 0, 0, 999, 0, 0]);
+        let x = TestXConn::default().with_prop(Xid(42), Atom::NetWmStrutPartial.as_ref(), strut);
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+
+        handle::update_strut(Xid(42), &mut state, &x).expect("to parse strut");
+
+        assert_eq!(
+            x.calls_for(Xid(42)),
+            vec!["get_prop", "select_property_events"]
+        );
+    }
+
+    fn urgent_xconn(urgent: &[Xid]) -> TestXConn {
+        let hints = WmHints::new(
+            WmHintsFlags::URGENCY_HINT,
+            true,
+            WmState::Normal,
+            0,
+            Xid(0),
+            Point::default(),
+            0,
+            0,
+        );
+
+        let x = [Xid(1), Xid(2), Xid(3)]
+            .into_iter()
+            .fold(TestXConn::new(vec![SCREEN]), |x, id| {
+                x.with_client(id, SCREEN)
+            });
+
+        urgent.iter().fold(x, |x, &id| {
+            x.with_prop(id, Atom::WmHints.as_ref(), Prop::WmHints(hints.clone()))
+        })
+    }
+
+    type HookCalls = Rc<RefCell<Vec<(Xid, bool)>>>;
+
+    fn urgent_test_state(x: &TestXConn) -> (State<TestXConn>, HookCalls) {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let hook_calls = calls.clone();
+
+        let mut config = Config::default();
+        config.compose_or_set_urgent_hook(
+            move |id: Xid, urgent: bool, _: &mut State<TestXConn>, _: &TestXConn| {
+                hook_calls.borrow_mut().push((id, urgent));
+                Ok(())
+            },
+        );
+
+        let mut state = State::try_new(config, x).expect("valid test state");
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+        state.client_set.move_client_to_tag(&Xid(2), "2");
+
+        (state, calls)
+    }
+
+    #[test]
+    fn urgency_hint_marks_workspace_as_urgent() {
+        let x = urgent_xconn(&[Xid(2)]);
+        let (mut state, calls) = urgent_test_state(&x);
+
+        let changed = handle::check_urgency_hint(Xid(2), &mut state, &x).unwrap();
+
+        assert!(changed);
+        assert_eq!(state.urgent_clients(), &[Xid(2)]);
+        assert!(state.workspace_is_urgent("2"));
+        assert!(!state.workspace_is_urgent("1"));
+        assert_eq!(*calls.borrow(), vec![(Xid(2), true)]);
+    }
+
+    #[test]
+    fn urgency_hint_is_ignored_for_the_focused_client() {
+        let x = urgent_xconn(&[Xid(1)]);
+        let (mut state, calls) = urgent_test_state(&x);
+
+        let changed = handle::check_urgency_hint(Xid(1), &mut state, &x).unwrap();
+
+        assert!(!changed);
+        assert!(state.urgent_clients().is_empty());
+        assert!(calls.borrow().is_empty());
+    }
+
+    #[test_case(|cs: &mut ClientSet| cs.focus_client(&Xid(2)); "focused")]
+    #[test_case(|cs: &mut ClientSet| cs.focus_tag("2"); "workspace viewed")]
+    #[test_case(|cs: &mut ClientSet| { cs.remove_client(&Xid(2)); }; "closed before viewed")]
+    #[test]
+    fn urgency_is_cleared(f: fn(&mut ClientSet)) {
+        let x = urgent_xconn(&[Xid(2)]);
+        let (mut state, calls) = urgent_test_state(&x);
+
+        handle::check_urgency_hint(Xid(2), &mut state, &x).unwrap();
+        x.modify_and_refresh(&mut state, f).unwrap();
+
+        assert!(state.urgent_clients().is_empty());
+        assert!(!state.workspace_is_urgent("2"));
+        assert_eq!(*calls.borrow(), vec![(Xid(2), true), (Xid(2), false)]);
+    }
+
+    #[test]
+    fn border_colors_track_focus_and_urgency() {
+        let x = urgent_xconn(&[Xid(2)]);
+        let (mut state, _) = urgent_test_state(&x);
+        let Config {
+            normal_border,
+            focused_border,
+            urgent_border,
+            ..
+        } = state.config;
+        let (normal, focused, urgent) = (
+            normal_border.argb_u32(),
+            focused_border.argb_u32(),
+            urgent_border.argb_u32(),
+        );
+
+        // An unfocused client becoming urgent
+        handle::check_urgency_hint(Xid(2), &mut state, &x).unwrap();
+        assert_eq!(x.border_color(Xid(2)), Some(urgent));
+
+        // Focusing an urgent client clears its urgency
+        x.modify_and_refresh(&mut state, |cs| cs.focus_client(&Xid(2)))
+            .unwrap();
+        assert_eq!(x.border_color(Xid(2)), Some(focused));
+        assert_eq!(x.border_color(Xid(1)), Some(normal));
+
+        // Losing focus
+        x.modify_and_refresh(&mut state, |cs| cs.focus_client(&Xid(1)))
+            .unwrap();
+        assert_eq!(x.border_color(Xid(2)), Some(normal));
+        assert_eq!(x.border_color(Xid(1)), Some(focused));
+    }
+
+    #[test]
+    fn focus_urgent_focuses_the_oldest_urgent_client() {
+        let x = urgent_xconn(&[Xid(2), Xid(3)]);
+        let (mut state, _) = urgent_test_state(&x);
+        state.client_set.insert(Xid(3));
+        state.client_set.move_client_to_tag(&Xid(3), "3");
+
+        handle::check_urgency_hint(Xid(3), &mut state, &x).unwrap();
+        handle::check_urgency_hint(Xid(2), &mut state, &x).unwrap();
+        assert_eq!(state.urgent_clients(), &[Xid(3), Xid(2)]);
+
+        focus_urgent().call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.current_tag(), "3");
+        assert_eq!(state.client_set.current_client(), Some(&Xid(3)));
+        assert_eq!(state.urgent_clients(), &[Xid(2)]);
+
+        focus_urgent().call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.current_client(), Some(&Xid(2)));
+        assert!(state.urgent_clients().is_empty());
+
+        focus_urgent().call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.current_client(), Some(&Xid(2)));
+    }
+
+    fn net_wm_state_atom(x: &TestXConn, atom: Atom) -> u32 {
+        *x.intern_atom(atom.as_ref()).expect("atom to be interned")
+    }
+
+    #[test_case(false, 0, false; "remove when not fullscreen")]
+    #[test_case(false, 1, true; "add when not fullscreen")]
+    #[test_case(false, 2, true; "toggle when not fullscreen")]
+    #[test_case(true, 0, false; "remove when fullscreen")]
+    #[test_case(true, 1, true; "add when fullscreen")]
+    #[test_case(true, 2, false; "toggle when fullscreen")]
+    #[test]
+    fn net_wm_state_fullscreen_messages_are_handled(
+        initially_fullscreen: bool,
+        action: u32,
+        expected: bool,
+    ) {
+        let x = TestXConn::new(vec![SCREEN]).with_client(Xid(1), SCREEN);
+        let fullscreen = net_wm_state_atom(&x, Atom::NetWmStateFullscreen);
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        state.client_set.insert(Xid(1));
+
+        if initially_fullscreen {
+            state.client_set.float_unchecked(Xid(1), SCREEN);
+            x.set_prop(
+                Xid(1),
+                Atom::NetWmState.as_ref(),
+                Prop::Cardinal(vec![fullscreen]),
+            )
+            .unwrap();
+        }
+
+        let msg = ClientMessage::new(
+            Xid(1),
+            ClientEventMask::NoEventMask,
+            Atom::NetWmState.as_ref(),
+            ClientMessageData::from([action, fullscreen, 0, 0, 0]),
+        );
+        handle::client_message(msg, &mut state, &x).expect("message to be handled");
+
+        let wm_state = x.prop(Xid(1), Atom::NetWmState.as_ref());
+        let expected_state = if expected { vec![fullscreen] } else { vec![] };
+
+        assert_eq!(state.client_set.is_floating(&Xid(1)), expected);
+        assert_eq!(wm_state, Some(Prop::Cardinal(expected_state)));
+    }
+
+    #[test]
+    fn net_wm_state_sticky_messages_are_handled() {
+        let x = TestXConn::new(vec![SCREEN])
+            .with_client(Xid(1), SCREEN)
+            .with_client(Xid(2), SCREEN);
+        let sticky = net_wm_state_atom(&x, Atom::NetWmStateSticky);
+        let mut wm =
+            WindowManager::new(Config::default(), HashMap::new(), HashMap::new(), x).unwrap();
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+
+        let sticky_msg = |action: u32| {
+            XEvent::ClientMessage(ClientMessage::new(
+                Xid(1),
+                ClientEventMask::NoEventMask,
+                Atom::NetWmState.as_ref(),
+                ClientMessageData::from([action, sticky, 0, 0, 0]),
+            ))
+        };
+        let wm_state = |wm: &WindowManager<TestXConn>| wm.x.prop(Xid(1), Atom::NetWmState.as_ref());
+
+        wm.process_event(sticky_msg(1));
+        assert!(wm.state.client_set.is_sticky(&Xid(1)));
+        assert_eq!(wm_state(&wm), Some(Prop::Cardinal(vec![sticky])));
+
+        // Sticky clients follow the focused workspace
+        wm.x.modify_and_refresh(&mut wm.state, |cs| cs.focus_tag("2"))
+            .unwrap();
+        let clients: Vec<Xid> = wm
+            .state
+            .client_set
+            .current_workspace()
+            .clients()
+            .copied()
+            .collect();
+        assert_eq!(clients, vec![Xid(1)]);
+
+        wm.process_event(sticky_msg(0));
+        assert!(!wm.state.client_set.is_sticky(&Xid(1)));
+        assert_eq!(wm_state(&wm), Some(Prop::Cardinal(vec![])));
+
+        wm.x.modify_and_refresh(&mut wm.state, |cs| cs.focus_tag("3"))
+            .unwrap();
+        assert_eq!(wm.state.client_set.tag_for_client(&Xid(1)), Some("2"));
+    }
+
+    fn stack_order(cs: &ClientSet) -> Vec<u32> {
+        let positions = cs.visible_client_positions();
+        positions.iter().map(|&(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn floating_client_positions_are_respected() {
+        let mut s = test_xid_stack_set(5, 2);
+
+        for n in 0..4 {
+            s.insert(Xid(n));
+        }
+
+        let r = Rect::new(50, 50, 50, 50);
+        s.float_unchecked(Xid(1), r);
+
+        let positions = s.visible_client_positions();
+
+        assert!(positions.contains(&(Xid(1), r)), "{positions:?}")
+    }
+
+    #[test]
+    fn floating_clients_stay_on_their_assigned_screen() {
+        let mut s = test_xid_stack_set(5, 2);
+
+        for n in 0..4 {
             s.insert(Xid(n));
         }
 
@@ -772,4 +1873,994 @@ mod tests {
             assert_eq!(stack_order(&s), expected, "{:?}", s.current_stack());
         }
     }
+
+    fn hydrate_test_wm(existing: &[u32], screens: Vec<Rect>) -> WindowManager<TestXConn> {
+        let x = existing.iter().fold(TestXConn::new(screens), |x, &id| {
+            x.with_client(Xid(id), SCREEN)
+        });
+
+        let config = Config {
+            default_layouts: crate::stack!(Monocle.boxed(), Grid.boxed()),
+            ..Config::default()
+        };
+
+        WindowManager::new(config, HashMap::new(), HashMap::new(), x)
+            .expect("valid test window manager")
+    }
+
+    // Clients 1 & 2 on tag "1", 3 (floating) on tag "2" and 4 on tag "3" with tag "2" focused
+    fn populated_client_set() -> ClientSet {
+        let mut wm = hydrate_test_wm(&[], vec![SCREEN]);
+        let cs = &mut wm.state.client_set;
+
+        cs.insert(Xid(1));
+        cs.insert(Xid(2));
+        cs.focus_tag("3");
+        cs.insert(Xid(4));
+        cs.focus_tag("2");
+        cs.insert(Xid(3));
+        cs.float(Xid(3), FLOATING).unwrap();
+
+        wm.state.client_set
+    }
+
+    fn tag_clients(cs: &ClientSet) -> Vec<(String, Vec<Xid>)> {
+        cs.ordered_workspaces()
+            .map(|w| (w.tag().to_owned(), w.clients().copied().collect()))
+            .collect()
+    }
+
+    fn assert_hydrated_matches(restored: ClientSet, original: &ClientSet) {
+        let mut wm = hydrate_test_wm(&[1, 2, 3, 4], vec![SCREEN]);
+        wm.hydrate(restored).unwrap();
+        let hydrated = &wm.state.client_set;
+
+        assert_eq!(tag_clients(hydrated), tag_clients(original));
+        assert_eq!(hydrated.floating, original.floating);
+        assert_eq!(hydrated.current_tag(), "2");
+        assert_eq!(hydrated.current_client(), Some(&Xid(3)));
+        assert_eq!(hydrated.previous_tag, original.previous_tag);
+        assert!(hydrated.workspaces().all(|w| w.layouts.len() == 2));
+    }
+
+    #[test]
+    fn a_client_set_can_be_hydrated() {
+        let cs = populated_client_set();
+
+        assert_hydrated_matches(cs.clone(), &cs);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_serialized_client_set_can_be_hydrated() {
+        let cs = populated_client_set();
+        let serialized = serde_json::to_string(&cs).unwrap();
+        let restored: ClientSet = serde_json::from_str(&serialized).unwrap();
+
+        assert_hydrated_matches(restored, &cs);
+    }
+
+    #[test_case(&[1, 2, 4], vec![SCREEN]; "missing client")]
+    #[test_case(&[1, 2, 3, 4], vec![Rect::new(0, 0, 1920, 1080)]; "screen size changed")]
+    #[test_case(&[1, 2, 3, 4], vec![SCREEN, Rect::new(1000, 0, 1000, 800)]; "screen added")]
+    #[test]
+    fn hydrating_state_that_does_not_match_x_is_an_error(existing: &[u32], screens: Vec<Rect>) {
+        let mut wm = hydrate_test_wm(existing, screens);
+        let res = wm.hydrate(populated_client_set());
+
+        assert!(
+            matches!(res, Err(Error::InvalidHydrationState { .. })),
+            "expected an error, got {res:?}"
+        );
+        assert_eq!(wm.state.client_set.clients().count(), 0);
+    }
+
+    fn str_bindings<X: XConn>(keys: &[&str]) -> HashMap<String, Box<dyn KeyEventHandler<X>>> {
+        keys.iter()
+            .map(|&k| {
+                let handler: Box<dyn KeyEventHandler<X>> =
+                    Box::new(|_: &mut State<X>, _: &X| Ok(()));
+                (k.to_owned(), handler)
+            })
+            .collect()
+    }
+
+    fn codes_for(codes: &[(&str, u8)]) -> HashMap<String, Vec<KeyCode>> {
+        codes
+            .iter()
+            .map(|&(name, code)| (name.to_owned(), vec![KeyCode { mask: 0, code }]))
+            .collect()
+    }
+
+    fn reload_test_wm() -> (WindowManager<TestXConn>, HashMap<String, Vec<KeyCode>>) {
+        let known_codes = codes_for(&[("a", 38), ("b", 56), ("Super_L", 133)]);
+        let key_bindings = parse_keybindings(str_bindings(&["M-a"]), &known_codes).unwrap();
+        let mut wm = WindowManager::new(
+            Config::default(),
+            key_bindings,
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+
+        wm.state.client_set.insert(Xid(1));
+        wm.state.client_set.insert(Xid(2));
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        (wm, known_codes)
+    }
+
+    #[test]
+    fn reload_relayouts_visible_workspaces_and_grabs_new_bindings() {
+        let (mut wm, known_codes) = reload_test_wm();
+        assert_ne!(wm.client_geometry(Xid(2)), Some(SCREEN));
+
+        let config = Config {
+            default_layouts: crate::stack!(Monocle.boxed(), Grid.boxed()),
+            ..Config::default()
+        };
+        wm.reload_with_keycodes(config, str_bindings(&["M-b", "M-S-b"]), &known_codes)
+            .unwrap();
+
+        let ws = wm.state.client_set.current_workspace();
+        assert_eq!(ws.layout_name(), Monocle.name());
+        assert_eq!(wm.client_geometry(Xid(2)), Some(SCREEN));
+        assert!(wm.state.client_set.contains(&Xid(1)));
+
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+        assert_eq!(grabbed, vec![56, 56]);
+        assert_eq!(wm.key_bindings.len(), 2);
+    }
+
+    #[test]
+    fn reload_with_invalid_bindings_keeps_the_current_config() {
+        let (mut wm, known_codes) = reload_test_wm();
+        let before = wm.client_geometry(Xid(2));
+
+        let config = Config {
+            default_layouts: crate::stack!(Monocle.boxed()),
+            ..Config::default()
+        };
+        let res =
+            wm.reload_with_keycodes(config, str_bindings(&["M-b", "M-unknown"]), &known_codes);
+
+        assert!(matches!(res, Err(Error::UnknownKeyName { .. })), "{res:?}");
+        assert_ne!(
+            wm.state.client_set.current_workspace().layout_name(),
+            Monocle.name()
+        );
+        assert_eq!(wm.client_geometry(Xid(2)), before);
+        assert!(wm.x.grabbed_keys().is_empty());
+        assert_eq!(wm.key_bindings.len(), 1);
+    }
+
+    #[cfg(feature = "keysyms")]
+    #[test]
+    fn reload_uses_the_keyboard_mapping_from_the_x_server() {
+        // 'a' and 'b' in the first group, with 'b' also produced by 'a' in the second group
+        let mapping = [(38, vec![0x61, 0x41, 0x62, 0x42]), (56, vec![0x62, 0x42])];
+        let x = TestXConn::new(vec![SCREEN]).with_keyboard_mapping(mapping.into_iter().collect());
+        let mut wm =
+            WindowManager::new(Config::default(), HashMap::new(), HashMap::new(), x).unwrap();
+
+        wm.reload(Config::default(), str_bindings(&["M-a b"]))
+            .unwrap();
+
+        assert_eq!(wm.x.n_calls("keyboard_mapping"), 1);
+        assert_eq!(grabbed_codes(&wm), vec![38]);
+
+        wm.reload(Config::default(), str_bindings(&["M-b"]))
+            .unwrap();
+
+        assert_eq!(
+            grabbed_codes(&wm),
+            vec![38, 56],
+            "b is bound in both groups"
+        );
+    }
+
+    #[test]
+    fn reload_keeps_workspace_layouts_when_the_defaults_are_unchanged() {
+        let (mut wm, known_codes) = reload_test_wm();
+        wm.state.client_set.next_layout();
+        let active = wm.state.client_set.current_workspace().layout_name();
+
+        wm.reload_with_keycodes(Config::default(), str_bindings(&["M-a"]), &known_codes)
+            .unwrap();
+
+        let ws = wm.state.client_set.current_workspace();
+        assert_eq!(ws.layout_name(), active);
+    }
+
+    #[test]
+    fn reloads_requested_by_actions_are_applied_after_the_current_event() {
+        let (reload_key, new_key) = (KeyCode { mask: 0, code: 38 }, KeyCode { mask: 0, code: 56 });
+        let load = move || {
+            let config = Config {
+                default_layouts: crate::stack!(Monocle.boxed()),
+                ..Config::default()
+            };
+            let key_bindings: KeyBindings<TestXConn> = [(new_key, key_handler(|_, _| Ok(())))]
+                .into_iter()
+                .collect();
+
+            Ok((config, key_bindings))
+        };
+        let key_bindings: KeyBindings<TestXConn> =
+            [(reload_key, reload(load))].into_iter().collect();
+        let x = TestXConn::new(vec![SCREEN]);
+        let mut wm =
+            WindowManager::new(Config::default(), key_bindings, HashMap::new(), x).unwrap();
+
+        wm.process_event(XEvent::KeyPress(reload_key));
+
+        let keys: Vec<KeyCode> = wm.key_bindings.keys().copied().collect();
+        assert_eq!(keys, vec![new_key]);
+        assert_eq!(
+            wm.state.client_set.current_workspace().layout_name(),
+            Monocle.name()
+        );
+        assert!(wm.state.pending_reload.is_none());
+    }
+
+    fn chord_test_wm(
+        chord_timeout: Option<Duration>,
+    ) -> (WindowManager<TestXConn>, Rc<RefCell<Vec<String>>>) {
+        let known_codes = codes_for(&[("a", 38), ("b", 56)]);
+        let called = Rc::new(RefCell::new(Vec::new()));
+
+        let str_bindings: HashMap<String, Box<dyn KeyEventHandler<TestXConn>>> =
+            ["M-a", "M-b a", "M-b M-b"]
+                .iter()
+                .map(|&k| {
+                    let called = called.clone();
+                    let handler: Box<dyn KeyEventHandler<TestXConn>> =
+                        Box::new(move |_: &mut State<TestXConn>, _: &TestXConn| {
+                            called.borrow_mut().push(k.to_owned());
+                            Ok(())
+                        });
+                    (k.to_owned(), handler)
+                })
+                .collect();
+
+        let key_bindings = parse_keybindings(str_bindings, &known_codes).unwrap();
+        let config = Config {
+            chord_timeout,
+            ..Config::default()
+        };
+        let wm = WindowManager::new(
+            config,
+            key_bindings,
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+
+        (wm, called)
+    }
+
+    fn press(wm: &mut WindowManager<TestXConn>, mask: u16, code: u8) {
+        wm.handle_xevent(XEvent::KeyPress(KeyCode { mask, code }))
+            .unwrap();
+    }
+
+    const META: u16 = 1 << 6;
+
+    #[test_case(&[(0, 38)], &["M-b a"]; "matching key")]
+    #[test_case(&[(META, 56)], &["M-b M-b"]; "matching key with modifier")]
+    #[test_case(&[(META, 38), (META, 38)], &["M-a"]; "non-matching key cancels")]
+    #[test_case(&[(0, 56), (META, 38)], &["M-a"]; "unbound key cancels")]
+    #[test]
+    fn key_chords_run_the_matching_binding(keys: &[(u16, u8)], expected: &[&str]) {
+        let (mut wm, called) = chord_test_wm(None);
+
+        press(&mut wm, META, 56);
+        assert!(wm.state.pending_chord.is_some());
+
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+        assert_eq!(grabbed, vec![38, 38, 56, 56], "chord keys are grabbed");
+
+        for &(mask, code) in keys {
+            press(&mut wm, mask, code);
+        }
+
+        assert_eq!(*called.borrow(), expected);
+        assert!(wm.state.pending_chord.is_none());
+
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+        assert_eq!(grabbed, vec![38, 56], "only top level keys are grabbed");
+    }
+
+    #[test]
+    fn key_chords_are_cancelled_after_the_timeout() {
+        let (mut wm, called) = chord_test_wm(Some(Duration::ZERO));
+
+        press(&mut wm, META, 56);
+        press(&mut wm, 0, 38);
+
+        assert!(called.borrow().is_empty());
+        assert!(wm.state.pending_chord.is_none());
+    }
+
+    #[test]
+    fn expired_key_chords_release_their_keys_when_the_event_loop_wakes() {
+        let (mut wm, called) = chord_test_wm(Some(Duration::ZERO));
+
+        press(&mut wm, META, 56);
+        let wake = wake_message(&wm.x);
+        wm.handle_xevent(wake).unwrap();
+
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+        assert_eq!(grabbed, vec![38, 56], "only top level keys are grabbed");
+        assert!(wm.state.pending_chord.is_none());
+        assert!(called.borrow().is_empty());
+    }
+
+    #[test_case(&["M-S-a", "S-M-a"]; "modifier order")]
+    #[test_case(&["M-a", "M-a b"]; "binding is a chord prefix")]
+    #[test_case(&["M-a b", "M-a"]; "chord prefix is a binding")]
+    #[test_case(&["M-a  b", "M-a b"]; "duplicate chord")]
+    #[test]
+    fn conflicting_key_bindings_are_an_error(keys: &[&str]) {
+        let known_codes = codes_for(&[("a", 38), ("b", 56)]);
+
+        let res = parse_keybindings(str_bindings::<TestXConn>(keys), &known_codes);
+
+        let mut expected: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+        expected.sort();
+
+        match res {
+            Err(Error::ConflictingKeyBindings { bindings }) => assert_eq!(bindings, expected),
+            res => panic!("expected conflicting bindings error, got {res:?}"),
+        }
+    }
+
+    // A US layout in the first group and a layout with "q" on a different key in the second
+    const TWO_GROUP_XMODMAP: &str = "\
+keycode  24 = q Q apostrophe quotedbl
+keycode  38 = a A a A
+keycode  53 = x X q Q
+";
+
+    const SECOND_GROUP: u16 = 1 << 13;
+
+    fn two_group_wm() -> (WindowManager<TestXConn>, Rc<RefCell<Vec<String>>>) {
+        let all_codes = parse_xmodmap_output(TWO_GROUP_XMODMAP);
+        let called = Rc::new(RefCell::new(Vec::new()));
+
+        let str_bindings: HashMap<String, Box<dyn KeyEventHandler<TestXConn>>> = ["M-q", "M-a"]
+            .iter()
+            .map(|&k| {
+                let called = called.clone();
+                let handler: Box<dyn KeyEventHandler<TestXConn>> =
+                    Box::new(move |_: &mut State<TestXConn>, _: &TestXConn| {
+                        called.borrow_mut().push(k.to_owned());
+                        Ok(())
+                    });
+                (k.to_owned(), handler)
+            })
+            .collect();
+
+        let key_bindings = parse_keybindings(str_bindings, &all_codes).unwrap();
+        let mut wm = WindowManager::new(
+            Config::default(),
+            key_bindings,
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.handle_xevent(XEvent::MappingNotify).unwrap();
+
+        (wm, called)
+    }
+
+    #[test]
+    fn key_bindings_match_in_all_keyboard_groups() {
+        let (mut wm, called) = two_group_wm();
+
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+        assert_eq!(
+            grabbed,
+            vec![24, 38, 53],
+            "keys from both groups are grabbed"
+        );
+        assert!(
+            wm.x.grabbed_keys().iter().all(|k| k.mask == META),
+            "grabs do not include the keyboard group"
+        );
+
+        press(&mut wm, META, 24);
+        press(&mut wm, META | SECOND_GROUP, 53);
+        press(&mut wm, META | SECOND_GROUP, 38);
+
+        assert_eq!(*called.borrow(), vec!["M-q", "M-q", "M-a"]);
+    }
+
+    #[test]
+    fn keys_from_other_groups_only_match_in_their_own_group() {
+        let (mut wm, called) = two_group_wm();
+
+        // 53 is "x" in the first group
+        press(&mut wm, META, 53);
+
+        assert!(called.borrow().is_empty());
+    }
+
+    fn key_mode_test_wm(
+        grab_keyboard: bool,
+    ) -> (WindowManager<TestXConn>, Rc<RefCell<Vec<String>>>) {
+        let known_codes = codes_for(&[("a", 38), ("h", 43), ("Escape", 9)]);
+        let called = Rc::new(RefCell::new(Vec::new()));
+
+        let recorder = |k: &str| {
+            let called = called.clone();
+            let k = k.to_owned();
+            let handler: Box<dyn KeyEventHandler<TestXConn>> =
+                Box::new(move |_: &mut State<TestXConn>, _: &TestXConn| {
+                    called.borrow_mut().push(k.clone());
+                    Ok(())
+                });
+            handler
+        };
+
+        let top_level: HashMap<&str, Box<dyn KeyEventHandler<TestXConn>>> =
+            [("M-a", enter_mode("resize")), ("M-h", recorder("M-h"))]
+                .into_iter()
+                .collect();
+        let mode: HashMap<&str, Box<dyn KeyEventHandler<TestXConn>>> =
+            [("h", recorder("h")), ("Escape", exit_mode())]
+                .into_iter()
+                .collect();
+
+        let mut wm = WindowManager::new(
+            Config::default(),
+            parse_keybindings(top_level, &known_codes).unwrap(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.add_key_mode(KeyMode::new(
+            "resize",
+            parse_keybindings(mode, &known_codes).unwrap(),
+            grab_keyboard,
+        ));
+
+        (wm, called)
+    }
+
+    fn grabbed_codes(wm: &WindowManager<TestXConn>) -> Vec<u8> {
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+
+        grabbed
+    }
+
+    #[test_case(false; "without keyboard grab")]
+    #[test_case(true; "with keyboard grab")]
+    #[test]
+    fn key_modes_can_be_entered_and_exited(grab_keyboard: bool) {
+        let (mut wm, called) = key_mode_test_wm(grab_keyboard);
+
+        press(&mut wm, 0, 43); // h is not bound at the top level
+        press(&mut wm, META, 38);
+        assert_eq!(wm.state.active_key_mode(), Some("resize"));
+        assert_eq!(grabbed_codes(&wm), vec![9, 38, 43, 43]);
+        assert_eq!(wm.x.is_keyboard_grabbed(), grab_keyboard);
+
+        press(&mut wm, 0, 43);
+        press(&mut wm, META, 43); // top level bindings are still available
+        press(&mut wm, 0, 9);
+        assert_eq!(wm.state.active_key_mode(), None);
+        assert_eq!(grabbed_codes(&wm), vec![38, 43]);
+        assert!(!wm.x.is_keyboard_grabbed());
+
+        press(&mut wm, 0, 43);
+        assert_eq!(*called.borrow(), vec!["h", "M-h"]);
+    }
+
+    #[test]
+    fn entering_an_unknown_key_mode_is_an_error() {
+        let (mut wm, _) = key_mode_test_wm(false);
+        let res = wm.state.enter_key_mode("unknown");
+
+        assert!(matches!(res, Err(Error::UnknownKeyMode { .. })), "{res:?}");
+        assert_eq!(wm.state.active_key_mode(), None);
+    }
+
+    #[test]
+    fn reloading_keeps_grabs_for_the_active_key_mode() {
+        let known_codes = codes_for(&[("a", 38), ("h", 43), ("Escape", 9)]);
+        let (mut wm, _) = key_mode_test_wm(false);
+        press(&mut wm, META, 38);
+
+        wm.reload_with_keycodes(Config::default(), str_bindings(&["M-a", "h"]), &known_codes)
+            .unwrap();
+        assert_eq!(grabbed_codes(&wm), vec![9, 38, 43]);
+
+        wm.reload_with_keycodes(Config::default(), str_bindings(&["M-a"]), &known_codes)
+            .unwrap();
+        assert_eq!(
+            grabbed_codes(&wm),
+            vec![9, 38, 43],
+            "h is still bound in the active key mode"
+        );
+    }
+
+    #[test_case(MouseButton::ScrollUp; "scroll up")]
+    #[test_case(MouseButton::ScrollLeft; "scroll left")]
+    #[test]
+    fn scroll_events_on_the_root_window_run_the_bound_action(button: MouseButton) {
+        let n_scrolls = Rc::new(RefCell::new(0));
+        let n = n_scrolls.clone();
+        let handler: Box<dyn MouseEventHandler<TestXConn>> =
+            Box::new(move |_: &mut State<TestXConn>, _: &TestXConn| {
+                *n.borrow_mut() += 1;
+                Ok(())
+            });
+
+        let state = MouseState::new(button, vec![]);
+        let mouse_bindings = [(state.clone(), handler)].into_iter().collect();
+        let mut wm = WindowManager::new(
+            Config::default(),
+            HashMap::new(),
+            mouse_bindings,
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+
+        let evt = MouseEvent::new(Xid(0), 10, 10, 10, 10, state, MouseEventKind::Press);
+        wm.handle_xevent(XEvent::MouseEvent(evt)).unwrap();
+
+        assert_eq!(*n_scrolls.borrow(), 1);
+        assert_eq!(
+            wm.state.held_mouse_state, None,
+            "scroll events are not held"
+        );
+    }
+
+    #[test_case(false, false, WarpPosition::Center, None; "disabled")]
+    #[test_case(false, true, WarpPosition::Center, Some(Point::new(798, 398)); "center")]
+    #[test_case(false, true, WarpPosition::TopLeft, Some(Point::new(600, 0)); "top left")]
+    #[test_case(true, false, WarpPosition::Center, Some(Point::new(798, 398)); "focus follows mouse")]
+    #[test]
+    fn focusing_a_client_warps_the_cursor_when_enabled(
+        focus_follow_mouse: bool,
+        warp_cursor_on_focus: bool,
+        warp_to: WarpPosition,
+        expected: Option<Point>,
+    ) {
+        let config = Config {
+            focus_follow_mouse,
+            warp_cursor_on_focus,
+            warp_to,
+            ..Config::default()
+        };
+        let mut wm = WindowManager::new(
+            config,
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+        wm.x.clear_calls();
+
+        wm.x.modify_and_refresh(&mut wm.state, |cs| cs.focus_client(&Xid(1)))
+            .unwrap();
+
+        let warped = wm.x.calls_for(Xid(1)).contains(&"warp_pointer");
+        assert_eq!(warped, expected.is_some());
+        if let Some(p) = expected {
+            assert_eq!(wm.x.cursor_position().unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn focus_changes_from_the_mouse_do_not_warp_the_cursor() {
+        let config = Config {
+            warp_cursor_on_focus: true,
+            ..Config::default()
+        };
+        let mut wm = WindowManager::new(
+            config,
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+        wm.x.clear_calls();
+
+        let state = MouseState::new(MouseButton::Left, vec![]);
+        let evt = MouseEvent::new(Xid(1), 10, 10, 10, 10, state, MouseEventKind::Press);
+        wm.state.current_event = Some(XEvent::MouseEvent(evt));
+        wm.x.modify_and_refresh(&mut wm.state, |cs| cs.focus_client(&Xid(1)))
+            .unwrap();
+
+        assert_eq!(wm.x.n_calls("warp_pointer"), 0);
+    }
+
+    fn wake_message(x: &impl XConn) -> XEvent {
+        XEvent::ClientMessage(ClientMessage::new(
+            x.root(),
+            ClientEventMask::SubstructureNotify,
+            WAKE_MESSAGE,
+            [0u32; 5].into(),
+        ))
+    }
+
+    #[test_case(Some(Duration::from_millis(20)), 1; "debounced")]
+    #[test_case(None, 3; "not debounced")]
+    #[test]
+    fn rapid_randr_events_are_coalesced(debounce: Option<Duration>, expected: usize) {
+        let config = Config {
+            screen_update_debounce: debounce,
+            ..Config::default()
+        };
+        let mut wm = WindowManager::new(
+            config,
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.state.client_set.focus_tag("3");
+        wm.x.clear_calls();
+
+        for _ in 0..3 {
+            wm.handle_xevent(XEvent::RandrNotify).unwrap();
+        }
+
+        if let Some(debounce) = debounce {
+            assert_eq!(wm.x.n_calls("output_details"), 0, "detection is deferred");
+            let due = Instant::now() + debounce;
+            handle::run_pending_screen_update(due, &mut wm.state, &wm.x).unwrap();
+        }
+
+        assert_eq!(wm.x.n_calls("output_details"), expected);
+        assert_eq!(wm.state.client_set.current_tag(), "3");
+    }
+
+    #[test]
+    fn randr_events_push_back_deferred_screen_detection() {
+        let debounce = Duration::from_millis(50);
+        let config = Config {
+            screen_update_debounce: Some(debounce),
+            ..Config::default()
+        };
+        let mut wm = WindowManager::new(
+            config,
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.x.clear_calls();
+
+        let start = Instant::now();
+        handle::detect_screens(start, &mut wm.state, &wm.x).unwrap();
+        handle::detect_screens(start + debounce / 2, &mut wm.state, &wm.x).unwrap();
+        handle::run_pending_screen_update(start + debounce, &mut wm.state, &wm.x).unwrap();
+
+        assert_eq!(
+            wm.x.n_calls("output_details"),
+            0,
+            "deadline was pushed back"
+        );
+
+        let deadline = start + debounce / 2 + debounce;
+        handle::run_pending_screen_update(deadline, &mut wm.state, &wm.x).unwrap();
+
+        assert_eq!(wm.x.n_calls("output_details"), 1);
+    }
+
+    fn positioned_clients(x: &TestXConn) -> Vec<Xid> {
+        let mut positioned: Vec<Xid> = x
+            .client_configs()
+            .into_iter()
+            .filter(|(_, c)| matches!(c, ClientConfig::Position(_)))
+            .map(|(id, _)| id)
+            .collect();
+        positioned.sort();
+
+        positioned
+    }
+
+    #[test]
+    fn x_requests_are_flushed_once_per_event() {
+        let mut wm = WindowManager::new(
+            Config::default(),
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+
+        // A layout pass over multiple clients doesn't flush any requests by itself
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            for n in 1..=5 {
+                cs.insert(Xid(n));
+            }
+        })
+        .unwrap();
+        assert_eq!(wm.x.n_calls("flush"), 0);
+
+        for n in 6..=8 {
+            wm.x.clear_calls();
+            wm.process_event(XEvent::MapRequest(Xid(n)));
+
+            let calls = wm.x.calls();
+            assert!(calls.len() > 1, "expected requests to be made: {calls:?}");
+            assert_eq!(wm.x.n_calls("flush"), 1, "{calls:?}");
+            assert_eq!(calls.last(), Some(&("flush", None)));
+        }
+    }
+
+    #[test]
+    fn after_flush_callbacks_run_once_after_the_next_flush() {
+        let mut wm = WindowManager::new(
+            Config::default(),
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+
+        // Callbacks record how many X requests had been made at the point that they ran
+        let ran = Rc::new(RefCell::new(Vec::new()));
+        let (outer, nested) = (ran.clone(), ran.clone());
+        wm.after_flush(move |s: &mut State<TestXConn>, x: &TestXConn| {
+            outer.borrow_mut().push(("callback", x.calls().len()));
+            let nested = nested.clone();
+            s.after_flush(move |_, x: &TestXConn| {
+                nested
+                    .borrow_mut()
+                    .push(("nested callback", x.calls().len()));
+                Ok(())
+            });
+            Ok(())
+        });
+        assert!(ran.borrow().is_empty(), "callback run before flush");
+
+        // Each callback runs between the flush for the event and a final flush of its own requests
+        let assert_ran_between_flushes = |x: &TestXConn, name: &str| {
+            let calls = take_calls(x);
+            let (ran_name, n) = ran.borrow_mut().remove(0);
+            assert_eq!(ran_name, name);
+            assert_eq!(calls[n - 1], ("flush", None));
+            assert_eq!(&calls[n..], &[("flush", None)]);
+        };
+
+        wm.process_event(XEvent::MapRequest(Xid(1)));
+        assert_ran_between_flushes(&wm.x, "callback");
+
+        wm.process_event(XEvent::MapRequest(Xid(2)));
+        assert_ran_between_flushes(&wm.x, "nested callback");
+        assert!(ran.borrow().is_empty(), "callback run more than once");
+
+        wm.process_event(XEvent::MapRequest(Xid(3)));
+        assert_eq!(wm.x.n_calls("flush"), 1);
+        assert!(ran.borrow().is_empty(), "callback run more than once");
+    }
+
+    fn take_calls(x: &TestXConn) -> Vec<(&'static str, Option<Xid>)> {
+        let calls = x.calls();
+        x.clear_calls();
+
+        calls
+    }
+
+    fn recording_wm_with_clients(n: u32) -> WindowManager<TestXConn> {
+        let mut wm = WindowManager::new(
+            Config::default(),
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            for n in 1..=n {
+                cs.insert(Xid(n));
+            }
+        })
+        .unwrap();
+        wm.x.clear_calls();
+
+        wm
+    }
+
+    #[test]
+    fn only_clients_whose_position_changed_are_repositioned() {
+        let mut wm = recording_wm_with_clients(4);
+        let before: HashMap<Xid, Rect> = wm.state.diff.after.positions.iter().copied().collect();
+
+        // Swapping the focused client with its neighbour leaves the other clients in place
+        wm.x.modify_and_refresh(&mut wm.state, |cs| cs.swap_down())
+            .unwrap();
+
+        let mut expected: Vec<Xid> = wm
+            .state
+            .diff
+            .after
+            .positions
+            .iter()
+            .filter(|(id, r)| before[id] != *r)
+            .map(|(id, _)| *id)
+            .collect();
+        expected.sort();
+
+        assert_eq!(expected.len(), 2);
+        assert_eq!(positioned_clients(&wm.x), expected);
+    }
+
+    #[test_case(1, Rect::new(500, 400, 250, 200), Some(Rect::new(500, 400, 250, 200)); "floating")]
+    #[test_case(1, Rect::new(900, 400, 250, 200), Some(Rect::new(750, 400, 250, 200)); "floating clamped")]
+    #[test_case(2, Rect::new(500, 400, 250, 200), Some(SCREEN); "tiled")]
+    #[test]
+    fn configure_requests_update_floating_positions(id: u32, r: Rect, expected: Option<Rect>) {
+        let mut wm = recording_wm_with_clients(2);
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            cs.float_unchecked(Xid(1), FLOATING);
+            cs.focus_client(&Xid(2));
+        })
+        .unwrap();
+        wm.x.clear_calls();
+
+        wm.handle_xevent(XEvent::ConfigureRequest(ConfigureEvent {
+            id: Xid(id),
+            r,
+            is_root: false,
+        }))
+        .unwrap();
+        let n_positioned = positioned_clients(&wm.x).len();
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        assert_eq!(n_positioned, if id == 1 { 1 } else { 0 });
+        assert_eq!(wm.client_geometry(Xid(id)), expected);
+    }
+
+    #[test]
+    fn refreshing_without_changes_does_not_reposition_clients() {
+        let mut wm = recording_wm_with_clients(4);
+
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        assert!(positioned_clients(&wm.x).is_empty());
+    }
+
+    #[test]
+    fn force_reposition_repositions_all_clients() {
+        let mut wm = recording_wm_with_clients(4);
+
+        wm.state.force_reposition();
+        wm.x.refresh(&mut wm.state).unwrap();
+
+        assert_eq!(
+            positioned_clients(&wm.x),
+            vec![Xid(1), Xid(2), Xid(3), Xid(4)]
+        );
+    }
+
+    #[test]
+    fn clients_in_stacking_order_matches_the_raise_order() {
+        let mut wm = recording_wm_with_clients(4);
+
+        wm.x.modify_and_refresh(&mut wm.state, |cs| {
+            cs.move_client_to_tag(&Xid(1), "2");
+            cs.float(Xid(3), Rect::new(10, 10, 100, 100))
+                .expect("client to be present");
+        })
+        .unwrap();
+
+        let mut raised = Vec::new();
+        for (id, c) in wm.x.client_configs() {
+            if let ClientConfig::StackAbove(prev) = c {
+                if raised.is_empty() {
+                    raised.push(prev);
+                }
+                raised.push(id);
+            }
+        }
+        let stacking: Vec<Xid> = wm.clients_in_stacking_order().collect();
+
+        assert_eq!(raised.len(), 3);
+        assert_eq!(stacking.last(), Some(&Xid(3)), "floating client is on top");
+        assert!(
+            !stacking.contains(&Xid(1)),
+            "hidden clients are not included"
+        );
+        assert_eq!(stacking, raised);
+    }
+
+    #[test]
+    fn post_startup_hooks_run_once_after_the_initial_layout() {
+        let runs = Rc::new(RefCell::new(Vec::new()));
+        let hook_runs = runs.clone();
+        let mut config = Config::default();
+        config.compose_or_set_post_startup_hook(move |wm: &mut WindowManager<TestXConn>| {
+            hook_runs
+                .borrow_mut()
+                .push(wm.client_geometry(Xid(1)).is_some());
+            Ok(())
+        });
+
+        let mut wm = WindowManager::new(
+            config,
+            HashMap::new(),
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        wm.state.client_set.insert(Xid(1));
+
+        wm.init().unwrap();
+        assert_eq!(
+            *runs.borrow(),
+            vec![true],
+            "hook runs after the first layout"
+        );
+
+        let mut new_config = Config::default();
+        let reload_runs = runs.clone();
+        new_config.compose_or_set_post_startup_hook(move |_: &mut WindowManager<TestXConn>| {
+            reload_runs.borrow_mut().push(false);
+            Ok(())
+        });
+        wm.reload_with_keycodes(new_config, str_bindings(&[]), &HashMap::new())
+            .unwrap();
+
+        assert_eq!(*runs.borrow(), vec![true], "hook is not run on reload");
+    }
+
+    #[test]
+    fn reloading_only_updates_grabs_for_changed_bindings() {
+        let known_codes = codes_for(&[("a", 38), ("b", 56), ("c", 54)]);
+        let key_bindings =
+            parse_keybindings(str_bindings(&["M-a", "M-b", "M-S-a"]), &known_codes).unwrap();
+        let mut wm = WindowManager::new(
+            Config::default(),
+            key_bindings,
+            HashMap::new(),
+            TestXConn::new(vec![SCREEN]),
+        )
+        .unwrap();
+        let initial: Vec<KeyCode> = wm.key_bindings.keys().copied().collect();
+        wm.x.grab(&initial, &[]).unwrap();
+        wm.x.clear_calls();
+
+        wm.reload_with_keycodes(
+            Config::default(),
+            str_bindings(&["M-a", "M-c", "M-S-a"]),
+            &known_codes,
+        )
+        .unwrap();
+
+        let mut grabbed: Vec<u8> = wm.x.grabbed_keys().iter().map(|k| k.code).collect();
+        grabbed.sort();
+        assert_eq!(grabbed, vec![38, 38, 54], "M-b is replaced by M-c");
+
+        let grab_calls: Vec<_> =
+            wm.x.calls()
+                .into_iter()
+                .filter(|(method, _)| method.contains("grab"))
+                .collect();
+        assert_eq!(
+            grab_calls,
+            vec![("ungrab_keys", None), ("grab_keys", None)],
+            "only the changed bindings are grabbed"
+        );
+    }
 }
@@ -0,0 +1,214 @@
+//! A point in time summary of the window manager state for use by external tools.
+//!
+//! A [StateSummary] is built entirely from the current [State] so creating one does not
+//! require any communication with the X server. With the `serde` feature enabled, summaries
+//! can be serialized to JSON using `StateSummary::to_json` or `WindowManager::state_json`.
+use crate::{core::State, x::XConn};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A summary of the current window manager state.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateSummary {
+    /// The index of the currently focused screen
+    pub focused_screen: usize,
+    /// The tag of the currently focused workspace
+    pub focused_tag: String,
+    /// The name of the active layout on the currently focused workspace
+    pub layout: String,
+    /// The title of the currently focused client (if there is one)
+    pub focused_title: Option<String>,
+    /// The workspace displayed on each screen, ordered by screen index
+    pub screens: Vec<ScreenSummary>,
+    /// Each workspace that has not been marked as invisible, ordered by workspace id
+    pub tags: Vec<TagSummary>,
+}
+
+/// A summary of the state of a single screen.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScreenSummary {
+    /// The index of this screen
+    pub index: usize,
+    /// The tag of the workspace being displayed on this screen
+    pub tag: String,
+    /// The name of the active layout for the workspace on this screen
+    pub layout: String,
+}
+
+/// A summary of the state of a single workspace.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TagSummary {
+    /// The tag of this workspace
+    pub tag: String,
+    /// The number of clients on this workspace
+    pub clients: usize,
+    /// Whether or not this workspace is currently displayed on a screen
+    pub visible: bool,
+    /// Whether or not this workspace contains any urgent clients
+    pub urgent: bool,
+}
+
+impl StateSummary {
+    /// Summarise the given [State].
+    pub fn from_state<X: XConn>(state: &State<X>) -> Self {
+        let cs = &state.client_set;
+
+        let mut screens: Vec<ScreenSummary> = cs
+            .screens()
+            .map(|s| ScreenSummary {
+                index: s.index(),
+                tag: s.workspace.tag().to_owned(),
+                layout: s.workspace.layout_name(),
+            })
+            .collect();
+        screens.sort_by_key(|s| s.index);
+
+        let tags = cs
+            .ordered_workspaces()
+            .map(|w| TagSummary {
+                tag: w.tag().to_owned(),
+                clients: w.clients().count(),
+                visible: screens.iter().any(|s| s.tag == w.tag()),
+                urgent: state.workspace_is_urgent(w.tag()),
+            })
+            .collect();
+
+        let focused_title = cs
+            .current_client()
+            .map(|id| state.titles.get(id).cloned().unwrap_or_default());
+
+        Self {
+            focused_screen: cs.current_screen().index(),
+            focused_tag: cs.current_tag().to_owned(),
+            layout: cs.current_workspace().layout_name(),
+            focused_title,
+            screens,
+            tags,
+        }
+    }
+
+    /// Serialize this summary as JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("summary to be serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{layout::LayoutStack, Xid},
+        pure::{geometry::Rect, StackSet},
+        x::test::TestXConn,
+    };
+
+    fn populated_state() -> State<TestXConn> {
+        let mut client_set = StackSet::try_new(
+            LayoutStack::default(),
+            ["1", "2", "3"],
+            vec![Rect::new(0, 0, 100, 100), Rect::new(100, 0, 100, 100)],
+        )
+        .unwrap();
+
+        client_set.insert(Xid(1));
+        client_set.insert(Xid(2));
+        client_set.focus_tag("3");
+        client_set.insert(Xid(3));
+
+        let mut state = State::from_client_set(client_set);
+        state.urgent = vec![Xid(1)];
+        state.titles = [(Xid(3), "htop".to_owned())].into_iter().collect();
+
+        state
+    }
+
+    #[test]
+    fn summary_reflects_the_current_state() {
+        let state = populated_state();
+        let summary = state.summary();
+
+        assert_eq!(summary.focused_screen, 0);
+        assert_eq!(summary.focused_tag, "3");
+        assert_eq!(summary.focused_title.as_deref(), Some("htop"));
+        assert_eq!(
+            summary.screens.iter().map(|s| &s.tag).collect::<Vec<_>>(),
+            vec!["3", "2"]
+        );
+
+        let counts: Vec<(&str, usize, bool, bool)> = summary
+            .tags
+            .iter()
+            .map(|t| (t.tag.as_str(), t.clients, t.visible, t.urgent))
+            .collect();
+
+        assert_eq!(
+            counts,
+            vec![
+                ("1", 2, false, true),
+                ("2", 0, true, false),
+                ("3", 1, true, false)
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn state_json_contains_the_expected_fields() {
+        let state = populated_state();
+        let json: serde_json::Value = serde_json::from_str(&state.state_json()).unwrap();
+        let layout = state.client_set.current_workspace().layout_name();
+
+        assert_eq!(json["focused_screen"], 0);
+        assert_eq!(json["focused_tag"], "3");
+        assert_eq!(json["layout"], layout.as_str());
+        assert_eq!(json["focused_title"], "htop");
+        assert_eq!(json["screens"][1]["tag"], "2");
+        assert_eq!(json["tags"][0]["tag"], "1");
+        assert_eq!(json["tags"][0]["clients"], 2);
+        assert_eq!(json["tags"][0]["urgent"], true);
+        assert_eq!(json["tags"][2]["visible"], true);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn title_changes_notify_subscribers_without_refreshing() {
+        use crate::{
+            core::{handle::property_notify, WindowManager},
+            x::{event::PropertyEvent, test::TestXConn, Prop, XConn, XConnExt},
+        };
+        use std::{cell::Cell, collections::HashMap, rc::Rc};
+
+        let refreshes = Rc::new(Cell::new(0));
+        let counter = refreshes.clone();
+        let mut config = Config::default();
+        config.compose_or_set_refresh_hook(move |_: &mut State<TestXConn>, _: &TestXConn| {
+            counter.set(counter.get() + 1);
+            Ok(())
+        });
+
+        let x = TestXConn::new(vec![Rect::new(0, 0, 1000, 800)]);
+        let mut wm = WindowManager::new(config, HashMap::new(), HashMap::new(), x).unwrap();
+        wm.state.client_set.insert(Xid(1));
+        wm.x.refresh(&mut wm.state).unwrap();
+        let refreshes_before = refreshes.get();
+
+        let rx = wm.subscribe_state_json();
+        wm.x.set_prop(Xid(1), "WM_NAME", Prop::UTF8String(vec!["vim".to_owned()]))
+            .unwrap();
+
+        let event = PropertyEvent {
+            id: Xid(1),
+            atom: "WM_NAME".to_owned(),
+            is_root: false,
+        };
+        property_notify(&event, &mut wm.state, &wm.x).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(json["focused_title"], "vim");
+        assert_eq!(refreshes.get(), refreshes_before);
+    }
+}
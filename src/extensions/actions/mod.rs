@@ -3,83 +3,19 @@ use crate::{
     builtin::actions::{key_handler, modify_with},
     core::{bindings::KeyEventHandler, layout::LayoutStack, State},
     util::spawn,
-    x::{atom::Atom, property::Prop, ClientConfig, XConn, XConnExt},
-    Error, Result, Xid,
+    x::{atom::Atom, property::Prop, XConn, XConnExt},
 };
-use tracing::{debug, error};
+use tracing::error;
 
 mod dynamic_select;
 
 #[doc(inline)]
 pub use dynamic_select::*;
 
-/// The possible valid actions to use when manipulating full screen state
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub enum FullScreenAction {
-    /// Force the window out of fullscreen state
-    Remove,
-    /// Force the window into fullscreen state
-    Add,
-    /// Toggle the fullscreen state of the window
-    Toggle,
-}
-
-/// Set the fullscreen state of a particular client
-pub fn set_fullscreen_state<X: XConn>(
-    id: Xid,
-    action: FullScreenAction,
-    state: &mut State<X>,
-    x: &X,
-) -> Result<()> {
-    use FullScreenAction::*;
-
-    let net_wm_state = Atom::NetWmState.as_ref();
-    let full_screen = x.intern_atom(Atom::NetWmStateFullscreen.as_ref())?;
-
-    let mut wstate = match x.get_prop(id, net_wm_state) {
-        Ok(Some(Prop::Cardinal(vals))) => vals,
-        _ => vec![],
-    };
-
-    let currently_fullscreen = wstate.contains(&full_screen);
-    debug!(%currently_fullscreen, ?action, %id, "setting fullscreen state");
-
-    if action == Add || (action == Toggle && !currently_fullscreen) {
-        let r = state
-            .client_set
-            .screen_for_client(&id)
-            .ok_or_else(|| Error::UnknownClient(id))?
-            .r;
-        state.client_set.float(id, r)?;
-        wstate.push(*full_screen);
-        x.set_client_config(id, &[ClientConfig::BorderPx(0)])?; // remove borders
-    } else if currently_fullscreen && (action == Remove || action == Toggle) {
-        state.client_set.sink(&id);
-        wstate.retain(|&val| val != *full_screen);
-        // replace borders
-        x.set_client_config(id, &[ClientConfig::BorderPx(state.config.border_width)])?;
-    }
-
-    x.set_prop(id, net_wm_state, Prop::Cardinal(wstate))?;
-    x.refresh(state)
-}
-
-/// Toggle the fullscreen state of the currently focused window.
-///
-/// **NOTE**: You will need to make use of [add_ewmh_hooks][0] for this action to
-///           work correctly.
-///
-///   [0]: crate::extensions::hooks::add_ewmh_hooks
-pub fn toggle_fullscreen<X: XConn>() -> Box<dyn KeyEventHandler<X>> {
-    key_handler(|state, x: &X| {
-        let id = match state.client_set.current_client() {
-            Some(&id) => id,
-            None => return Ok(()),
-        };
-
-        set_fullscreen_state(id, FullScreenAction::Toggle, state, x)
-    })
-}
+#[doc(inline)]
+pub use crate::builtin::actions::fullscreen::{
+    set_fullscreen_state, toggle_fullscreen, FullScreenAction,
+};
 
 /// Jump to, or create a [Workspace][0].
 ///
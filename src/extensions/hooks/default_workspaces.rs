@@ -1,10 +1,11 @@
 //! Configure workspaces to auto-spawn a set of windows if they are empty when they gain focus
 use crate::{
-    core::{hooks::StateHook, State},
+    core::{hooks::StateHook, Config, State},
     util::spawn,
     x::XConn,
     Result,
 };
+use std::collections::{HashMap, HashSet};
 
 /// Specify a workspace by `tag` and use a named layout to spawn a set of default programs
 /// on it if it gains focus while currently empty.
@@ -56,3 +57,142 @@ where
         Ok(())
     }
 }
+
+/// Add a [DefaultWorkspacePrograms] refresh hook to an existing [Config].
+///
+/// `programs` maps workspace tags to the name of the layout to use for that workspace and the
+/// programs that should be spawned on it.
+pub fn default_workspace_programs<X, I, T, L, P>(mut config: Config<X>, programs: I) -> Config<X>
+where
+    X: XConn + 'static,
+    I: IntoIterator<Item = (T, (L, Vec<P>))>,
+    T: Into<String>,
+    L: Into<String>,
+    P: Into<String>,
+{
+    config.compose_or_set_refresh_hook(DefaultWorkspacePrograms::new(programs));
+
+    config
+}
+
+/// Set the layout and spawn a set of default programs for workspaces the first time they are
+/// focused while empty.
+///
+/// Unlike [DefaultWorkspace], the programs for each workspace are only ever spawned once: if
+/// the workspace is later emptied and focused again the programs will not be respawned.
+#[derive(Debug, Clone)]
+pub struct DefaultWorkspacePrograms {
+    programs: HashMap<String, (String, Vec<String>)>,
+    spawned: HashSet<String>,
+    spawn: fn(&str) -> Result<()>,
+}
+
+impl DefaultWorkspacePrograms {
+    /// Create a new [DefaultWorkspacePrograms] hook from a mapping of workspace tags to the
+    /// name of the layout to use and the programs to spawn on that workspace.
+    pub fn new<I, T, L, P>(programs: I) -> Self
+    where
+        I: IntoIterator<Item = (T, (L, Vec<P>))>,
+        T: Into<String>,
+        L: Into<String>,
+        P: Into<String>,
+    {
+        let programs = programs
+            .into_iter()
+            .map(|(tag, (layout, progs))| {
+                let progs = progs.into_iter().map(|p| p.into()).collect();
+                (tag.into(), (layout.into(), progs))
+            })
+            .collect();
+
+        Self {
+            programs,
+            spawned: HashSet::new(),
+            spawn: |prog| spawn(prog),
+        }
+    }
+}
+
+impl<X> StateHook<X> for DefaultWorkspacePrograms
+where
+    X: XConn,
+{
+    fn call(&mut self, state: &mut State<X>, _x: &X) -> Result<()> {
+        let tag = state.client_set.current_tag();
+        if self.spawned.contains(tag) || !state.client_set.current_workspace().is_empty() {
+            return Ok(());
+        }
+
+        let (layout, progs) = match self.programs.get(tag) {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+
+        self.spawned.insert(tag.to_owned());
+        state.client_set.set_layout_by_name(layout);
+        progs.iter().try_for_each(|p| (self.spawn)(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        builtin::layout::{Grid, Monocle},
+        core::layout::Layout,
+        pure::geometry::Rect,
+        stack,
+        x::MockXConn,
+        Xid,
+    };
+    use std::cell::RefCell;
+
+    thread_local! {
+        static SPAWNED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record_spawn(prog: &str) -> Result<()> {
+        SPAWNED.with(|s| s.borrow_mut().push(prog.to_owned()));
+        Ok(())
+    }
+
+    struct EmptyXConn;
+    impl MockXConn for EmptyXConn {
+        fn mock_screen_details(&self) -> Result<Vec<Rect>> {
+            Ok(vec![Rect::new(0, 0, 1000, 800)])
+        }
+    }
+
+    #[test]
+    fn programs_are_spawned_once_when_an_empty_tag_is_focused() {
+        let x = EmptyXConn;
+        let config = Config {
+            default_layouts: stack!(Monocle.boxed(), Grid.boxed()),
+            ..Default::default()
+        };
+        let mut state = State::try_new(config, &x).expect("valid test state");
+        let mut hook = DefaultWorkspacePrograms::new([("2", ("Grid", vec!["st", "firefox"]))]);
+        hook.spawn = record_spawn;
+
+        let spawned = || SPAWNED.with(|s| s.borrow().clone());
+
+        hook.call(&mut state, &x).unwrap();
+        assert!(spawned().is_empty(), "tag 1 has no default programs");
+
+        state.client_set.focus_tag("2");
+        hook.call(&mut state, &x).unwrap();
+        hook.call(&mut state, &x).unwrap();
+        assert_eq!(spawned(), vec!["st", "firefox"]);
+        assert_eq!(state.client_set.current_workspace().layout_name(), "Grid");
+
+        // Re-viewing the tag once it is populated (or after it is emptied) doesn't respawn
+        state.client_set.insert(Xid(1));
+        state.client_set.focus_tag("1");
+        state.client_set.focus_tag("2");
+        hook.call(&mut state, &x).unwrap();
+        state.client_set.remove_client(&Xid(1));
+        hook.call(&mut state, &x).unwrap();
+
+        assert_eq!(spawned().len(), 2);
+    }
+}
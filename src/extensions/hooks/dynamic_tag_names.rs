@@ -0,0 +1,124 @@
+//! Name workspaces after the class of their focused client
+//!
+//! The [DynamicTagNames] refresh hook sets the display name of each workspace to the class
+//! name (the second string of `WM_CLASS`) of its focused client, reverting to the workspace tag
+//! once the workspace is empty. Display names are used by status bars and for setting
+//! `_NET_DESKTOP_NAMES` when the [ewmh][super::ewmh] hooks are enabled.
+//!
+//! Workspace tags themselves are never modified, so the original name of a workspace is
+//! always available using [Workspace::tag][crate::pure::Workspace::tag] and key bindings
+//! referring to tags continue to work as normal.
+use crate::{
+    core::{hooks::StateHook, Config, State},
+    x::{atom::Atom, property::Prop, XConn},
+    Result, Xid,
+};
+use std::collections::HashMap;
+
+/// Add a [DynamicTagNames] refresh hook to an existing [Config].
+pub fn add_dynamic_tag_names<X>(mut config: Config<X>) -> Config<X>
+where
+    X: XConn + 'static,
+{
+    config.compose_or_set_refresh_hook(DynamicTagNames::default());
+
+    config
+}
+
+/// Set the display name of each workspace to the class of its focused client on each refresh.
+///
+/// The client each workspace was last named after is tracked so that `WM_CLASS` is only
+/// fetched when the focused client of a workspace changes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DynamicTagNames {
+    named_for: HashMap<String, Xid>,
+}
+
+impl<X: XConn> StateHook<X> for DynamicTagNames {
+    fn call(&mut self, state: &mut State<X>, x: &X) -> Result<()> {
+        for w in state.client_set.workspaces_mut() {
+            let id = match w.focus() {
+                Some(&id) => id,
+                None => {
+                    w.clear_display_name();
+                    self.named_for.remove(w.tag());
+                    continue;
+                }
+            };
+
+            if self.named_for.get(w.tag()) == Some(&id) {
+                continue;
+            }
+
+            match x.get_prop(id, Atom::WmClass.as_ref())? {
+                Some(Prop::UTF8String(strs)) if strs.len() > 1 => w.set_display_name(&strs[1]),
+                _ => w.clear_display_name(),
+            }
+            self.named_for.insert(w.tag().to_owned(), id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pure::geometry::Rect, x::MockXConn};
+
+    struct ClassXConn;
+
+    impl MockXConn for ClassXConn {
+        fn mock_screen_details(&self) -> Result<Vec<Rect>> {
+            Ok(vec![Rect::new(0, 0, 1000, 800)])
+        }
+
+        fn mock_get_prop(&self, id: Xid, prop_name: &str) -> Result<Option<Prop>> {
+            assert_eq!(prop_name, Atom::WmClass.as_ref());
+            let class = match *id {
+                1 => "firefox",
+                _ => "Alacritty",
+            };
+
+            Ok(Some(Prop::UTF8String(vec![
+                class.to_lowercase(),
+                class.to_owned(),
+            ])))
+        }
+    }
+
+    fn display_names(state: &State<ClassXConn>) -> Vec<&str> {
+        state
+            .client_set
+            .ordered_workspaces()
+            .take(3)
+            .map(|w| w.display_name())
+            .collect()
+    }
+
+    #[test]
+    fn focusing_a_client_updates_the_display_name_of_its_tag() {
+        let x = ClassXConn;
+        let mut state = State::try_new(Config::default(), &x).expect("valid test state");
+        let mut hook = DynamicTagNames::default();
+
+        state.client_set.insert(Xid(1));
+        state.client_set.focus_tag("2");
+        state.client_set.insert(Xid(2));
+        hook.call(&mut state, &x).unwrap();
+        assert_eq!(display_names(&state), vec!["firefox", "Alacritty", "3"]);
+
+        state.client_set.move_client_to_tag(&Xid(1), "2");
+        state.client_set.focus_client(&Xid(1));
+        hook.call(&mut state, &x).unwrap();
+        assert_eq!(display_names(&state), vec!["1", "firefox", "3"]);
+
+        let tags: Vec<&str> = state
+            .client_set
+            .ordered_workspaces()
+            .take(3)
+            .map(|w| w.tag())
+            .collect();
+        assert_eq!(tags, vec!["1", "2", "3"], "original tags are unchanged");
+    }
+}
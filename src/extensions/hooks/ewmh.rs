@@ -7,16 +7,10 @@
 //!   <https://specifications.freedesktop.org/wm-spec/wm-spec-latest.html>
 use crate::{
     core::{ClientSet, Config, State},
-    extensions::actions::{set_fullscreen_state, FullScreenAction},
-    x::{
-        atom::Atom,
-        event::{ClientMessage, ClientMessageData},
-        property::Prop,
-        XConn, XConnExt, XEvent,
-    },
+    x::{atom::Atom, event::ClientMessage, property::Prop, XConn, XConnExt, XEvent},
     Result, Xid,
 };
-use tracing::{debug, warn};
+use tracing::debug;
 
 /// The set of Atoms this extension adds support for.
 ///
@@ -25,6 +19,7 @@ pub const EWMH_SUPPORTED_ATOMS: &[Atom] = &[
     Atom::NetWmStateHidden,
     Atom::NetWmStateFullscreen,
     Atom::NetWmStateDemandsAttention,
+    Atom::NetWmStateSticky,
     Atom::NetNumberOfDesktops,
     Atom::NetClientList,
     Atom::NetClientListStacking,
@@ -33,12 +28,17 @@ pub const EWMH_SUPPORTED_ATOMS: &[Atom] = &[
     Atom::NetActiveWindow,
     Atom::NetWmDesktop,
     Atom::NetWmStrut,
+    Atom::NetWmStrutPartial,
     Atom::NetWmState,
     Atom::NetWmName,
+    Atom::NetWorkarea,
     // TODO: read up on how this works and implement
     // Atom::NetDesktopViewport,
 ];
 
+/// The value of _NET_WM_DESKTOP used to indicate that a client is visible on all desktops.
+pub const ALL_DESKTOPS: u32 = 0xFFFFFFFF;
+
 /// The WM_NAME that will be set for the X server
 pub const WM_NAME: &str = "penrose";
 
@@ -86,7 +86,9 @@ pub fn startup_hook<X: XConn>(_state: &mut State<X>, x: &X) -> Result<()> {
 ///   - _NET_WM_DESKTOP      :: moving clients between workspaces
 ///   - _NET_ACTIVE_WINDOW   :: focus a new client and handle workspace switching
 ///   - _NET_CLOSE_WINDOW    :: closing a client window
-///   - _NET_WM_STATE        :: support for fullscreen windows
+///
+/// Requests to modify _NET_WM_STATE (such as clients requesting to be made fullscreen) are
+/// handled by the default event handling logic.
 pub fn event_hook<X: XConn>(event: &XEvent, state: &mut State<X>, x: &X) -> Result<bool> {
     let ClientMessage {
         id, dtype, data, ..
@@ -117,10 +119,8 @@ pub fn event_hook<X: XConn>(event: &XEvent, state: &mut State<X>, x: &X) -> Resu
         // If the request came from a pager, the first data element should be 2.
         // For pager requests, set the active client (see docs linked at the top of
         // this file for more details on the semantics of this message)
-        "_NET_ACTIVE_WINDOW" => {
-            if data.as_u32()[0] == 2 {
-                x.set_active_client(*id, state)?;
-            }
+        "_NET_ACTIVE_WINDOW" if data.as_u32()[0] == 2 => {
+            x.set_active_client(*id, state)?;
         }
 
         // Attempt to remove the requested client
@@ -128,9 +128,6 @@ pub fn event_hook<X: XConn>(event: &XEvent, state: &mut State<X>, x: &X) -> Resu
             cs.remove_client(id);
         })?,
 
-        // Handle clients that want fullscreen behaviour
-        "_NET_WM_STATE" => handle_fullscreen_message(*id, data, state, x)?,
-
         // Leave other client messages for the default event handling
         _ => (),
     }
@@ -138,46 +135,21 @@ pub fn event_hook<X: XConn>(event: &XEvent, state: &mut State<X>, x: &X) -> Resu
     Ok(true)
 }
 
-fn handle_fullscreen_message<X: XConn>(
-    id: Xid,
-    data: &ClientMessageData,
-    state: &mut State<X>,
-    x: &X,
-) -> Result<()> {
-    let mut data32 = data.as_u32();
-    if data32.is_empty() {
-        warn!(?data, "malformed data in _NET_WM_STATE message");
-        return Ok(());
-    }
-
-    let full_screen = x.intern_atom(Atom::NetWmStateFullscreen.as_ref())?;
-    let raw_action = data32.remove(0);
-
-    // Only handling fullscreen messages and only for known clients
-    if !(data32.contains(&full_screen) && state.client_set.contains(&id)) {
-        return Ok(());
-    }
-
-    let action = match raw_action {
-        0 => FullScreenAction::Remove,
-        1 => FullScreenAction::Add,
-        2 => FullScreenAction::Toggle,
-        action => {
-            warn!(%action, "invalid fullscreen action: expected 0, 1 or 2");
-            return Ok(());
-        }
-    };
-
-    set_fullscreen_state(id, action, state, x)
-}
-
 /// Notify external clients of the current status of workspaces and clients
+///
+/// `_NET_WM_DESKTOP` is updated for every client on each refresh so that it tracks clients
+/// being moved between workspaces. Sticky clients are set to [ALL_DESKTOPS] and clients on
+/// invisible workspaces (such as hidden scratchpads) have the property removed.
+///
+/// `_NET_WORKAREA` is set to the region of each desktop that is not reserved by struts
+/// (such as those set by status bars) so that it tracks screen and strut changes.
 pub fn refresh_hook<X: XConn>(state: &mut State<X>, x: &X) -> Result<()> {
     set_known_desktops(&state.client_set, x)?;
-    set_known_clients(&state.client_set, x)?;
+    set_known_clients(state, x)?;
     set_current_desktop(&state.client_set, x)?;
     set_client_desktops(&state.client_set, x)?;
     set_active_client(&state.client_set, x)?;
+    set_workarea(state, x)?;
 
     // TODO: set desktop viewport
 
@@ -188,7 +160,10 @@ fn set_known_desktops<X>(cs: &ClientSet, x: &X) -> Result<()>
 where
     X: XConn,
 {
-    let workspaces_names = cs.ordered_tags();
+    let workspaces_names: Vec<String> = cs
+        .ordered_workspaces()
+        .map(|w| w.display_name().to_owned())
+        .collect();
 
     x.set_prop(
         x.root(),
@@ -203,12 +178,11 @@ where
     )
 }
 
-fn set_known_clients<X>(cs: &ClientSet, x: &X) -> Result<()>
+fn set_known_clients<X>(state: &State<X>, x: &X) -> Result<()>
 where
     X: XConn,
 {
-    // FIXME: this currently isn't in stacking order
-    let ordered_clients: Vec<Xid> = cs.clients().copied().collect();
+    let ordered_clients: Vec<Xid> = state.client_set.clients().copied().collect();
 
     x.set_prop(
         x.root(),
@@ -219,10 +193,26 @@ where
     x.set_prop(
         x.root(),
         Atom::NetClientListStacking.as_ref(),
-        Prop::Window(ordered_clients),
+        Prop::Window(stacking_order(state, ordered_clients)),
     )
 }
 
+// Clients on hidden workspaces are placed below the visible clients
+fn stacking_order<X>(state: &State<X>, clients: Vec<Xid>) -> Vec<Xid>
+where
+    X: XConn,
+{
+    let visible: Vec<Xid> = state.clients_in_stacking_order().collect();
+
+    let mut stacking: Vec<Xid> = clients
+        .into_iter()
+        .filter(|id| !visible.contains(id))
+        .collect();
+    stacking.extend(visible);
+
+    stacking
+}
+
 fn set_current_desktop<X>(cs: &ClientSet, x: &X) -> Result<()>
 where
     X: XConn,
@@ -240,18 +230,29 @@ fn set_client_desktops<X>(cs: &ClientSet, x: &X) -> Result<()>
 where
     X: XConn,
 {
-    let client_desktops = cs.workspaces().flat_map(|w| {
-        w.stack
-            .iter()
-            .flat_map(|s| s.iter().map(|&c| (w.id as u32, c)))
-    });
+    for w in cs.workspaces() {
+        let visible = cs.non_hidden_workspaces().any(|v| v.tag == w.tag);
+
+        for &client in w.clients() {
+            // Clients on invisible workspaces (such as hidden scratchpads) are not on any of
+            // the desktops we advertise so they have their desktop cleared instead.
+            if !visible {
+                x.delete_prop(client, Atom::NetWmDesktop.as_ref())?;
+                continue;
+            }
 
-    for (desktop, client) in client_desktops {
-        x.set_prop(
-            client,
-            Atom::NetWmDesktop.as_ref(),
-            Prop::Cardinal(vec![desktop]),
-        )?;
+            let desktop = if cs.is_sticky(&client) {
+                ALL_DESKTOPS
+            } else {
+                w.id as u32
+            };
+
+            x.set_prop(
+                client,
+                Atom::NetWmDesktop.as_ref(),
+                Prop::Cardinal(vec![desktop]),
+            )?;
+        }
     }
 
     Ok(())
@@ -271,3 +272,149 @@ where
 
     Ok(())
 }
+
+// Workspaces that are not currently visible use the usable region of the focused screen as
+// that is where they will be shown if they are focused.
+fn set_workarea<X>(state: &State<X>, x: &X) -> Result<()>
+where
+    X: XConn,
+{
+    let cs = &state.client_set;
+    let current = state.usable_screen_region(cs.current_screen().r);
+
+    let workarea: Vec<u32> = cs
+        .ordered_workspaces()
+        .flat_map(|w| {
+            let r = cs
+                .screens()
+                .find(|s| s.workspace.tag == w.tag)
+                .map(|s| state.usable_screen_region(s.r))
+                .unwrap_or(current);
+
+            [r.x, r.y, r.w, r.h]
+        })
+        .collect();
+
+    x.set_prop(
+        x.root(),
+        Atom::NetWorkarea.as_ref(),
+        Prop::Cardinal(workarea),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pure::geometry::Rect,
+        x::{property::Strut, test::TestXConn},
+    };
+
+    #[test]
+    fn client_list_stacking_matches_the_raise_order() {
+        let r = Rect::new(0, 0, 1000, 800);
+        let x = (1..=5).fold(TestXConn::new(vec![r]), |x, id| x.with_client(Xid(id), r));
+        let mut state = x.new_state(add_ewmh_hooks(Config::default())).unwrap();
+
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert_as_focus_for("2", Xid(5));
+            for id in 1..=4 {
+                cs.insert(Xid(id));
+            }
+            cs.float(Xid(2), Rect::new(100, 100, 300, 300))
+                .expect("client to be present");
+        })
+        .unwrap();
+
+        let raised = x.stacking_order();
+        let stacking = match x.prop(x.root(), "_NET_CLIENT_LIST_STACKING") {
+            Some(Prop::Window(ids)) => ids,
+            prop => panic!("expected _NET_CLIENT_LIST_STACKING to be set, got {prop:?}"),
+        };
+
+        assert_eq!(raised.len(), 4);
+        assert_eq!(raised.last(), Some(&Xid(2)), "floating client is on top");
+        assert_eq!(stacking[0], Xid(5), "hidden clients are at the bottom");
+        assert_eq!(stacking[1..], raised);
+    }
+
+    #[test]
+    fn client_desktops_are_updated_when_clients_move() {
+        let r = Rect::new(0, 0, 1000, 800);
+        let x = TestXConn::new(vec![r])
+            .with_client(Xid(1), r)
+            .with_client(Xid(2), r);
+        let mut state = x.new_state(add_ewmh_hooks(Config::default())).unwrap();
+        state.client_set.add_invisible_workspace("NSP").unwrap();
+        let desktop = |id| match x.prop(Xid(id), Atom::NetWmDesktop.as_ref()) {
+            Some(Prop::Cardinal(vals)) => vals.first().copied(),
+            _ => None,
+        };
+
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+        assert_eq!(desktop(1), Some(0));
+        assert_eq!(desktop(2), Some(0));
+
+        x.modify_and_refresh(&mut state, |cs| cs.move_focused_to_tag("3"))
+            .unwrap();
+        assert_eq!(desktop(1), Some(0));
+        assert_eq!(desktop(2), Some(2));
+
+        x.modify_and_refresh(&mut state, |cs| cs.move_client_to_tag(&Xid(1), "NSP"))
+            .unwrap();
+        assert_eq!(desktop(1), None, "hidden clients are not on any desktop");
+
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.move_client_to_current_tag(&Xid(1));
+            cs.set_sticky(&Xid(1), true);
+        })
+        .unwrap();
+        assert_eq!(desktop(1), Some(ALL_DESKTOPS));
+
+        x.modify_and_refresh(&mut state, |cs| cs.set_sticky(&Xid(1), false))
+            .unwrap();
+        assert_eq!(desktop(1), Some(0));
+    }
+
+    #[test]
+    fn workarea_excludes_reserved_regions() {
+        let screens = vec![Rect::new(0, 0, 1000, 800), Rect::new(1000, 0, 1000, 800)];
+        let x = TestXConn::new(screens);
+        let mut state = x.new_state(add_ewmh_hooks(Config::default())).unwrap();
+
+        // A 20px bar across the top of the first screen only
+        let strut = Strut::try_from_bytes(&[0, 0, 20, 0, 0, 0, 0, 0, 0, 999, 0, 0]).unwrap();
+        state.struts.insert(Xid(99), strut);
+        x.refresh(&mut state).unwrap();
+
+        let workarea = |x: &TestXConn| match x.prop(x.root(), Atom::NetWorkarea.as_ref()) {
+            Some(Prop::Cardinal(vals)) => vals,
+            prop => panic!("expected _NET_WORKAREA to be set, got {prop:?}"),
+        };
+
+        let vals = workarea(&x);
+        assert_eq!(
+            vals.len(),
+            4 * state.client_set.ordered_workspaces().count()
+        );
+        assert_eq!(vals[0..4], [0, 20, 1000, 780], "first screen has the bar");
+        assert_eq!(
+            vals[4..8],
+            [1000, 0, 1000, 800],
+            "second screen is unreserved"
+        );
+        assert_eq!(
+            vals[8..12],
+            [0, 20, 1000, 780],
+            "hidden tags use the focused screen"
+        );
+
+        state.struts.clear();
+        x.refresh(&mut state).unwrap();
+        assert_eq!(workarea(&x)[0..4], [0, 0, 1000, 800]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Focus follows mouse as an [EventHook]
+//!
+//! The default event handling logic for penrose will focus clients as the mouse enters them
+//! when `focus_follow_mouse` is set in your [Config]. This extension replaces that handling
+//! with some additional control over _when_ focus should change:
+//!
+//!   - Enter events that are the result of windows moving beneath a stationary pointer
+//!     (for example, following a layout change) can optionally be ignored.
+//!   - Focus is never taken away from a client that is currently fullscreen.
+use crate::{
+    builtin::actions::fullscreen::is_fullscreen,
+    core::{hooks::EventHook, Config, State},
+    pure::geometry::Point,
+    x::{event::PointerChange, XConn, XConnExt, XEvent},
+    Result,
+};
+use tracing::trace;
+
+/// Add a [FocusFollowsMouse] event hook to an existing [Config] that ignores enter events
+/// caused by layout changes.
+pub fn add_focus_follows_mouse<X>(mut config: Config<X>) -> Config<X>
+where
+    X: XConn + 'static,
+{
+    config.compose_or_set_event_hook(FocusFollowsMouse::default());
+
+    config
+}
+
+/// Focus clients as the mouse enters them.
+///
+/// Enter events are not passed on to the default event handling logic once this hook
+/// has processed them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusFollowsMouse {
+    ignore_layout_changes: bool,
+    last_pointer: Option<Point>,
+}
+
+impl Default for FocusFollowsMouse {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl FocusFollowsMouse {
+    /// Create a new [FocusFollowsMouse] hook.
+    ///
+    /// If `ignore_layout_changes` is true then enter events where the pointer has not moved
+    /// since the previous enter event will not result in a focus change: in this case it was
+    /// the window under the pointer that moved rather than the pointer itself.
+    pub fn new(ignore_layout_changes: bool) -> Self {
+        Self {
+            ignore_layout_changes,
+            last_pointer: None,
+        }
+    }
+
+    fn should_focus<X: XConn>(&mut self, p: &PointerChange, state: &State<X>, x: &X) -> bool {
+        let pointer_moved = self.last_pointer != Some(p.abs);
+        self.last_pointer = Some(p.abs);
+
+        if self.ignore_layout_changes && !pointer_moved {
+            trace!(id = %p.id, "pointer has not moved: ignoring enter event");
+            return false;
+        }
+
+        if !state.client_set.contains(&p.id) {
+            return false;
+        }
+
+        match state.client_set.current_client() {
+            Some(&focused) if focused == p.id => false,
+            Some(&focused) => !is_fullscreen(focused, x),
+            None => true,
+        }
+    }
+}
+
+impl<X: XConn> EventHook<X> for FocusFollowsMouse {
+    fn call(&mut self, event: &XEvent, state: &mut State<X>, x: &X) -> Result<bool> {
+        let p = match event {
+            XEvent::Enter(p) => p,
+            _ => return Ok(true),
+        };
+
+        if self.should_focus(p, state, x) {
+            trace!(id = %p.id, "focusing client under the pointer");
+            x.modify_and_refresh(state, |cs| cs.focus_client(&p.id))?;
+        }
+
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pure::geometry::Rect,
+        x::{atom::Atom, property::Prop, test::TestXConn},
+        Xid,
+    };
+    use simple_test_case::test_case;
+
+    fn test_xconn(fullscreen: Option<Xid>) -> TestXConn {
+        let r = Rect::new(0, 0, 1000, 800);
+        let x = TestXConn::new(vec![r])
+            .with_client(Xid(1), r)
+            .with_client(Xid(2), r);
+
+        match fullscreen {
+            Some(id) => {
+                let atom = x.intern_atom(Atom::NetWmStateFullscreen.as_ref()).unwrap();
+                x.with_prop(id, Atom::NetWmState.as_ref(), Prop::Cardinal(vec![*atom]))
+            }
+            None => x,
+        }
+    }
+
+    fn enter(id: u32, x: u32, y: u32) -> XEvent {
+        XEvent::Enter(PointerChange {
+            id: Xid(id),
+            abs: Point::new(x, y),
+            relative: Point::new(x, y),
+            same_screen: true,
+        })
+    }
+
+    #[test_case(None, 1, 1; "known client")]
+    #[test_case(None, 42, 2; "unknown client")]
+    #[test_case(Some(Xid(2)), 1, 2; "focused client is fullscreen")]
+    #[test_case(Some(Xid(1)), 1, 1; "other client is fullscreen")]
+    #[test]
+    fn enter_events_focus_the_client(fullscreen: Option<Xid>, entered: u32, expected: u32) {
+        let x = test_xconn(fullscreen);
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+
+        let mut hook = FocusFollowsMouse::default();
+        let run_default = hook.call(&enter(entered, 10, 10), &mut state, &x).unwrap();
+
+        assert!(!run_default);
+        assert_eq!(state.client_set.current_client(), Some(&Xid(expected)));
+    }
+
+    #[test_case(true, 2; "ignored")]
+    #[test_case(false, 1; "not ignored")]
+    #[test]
+    fn enter_events_without_pointer_motion(ignore_layout_changes: bool, expected: u32) {
+        let x = test_xconn(None);
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+
+        let mut hook = FocusFollowsMouse::new(ignore_layout_changes);
+        hook.call(&enter(2, 10, 10), &mut state, &x).unwrap();
+        hook.call(&enter(1, 10, 10), &mut state, &x).unwrap();
+
+        assert_eq!(state.client_set.current_client(), Some(&Xid(expected)));
+    }
+}
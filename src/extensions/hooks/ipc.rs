@@ -0,0 +1,453 @@
+//! A control socket for driving a running penrose window manager from the command line.
+//!
+//! Adding the [IpcHook] to your [Config] via [add_ipc_hook] starts a background thread
+//! listening on a Unix domain socket for newline delimited commands. Each command that is
+//! received is parsed into an [IpcCommand] and forwarded to the main event loop where it is
+//! run in the same way as a key binding. The following commands are supported:
+//!
+//!   - `focus-tag <tag>`: focus the given workspace
+//!   - `move-to-tag <tag>`: move the focused client to the given workspace
+//!   - `focus-next` / `focus-prev`: move focus within the current workspace
+//!   - `next-layout` / `prev-layout` (or `cycle-layout`): change the active layout
+//!   - `kill`: close the focused client
+//!   - `spawn <program> [args...]`: run an external program
+//!   - `exit`: exit penrose
+//!   - `query <tag|tags|layout|title|state>`: look up part of the current state (see [IpcQuery])
+//!
+//! Each command receives a single line response of either `ok` or `error: <reason>`, so the
+//! socket can be used from a shell script with a tool such as `socat`. Queries respond with
+//! the requested value in place of `ok`:
+//!
+//! ```sh
+//! echo "focus-tag 3" | socat - UNIX-CONNECT:/tmp/penrose.sock
+//! echo "query layout" | socat - UNIX-CONNECT:/tmp/penrose.sock
+//! ```
+//!
+//! > **NOTE**: the main event loop blocks while waiting for events from the X server. When
+//! > the `x11rb` feature is enabled, the listening thread holds its own connection to the X
+//! > server which it uses to send a client message to the root window after each command is
+//! > received in order to wake the event loop. Without it, commands will be run (and queries
+//! > answered) the next time an event is received from the X server.
+use crate::{
+    builtin::actions::{key_handler, modify_with},
+    core::{bindings::KeyEventHandler, hooks::EventHook, summary::StateSummary, Config, State},
+    util,
+    x::{XConn, XEvent},
+    Error, Result,
+};
+use std::{
+    fs,
+    io::{BufRead, BufReader, ErrorKind, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+use tracing::{debug, error, warn};
+
+/// The client message type used to wake the main event loop after a command is received.
+pub const IPC_MESSAGE: &str = "_PENROSE_IPC";
+
+/// How long to wait for the main event loop to answer a query before giving up.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Start listening for commands on a Unix domain socket at the given path and add an
+/// [IpcHook] to an existing [Config] in order to run them.
+///
+/// A socket left behind at `path` by a previous run is removed before binding the socket. If
+/// anything other than a socket exists at `path` then an error is returned.
+pub fn add_ipc_hook<X>(mut config: Config<X>, path: impl AsRef<Path>) -> Result<Config<X>>
+where
+    X: XConn + 'static,
+{
+    config.compose_or_set_event_hook(IpcHook::listen(path)?);
+
+    Ok(config)
+}
+
+/// A command that can be sent to a running window manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpcCommand {
+    /// Focus the workspace with the given tag
+    FocusTag(String),
+    /// Move the focused client to the workspace with the given tag
+    MoveToTag(String),
+    /// Move focus to the next client in the current workspace
+    FocusNext,
+    /// Move focus to the previous client in the current workspace
+    FocusPrevious,
+    /// Switch to the next layout for the current workspace
+    NextLayout,
+    /// Switch to the previous layout for the current workspace
+    PreviousLayout,
+    /// Kill the currently focused client
+    KillFocused,
+    /// Spawn an external program
+    Spawn(String),
+    /// Exit penrose
+    Exit,
+    /// Look up part of the current window manager state
+    Query(IpcQuery),
+}
+
+impl IpcCommand {
+    /// Parse a single line command.
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line.trim();
+        let (cmd, arg) = match line.split_once(char::is_whitespace) {
+            Some((cmd, arg)) => (cmd, Some(arg.trim())),
+            None => (line, None),
+        };
+
+        let invalid = |reason: &str| Error::InvalidIpcCommand {
+            command: line.to_owned(),
+            reason: reason.to_owned(),
+        };
+
+        let required = |arg: Option<&str>| match arg {
+            Some(arg) if !arg.is_empty() => Ok(arg.to_owned()),
+            _ => Err(invalid("missing argument")),
+        };
+
+        let no_arg = |c: Self| match arg {
+            None => Ok(c),
+            Some(_) => Err(invalid("unexpected argument")),
+        };
+
+        match cmd {
+            "focus-tag" => Ok(Self::FocusTag(required(arg)?)),
+            "move-to-tag" => Ok(Self::MoveToTag(required(arg)?)),
+            "spawn" => Ok(Self::Spawn(required(arg)?)),
+            "focus-next" => no_arg(Self::FocusNext),
+            "focus-prev" => no_arg(Self::FocusPrevious),
+            "next-layout" | "cycle-layout" => no_arg(Self::NextLayout),
+            "prev-layout" => no_arg(Self::PreviousLayout),
+            "kill" => no_arg(Self::KillFocused),
+            "exit" => no_arg(Self::Exit),
+            "query" => match IpcQuery::parse(&required(arg)?) {
+                Some(q) => Ok(Self::Query(q)),
+                None => Err(invalid("unknown query")),
+            },
+            _ => Err(invalid("unknown command")),
+        }
+    }
+
+    /// The [KeyEventHandler] that runs this command.
+    ///
+    /// Queries do not modify the window manager state so their handler does nothing: they are
+    /// answered by the [IpcHook] using [IpcQuery::answer].
+    pub fn handler<X: XConn>(self) -> Box<dyn KeyEventHandler<X>> {
+        match self {
+            Self::FocusTag(tag) => modify_with(move |cs| cs.focus_tag(&tag)),
+            Self::MoveToTag(tag) => modify_with(move |cs| cs.move_focused_to_tag(&tag)),
+            Self::FocusNext => modify_with(|cs| cs.focus_down()),
+            Self::FocusPrevious => modify_with(|cs| cs.focus_up()),
+            Self::NextLayout => modify_with(|cs| cs.next_layout()),
+            Self::PreviousLayout => modify_with(|cs| cs.previous_layout()),
+            Self::KillFocused => modify_with(|cs| cs.kill_focused()),
+            Self::Spawn(cmd) => key_handler(move |_, _| util::spawn(cmd.clone())),
+            Self::Exit => key_handler(|s: &mut State<X>, _| {
+                s.running = false;
+                Ok(())
+            }),
+            Self::Query(_) => key_handler(|_, _| Ok(())),
+        }
+    }
+}
+
+/// A read only query about the current window manager state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcQuery {
+    /// `tag`: the tag of the focused workspace
+    Tag,
+    /// `tags`: the tags of all workspaces ordered by workspace id, separated by spaces. Workspaces
+    /// that have been marked as invisible are not included.
+    Tags,
+    /// `layout`: the name of the active layout on the focused workspace
+    Layout,
+    /// `title`: the title of the focused client (empty if there is no focused client)
+    Title,
+    /// `state`: a JSON summary of the full window manager state (see [StateSummary])
+    #[cfg(feature = "serde")]
+    State,
+}
+
+impl IpcQuery {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "tag" => Some(Self::Tag),
+            "tags" => Some(Self::Tags),
+            "layout" => Some(Self::Layout),
+            "title" => Some(Self::Title),
+            #[cfg(feature = "serde")]
+            "state" => Some(Self::State),
+            _ => None,
+        }
+    }
+
+    /// Answer this query using the current [State].
+    pub fn answer<X: XConn>(&self, state: &State<X>) -> String {
+        let summary = StateSummary::from_state(state);
+
+        match self {
+            Self::Tag => summary.focused_tag,
+            Self::Tags => summary
+                .tags
+                .iter()
+                .map(|t| t.tag.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            Self::Layout => summary.layout,
+            Self::Title => summary.focused_title.unwrap_or_default(),
+            #[cfg(feature = "serde")]
+            Self::State => summary.to_json(),
+        }
+    }
+}
+
+// A command received from the socket along with a channel for answering it if it is a query
+type IpcRequest = (IpcCommand, Option<Sender<String>>);
+
+/// An [EventHook] that runs [IpcCommand]s received from a control socket.
+///
+/// Pending commands are run each time an event is received from the X server. The client
+/// messages used to wake the event loop are not passed on to the default event handling.
+#[derive(Debug)]
+pub struct IpcHook {
+    rx: Receiver<IpcRequest>,
+}
+
+impl IpcHook {
+    /// Bind a Unix domain socket at the given path and start listening for commands on a
+    /// background thread.
+    pub fn listen(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path)?,
+            Ok(_) => {
+                return Err(Error::Custom(format!(
+                    "unable to bind ipc socket: {} exists and is not a socket",
+                    path.display()
+                )))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return Err(e.into()),
+        }
+
+        let listener = UnixListener::bind(path)?;
+        let (tx, rx) = channel();
+        let wake = event_loop_waker();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = handle_connection(stream, &tx, &wake) {
+                            warn!(%e, "error handling ipc connection");
+                        }
+                    }
+                    Err(e) => error!(%e, "error accepting ipc connection"),
+                }
+            }
+        });
+
+        Ok(Self { rx })
+    }
+}
+
+impl<X: XConn> EventHook<X> for IpcHook {
+    fn call(&mut self, event: &XEvent, state: &mut State<X>, x: &X) -> Result<bool> {
+        for (cmd, reply) in self.rx.try_iter() {
+            if let (IpcCommand::Query(q), Some(reply)) = (&cmd, reply) {
+                debug!(?q, "answering ipc query");
+                let _ = reply.send(q.answer(state));
+                continue;
+            }
+
+            debug!(?cmd, "running ipc command");
+            if let Err(e) = cmd.handler().call(state, x) {
+                error!(%e, "error running ipc command");
+            }
+        }
+
+        match event {
+            XEvent::ClientMessage(m) if m.dtype == IPC_MESSAGE => Ok(false),
+            _ => Ok(true),
+        }
+    }
+}
+
+fn handle_connection(stream: UnixStream, tx: &Sender<IpcRequest>, wake: &Waker) -> Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let response = handle_line(&line?, tx, wake, QUERY_TIMEOUT);
+        writeln!(writer, "{response}")?;
+    }
+
+    Ok(())
+}
+
+// Parse a command and forward it to the event loop, returning the response for the client
+fn handle_line(
+    line: &str,
+    tx: &Sender<IpcRequest>,
+    wake: &Waker,
+    query_timeout: Duration,
+) -> String {
+    let cmd = match IpcCommand::parse(line) {
+        Ok(cmd) => cmd,
+        Err(e) => return format!("error: {e}"),
+    };
+
+    let (reply, answer) = match cmd {
+        IpcCommand::Query(_) => {
+            let (reply, answer) = channel();
+            (Some(reply), Some(answer))
+        }
+        _ => (None, None),
+    };
+
+    if tx.send((cmd, reply)).is_err() {
+        return "error: window manager is not running".to_owned();
+    }
+
+    if let Err(e) = wake() {
+        warn!(%e, "unable to wake event loop");
+    }
+
+    match answer {
+        Some(answer) => answer
+            .recv_timeout(query_timeout)
+            .unwrap_or_else(|_| "error: no response from window manager".to_owned()),
+        None => "ok".to_owned(),
+    }
+}
+
+type Waker = Box<dyn Fn() -> Result<()> + Send>;
+
+// The listening thread holds a single connection to the X server for waking the event loop
+// which is reused for every command that is received.
+#[cfg(feature = "x11rb")]
+fn event_loop_waker() -> Waker {
+    match crate::x11rb::Waker::new(IPC_MESSAGE) {
+        Ok(waker) => Box::new(move || waker.wake()),
+        Err(e) => {
+            warn!(%e, "unable to connect to the X server: ipc commands will run on the next event");
+            Box::new(|| Ok(()))
+        }
+    }
+}
+
+#[cfg(not(feature = "x11rb"))]
+fn event_loop_waker() -> Waker {
+    Box::new(|| Ok(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x::test::TestXConn;
+    use simple_test_case::test_case;
+
+    #[test_case("focus-tag 3", IpcCommand::FocusTag("3".into()); "focus tag")]
+    #[test_case("move-to-tag  web ", IpcCommand::MoveToTag("web".into()); "move to tag")]
+    #[test_case("focus-next", IpcCommand::FocusNext; "focus next")]
+    #[test_case("focus-prev", IpcCommand::FocusPrevious; "focus prev")]
+    #[test_case("cycle-layout", IpcCommand::NextLayout; "cycle layout")]
+    #[test_case("prev-layout", IpcCommand::PreviousLayout; "prev layout")]
+    #[test_case("kill", IpcCommand::KillFocused; "kill")]
+    #[test_case("spawn st -e htop", IpcCommand::Spawn("st -e htop".into()); "spawn")]
+    #[test_case("exit\n", IpcCommand::Exit; "exit")]
+    #[test_case("query layout", IpcCommand::Query(IpcQuery::Layout); "query")]
+    #[test]
+    fn valid_commands_parse(line: &str, expected: IpcCommand) {
+        assert_eq!(IpcCommand::parse(line).unwrap(), expected);
+    }
+
+    #[test_case("focus-tag"; "missing argument")]
+    #[test_case("kill 3"; "unexpected argument")]
+    #[test_case("launch-missiles"; "unknown command")]
+    #[test_case("query"; "missing query")]
+    #[test_case("query everything"; "unknown query")]
+    #[test_case(""; "empty")]
+    #[test]
+    fn invalid_commands_error(line: &str) {
+        let res = IpcCommand::parse(line);
+
+        assert!(
+            matches!(res, Err(Error::InvalidIpcCommand { .. })),
+            "{res:?}"
+        );
+    }
+
+    #[test]
+    fn valid_commands_are_forwarded_to_the_event_loop() {
+        let (tx, rx) = channel();
+        let wake: Waker = Box::new(|| Ok(()));
+        let t = QUERY_TIMEOUT;
+
+        assert_eq!(handle_line("focus-tag 2", &tx, &wake, t), "ok");
+        assert!(handle_line("focus-tag", &tx, &wake, t).starts_with("error: "));
+        assert_eq!(handle_line("next-layout", &tx, &wake, t), "ok");
+
+        let received: Vec<IpcCommand> = rx.try_iter().map(|(cmd, _)| cmd).collect();
+        assert_eq!(
+            received,
+            vec![IpcCommand::FocusTag("2".into()), IpcCommand::NextLayout]
+        );
+    }
+
+    #[test]
+    fn queries_are_answered_by_the_event_loop() {
+        let (tx, rx) = channel();
+        let (woken_tx, woken) = channel();
+        let wake: Waker = Box::new(move || {
+            let _ = woken_tx.send(());
+            Ok(())
+        });
+
+        // The event loop blocks until it is woken and then runs the hook once
+        let event_loop = thread::spawn(move || {
+            let x = TestXConn::default();
+            let mut state = x.new_state(Config::default()).unwrap();
+            state.client_set.focus_tag("3");
+            let mut hook = IpcHook { rx };
+
+            woken.recv().unwrap();
+            hook.call(&XEvent::MappingNotify, &mut state, &x).unwrap();
+        });
+
+        let response = handle_line("query tag", &tx, &wake, QUERY_TIMEOUT);
+        event_loop.join().unwrap();
+
+        assert_eq!(response, "3");
+    }
+
+    #[test]
+    fn unanswered_queries_time_out() {
+        let (tx, _rx) = channel();
+        let wake: Waker = Box::new(|| Ok(()));
+
+        let response = handle_line("query tag", &tx, &wake, Duration::from_millis(10));
+
+        assert!(response.starts_with("error: "), "{response}");
+    }
+
+    #[test]
+    fn existing_files_that_are_not_sockets_are_not_removed() {
+        let path = std::env::temp_dir().join(format!("penrose-ipc-test-{}", std::process::id()));
+        fs::write(&path, "important").unwrap();
+
+        let res = IpcHook::listen(&path);
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(res.is_err());
+        assert_eq!(contents, "important");
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
     x::{Query, XConn},
     Result, Xid,
 };
+use std::fmt;
 
 // A tuple of (query, manage hook) runs conditionally if the query holds
 // for the window being managed.
@@ -82,6 +83,41 @@ impl<X: XConn> ManageHook<X> for FloatingCentered {
     }
 }
 
+/// Float clients matching a set of [Query] rules in the center of the screen.
+///
+/// Rules are checked in order and only the placement of the first matching rule is applied.
+/// Clients not matching any of the rules are left to be tiled as normal.
+pub struct FloatingRules<X: XConn> {
+    rules: Vec<(Box<dyn Query<X>>, FloatingCentered)>,
+}
+
+impl<X: XConn> fmt::Debug for FloatingRules<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FloatingRules")
+            .field("rules", &self.rules)
+            .finish()
+    }
+}
+
+impl<X: XConn> FloatingRules<X> {
+    /// Create a new [FloatingRules] manage hook from a list of rules.
+    pub fn new(rules: Vec<(Box<dyn Query<X>>, FloatingCentered)>) -> Self {
+        Self { rules }
+    }
+}
+
+impl<X: XConn> ManageHook<X> for FloatingRules<X> {
+    fn call(&mut self, client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
+        for (query, placement) in self.rules.iter_mut() {
+            if query.run(client, x)? {
+                return placement.call(client, state, x);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Float clients at a relative position to the current screen.
 #[derive(Debug)]
 pub struct FloatingRelative(pub RelativeRect);
@@ -101,6 +137,101 @@ impl<X: XConn> ManageHook<X> for FloatingRelative {
     }
 }
 
+/// Float clients at a fixed position relative to the top left corner of the current screen.
+///
+/// Unlike [FloatingFixed], the position of the client is offset by the position of the screen
+/// that is focused when the client is managed. Rects that do not fit on the screen are moved
+/// and resized so that they are fully visible.
+#[derive(Debug)]
+pub struct FloatingAt {
+    /// The position of the client relative to the screen
+    pub rect: Rect,
+}
+
+impl FloatingAt {
+    /// Create a new [FloatingAt] for the given position relative to the screen.
+    pub fn new(rect: Rect) -> Self {
+        Self { rect }
+    }
+
+    /// The position on screen given to clients when `r_screen` is the current screen.
+    pub fn placement(&self, r_screen: &Rect) -> Rect {
+        let r = Rect {
+            x: r_screen.x + self.rect.x,
+            y: r_screen.y + self.rect.y,
+            ..self.rect
+        };
+
+        r.clamped_to(r_screen)
+    }
+}
+
+impl<X: XConn> ManageHook<X> for FloatingAt {
+    fn call(&mut self, client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
+        let r = self.placement(&state.client_set.screens.focus.r);
+
+        float(client, r, state, x)
+    }
+}
+
+/// A corner of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner {
+    /// The top left corner
+    TopLeft,
+    /// The top right corner
+    TopRight,
+    /// The bottom left corner
+    BottomLeft,
+    /// The bottom right corner
+    BottomRight,
+}
+
+/// Float clients with a fixed size in a corner of the current screen.
+///
+/// Clients larger than the screen are shrunk to fit.
+#[derive(Debug)]
+pub struct FloatingCorner {
+    /// The corner of the screen to place the client in
+    pub corner: Corner,
+    /// The width of the client in pixels
+    pub w: u32,
+    /// The height of the client in pixels
+    pub h: u32,
+}
+
+impl FloatingCorner {
+    /// Create a new [FloatingCorner] for the given corner, width and height.
+    pub fn new(corner: Corner, w: u32, h: u32) -> Self {
+        Self { corner, w, h }
+    }
+
+    /// The position on screen given to clients when `r_screen` is the current screen.
+    pub fn placement(&self, r_screen: &Rect) -> Rect {
+        let w = self.w.min(r_screen.w);
+        let h = self.h.min(r_screen.h);
+        let (left, top) = (r_screen.x, r_screen.y);
+        let (right, bottom) = (left + r_screen.w - w, top + r_screen.h - h);
+
+        let (x, y) = match self.corner {
+            Corner::TopLeft => (left, top),
+            Corner::TopRight => (right, top),
+            Corner::BottomLeft => (left, bottom),
+            Corner::BottomRight => (right, bottom),
+        };
+
+        Rect::new(x, y, w, h)
+    }
+}
+
+impl<X: XConn> ManageHook<X> for FloatingCorner {
+    fn call(&mut self, client: Xid, state: &mut State<X>, x: &X) -> Result<()> {
+        let r = self.placement(&state.client_set.screens.focus.r);
+
+        float(client, r, state, x)
+    }
+}
+
 /// Move the specified client to the named workspace.
 #[derive(Debug)]
 pub struct SetWorkspace(pub &'static str);
@@ -110,3 +241,116 @@ impl<X: XConn> ManageHook<X> for SetWorkspace {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::Config,
+        x::{atom::Atom, property::Prop, query::ClassName, MockXConn},
+    };
+    use simple_test_case::test_case;
+
+    const SCREEN: Rect = Rect::new(0, 0, 1000, 800);
+
+    struct ClassXConn;
+    impl MockXConn for ClassXConn {
+        fn mock_screen_details(&self) -> Result<Vec<Rect>> {
+            Ok(vec![SCREEN])
+        }
+
+        fn mock_get_prop(&self, id: Xid, prop_name: &str) -> Result<Option<Prop>> {
+            if prop_name != Atom::WmClass.as_ref() {
+                return Ok(None);
+            }
+
+            let class = match *id {
+                1 => "first",
+                2 => "second",
+                _ => "other",
+            };
+
+            Ok(Some(Prop::UTF8String(vec![
+                class.to_owned(),
+                class.to_owned(),
+            ])))
+        }
+    }
+
+    #[test_case(1, Some(Rect::new(250, 200, 500, 400)); "first rule")]
+    #[test_case(2, Some(Rect::new(400, 320, 200, 160)); "second rule")]
+    #[test_case(3, None; "no matching rule")]
+    #[test]
+    fn floating_rules_float_matching_clients(id: u32, expected: Option<Rect>) {
+        let x = ClassXConn;
+        let mut state = State::try_new(Config::default(), &x).expect("valid test state");
+        state.client_set.insert(Xid(id));
+
+        let mut hook = FloatingRules::new(vec![
+            (
+                Box::new(ClassName("first")),
+                FloatingCentered::new(0.5, 0.5),
+            ),
+            (
+                Box::new(ClassName("second")),
+                FloatingCentered::new(0.2, 0.2),
+            ),
+        ]);
+        hook.call(Xid(id), &mut state, &x).unwrap();
+
+        let r = state
+            .client_set
+            .floating
+            .get(&Xid(id))
+            .map(|r| r.applied_to(&SCREEN));
+
+        assert_eq!(r, expected);
+    }
+
+    const SECOND_SCREEN: Rect = Rect::new(1000, 200, 1920, 1080);
+
+    #[test_case(Corner::TopLeft, Rect::new(1000, 200, 400, 300); "top left")]
+    #[test_case(Corner::TopRight, Rect::new(2520, 200, 400, 300); "top right")]
+    #[test_case(Corner::BottomLeft, Rect::new(1000, 980, 400, 300); "bottom left")]
+    #[test_case(Corner::BottomRight, Rect::new(2520, 980, 400, 300); "bottom right")]
+    #[test]
+    fn floating_corner_placement(corner: Corner, expected: Rect) {
+        let r = FloatingCorner::new(corner, 400, 300).placement(&SECOND_SCREEN);
+
+        assert_eq!(r, expected);
+    }
+
+    #[test]
+    fn floating_corner_shrinks_large_clients_to_fit() {
+        let r = FloatingCorner::new(Corner::BottomRight, 3000, 300).placement(&SECOND_SCREEN);
+
+        assert_eq!(r, Rect::new(1000, 980, 1920, 300));
+    }
+
+    #[test_case(Rect::new(10, 20, 400, 300), Rect::new(1010, 220, 400, 300); "offset by screen")]
+    #[test_case(Rect::new(1800, 20, 400, 300), Rect::new(2520, 220, 400, 300); "clamped to screen")]
+    #[test]
+    fn floating_at_placement(rect: Rect, expected: Rect) {
+        let r = FloatingAt::new(rect).placement(&SECOND_SCREEN);
+
+        assert_eq!(r, expected);
+    }
+
+    #[test]
+    fn floating_corner_floats_the_client() {
+        let x = ClassXConn;
+        let mut state = State::try_new(Config::default(), &x).expect("valid test state");
+        state.client_set.insert(Xid(1));
+
+        let mut hook = FloatingCorner::new(Corner::TopRight, 400, 200);
+        hook.call(Xid(1), &mut state, &x).unwrap();
+
+        let r = state
+            .client_set
+            .floating
+            .get(&Xid(1))
+            .map(|r| r.applied_to(&SCREEN));
+
+        assert_eq!(r, Some(Rect::new(600, 0, 400, 200)));
+    }
+}
@@ -1,12 +1,23 @@
 //! Hook implementations and helpers for adding to your Penrose window manager
 pub mod default_workspaces;
+pub mod dynamic_tag_names;
 pub mod ewmh;
+pub mod focus_follows_mouse;
+pub mod ipc;
 pub mod manage;
 pub mod named_scratchpads;
+pub mod opacity;
 pub mod startup;
 pub mod window_swallowing;
 
+pub use default_workspaces::{default_workspace_programs, DefaultWorkspacePrograms};
+pub use dynamic_tag_names::{add_dynamic_tag_names, DynamicTagNames};
 pub use ewmh::add_ewmh_hooks;
-pub use named_scratchpads::{add_named_scratchpads, NamedScratchPad, ToggleNamedScratchPad};
+pub use focus_follows_mouse::{add_focus_follows_mouse, FocusFollowsMouse};
+pub use ipc::{add_ipc_hook, IpcCommand, IpcHook, IpcQuery};
+pub use named_scratchpads::{
+    add_named_scratchpads, send_focused_to_scratchpad, NamedScratchPad, ToggleNamedScratchPad,
+};
+pub use opacity::{add_inactive_opacity, InactiveOpacity};
 pub use startup::SpawnOnStartup;
 pub use window_swallowing::WindowSwallowing;
@@ -1,12 +1,21 @@
 //! Support for managing multiple floating scratchpad programs that can be
 //! toggled on or off on the active workspace.
+//!
+//! By default each scratchpad owns a single client that is shared between all workspaces.
+//! Scratchpads created using [NamedScratchPad::new_per_workspace] instead maintain a separate
+//! client for each workspace they are toggled on.
 use crate::{
+    builtin::actions::key_handler,
     core::{bindings::KeyEventHandler, hooks::ManageHook, State, WindowManager},
     util::spawn,
     x::{Query, XConn, XConnExt, XEvent},
     Result, Xid,
 };
-use std::{borrow::Cow, collections::HashMap, fmt};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    fmt,
+};
 use tracing::{debug, error, warn};
 
 /// The tag used for a placeholder Workspace that holds scratchpad windows when
@@ -21,6 +30,9 @@ where
     name: Cow<'static, str>,
     prog: Cow<'static, str>,
     client: Option<Xid>,
+    per_workspace: bool,
+    clients: HashMap<String, Xid>,
+    pending_tags: VecDeque<String>,
     query: Box<dyn Query<X>>,
     hook: Box<dyn ManageHook<X>>,
 }
@@ -31,6 +43,9 @@ impl<X: XConn> fmt::Debug for NamedScratchPad<X> {
             .field("name", &self.name)
             .field("prog", &self.prog)
             .field("client", &self.client)
+            .field("per_workspace", &self.per_workspace)
+            .field("clients", &self.clients)
+            .field("pending_tags", &self.pending_tags)
             .finish()
     }
 }
@@ -47,6 +62,39 @@ where
         manage_hook: H,
         run_hook_on_toggle: bool,
     ) -> (Self, ToggleNamedScratchPad)
+    where
+        Q: Query<X> + 'static,
+        H: ManageHook<X> + 'static,
+    {
+        Self::build(name, prog, query, manage_hook, run_hook_on_toggle, false)
+    }
+
+    /// Create a new named scratchpad that maintains a separate client for each workspace.
+    ///
+    /// Toggling the scratchpad on a workspace that does not yet have its own client will
+    /// spawn a new instance of the program for that workspace.
+    pub fn new_per_workspace<Q, H>(
+        name: impl Into<Cow<'static, str>>,
+        prog: impl Into<Cow<'static, str>>,
+        query: Q,
+        manage_hook: H,
+        run_hook_on_toggle: bool,
+    ) -> (Self, ToggleNamedScratchPad)
+    where
+        Q: Query<X> + 'static,
+        H: ManageHook<X> + 'static,
+    {
+        Self::build(name, prog, query, manage_hook, run_hook_on_toggle, true)
+    }
+
+    fn build<Q, H>(
+        name: impl Into<Cow<'static, str>>,
+        prog: impl Into<Cow<'static, str>>,
+        query: Q,
+        manage_hook: H,
+        run_hook_on_toggle: bool,
+        per_workspace: bool,
+    ) -> (Self, ToggleNamedScratchPad)
     where
         Q: Query<X> + 'static,
         H: ManageHook<X> + 'static,
@@ -56,6 +104,9 @@ where
             name: name.clone(),
             prog: prog.into(),
             client: None,
+            per_workspace,
+            clients: HashMap::new(),
+            pending_tags: VecDeque::new(),
             query: Box::new(query),
             hook: Box::new(manage_hook),
         };
@@ -68,6 +119,62 @@ where
             },
         )
     }
+
+    /// Clear the stored client for this scratchpad if it matches the given [Xid].
+    ///
+    /// Returns `true` if the client was cleared. This is called automatically for destroyed
+    /// clients when using [add_named_scratchpads] so that a future toggle will spawn a new
+    /// client rather than trying to reuse an ID that may now belong to another window.
+    pub fn remove_client(&mut self, id: Xid) -> bool {
+        let n_clients = self.clients.len();
+        self.clients.retain(|_, &mut c| c != id);
+
+        if self.client == Some(id) {
+            self.client = None;
+            true
+        } else {
+            self.clients.len() != n_clients
+        }
+    }
+
+    /// Whether or not this scratchpad maintains a separate client for each workspace.
+    pub fn is_per_workspace(&self) -> bool {
+        self.per_workspace
+    }
+
+    /// The client owned by this scratchpad for the given tag, if there is one.
+    ///
+    /// Scratchpads that are not per-workspace return the same client for all tags.
+    pub fn client_for(&self, tag: &str) -> Option<Xid> {
+        if self.per_workspace {
+            self.clients.get(tag).copied()
+        } else {
+            self.client
+        }
+    }
+
+    // Set the client for the given tag, returning the previous client if there was one
+    fn set_client_for(&mut self, tag: &str, id: Option<Xid>) -> Option<Xid> {
+        match (self.per_workspace, id) {
+            (false, id) => std::mem::replace(&mut self.client, id),
+            (true, Some(id)) => self.clients.insert(tag.to_owned(), id),
+            (true, None) => self.clients.remove(tag),
+        }
+    }
+
+    // The tag that a newly managed client matching our query should be stored against, if we
+    // are able to accept another client.
+    fn tag_for_new_client(&mut self, current_tag: &str) -> Option<String> {
+        if !self.per_workspace {
+            return self.client.is_none().then(|| current_tag.to_owned());
+        }
+
+        match self.pending_tags.pop_front() {
+            Some(tag) => Some(tag),
+            None if !self.clients.contains_key(current_tag) => Some(current_tag.to_owned()),
+            None => None,
+        }
+    }
 }
 
 // Private wrapper type to ensure that only this module can access this state extension
@@ -105,9 +212,18 @@ pub fn manage_hook<X: XConn + 'static>(id: Xid, state: &mut State<X>, x: &X) ->
     let s = state.extension::<NamedScratchPadState<X>>()?;
 
     for sp in s.borrow_mut().0.values_mut() {
-        if sp.client.is_none() && sp.query.run(id, x)? {
-            debug!(scratchpad=sp.name.as_ref(), %id, "matched query for named scratchpad");
-            sp.client = Some(id);
+        let current_tag = state.client_set.current_tag();
+        if sp.client_for(current_tag).is_some() && sp.pending_tags.is_empty() {
+            continue;
+        }
+
+        if sp.query.run(id, x)? {
+            let tag = match sp.tag_for_new_client(current_tag) {
+                Some(tag) => tag,
+                None => continue,
+            };
+            debug!(scratchpad=sp.name.as_ref(), %id, %tag, "matched query for named scratchpad");
+            sp.set_client_for(&tag, Some(id));
             return sp.hook.call(id, state, x);
         }
     }
@@ -125,9 +241,8 @@ pub fn event_hook<X: XConn + 'static>(event: &XEvent, state: &mut State<X>, _: &
     let s = state.extension::<NamedScratchPadState<X>>()?;
 
     for sp in s.borrow_mut().0.values_mut() {
-        if sp.client == Some(*destroyed) {
+        if sp.remove_client(*destroyed) {
             debug!(%sp.name, %destroyed, "scratchpad client destroyed");
-            sp.client = None;
             break;
         }
     }
@@ -153,23 +268,10 @@ impl<X: XConn + 'static> KeyEventHandler<X> for ToggleNamedScratchPad {
         let mut s = _s.borrow_mut();
         let name = self.name.as_ref();
 
-        let (id, hook) = match s.0.get_mut(&self.name) {
-            // Active client somewhere in the StackSet
-            Some(NamedScratchPad {
-                client: Some(id),
-                hook,
-                ..
-            }) if state.client_set.contains(id) => {
-                debug!(%id, %name, "NamedScratchPad client exists in state");
-                (*id, hook)
-            }
+        let current_tag = state.client_set.current_tag().to_owned();
 
-            // No active client or client is no longer in state
-            Some(nsp) => {
-                debug!(%nsp.prog, %name, ?nsp.client, "spawning NamedScratchPad program");
-                nsp.client = None;
-                return spawn(nsp.prog.as_ref());
-            }
+        let nsp = match s.0.get_mut(&self.name) {
+            Some(nsp) => nsp,
 
             // The user created a ToggleNamedScratchPad but didn't register the scratchpad
             None => {
@@ -178,6 +280,25 @@ impl<X: XConn + 'static> KeyEventHandler<X> for ToggleNamedScratchPad {
             }
         };
 
+        let id = match nsp.client_for(&current_tag) {
+            // Active client somewhere in the StackSet
+            Some(id) if state.client_set.contains(&id) => {
+                debug!(%id, %name, "NamedScratchPad client exists in state");
+                id
+            }
+
+            // No active client or client is no longer in state
+            client => {
+                debug!(%nsp.prog, %name, ?client, "spawning NamedScratchPad program");
+                nsp.set_client_for(&current_tag, None);
+                if nsp.per_workspace {
+                    nsp.pending_tags.push_back(current_tag);
+                }
+                return spawn(nsp.prog.as_ref());
+            }
+        };
+        let hook = &mut nsp.hook;
+
         debug!(
             %id,
             %name,
@@ -206,3 +327,211 @@ impl<X: XConn + 'static> KeyEventHandler<X> for ToggleNamedScratchPad {
         x.refresh(state)
     }
 }
+
+/// Capture the focused client as the client for an existing NamedScratchPad.
+///
+/// The manage hook for the scratchpad is run against the client in order to apply its
+/// placement before it is hidden. Subsequent uses of the matching [ToggleNamedScratchPad] will
+/// show and hide the captured client. If the scratchpad already has a client then it is
+/// swapped out: the previous client is released and moved to the current workspace if it was
+/// hidden.
+pub fn send_focused_to_scratchpad<X>(
+    name: impl Into<Cow<'static, str>>,
+) -> Box<dyn KeyEventHandler<X>>
+where
+    X: XConn + 'static,
+{
+    let name = name.into();
+
+    key_handler(move |state: &mut State<X>, x: &X| {
+        let id = match state.client_set.current_client() {
+            Some(&id) => id,
+            None => return Ok(()),
+        };
+
+        let _s = state.extension::<NamedScratchPadState<X>>()?;
+        let mut s = _s.borrow_mut();
+
+        if !s.0.contains_key(&name) {
+            warn!(%name, "unknown scratchpad: did you remember to call add_named_scratchpads?");
+            return Ok(());
+        }
+
+        for sp in s.0.values_mut() {
+            sp.remove_client(id);
+        }
+
+        let current_tag = state.client_set.current_tag().to_owned();
+        let nsp = s.0.get_mut(&name).expect("scratchpad to be present");
+        if let Some(prev) = nsp.set_client_for(&current_tag, Some(id)) {
+            debug!(%prev, %id, %name, "replacing existing NamedScratchPad client");
+            if state.client_set.tag_for_client(&prev) == Some(NSP_TAG) {
+                state.client_set.move_client_to_current_tag(&prev);
+            }
+        }
+
+        debug!(%id, %name, "sending focused client to NamedScratchPad");
+        if let Err(e) = nsp.hook.call(id, state, x) {
+            error!(%e, %name, %id, "unable to run NSP manage hook for captured client");
+        }
+        state.client_set.move_client_to_tag(&id, NSP_TAG);
+
+        x.refresh(state)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::Config, extensions::hooks::manage::DefaultTiled, pure::geometry::Rect,
+        x::test::TestXConn,
+    };
+
+    fn test_xconn() -> TestXConn {
+        let r = Rect::new(0, 0, 1000, 800);
+
+        [1, 2, 5, 10, 20]
+            .into_iter()
+            .fold(TestXConn::new(vec![r]), |x, id| x.with_client(Xid(id), r))
+    }
+
+    struct IsClient(Xid);
+    impl<X: XConn> Query<X> for IsClient {
+        fn run(&self, id: Xid, _: &X) -> Result<bool> {
+            Ok(id == self.0)
+        }
+    }
+
+    struct IsAnyOf(Vec<Xid>);
+    impl<X: XConn> Query<X> for IsAnyOf {
+        fn run(&self, id: Xid, _: &X) -> Result<bool> {
+            Ok(self.0.contains(&id))
+        }
+    }
+
+    fn stored_client(state: &State<TestXConn>) -> Option<Xid> {
+        let s = state
+            .extension::<NamedScratchPadState<TestXConn>>()
+            .unwrap();
+        let client = s.borrow().0["test"].client;
+
+        client
+    }
+
+    #[test]
+    fn destroyed_scratchpad_clients_are_cleared() {
+        let x = test_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        let (nsp, _) = NamedScratchPad::<TestXConn>::new(
+            "test",
+            "prog",
+            IsClient(Xid(5)),
+            DefaultTiled,
+            false,
+        );
+        let nsps = [(nsp.name.clone(), nsp)].into_iter().collect();
+        state.add_extension(NamedScratchPadState(nsps));
+
+        manage_hook(Xid(5), &mut state, &x).unwrap();
+        assert_eq!(stored_client(&state), Some(Xid(5)));
+
+        event_hook(&XEvent::Destroy(Xid(6)), &mut state, &x).unwrap();
+        assert_eq!(
+            stored_client(&state),
+            Some(Xid(5)),
+            "other client destroyed"
+        );
+
+        event_hook(&XEvent::Destroy(Xid(5)), &mut state, &x).unwrap();
+        assert_eq!(stored_client(&state), None, "scratchpad client destroyed");
+    }
+
+    #[test]
+    fn focused_clients_can_be_sent_to_a_scratchpad() {
+        let x = test_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        let (nsp, mut toggle) = NamedScratchPad::<TestXConn>::new(
+            "test",
+            "prog",
+            IsClient(Xid(5)),
+            DefaultTiled,
+            false,
+        );
+        let nsps = [(nsp.name.clone(), nsp)].into_iter().collect();
+        state.add_extension(NamedScratchPadState(nsps));
+        state.client_set.add_invisible_workspace(NSP_TAG).unwrap();
+        state.client_set.insert(Xid(1));
+        state.client_set.insert(Xid(2));
+
+        send_focused_to_scratchpad("test")
+            .call(&mut state, &x)
+            .unwrap();
+        assert_eq!(stored_client(&state), Some(Xid(2)));
+        assert_eq!(state.client_set.tag_for_client(&Xid(2)), Some(NSP_TAG));
+
+        toggle.call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.tag_for_client(&Xid(2)), Some("1"));
+
+        toggle.call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.tag_for_client(&Xid(2)), Some(NSP_TAG));
+
+        // Capturing another client releases the previous one
+        state.client_set.focus_client(&Xid(1));
+        send_focused_to_scratchpad("test")
+            .call(&mut state, &x)
+            .unwrap();
+        assert_eq!(stored_client(&state), Some(Xid(1)));
+        assert_eq!(state.client_set.tag_for_client(&Xid(1)), Some(NSP_TAG));
+        assert_eq!(state.client_set.tag_for_client(&Xid(2)), Some("1"));
+    }
+
+    #[test]
+    fn per_workspace_scratchpads_track_a_client_for_each_tag() {
+        let x = test_xconn();
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        let (nsp, mut toggle) = NamedScratchPad::<TestXConn>::new_per_workspace(
+            "test",
+            "true",
+            IsAnyOf(vec![Xid(10), Xid(20)]),
+            DefaultTiled,
+            false,
+        );
+        let nsps = [(nsp.name.clone(), nsp)].into_iter().collect();
+        state.add_extension(NamedScratchPadState(nsps));
+        state.client_set.add_invisible_workspace(NSP_TAG).unwrap();
+
+        // Toggling on each tag spawns a new instance that is stored against that tag
+        for (tag, id) in [("1", Xid(10)), ("2", Xid(20))] {
+            state.client_set.focus_tag(tag);
+            toggle.call(&mut state, &x).unwrap();
+            state.client_set.insert(id);
+            manage_hook(id, &mut state, &x).unwrap();
+        }
+
+        let clients = |state: &State<TestXConn>| {
+            let s = state
+                .extension::<NamedScratchPadState<TestXConn>>()
+                .unwrap();
+            let nsp = &s.borrow().0["test"];
+            (nsp.client_for("1"), nsp.client_for("2"))
+        };
+        assert_eq!(clients(&state), (Some(Xid(10)), Some(Xid(20))));
+
+        toggle.call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.tag_for_client(&Xid(20)), Some(NSP_TAG));
+        assert_eq!(state.client_set.tag_for_client(&Xid(10)), Some("1"));
+
+        state.client_set.focus_tag("1");
+        toggle.call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.tag_for_client(&Xid(10)), Some(NSP_TAG));
+
+        state.client_set.focus_tag("2");
+        toggle.call(&mut state, &x).unwrap();
+        assert_eq!(state.client_set.tag_for_client(&Xid(20)), Some("2"));
+        assert_eq!(state.client_set.tag_for_client(&Xid(10)), Some(NSP_TAG));
+
+        event_hook(&XEvent::Destroy(Xid(10)), &mut state, &x).unwrap();
+        assert_eq!(clients(&state), (None, Some(Xid(20))));
+    }
+}
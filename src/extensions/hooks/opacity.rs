@@ -0,0 +1,184 @@
+//! Dim unfocused clients using `_NET_WM_WINDOW_OPACITY`
+//!
+//! The [InactiveOpacity] refresh hook sets a reduced opacity on every client other than the
+//! focused one. Clients that are fullscreen when they lose focus are left fully opaque. Window
+//! opacity is applied by your compositor so this hook has no visible effect unless one is
+//! running.
+use crate::{
+    builtin::actions::fullscreen::net_wm_state,
+    core::{hooks::StateHook, Config, State},
+    x::{atom::Atom, XConn, XConnExt},
+    Result, Xid,
+};
+use std::collections::HashSet;
+
+/// Add an [InactiveOpacity] refresh hook to an existing [Config].
+pub fn add_inactive_opacity<X>(mut config: Config<X>, opacity: f32) -> Config<X>
+where
+    X: XConn + 'static,
+{
+    config.compose_or_set_refresh_hook(InactiveOpacity::new(opacity));
+
+    config
+}
+
+/// Set the opacity of all unfocused clients to a fixed value.
+///
+/// The focused client is tracked between refreshes so that opacity is only written for newly
+/// managed clients and for the clients that have gained or lost focus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InactiveOpacity {
+    opacity: f32,
+    fullscreen_atom: Option<u32>,
+    focused: Option<Xid>,
+    seen: HashSet<Xid>,
+}
+
+impl InactiveOpacity {
+    /// Create a new [InactiveOpacity] hook that sets unfocused clients to the given opacity,
+    /// where `1.0` is fully opaque.
+    pub fn new(opacity: f32) -> Self {
+        Self {
+            opacity: opacity.clamp(0.0, 1.0),
+            fullscreen_atom: None,
+            focused: None,
+            seen: HashSet::new(),
+        }
+    }
+
+    fn is_fullscreen<X: XConn>(&mut self, id: Xid, x: &X) -> Result<bool> {
+        let atom = match self.fullscreen_atom {
+            Some(atom) => atom,
+            None => {
+                let atom = *x.intern_atom(Atom::NetWmStateFullscreen.as_ref())?;
+                self.fullscreen_atom = Some(atom);
+                atom
+            }
+        };
+
+        Ok(net_wm_state(id, x)
+            .map(|s| s.contains(&atom))
+            .unwrap_or(false))
+    }
+}
+
+impl<X: XConn> StateHook<X> for InactiveOpacity {
+    fn call(&mut self, state: &mut State<X>, x: &X) -> Result<()> {
+        let focused = state.client_set.current_client().copied();
+        let prev = std::mem::replace(&mut self.focused, focused);
+        self.seen.retain(|id| state.client_set.contains(id));
+
+        let changed: Vec<Xid> = state
+            .client_set
+            .clients()
+            .copied()
+            .filter(|&id| {
+                let is_new = self.seen.insert(id);
+                let focus_changed = prev != focused && (Some(id) == prev || Some(id) == focused);
+
+                is_new || focus_changed
+            })
+            .collect();
+
+        for id in changed {
+            let opacity = if Some(id) == focused || self.is_fullscreen(id, x)? {
+                1.0
+            } else {
+                self.opacity
+            };
+
+            x.set_opacity(id, opacity)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pure::geometry::Rect,
+        x::{atom::Atom, property::Prop, MockXConn},
+    };
+    use std::cell::RefCell;
+
+    const FULLSCREEN_ATOM: u32 = 10;
+
+    struct OpacityXConn {
+        fullscreen: Option<Xid>,
+        writes: RefCell<Vec<(Xid, u32)>>,
+        interned: RefCell<usize>,
+    }
+
+    impl MockXConn for OpacityXConn {
+        fn mock_screen_details(&self) -> Result<Vec<Rect>> {
+            Ok(vec![Rect::new(0, 0, 1000, 800)])
+        }
+
+        fn mock_get_prop(&self, id: Xid, prop_name: &str) -> Result<Option<Prop>> {
+            if prop_name == Atom::NetWmState.as_ref() && self.fullscreen == Some(id) {
+                Ok(Some(Prop::Cardinal(vec![FULLSCREEN_ATOM])))
+            } else {
+                Ok(None)
+            }
+        }
+
+        fn mock_intern_atom(&self, atom: &str) -> Result<Xid> {
+            *self.interned.borrow_mut() += 1;
+            if atom == Atom::NetWmStateFullscreen.as_ref() {
+                Ok(Xid(FULLSCREEN_ATOM))
+            } else {
+                Ok(Xid(0))
+            }
+        }
+
+        fn mock_set_prop(&self, id: Xid, prop_name: &str, val: Prop) -> Result<()> {
+            assert_eq!(prop_name, Atom::NetWmWindowOpacity.as_ref());
+            match val {
+                Prop::Cardinal(vals) => self.writes.borrow_mut().push((id, vals[0])),
+                _ => panic!("unexpected prop value: {val:?}"),
+            }
+
+            Ok(())
+        }
+    }
+
+    const HALF: u32 = u32::MAX / 2;
+
+    #[test]
+    fn focus_changes_update_client_opacity() {
+        let x = OpacityXConn {
+            fullscreen: Some(Xid(3)),
+            writes: RefCell::new(Vec::new()),
+            interned: RefCell::new(0),
+        };
+        let mut state = State::try_new(Config::default(), &x).expect("valid test state");
+        for id in 1..=3 {
+            state.client_set.insert(Xid(id));
+        }
+        let mut hook = InactiveOpacity::new(0.5);
+
+        state.client_set.focus_client(&Xid(1));
+        hook.call(&mut state, &x).unwrap();
+        let mut writes = x.writes.take();
+        writes.sort();
+        assert_eq!(
+            writes,
+            vec![(Xid(1), u32::MAX), (Xid(2), HALF), (Xid(3), u32::MAX)]
+        );
+
+        state.client_set.focus_client(&Xid(2));
+        hook.call(&mut state, &x).unwrap();
+        let mut writes = x.writes.take();
+        writes.sort();
+        assert_eq!(writes, vec![(Xid(1), HALF), (Xid(2), u32::MAX)]);
+
+        hook.call(&mut state, &x).unwrap();
+        assert!(
+            x.writes.borrow().is_empty(),
+            "unchanged opacity is not rewritten"
+        );
+        assert_eq!(*x.interned.borrow(), 1, "atom is only interned once");
+    }
+}
@@ -1,6 +1,9 @@
 //! Startup hooks for direct adding to your penrose config.
 use crate::{
-    core::{hooks::StateHook, State},
+    core::{
+        hooks::{PostStartupHook, StateHook},
+        State, WindowManager,
+    },
     util::spawn,
     x::XConn,
     Result,
@@ -8,6 +11,9 @@ use crate::{
 use std::borrow::Cow;
 
 /// Spawn a client program on window manager startup
+///
+/// When added as a [PostStartupHook] the program is spawned once the initial layout has been
+/// applied rather than before existing clients are managed.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SpawnOnStartup {
     prog: Cow<'static, str>,
@@ -36,3 +42,12 @@ where
         spawn(self.prog.as_ref())
     }
 }
+
+impl<X> PostStartupHook<X> for SpawnOnStartup
+where
+    X: XConn,
+{
+    fn call(&mut self, _wm: &mut WindowManager<X>) -> Result<()> {
+        spawn(self.prog.as_ref())
+    }
+}
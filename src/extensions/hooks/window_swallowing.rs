@@ -71,7 +71,7 @@ impl WindowSwallowingState {
             .floating
             .clone_from(&self.floating_before_close);
         old_stack.focus = parent;
-        state.client_set.modify_occupied(|_| old_stack);
+        state.client_set.modify(|_| Some(old_stack));
         x.refresh(state)?;
         self.clear_state_for(child);
 
@@ -84,6 +84,7 @@ impl WindowSwallowingState {
 pub struct WindowSwallowing<X: XConn> {
     parent: Box<dyn Query<X>>,
     child: Option<Box<dyn Query<X>>>,
+    parent_pid: fn(u32) -> Option<u32>,
 }
 
 impl<X: XConn> WindowSwallowing<X> {
@@ -96,6 +97,7 @@ impl<X: XConn> WindowSwallowing<X> {
         Box::new(Self {
             parent: Box::new(parent),
             child: None,
+            parent_pid,
         })
     }
 
@@ -121,7 +123,7 @@ impl<X: XConn> WindowSwallowing<X> {
             None => return Ok(true), // No parent currently so run default handling
         };
 
-        if !self.queries_hold(child, parent, x) || !is_child_of(child, parent, x) {
+        if !self.queries_hold(child, parent, x) || !is_child_of(child, parent, self.parent_pid, x) {
             return Ok(true);
         }
 
@@ -175,9 +177,9 @@ fn transfer_floating_state(from: Xid, to: Xid, floating: &mut HashMap<Xid, Relat
     }
 }
 
-fn is_child_of<X: XConn>(id: Xid, parent: Xid, x: &X) -> bool {
+fn is_child_of<X: XConn>(id: Xid, parent: Xid, parent_pid: fn(u32) -> Option<u32>, x: &X) -> bool {
     match (x.window_pid(parent), x.window_pid(id)) {
-        (Some(p_pid), Some(c_pid)) => parent_pid_chain(c_pid).contains(&p_pid),
+        (Some(p_pid), Some(c_pid)) => parent_pid_chain(c_pid, parent_pid).contains(&p_pid),
         _ => false,
     }
 }
@@ -191,7 +193,7 @@ fn parent_pid(pid: u32) -> Option<u32> {
     s_parent_pid.parse().ok()
 }
 
-fn parent_pid_chain(mut pid: u32) -> Vec<u32> {
+fn parent_pid_chain(mut pid: u32, parent_pid: fn(u32) -> Option<u32>) -> Vec<u32> {
     let mut parents = vec![];
 
     while let Some(parent) = parent_pid(pid) {
@@ -201,3 +203,113 @@ fn parent_pid_chain(mut pid: u32) -> Vec<u32> {
 
     parents
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::Config,
+        pure::geometry::Rect,
+        x::{atom::Atom, event::ConfigureEvent, property::Prop, query::ClassName, test::TestXConn},
+    };
+
+    const TERMINAL: Xid = Xid(1);
+    const CHILD: Xid = Xid(2);
+    const UNRELATED: Xid = Xid(3);
+
+    // terminal (10) -> shell (20) -> child (30)
+    fn stub_parent_pid(pid: u32) -> Option<u32> {
+        match pid {
+            30 => Some(20),
+            20 => Some(10),
+            10 | 40 => Some(1),
+            _ => None,
+        }
+    }
+
+    fn test_xconn() -> TestXConn {
+        let r = Rect::new(0, 0, 1000, 800);
+        let pid = |n| Prop::Cardinal(vec![n]);
+
+        TestXConn::new(vec![r])
+            .with_client(TERMINAL, r)
+            .with_client(CHILD, r)
+            .with_client(UNRELATED, r)
+            .with_prop(TERMINAL, "_NET_WM_PID", pid(10))
+            .with_prop(CHILD, "_NET_WM_PID", pid(30))
+            .with_prop(UNRELATED, "_NET_WM_PID", pid(40))
+            .with_prop(
+                TERMINAL,
+                Atom::WmClass.as_ref(),
+                Prop::UTF8String(vec!["st".to_owned(), "st".to_owned()]),
+            )
+    }
+
+    fn test_hook() -> WindowSwallowing<TestXConn> {
+        WindowSwallowing {
+            parent: Box::new(ClassName("st")),
+            child: None,
+            parent_pid: stub_parent_pid,
+        }
+    }
+
+    fn test_state(x: &TestXConn) -> State<TestXConn> {
+        let mut state = x.new_state(Config::default()).expect("valid test state");
+        state.client_set.insert(TERMINAL);
+
+        state
+    }
+
+    #[test]
+    fn child_processes_of_the_parent_are_swallowed() {
+        let x = test_xconn();
+        let mut state = test_state(&x);
+        let mut hook = test_hook();
+
+        let run_default = hook
+            .call(&XEvent::MapRequest(CHILD), &mut state, &x)
+            .unwrap();
+
+        assert!(!run_default);
+        assert_eq!(state.client_set.current_client(), Some(&CHILD));
+        assert!(!state.client_set.contains(&TERMINAL));
+    }
+
+    #[test]
+    fn unrelated_windows_are_not_swallowed() {
+        let x = test_xconn();
+        let mut state = test_state(&x);
+        let mut hook = test_hook();
+
+        let run_default = hook
+            .call(&XEvent::MapRequest(UNRELATED), &mut state, &x)
+            .unwrap();
+
+        assert!(run_default);
+        assert_eq!(state.client_set.current_client(), Some(&TERMINAL));
+    }
+
+    #[test]
+    fn parent_is_restored_when_the_child_is_destroyed() {
+        let x = test_xconn();
+        let mut state = test_state(&x);
+        let mut hook = test_hook();
+
+        hook.call(&XEvent::MapRequest(CHILD), &mut state, &x)
+            .unwrap();
+
+        let configure = XEvent::ConfigureRequest(ConfigureEvent {
+            id: CHILD,
+            r: Rect::new(0, 0, 1000, 800),
+            is_root: false,
+        });
+        hook.call(&configure, &mut state, &x).unwrap();
+
+        // The child is unmanaged by the default event handling before being destroyed
+        state.client_set.remove_client(&CHILD);
+        let run_default = hook.call(&XEvent::Destroy(CHILD), &mut state, &x).unwrap();
+
+        assert!(!run_default);
+        assert_eq!(state.client_set.current_client(), Some(&TERMINAL));
+    }
+}
@@ -3,4 +3,5 @@
 pub mod actions;
 pub mod hooks;
 pub mod layout;
+pub mod status;
 pub mod util;
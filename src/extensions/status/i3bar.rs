@@ -0,0 +1,275 @@
+//! Output the window manager state using the i3bar JSON protocol
+//!
+//! Rather than drawing a status bar directly, the [I3barHook] writes a status line describing
+//! the current workspaces, layout and focused client each time the [StateSummary] changes. The
+//! output follows the [i3bar protocol][1] so it can be used as the status command for i3bar,
+//! swaybar or any other bar that understands it:
+//!
+//!   - A header line of `{"version":1}`
+//!   - The opening `[` of an infinite JSON array
+//!   - One array of [I3barBlock]s per status line, with each line after the first being
+//!     prefixed by a `,`
+//!
+//! Using [add_i3bar_hook] writes the status lines to stdout.
+//!
+//! [1]: https://i3wm.org/docs/i3bar-protocol.html
+use crate::{
+    core::{hooks::StateHook, summary::StateSummary, Config, State},
+    x::XConn,
+    Color, Result,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// The version of the i3bar protocol that is output.
+pub const I3BAR_PROTOCOL_VERSION: u32 = 1;
+
+/// Add an [I3barHook] writing to stdout to an existing [Config].
+pub fn add_i3bar_hook<X>(mut config: Config<X>) -> Config<X>
+where
+    X: XConn + 'static,
+{
+    config.compose_or_set_refresh_hook(I3barHook::new(io::stdout(), I3barColors::default()));
+
+    config
+}
+
+/// A single block within an i3bar status line.
+///
+/// Optional fields that are not set are omitted from the output so that the bar will use
+/// its own defaults.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct I3barBlock {
+    /// The text to display for this block
+    pub full_text: String,
+    /// The text color for this block as a #RRGGBB hex string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// The name of this block, used to identify it in click events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The instance of this block, used to identify it in click events
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Whether or not this block should be highlighted as urgent
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub urgent: bool,
+}
+
+/// The colors used for the workspace blocks of an i3bar status line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct I3barColors {
+    /// The color of the currently focused workspace
+    pub focused: Color,
+    /// The color of workspaces that are visible on an unfocused screen
+    pub visible: Color,
+    /// The color of hidden workspaces that contain clients
+    pub occupied: Color,
+    /// The color of hidden workspaces that do not contain any clients
+    pub empty: Color,
+}
+
+impl Default for I3barColors {
+    fn default() -> Self {
+        Self {
+            focused: 0xebdbb2ff.into(),
+            visible: 0x458588ff.into(),
+            occupied: 0xa89984ff.into(),
+            empty: 0x504945ff.into(),
+        }
+    }
+}
+
+impl I3barColors {
+    /// The blocks making up a status line for the given [StateSummary].
+    ///
+    /// A block is generated for each workspace followed by a block for the active layout and
+    /// then a block for the title of the focused client if there is one.
+    pub fn blocks(&self, summary: &StateSummary) -> Vec<I3barBlock> {
+        let mut blocks: Vec<I3barBlock> = summary
+            .tags
+            .iter()
+            .map(|t| {
+                let color = if t.tag == summary.focused_tag {
+                    self.focused
+                } else if t.visible {
+                    self.visible
+                } else if t.clients > 0 {
+                    self.occupied
+                } else {
+                    self.empty
+                };
+
+                I3barBlock {
+                    full_text: t.tag.clone(),
+                    color: Some(color.as_rgb_hex_string()),
+                    name: Some("workspace".to_owned()),
+                    instance: Some(t.tag.clone()),
+                    urgent: t.urgent,
+                }
+            })
+            .collect();
+
+        blocks.push(I3barBlock {
+            full_text: summary.layout.clone(),
+            name: Some("layout".to_owned()),
+            ..Default::default()
+        });
+
+        if let Some(title) = &summary.focused_title {
+            blocks.push(I3barBlock {
+                full_text: title.clone(),
+                name: Some("title".to_owned()),
+                ..Default::default()
+            });
+        }
+
+        blocks
+    }
+}
+
+/// A refresh hook that writes an i3bar status line each time the window manager state changes.
+///
+/// The protocol header is written on the first refresh along with the opening of the
+/// infinite array that status lines are written into.
+#[derive(Debug)]
+pub struct I3barHook<W: Write> {
+    writer: W,
+    colors: I3barColors,
+    prev: Option<StateSummary>,
+}
+
+impl<W: Write> I3barHook<W> {
+    /// Create a new [I3barHook] writing to the given output.
+    pub fn new(writer: W, colors: I3barColors) -> Self {
+        Self {
+            writer,
+            colors,
+            prev: None,
+        }
+    }
+
+    fn write_status(&mut self, summary: StateSummary) -> Result<()> {
+        if self.prev.as_ref() == Some(&summary) {
+            return Ok(());
+        }
+
+        let line = serde_json::to_string(&self.colors.blocks(&summary))
+            .expect("blocks to be serializable");
+
+        if self.prev.is_none() {
+            writeln!(self.writer, "{{\"version\":{I3BAR_PROTOCOL_VERSION}}}")?;
+            writeln!(self.writer, "[")?;
+            writeln!(self.writer, "{line}")?;
+        } else {
+            writeln!(self.writer, ",{line}")?;
+        }
+        self.writer.flush()?;
+        self.prev = Some(summary);
+
+        Ok(())
+    }
+}
+
+impl<X, W> StateHook<X> for I3barHook<W>
+where
+    X: XConn,
+    W: Write,
+{
+    fn call(&mut self, state: &mut State<X>, _: &X) -> Result<()> {
+        self.write_status(state.summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::summary::TagSummary;
+
+    fn summary(focused_tag: &str, focused_title: Option<&str>) -> StateSummary {
+        let tag = |tag: &str, clients, visible, urgent| TagSummary {
+            tag: tag.to_owned(),
+            clients,
+            visible,
+            urgent,
+        };
+
+        StateSummary {
+            focused_tag: focused_tag.to_owned(),
+            layout: "[side]".to_owned(),
+            focused_title: focused_title.map(|s| s.to_owned()),
+            tags: vec![
+                tag("1", 2, true, false),
+                tag("2", 1, false, true),
+                tag("3", 0, false, false),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn status_lines_are_valid_protocol_frames() {
+        let mut hook = I3barHook::new(Vec::new(), I3barColors::default());
+
+        hook.write_status(summary("1", Some("htop"))).unwrap();
+        hook.write_status(summary("1", Some("htop"))).unwrap();
+        hook.write_status(summary("1", None)).unwrap();
+
+        let output = String::from_utf8(hook.writer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 4, "unchanged states are not written: {output}");
+        assert_eq!(lines[0], "{\"version\":1}");
+        assert_eq!(lines[1], "[");
+        assert!(lines[3].starts_with(','), "{}", lines[3]);
+
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["version"], I3BAR_PROTOCOL_VERSION);
+
+        // Closing the infinite array should give valid JSON containing each status line
+        let frames: Vec<Vec<I3barBlock>> =
+            serde_json::from_str(&format!("{}]", lines[1..].concat())).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        let text: Vec<&str> = frames[0].iter().map(|b| b.full_text.as_str()).collect();
+        assert_eq!(text, vec!["1", "2", "3", "[side]", "htop"]);
+        assert_eq!(
+            frames[1].len(),
+            4,
+            "no title block without a focused client"
+        );
+    }
+
+    #[test]
+    fn workspace_blocks_reflect_tag_state() {
+        let colors = I3barColors::default();
+        let blocks = colors.blocks(&summary("1", None));
+
+        let workspaces: Vec<(Option<String>, bool)> = blocks[..3]
+            .iter()
+            .map(|b| (b.color.clone(), b.urgent))
+            .collect();
+
+        assert_eq!(
+            workspaces,
+            vec![
+                (Some(colors.focused.as_rgb_hex_string()), false),
+                (Some(colors.occupied.as_rgb_hex_string()), true),
+                (Some(colors.empty.as_rgb_hex_string()), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn unset_fields_are_omitted() {
+        let block = I3barBlock {
+            full_text: "tiled".to_owned(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_string(&block).unwrap(),
+            "{\"full_text\":\"tiled\"}"
+        );
+    }
+}
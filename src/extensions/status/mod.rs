@@ -0,0 +1,6 @@
+//! Status output for use with external status bars
+#[cfg(feature = "serde")]
+pub mod i3bar;
+
+#[cfg(feature = "serde")]
+pub use i3bar::{add_i3bar_hook, I3barBlock, I3barColors, I3barHook};
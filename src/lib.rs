@@ -91,6 +91,13 @@ pub enum Error {
     #[error("Client {0} is not currently visible")]
     ClientIsNotVisible(Xid),
 
+    /// Multiple key bindings resolve to the same key presses
+    #[error("Key bindings conflict with one another: {}", .bindings.join(", "))]
+    ConflictingKeyBindings {
+        /// The conflicting key bindings as they were originally specified
+        bindings: Vec<String>,
+    },
+
     /// A custom error message from user code or extensions
     #[error("{0}")]
     Custom(String),
@@ -111,6 +118,13 @@ pub enum Error {
         format: u8,
     },
 
+    /// Attempt to create a `Color` from an invalid `rgb(...)` or `rgba(...)` string
+    #[error("Invalid color: '{color}'")]
+    InvalidColor {
+        /// The string that was used
+        color: String,
+    },
+
     /// Attempt to create a `Color` from an invalid hex string
     #[error("Invalid Hex color code: '{hex_code}'")]
     InvalidHexColor {
@@ -118,6 +132,14 @@ pub enum Error {
         hex_code: String,
     },
 
+    /// A [ClientSet][crate::core::ClientSet] being restored does not match the current state
+    /// of the X server
+    #[error("Unable to restore window manager state: {reason}")]
+    InvalidHydrationState {
+        /// Why the state could not be restored
+        reason: String,
+    },
+
     /// A window hints message was received but unable to be parsed
     #[error("Invalid window hints message: {reason}")]
     InvalidHints {
@@ -125,6 +147,15 @@ pub enum Error {
         reason: String,
     },
 
+    /// A command received over the IPC control socket was invalid
+    #[error("Invalid IPC command '{command}': {reason}")]
+    InvalidIpcCommand {
+        /// The command that was received
+        command: String,
+        /// Why parsing failed
+        reason: String,
+    },
+
     /// IO error
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -151,6 +182,17 @@ pub enum Error {
         tags: Vec<String>,
     },
 
+    /// An external command exited with a non-zero exit status
+    #[error("'{cmd}' exited with {status}: {stderr}")]
+    NonZeroExitStatus {
+        /// The command that was run
+        cmd: String,
+        /// The exit status of the command
+        status: std::process::ExitStatus,
+        /// Anything written to stderr by the command
+        stderr: String,
+    },
+
     /// Penrose is running without any screens to connect to
     #[error("There are no screens available")]
     NoScreens,
@@ -163,6 +205,14 @@ pub enum Error {
     #[error("Error initialising randr: {0}")]
     Randr(String),
 
+    /// A workspace tag was provided that is reserved for internal use by penrose or that
+    /// belongs to an invisible workspace
+    #[error("'{tag}' is a reserved workspace tag")]
+    ReservedTag {
+        /// The reserved tag
+        tag: String,
+    },
+
     /// An operation was requested on a client window that is unknown
     #[error("Client {0} is not in found")]
     UnknownClient(Xid),
@@ -174,6 +224,14 @@ pub enum Error {
         name: String,
     },
 
+    /// A [KeyMode][crate::core::bindings::KeyMode] was requested that has not been added
+    /// to the window manager
+    #[error("'{name}' is not a known key mode")]
+    UnknownKeyMode {
+        /// The requested mode name
+        name: String,
+    },
+
     /// An unknown character has been used to specify a modifier key
     #[error("'{name}' is not a known modifier key")]
     UnknownModifier {
@@ -195,6 +253,14 @@ pub enum Error {
         type_id: TypeId,
     },
 
+    /// An [XConn][crate::x::XConn] method was called that is not supported by the connection
+    /// being used
+    #[error("'{method}' is not supported by this XConn")]
+    UnsupportedXConnMethod {
+        /// The name of the unsupported method
+        method: &'static str,
+    },
+
     // TODO: These backend specific errors should be abstracted out to a
     //       set of common error variants that they can be mapped to without
     //       needing to extend the enum conditionally when flags are enabled
@@ -280,6 +346,94 @@ impl Color {
     pub fn argb_u32(&self) -> u32 {
         ((self.rgba_hex & 0x000000FF) << 24) + (self.rgba_hex >> 8)
     }
+
+    /// A lighter version of this color with `amount` (0.0-1.0) added to each of R, G and B.
+    ///
+    /// Channels are clamped to the valid range and alpha is preserved.
+    pub fn lighten(&self, amount: f64) -> Self {
+        let (r, g, b, a) = self.rgba();
+
+        Self::from_clamped_floats(r + amount, g + amount, b + amount, a)
+    }
+
+    /// A darker version of this color with `amount` (0.0-1.0) subtracted from each of R, G
+    /// and B.
+    ///
+    /// Channels are clamped to the valid range and alpha is preserved.
+    pub fn darken(&self, amount: f64) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Linearly interpolate between this color and `other`, where `t = 0.0` is this color and
+    /// `t = 1.0` is `other`.
+    ///
+    /// `t` is clamped to the range 0.0-1.0 and the alpha of this color is preserved.
+    pub fn blend(&self, other: Color, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let (r1, g1, b1, a) = self.rgba();
+        let (r2, g2, b2) = other.rgb();
+        let lerp = |x: f64, y: f64| x + (y - x) * t;
+
+        Self::from_clamped_floats(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2), a)
+    }
+
+    /// Create a new fully opaque Color from HSL values.
+    ///
+    /// Hue is given in degrees (0.0-360.0) and saturation and lightness are given as
+    /// 0.0-1.0 range floats. Hues outside of the valid range wrap around and saturation and
+    /// lightness are clamped.
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let m = l - c / 2.0;
+
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        Self::from_clamped_floats(r + m, g + m, b + m, 1.0)
+    }
+
+    /// The HSL information of this color: hue in degrees (0.0-360.0) along with saturation
+    /// and lightness as 0.0-1.0 range floats.
+    pub fn hsl(&self) -> (f64, f64, f64) {
+        let (r, g, b) = self.rgb();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let l = (max + min) / 2.0;
+
+        if delta == 0.0 {
+            return (0.0, 0.0, l);
+        }
+
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+
+        (h * 60.0, s, l)
+    }
+
+    // Round rather than truncate so that converting to and from floats is lossless
+    fn from_clamped_floats(r: f64, g: f64, b: f64, a: f64) -> Self {
+        let u = |f: f64| (f.clamp(0.0, 1.0) * 255.0).round() as u32;
+
+        Self::new_from_hex((u(r) << 24) + (u(g) << 16) + (u(b) << 8) + u(a))
+    }
 }
 
 impl From<u32> for Color {
@@ -316,10 +470,17 @@ impl TryFrom<String> for Color {
     }
 }
 
+/// Colors can be parsed from hex codes (`#RRGGBB` or `#RRGGBBAA`) or CSS style `rgb(r, g, b)`
+/// and `rgba(r, g, b, a)` strings where r, g and b are 0-255 integers and a is a 0.0-1.0 float.
 impl TryFrom<&str> for Color {
     type Error = Error;
 
     fn try_from(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.starts_with("rgb") {
+            return parse_css_color(s).ok_or_else(|| Error::InvalidColor { color: s.into() });
+        }
+
         let hex = u32::from_str_radix(s.strip_prefix('#').unwrap_or(s), 16)?;
 
         if s.len() == 7 {
@@ -332,6 +493,36 @@ impl TryFrom<&str> for Color {
     }
 }
 
+// Parse an rgb(r, g, b) or rgba(r, g, b, a) string
+fn parse_css_color(s: &str) -> Option<Color> {
+    let (args, has_alpha) = match s.strip_prefix("rgba") {
+        Some(args) => (args, true),
+        None => (s.strip_prefix("rgb")?, false),
+    };
+
+    let args = args.trim().strip_prefix('(')?.strip_suffix(')')?;
+    let parts: Vec<&str> = args.split(',').map(|p| p.trim()).collect();
+    let channel = |p: &str| p.parse::<u8>().ok().map(u32::from);
+
+    let (rgb, alpha) = match (has_alpha, parts.as_slice()) {
+        (false, [r, g, b]) => ([r, g, b], 0xFF),
+        (true, [r, g, b, a]) => {
+            let a: f64 = a.parse().ok()?;
+            if !(0.0..=1.0).contains(&a) {
+                return None;
+            }
+            ([r, g, b], (a * 255.0).round() as u32)
+        }
+        _ => return None,
+    };
+
+    let [r, g, b] = rgb;
+
+    Some(Color::new_from_hex(
+        (channel(r)? << 24) + (channel(g)? << 16) + (channel(b)? << 8) + alpha,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,4 +537,117 @@ mod tests {
 
         assert_eq!(&c.as_rgb_hex_string(), expected);
     }
+
+    #[test_case("rgb(255,0,0)", 0xFF0000FF; "rgb")]
+    #[test_case("rgba(0,0,0,0.5)", 0x00000080; "rgba")]
+    #[test_case("  rgb( 69, 133 ,136 ) ", 0x458588FF; "rgb with whitespace")]
+    #[test_case("rgba (255, 255, 255, 1)", 0xFFFFFFFF; "rgba with whitespace")]
+    #[test_case("#458588", 0x458588FF; "hex")]
+    #[test_case(" #45858880 ", 0x45858880; "hex with alpha and whitespace")]
+    #[test]
+    fn valid_color_strings_parse(s: &str, expected: u32) {
+        let c = Color::try_from(s).unwrap();
+
+        assert_eq!(c, Color::from(expected));
+    }
+
+    #[test_case("rgb(256,0,0)"; "channel out of range")]
+    #[test_case("rgb(-1,0,0)"; "negative channel")]
+    #[test_case("rgb(0,0)"; "too few channels")]
+    #[test_case("rgb(0,0,0,0.5)"; "alpha for rgb")]
+    #[test_case("rgba(0,0,0)"; "missing alpha")]
+    #[test_case("rgba(0,0,0,1.5)"; "alpha out of range")]
+    #[test_case("rgb(0,0,0"; "unclosed parens")]
+    #[test_case("rgb 0,0,0"; "missing parens")]
+    #[test_case("rgb(a,b,c)"; "not a number")]
+    #[test]
+    fn invalid_css_color_strings_error(s: &str) {
+        let res = Color::try_from(s);
+
+        assert!(
+            matches!(res, Err(Error::InvalidColor { .. })),
+            "expected an error, got {res:?}"
+        );
+    }
+
+    #[test_case("#12345"; "too short")]
+    #[test_case("#1234567890"; "too long")]
+    #[test_case("#GGHHII"; "not hex")]
+    #[test]
+    fn invalid_hex_color_strings_error(s: &str) {
+        assert!(Color::try_from(s).is_err());
+    }
+
+    const EPSILON: f64 = 0.01;
+
+    fn assert_hsl_eq((h1, s1, l1): (f64, f64, f64), (h2, s2, l2): (f64, f64, f64)) {
+        let close = |a: f64, b: f64, max: f64| (a - b).abs() < EPSILON * max;
+
+        assert!(
+            close(h1, h2, 360.0) && close(s1, s2, 1.0) && close(l1, l2, 1.0),
+            "({h1}, {s1}, {l1}) != ({h2}, {s2}, {l2})"
+        );
+    }
+
+    #[test_case(0xFF0000FF, (0.0, 1.0, 0.5); "red")]
+    #[test_case(0x00FF00FF, (120.0, 1.0, 0.5); "green")]
+    #[test_case(0x0000FFFF, (240.0, 1.0, 0.5); "blue")]
+    #[test_case(0xFFFFFFFF, (0.0, 0.0, 1.0); "white")]
+    #[test_case(0x000000FF, (0.0, 0.0, 0.0); "black")]
+    #[test_case(0x808080FF, (0.0, 0.0, 0.502); "mid grey")]
+    #[test_case(0x458588FF, (183.0, 0.33, 0.402); "gruvbox blue")]
+    #[test_case(0xD79921FF, (39.6, 0.734, 0.486); "gruvbox yellow")]
+    #[test]
+    fn hsl_round_trips(rgba_hex: u32, hsl: (f64, f64, f64)) {
+        let c = Color::from(rgba_hex);
+
+        let (h, s, l) = c.hsl();
+
+        assert_hsl_eq((h, s, l), hsl);
+        assert_eq!(Color::from_hsl(h, s, l), c);
+    }
+
+    #[test]
+    fn from_hsl_wraps_hue() {
+        assert_eq!(Color::from_hsl(360.0, 1.0, 0.5), Color::from(0xFF0000FF));
+        assert_eq!(Color::from_hsl(-120.0, 1.0, 0.5), Color::from(0x0000FFFF));
+    }
+
+    #[test_case(0x000000FF, 0xFFFFFFFF, 0.5, 0x808080FF; "black and white gives mid grey")]
+    #[test_case(0x000000FF, 0xFFFFFFFF, 0.0, 0x000000FF; "t of 0 is self")]
+    #[test_case(0x000000FF, 0xFFFFFFFF, 1.0, 0xFFFFFFFF; "t of 1 is other")]
+    #[test_case(0xFF000080, 0x0000FFFF, 0.5, 0x80008080; "alpha is preserved")]
+    #[test_case(0x000000FF, 0xFFFFFFFF, 2.0, 0xFFFFFFFF; "t is clamped")]
+    #[test]
+    fn blend_is_correct(c1: u32, c2: u32, t: f64, expected: u32) {
+        let blended = Color::from(c1).blend(c2.into(), t);
+
+        assert_eq!(blended, Color::from(expected));
+    }
+
+    #[test_case(0x000000FF, 0.2, 0x333333FF; "black")]
+    #[test_case(0xF0F0F0FF, 0.2, 0xFFFFFFFF; "clamped")]
+    #[test_case(0x10203080, 0.2, 0x43536380; "alpha is preserved")]
+    #[test]
+    fn lighten_is_correct(c: u32, amount: f64, expected: u32) {
+        assert_eq!(Color::from(c).lighten(amount), Color::from(expected));
+    }
+
+    #[test_case(0xFFFFFFFF, 0.2, 0xCCCCCCFF; "white")]
+    #[test_case(0x101010FF, 0.2, 0x000000FF; "clamped")]
+    #[test]
+    fn darken_is_correct(c: u32, amount: f64, expected: u32) {
+        assert_eq!(Color::from(c).darken(amount), Color::from(expected));
+    }
+
+    #[test_case(0x458588FF; "blue")]
+    #[test_case(0x504945FF; "grey")]
+    #[test_case(0xCC241D80; "translucent red")]
+    #[test]
+    fn lighten_and_darken_are_inverse_for_small_amounts(c: u32) {
+        let c = Color::from(c);
+
+        assert_eq!(c.lighten(0.05).darken(0.05), c);
+        assert_eq!(c.darken(0.05).lighten(0.05), c);
+    }
 }
@@ -349,6 +349,22 @@ impl Rect {
         })
     }
 
+    /// Move and resize this Rect so that it fits inside of `enclosing`.
+    ///
+    /// The Rect is shrunk if it is larger than `enclosing` and then moved the minimum amount
+    /// required for it to be fully contained.
+    pub fn clamped_to(&self, enclosing: &Rect) -> Self {
+        let w = self.w.min(enclosing.w);
+        let h = self.h.min(enclosing.h);
+
+        Self {
+            x: self.x.clamp(enclosing.x, enclosing.x + enclosing.w - w),
+            y: self.y.clamp(enclosing.y, enclosing.y + enclosing.h - h),
+            w,
+            h,
+        }
+    }
+
     /// Split this `Rect` into evenly sized rows.
     pub fn as_rows(&self, n_rows: u32) -> Vec<Rect> {
         if n_rows <= 1 {
@@ -486,6 +502,20 @@ impl Rect {
     }
 }
 
+/// A cardinal direction on the screen.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Towards the left edge of the screen
+    Left,
+    /// Towards the right edge of the screen
+    Right,
+    /// Towards the top of the screen
+    Up,
+    /// Towards the bottom of the screen
+    Down,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -629,6 +659,17 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    #[test_case(r(5, 5, 10, 10), r(5, 5, 10, 10); "contained")]
+    #[test_case(r(15, 15, 10, 10), r(10, 10, 10, 10); "overlapping")]
+    #[test_case(r(0, 50, 10, 10), r(0, 10, 10, 10); "outside")]
+    #[test_case(r(5, 5, 100, 10), r(0, 5, 20, 10); "too large")]
+    #[test]
+    fn clamped_to(inner: Rect, expected: Rect) {
+        let outer = Rect::new(0, 0, 20, 20);
+
+        assert_eq!(inner.clamped_to(&outer), expected);
+    }
+
     #[test_case(r(0, 0, 100, 100), 1; "simple single")]
     #[test_case(r(0, 0, 100, 100), 4; "simple even")]
     #[test_case(r(0, 0, 100, 100), 7; "simple odd")]
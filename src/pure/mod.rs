@@ -14,7 +14,7 @@ pub use screen::Screen;
 #[doc(inline)]
 pub use stack::{Position, Stack};
 #[doc(inline)]
-pub use stack_set::StackSet;
+pub use stack_set::{StackSet, MINIMIZED_TAG};
 #[doc(inline)]
 pub use workspace::Workspace;
 
@@ -7,14 +7,20 @@ use crate::{
 };
 use std::{collections::HashMap, fmt};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A wrapper around a single [Workspace] that includes the physical screen
 /// size as a [Rect].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Default, Debug, Clone)]
 pub struct Screen<C> {
     pub(crate) index: usize,
     /// The [Workspace] current visible on this screen
     pub workspace: Workspace<C>,
     pub(crate) r: Rect,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) output: Option<String>,
 }
 
 impl<C: fmt::Display> fmt::Display for Screen<C> {
@@ -40,6 +46,11 @@ impl<C> Screen<C> {
     pub fn geometry(&self) -> Rect {
         self.r
     }
+
+    /// The name of the physical output (e.g. `HDMI-1`) backing this [Screen] if known.
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
 }
 
 impl Screen<Xid> {
@@ -104,10 +104,7 @@ impl<T> Stack<T> {
     {
         let mut it = iter.into_iter();
 
-        let focus = match it.next() {
-            Some(t) => t,
-            None => return None,
-        };
+        let focus = it.next()?;
 
         Some(Self {
             up: VecDeque::default(),
@@ -313,6 +310,8 @@ impl<T> Stack<T> {
     }
 
     /// Map a function over all elements in this [Stack], returning a new one.
+    ///
+    /// The position of each element and the element that is focused are preserved.
     pub fn map<F, U>(self, f: F) -> Stack<U>
     where
         F: Fn(T) -> U,
@@ -471,6 +470,37 @@ impl<T> Stack<T> {
         }
     }
 
+    /// Swap the positions of the focused element and the given element in the stack order.
+    /// The currently focused element is maintained by this operation.
+    ///
+    /// If the given element is not present in the stack then it is left unchanged.
+    ///
+    /// ```
+    /// # use penrose::stack;
+    ///
+    /// let mut s = stack!([1, 2], 3, [4, 5]);
+    /// s.swap_focus_with(&1);
+    /// assert_eq!(s, stack!(3, [2, 1, 4, 5]));
+    /// ```
+    pub fn swap_focus_with(&mut self, t: &T) -> &mut Self
+    where
+        T: PartialEq,
+    {
+        if let Some(ix) = self.down.iter().position(|elem| elem == t) {
+            swap(&mut self.focus, &mut self.down[ix]);
+            for _ in 0..=ix {
+                self.focus_down();
+            }
+        } else if let Some(ix) = self.up.iter().position(|elem| elem == t) {
+            swap(&mut self.focus, &mut self.up[ix]);
+            for _ in 0..=ix {
+                self.focus_up();
+            }
+        }
+
+        self
+    }
+
     /// Rotate all elements of the stack forward, wrapping from top to bottom.
     /// The currently focused element in the stack is maintained by this operation.
     pub fn rotate_up(&mut self) -> &mut Self {
@@ -686,6 +716,19 @@ mod tests {
         assert_eq!(s, expected);
     }
 
+    #[test_case(stack!([1, 2], 3, [4, 5]), 1, stack!(3, [2, 1, 4, 5]); "head")]
+    #[test_case(stack!([1, 2], 3, [4, 5]), 2, stack!([1], 3, [2, 4, 5]); "adjacent up")]
+    #[test_case(stack!([1, 2], 3, [4, 5]), 4, stack!([1, 2, 4], 3, [5]); "adjacent down")]
+    #[test_case(stack!([1, 2], 3, [4, 5]), 5, stack!([1, 2, 5, 4], 3); "last")]
+    #[test_case(stack!([1, 2], 3, [4, 5]), 3, stack!([1, 2], 3, [4, 5]); "focus")]
+    #[test_case(stack!([1, 2], 3, [4, 5]), 6, stack!([1, 2], 3, [4, 5]); "missing")]
+    #[test]
+    fn swap_focus_with(mut s: Stack<u8>, t: u8, expected: Stack<u8>) {
+        s.swap_focus_with(&t);
+
+        assert_eq!(s, expected);
+    }
+
     #[test_case(stack!([1, 2], 3, [4, 5]), stack!(3, [4, 5, 1, 2]); "items up and down")]
     #[test_case(stack!([1, 2], 3), stack!(3, [1, 2]); "items up")]
     #[test_case(stack!(3, [4, 5]), stack!(3, [4, 5]); "items down")]
@@ -10,14 +10,27 @@ use crate::{
     stack, Error, Result, Xid,
 };
 use std::{
-    cmp::Ordering,
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
     mem::{swap, take},
 };
 use tracing::debug;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The tag of the invisible [Workspace] used to hold minimized clients.
+///
+/// This tag is reserved for use by penrose: attempting to create or rename a workspace to
+/// use this tag will return an error.
+pub const MINIMIZED_TAG: &str = "__penrose_minimized__";
+
 /// The side-effect free internal state representation of the window manager.
+///
+/// When the `serde` feature is enabled a StackSet can be serialized in order to persist the
+/// current state across restarts. [LayoutStack]s are not serialized: see
+/// [WindowManager::hydrate][crate::core::WindowManager::hydrate] for details on restoring state.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Default, Debug, Clone)]
 pub struct StackSet<C>
 where
@@ -26,8 +39,17 @@ where
     pub(crate) screens: Stack<Screen<C>>, // Workspaces visible on screens
     pub(crate) hidden: VecDeque<Workspace<C>>, // Workspaces not currently on any screen
     pub(crate) floating: HashMap<C, RelativeRect>, // Floating windows
-    pub(crate) previous_tag: String,      // The last tag to be focused before the current one
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) previous_floating: HashMap<C, RelativeRect>, // Last position of sunk floating windows
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) previous_outputs: HashMap<String, String>, // Last workspace shown on disconnected outputs
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) sticky: HashSet<C>, // Clients that remain visible when changing workspace
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) minimized: HashMap<String, Vec<C>>, // Minimized clients by their original tag
+    pub(crate) previous_tag: String, // The last tag to be focused before the current one
     pub(crate) invisible_tags: Vec<String>, // Tags that should never be focused
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) killed_clients: Vec<C>, // clients that have been removed and need processing on the X side
 }
 
@@ -83,6 +105,7 @@ where
                     workspace,
                     index,
                     r,
+                    output: None,
                 },
             ));
 
@@ -92,6 +115,10 @@ where
             screens,
             hidden,
             floating,
+            previous_floating: HashMap::new(),
+            previous_outputs: HashMap::new(),
+            sticky: HashSet::new(),
+            minimized: HashMap::new(),
             previous_tag,
             invisible_tags: vec![],
             killed_clients: vec![],
@@ -212,6 +239,14 @@ where
 
     /// Toggle focus back to the previously focused [Workspace] based on its tag
     pub fn toggle_tag(&mut self) {
+        self.focus_previous_tag();
+    }
+
+    /// Focus the [Workspace] that was focused immediately before the current one.
+    ///
+    /// Calling this repeatedly will switch back and forth between the two most recently
+    /// focused workspaces. If no other workspace has been focused yet then this is a no-op.
+    pub fn focus_previous_tag(&mut self) {
         self.focus_tag(self.previous_tag.clone());
     }
 
@@ -266,9 +301,45 @@ where
 
     /// Clear the floating status of a client, returning its previous preferred
     /// screen position if the client was known, otherwise `None`.
+    ///
+    /// The floating position of the client is remembered so that it can be restored
+    /// if the client is floated again using `toggle_floating_state` or
+    /// `toggle_floating_focused`.
     pub fn sink(&mut self, client: &C) -> Option<Rect> {
-        self.floating
-            .remove(client)
+        let rr = self.floating.remove(client)?;
+        self.previous_floating.insert(client.clone(), rr);
+
+        Some(rr.applied_to(&self.screens.focus.r))
+    }
+
+    /// Sink all floating clients, remembering their floating positions.
+    pub fn sink_all(&mut self) {
+        self.previous_floating.extend(self.floating.drain());
+    }
+
+    /// Sink all floating clients on the current [Workspace], remembering their floating
+    /// positions. Floating clients on other workspaces are left unchanged.
+    ///
+    /// Sunk clients keep their existing positions within the [Stack] of the workspace.
+    pub fn sink_current_workspace(&mut self) {
+        let clients: Vec<C> = self
+            .screens
+            .focus
+            .workspace
+            .clients()
+            .filter(|c| self.floating.contains_key(c))
+            .cloned()
+            .collect();
+
+        for c in clients {
+            self.sink(&c);
+        }
+    }
+
+    /// The floating position a client had when it was last sunk, if there is one.
+    pub fn previous_floating_position(&self, client: &C) -> Option<Rect> {
+        self.previous_floating
+            .get(client)
             .map(|rr| rr.applied_to(&self.screens.focus.r))
     }
 
@@ -286,9 +357,136 @@ where
             .unwrap_or(false)
     }
 
+    /// Move focus to the next floating client on the current [Workspace], wrapping to the top
+    /// of the [Stack] if there are no floating clients below the focused client.
+    ///
+    /// This is a no-op if there are no floating clients on the current workspace.
+    pub fn focus_next_floating(&mut self) {
+        self.focus_next_in_layer(true)
+    }
+
+    /// Move focus to the next tiled client on the current [Workspace], wrapping to the top
+    /// of the [Stack] if there are no tiled clients below the focused client.
+    ///
+    /// This is a no-op if there are no tiled clients on the current workspace.
+    pub fn focus_next_tiled(&mut self) {
+        self.focus_next_in_layer(false)
+    }
+
+    fn focus_next_in_layer(&mut self, floating: bool) {
+        let in_layer = |c: &C| self.floating.contains_key(c) == floating;
+
+        if let Some(ref mut stack) = self.screens.focus.workspace.stack {
+            if stack.iter().any(in_layer) {
+                stack.focus_down();
+                stack.focus_element_by(in_layer);
+            }
+        }
+    }
+
+    /// Mark a client as being sticky (or clear its sticky status).
+    ///
+    /// Sticky clients remain visible when the [Workspace] on their screen changes by being
+    /// moved to the current workspace. Unknown clients are ignored.
+    pub fn set_sticky(&mut self, client: &C, sticky: bool) {
+        if !sticky {
+            self.sticky.remove(client);
+        } else if self.contains(client) {
+            self.sticky.insert(client.clone());
+        }
+    }
+
+    /// Check whether a given client is currently sticky.
+    pub fn is_sticky(&self, client: &C) -> bool {
+        self.sticky.contains(client)
+    }
+
+    /// Move any sticky clients that are no longer on a visible [Workspace] to the end of the
+    /// current workspace, leaving the focused client unchanged.
+    pub fn pull_sticky_clients(&mut self) {
+        let hidden: Vec<C> = self
+            .sticky
+            .iter()
+            .filter(|c| !self.is_minimized(c))
+            .filter(|c| !self.screens.iter().any(|s| s.workspace.contains(c)))
+            .cloned()
+            .collect();
+
+        for c in hidden {
+            let removed = self.hidden.iter_mut().find_map(|w| w.remove(&c));
+            if let Some(c) = removed {
+                self.insert_at(Position::Tail, c);
+            }
+        }
+    }
+
+    /// Minimize a client, hiding it without closing it until it is restored using
+    /// [StackSet::restore_last_minimized].
+    ///
+    /// Minimized clients are moved to an invisible [Workspace] (created the first time that a
+    /// client is minimized) so they are not laid out, and are remembered against the tag of the
+    /// workspace they were minimized from. If the client is unknown or already minimized then
+    /// the [StackSet] is unmodified.
+    pub fn minimize(&mut self, client: &C) {
+        let tag = match self.tag_for_client(client) {
+            Some(tag) if tag != MINIMIZED_TAG => tag.to_owned(),
+            _ => return,
+        };
+
+        if !self.contains_tag(MINIMIZED_TAG) {
+            self.push_workspace(MINIMIZED_TAG.to_owned(), LayoutStack::default());
+            self.invisible_tags.push(MINIMIZED_TAG.to_owned());
+        }
+
+        self.move_client_to_tag(client, MINIMIZED_TAG);
+        self.minimized.entry(tag).or_default().push(client.clone());
+    }
+
+    /// Minimize the currently focused client if there is one.
+    pub fn minimize_focused(&mut self) {
+        if let Some(client) = self.current_client().cloned() {
+            self.minimize(&client);
+        }
+    }
+
+    /// Restore the most recently minimized client from the current [Workspace], returning it to
+    /// the workspace and giving it focus.
+    ///
+    /// The restored client is returned as `Some(C)` if there was one.
+    pub fn restore_last_minimized(&mut self) -> Option<C> {
+        let tag = self.current_tag().to_owned();
+        let clients = self.minimized.get_mut(&tag)?;
+        let client = clients.pop()?;
+        if clients.is_empty() {
+            self.minimized.remove(&tag);
+        }
+
+        self.move_client_to_tag(&client, &tag);
+
+        Some(client)
+    }
+
+    /// Check whether a given client is currently minimized.
+    pub fn is_minimized(&self, client: &C) -> bool {
+        self.minimized.values().any(|cs| cs.contains(client))
+    }
+
+    /// The clients that were minimized from the [Workspace] with the given tag, in the order that
+    /// they were minimized.
+    pub fn minimized_clients(&self, tag: &str) -> impl Iterator<Item = &C> {
+        self.minimized.get(tag).into_iter().flatten()
+    }
+
     /// Delete a client from this [StackSet].
     pub fn remove_client(&mut self, client: &C) -> Option<C> {
-        self.sink(client); // Clear any floating information we might have
+        // Clear any floating information we might have
+        self.floating.remove(client);
+        self.previous_floating.remove(client);
+        self.sticky.remove(client);
+        self.minimized.retain(|_, cs| {
+            cs.retain(|c| c != client);
+            !cs.is_empty()
+        });
 
         self.workspaces_mut()
             .map(|w| w.remove(client))
@@ -532,16 +730,26 @@ where
     /// The id assigned to this workspace will be max(workspace ids) + 1.
     ///
     /// # Errors
-    /// This function will error with `NonUniqueTags` if the given tag is already present.
+    /// This function will error with `NonUniqueTags` if the given tag is already present and
+    /// `ReservedTag` if the given tag is [MINIMIZED_TAG].
     pub fn add_workspace<T>(&mut self, tag: T, layouts: LayoutStack) -> Result<()>
     where
         T: Into<String>,
     {
         let tag = tag.into();
+        if tag == MINIMIZED_TAG {
+            return Err(Error::ReservedTag { tag });
+        }
         if self.contains_tag(&tag) {
             return Err(Error::NonUniqueTags { tags: vec![tag] });
         }
 
+        self.push_workspace(tag, layouts);
+
+        Ok(())
+    }
+
+    fn push_workspace(&mut self, tag: String, layouts: LayoutStack) {
         let id = self
             .workspaces()
             .map(|w| w.id)
@@ -550,8 +758,6 @@ where
             + 1;
         let ws = Workspace::new(id, tag, layouts, None);
         self.hidden.push_front(ws);
-
-        Ok(())
     }
 
     /// Add a new invisible [Workspace] to this [StackSet].
@@ -561,7 +767,8 @@ where
     /// The id assigned to this workspace will be max(workspace ids) + 1.
     ///
     /// # Errors
-    /// This function will error with `NonUniqueTags` if the given tag is already present.
+    /// This function will error with `NonUniqueTags` if the given tag is already present and
+    /// `ReservedTag` if the given tag is [MINIMIZED_TAG].
     pub fn add_invisible_workspace<T>(&mut self, tag: T) -> Result<()>
     where
         T: Into<String>,
@@ -573,6 +780,167 @@ where
         Ok(())
     }
 
+    /// Rename the [Workspace] with the tag `old` to have the tag `new`.
+    ///
+    /// Any clients on the workspace remain on it following the rename and if `old` was
+    /// the previously focused tag (or marked as invisible) then that is updated to refer to
+    /// `new`. If there is no workspace tagged `old` then the [StackSet] is unmodified.
+    ///
+    /// If you are using the EWMH hooks, the updated desktop names will be sent to the X
+    /// server the next time that the window manager state is refreshed.
+    ///
+    /// # Errors
+    /// This function will error with `NonUniqueTags` if `new` is already in use by another
+    /// workspace and `ReservedTag` if either tag is [MINIMIZED_TAG].
+    pub fn rename_tag(&mut self, old: &str, new: impl Into<String>) -> Result<()> {
+        let new = new.into();
+        if old == MINIMIZED_TAG || new == MINIMIZED_TAG {
+            let tag = MINIMIZED_TAG.to_owned();
+            return Err(Error::ReservedTag { tag });
+        }
+        if old == new || !self.contains_tag(old) {
+            return Ok(());
+        }
+        if self.contains_tag(&new) {
+            return Err(Error::NonUniqueTags { tags: vec![new] });
+        }
+
+        self.modify_workspace(old, |w| w.tag.clone_from(&new));
+
+        if self.previous_tag == old {
+            self.previous_tag.clone_from(&new);
+        }
+
+        for t in self.invisible_tags.iter_mut().filter(|t| *t == old) {
+            t.clone_from(&new);
+        }
+
+        if let Some(clients) = self.minimized.remove(old) {
+            self.minimized.insert(new, clients);
+        }
+
+        Ok(())
+    }
+
+    /// Add a new hidden [Workspace] with the given tag at runtime.
+    ///
+    /// The new workspace uses a copy of the layouts of the current workspace and is assigned
+    /// the id max(workspace ids) + 1 so that it is ordered after all existing workspaces.
+    ///
+    /// # Errors
+    /// This function will error with `NonUniqueTags` if the given tag is already present.
+    pub fn add_tag(&mut self, tag: impl Into<String>) -> Result<()> {
+        let layouts = self.screens.focus.workspace.layouts.clone();
+
+        self.add_workspace(tag, layouts)
+    }
+
+    /// Add a new hidden [Workspace] with the given tag if one is not already present.
+    ///
+    /// See [StackSet::add_tag] for details of how the workspace is created.
+    pub fn ensure_tag(&mut self, tag: impl Into<String>) {
+        let tag = tag.into();
+        if !self.contains_tag(&tag) {
+            _ = self.add_tag(tag);
+        }
+    }
+
+    /// Remove the [Workspace] with the given tag, moving any clients it contains to the end of
+    /// a fallback workspace.
+    ///
+    /// The fallback is the previously focused tag if it is still available, otherwise the
+    /// first remaining tag ordered by workspace id. If the workspace being removed is currently
+    /// on a screen then it is replaced by the fallback workspace (or another hidden workspace
+    /// if the fallback is visible on another screen). The ids of the remaining workspaces are
+    /// updated so that they remain contiguous. If there is no workspace tagged `tag` then the
+    /// [StackSet] is unmodified.
+    ///
+    /// # Errors
+    /// This function will error with `InsufficientWorkspaces` if removing the workspace would
+    /// leave too few workspaces to cover the current screens and `ReservedTag` if `tag` is
+    /// [MINIMIZED_TAG] or the tag of an invisible workspace.
+    pub fn remove_tag(&mut self, tag: &str) -> Result<()> {
+        if tag == MINIMIZED_TAG || self.invisible_tags.iter().any(|t| t == tag) {
+            let tag = tag.to_owned();
+            return Err(Error::ReservedTag { tag });
+        }
+
+        let id = match self.workspace(tag) {
+            Some(w) => w.id,
+            None => return Ok(()),
+        };
+
+        let remaining: Vec<String> = self
+            .ordered_tags()
+            .into_iter()
+            .filter(|t| t != tag)
+            .collect();
+        let n_screens = self.screens.len();
+        if remaining.len() < n_screens {
+            return Err(Error::InsufficientWorkspaces {
+                n_ws: remaining.len(),
+                n_screens,
+            });
+        }
+
+        let fallback = if remaining.contains(&self.previous_tag) {
+            self.previous_tag.clone()
+        } else {
+            remaining[0].clone()
+        };
+
+        if let Some(screen) = self.screens.iter_mut().find(|s| s.workspace.tag == tag) {
+            let ix = self
+                .hidden
+                .iter()
+                .position(|w| w.tag == fallback)
+                .or_else(|| {
+                    self.hidden
+                        .iter()
+                        .position(|w| !self.invisible_tags.contains(&w.tag))
+                })
+                .expect("a hidden workspace to be available");
+            swap(&mut screen.workspace, &mut self.hidden[ix]);
+        }
+
+        let ix = self
+            .hidden
+            .iter()
+            .position(|w| w.tag == tag)
+            .expect("workspace to be hidden");
+        let removed = self.hidden.remove(ix).expect("valid index");
+
+        if let Some(stack) = removed.stack {
+            self.modify_workspace(&fallback, |w| match w.stack {
+                Some(ref mut s) => {
+                    for c in stack.flatten() {
+                        s.insert_at(Position::Tail, c);
+                    }
+                }
+                None => w.stack = Some(stack),
+            });
+        }
+
+        for w in self.workspaces_mut().filter(|w| w.id > id) {
+            w.id -= 1;
+        }
+
+        if let Some(clients) = self.minimized.remove(tag) {
+            self.minimized
+                .entry(fallback.clone())
+                .or_default()
+                .extend(clients);
+        }
+
+        if self.previous_tag == tag {
+            self.previous_tag = fallback;
+        }
+        self.invisible_tags.retain(|t| t != tag);
+        self.previous_outputs.retain(|_, t| t != tag);
+
+        Ok(())
+    }
+
     /// A reference to the [Workspace] with a tag of `tag` if there is one
     pub fn workspace(&self, tag: &str) -> Option<&Workspace<C>> {
         self.workspaces().find(|w| w.tag == tag)
@@ -624,6 +992,36 @@ where
         self.screens.focus_up();
     }
 
+    /// Move the focused client to the focused position of the [Workspace] on the next
+    /// [Screen], moving focus to that screen so the client remains focused.
+    ///
+    /// This is a no-op if there is only a single screen.
+    pub fn move_focused_to_next_screen(&mut self) {
+        self.move_focused_following_screen_focus(Self::next_screen)
+    }
+
+    /// Move the focused client to the focused position of the [Workspace] on the previous
+    /// [Screen], moving focus to that screen so the client remains focused.
+    ///
+    /// This is a no-op if there is only a single screen.
+    pub fn move_focused_to_previous_screen(&mut self) {
+        self.move_focused_following_screen_focus(Self::previous_screen)
+    }
+
+    fn move_focused_following_screen_focus(&mut self, change_screen: fn(&mut Self)) {
+        if self.screens.len() == 1 {
+            return;
+        }
+
+        let c = match self.screens.focus.workspace.remove_focused() {
+            None => return,
+            Some(c) => c,
+        };
+
+        change_screen(self);
+        self.screens.focus.workspace.insert_as_focus(c);
+    }
+
     /// Drag the focused workspace onto the next [Screen], holding focus
     pub fn drag_workspace_forward(&mut self) {
         if self.screens.len() == 1 {
@@ -756,22 +1154,11 @@ where
 #[cfg(test)]
 impl StackSet<Xid> {
     /// This is a test implementation that runs the `State::visible_client_positions`
-    /// logic using a stub XConn and no layout hook.
+    /// logic using a test XConn and no layout hook.
     pub(crate) fn visible_client_positions(&self) -> Vec<(Xid, Rect)> {
-        let mut s = crate::core::State {
-            client_set: self.clone(),
-            config: Default::default(),
-            extensions: anymap::AnyMap::new(),
-            root: Xid(0),
-            mapped: Default::default(),
-            pending_unmap: Default::default(),
-            current_event: None,
-            diff: Default::default(),
-            running: false,
-            held_mouse_state: None,
-        };
+        let mut s = crate::core::State::from_client_set(self.clone());
 
-        s.visible_client_positions(&crate::x::StubXConn)
+        s.visible_client_positions(&crate::x::test::TestXConn::default())
     }
 
     /// This is a test implementation that runs the `State::position_and_snapshot`
@@ -807,7 +1194,8 @@ impl StackSet<Xid> {
     }
 
     /// If a known client is floating, sink it and return its previous preferred screen position.
-    /// Otherwise, record it as floating with its preferred screen position.
+    /// Otherwise, record it as floating: restoring the position it had when it was last sunk if
+    /// there is one or using the given preferred screen position if not.
     ///
     /// # Errors
     /// This method with return [Error::UnknownClient] if the given client is
@@ -825,6 +1213,7 @@ impl StackSet<Xid> {
 
             self.sink(&client)
         } else {
+            let r = self.previous_floating_position(&client).unwrap_or(r);
             self.float(client, r)?;
             None
         };
@@ -832,47 +1221,106 @@ impl StackSet<Xid> {
         Ok(rect)
     }
 
-    pub(crate) fn update_screens(&mut self, rects: Vec<Rect>) -> Result<()> {
-        let n_old = self.screens.len();
-        let n_new = rects.len();
+    /// If the focused client is floating, sink it. Otherwise, record it as floating: restoring
+    /// the position it had when it was last sunk if there is one or using the given preferred
+    /// screen position if not.
+    ///
+    /// This is a no-op if there is no focused client.
+    pub fn toggle_floating_focused(&mut self, r: Rect) {
+        let id = match self.current_client() {
+            Some(&id) => id,
+            None => return,
+        };
+
+        if self.sink(&id).is_none() {
+            let r = self.previous_floating_position(&id).unwrap_or(r);
+            self.float_unchecked(id, r);
+        }
+    }
 
-        if n_new == 0 {
+    /// Update the screens to match the given named outputs.
+    ///
+    /// Workspaces stay on the output they were visible on when it is still connected and
+    /// focus stays with the focused output if possible. Outputs that have not been seen
+    /// before are given the workspaces from disconnected outputs or from the hidden
+    /// workspaces, and outputs that are reconnected are given back the workspace they were
+    /// last showing if it is still hidden.
+    ///
+    /// Outputs without a name are matched to the previous unnamed screens by position.
+    pub(crate) fn update_outputs(&mut self, outputs: Vec<(Option<String>, Rect)>) -> Result<()> {
+        if outputs.is_empty() {
             return Err(Error::NoScreens);
         }
 
-        match n_new.cmp(&n_old) {
-            // Just a change in dimensions
-            Ordering::Equal => (),
-
-            // We have more screens now: pull in hidden workspaces to fill them
-            // If we run out of workspaces we backfill using generated defaults
-            Ordering::Greater => {
-                let padding = self.take_from_hidden(n_new - n_old);
-                for (n, w) in padding.into_iter().enumerate() {
-                    self.screens.insert_at(
-                        Position::Tail,
-                        Screen {
-                            workspace: w,
-                            index: n_old + n,
-                            r: Rect::default(),
-                        },
-                    );
-                }
-            }
+        let focused_output = self.screens.focus.output.clone();
+        let focused_index = self.screens.focus.index;
+        let mut old = take(&mut self.screens).flatten();
 
-            // We have fewer screens now: focus moves to the first screen and
-            // we drop from the back of the stack
-            Ordering::Less => {
-                let mut raw = take(&mut self.screens).flatten();
-                let removed = raw.split_off(n_new);
-                self.hidden.extend(removed.into_iter().map(|s| s.workspace));
-                self.screens = Stack::from_iter_unchecked(raw);
+        let mut assigned: Vec<Option<Workspace<Xid>>> = outputs
+            .iter()
+            .map(|(name, _)| {
+                let ix = old.iter().position(|s| &s.output == name)?;
+                Some(old.remove(ix).workspace)
+            })
+            .collect();
+
+        for s in old.iter() {
+            if let Some(name) = &s.output {
+                self.previous_outputs
+                    .insert(name.clone(), s.workspace.tag.clone());
+            }
+        }
+        let mut displaced: VecDeque<Workspace<Xid>> =
+            old.into_iter().map(|s| s.workspace).collect();
+
+        for ((name, _), slot) in outputs.iter().zip(assigned.iter_mut()) {
+            let prev = name
+                .as_ref()
+                .and_then(|name| self.previous_outputs.get(name));
+            if let (None, Some(tag)) = (&slot, prev) {
+                let is_tag = |w: &Workspace<Xid>| &w.tag == tag;
+                *slot = match self.hidden.iter().position(is_tag) {
+                    Some(ix) => self.hidden.remove(ix),
+                    None => displaced
+                        .iter()
+                        .position(is_tag)
+                        .and_then(|ix| displaced.remove(ix)),
+                };
             }
         }
 
-        // self.screens.len() is now correct so update the screen dimensions
-        for (s, r) in self.screens.iter_mut().zip(rects) {
-            s.r = r;
+        let n_missing = assigned.iter().filter(|w| w.is_none()).count();
+        if n_missing > displaced.len() {
+            let padding = self.take_from_hidden(n_missing - displaced.len());
+            displaced.extend(padding);
+        }
+
+        for slot in assigned.iter_mut().filter(|w| w.is_none()) {
+            *slot = displaced.pop_front();
+        }
+        self.hidden.extend(displaced);
+
+        self.screens =
+            Stack::from_iter_unchecked(outputs.into_iter().zip(assigned).enumerate().map(
+                |(index, ((name, r), workspace))| Screen {
+                    index,
+                    workspace: workspace.expect("all outputs to have a workspace"),
+                    r,
+                    output: name,
+                },
+            ));
+
+        let focused_index = match focused_output {
+            Some(_) => self
+                .screens
+                .iter()
+                .find(|s| s.output == focused_output)
+                .map(|s| s.index),
+            None => Some(focused_index).filter(|&ix| ix < self.screens.len()),
+        };
+
+        if let Some(index) = focused_index {
+            self.focus_screen(index);
         }
 
         Ok(())
@@ -1230,6 +1678,189 @@ pub mod tests {
         assert_eq!(s.previous_tag, tag);
     }
 
+    #[test_case(1; "single screen")]
+    #[test_case(2; "two screens")]
+    #[test]
+    fn focus_previous_tag_toggles_between_tags(n_screens: usize) {
+        let mut s = test_stack_set(5, n_screens);
+
+        s.focus_tag("1");
+        s.focus_tag("3");
+        assert_eq!(s.current_tag(), "3");
+
+        s.focus_previous_tag();
+        assert_eq!(s.current_tag(), "1");
+
+        s.focus_previous_tag();
+        assert_eq!(s.current_tag(), "3");
+    }
+
+    #[test]
+    fn focus_previous_tag_without_history_is_a_noop() {
+        let mut s = test_stack_set(5, 2);
+
+        s.focus_previous_tag();
+
+        assert_eq!(s.current_tag(), "1");
+        assert_eq!(s.current_screen().index(), 0);
+    }
+
+    #[test_case("1"; "focused")]
+    #[test_case("2"; "visible on other screen")]
+    #[test_case("3"; "hidden")]
+    #[test]
+    fn rename_tag_keeps_clients_on_the_workspace(tag: &str) {
+        let mut s = test_stack_set_with_stacks(
+            vec![Some(stack!(1, [2])), Some(stack!(3)), Some(stack!([4], 5))],
+            2,
+        );
+        let before: Vec<u8> = s.workspace(tag).unwrap().clients().copied().collect();
+
+        s.rename_tag(tag, "renamed").expect("no tag collision");
+
+        assert!(!s.contains_tag(tag));
+        let after: Vec<u8> = s.workspace("renamed").unwrap().clients().copied().collect();
+        assert_eq!(after, before);
+        for c in before {
+            assert_eq!(s.tag_for_client(&c), Some("renamed"));
+        }
+    }
+
+    #[test]
+    fn rename_tag_updates_previous_tag() {
+        let mut s = test_stack_set(5, 1);
+        s.focus_tag("3");
+
+        s.rename_tag("1", "first").expect("no tag collision");
+        s.focus_previous_tag();
+
+        assert_eq!(s.current_tag(), "first");
+    }
+
+    #[test]
+    fn rename_tag_rejects_existing_tags() {
+        let mut s = test_stack_set(5, 1);
+
+        let res = s.rename_tag("1", "2");
+
+        assert!(matches!(res, Err(Error::NonUniqueTags { .. })));
+        assert!(s.contains_tag("1"));
+        assert_eq!(s.ordered_tags(), vec!["1", "2", "3", "4", "5"]);
+    }
+
+    #[test]
+    fn added_tags_can_be_removed_relocating_their_clients() {
+        let mut s = test_stack_set(3, 1);
+        s.insert(1);
+        s.insert(2);
+
+        s.add_tag("dynamic").expect("tag to be unique");
+        s.ensure_tag("dynamic");
+        assert!(matches!(
+            s.add_tag("dynamic"),
+            Err(Error::NonUniqueTags { .. })
+        ));
+        assert_eq!(s.ordered_tags(), vec!["1", "2", "3", "dynamic"]);
+
+        s.move_client_to_tag(&2, "dynamic");
+        assert_eq!(s.tag_for_client(&2), Some("dynamic"));
+
+        s.focus_tag("3");
+        s.remove_tag("dynamic").expect("enough workspaces");
+
+        assert_eq!(s.ordered_tags(), vec!["1", "2", "3"]);
+        assert_eq!(s.current_tag(), "3");
+        assert_eq!(s.tag_for_client(&2), Some("1"));
+    }
+
+    #[test_case("1", "2", vec![3, 1, 2], "3"; "focused")]
+    #[test_case("2", "1", vec![1, 2, 3], "1"; "visible on other screen")]
+    #[test_case("3", "1", vec![1, 2, 4, 5], "1"; "hidden")]
+    #[test_case("4", "1", vec![1, 2], "1"; "empty")]
+    #[test]
+    fn remove_tag_moves_clients_to_the_fallback(
+        tag: &str,
+        fallback: &str,
+        expected: Vec<u8>,
+        current_tag: &str,
+    ) {
+        let mut s = test_stack_set_with_stacks(
+            vec![
+                Some(stack!(1, [2])),
+                Some(stack!(3)),
+                Some(stack!([4], 5)),
+                None,
+            ],
+            2,
+        );
+
+        s.remove_tag(tag).expect("enough workspaces");
+
+        assert!(!s.contains_tag(tag));
+        assert_eq!(s.current_tag(), current_tag);
+        let clients: Vec<u8> = s.workspace(fallback).unwrap().clients().copied().collect();
+        assert_eq!(clients, expected);
+        let ids: Vec<usize> = s.ordered_workspaces().map(|w| w.id).collect();
+        assert_eq!(ids, vec![0, 1, 2], "workspace ids remain contiguous");
+        assert_eq!(s.on_screen_workspaces().count(), 2);
+    }
+
+    #[test]
+    fn remove_tag_requires_enough_workspaces_for_each_screen() {
+        let mut s = test_stack_set(2, 2);
+
+        let res = s.remove_tag("1");
+
+        assert!(matches!(res, Err(Error::InsufficientWorkspaces { .. })));
+        assert_eq!(s.ordered_tags(), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn remove_tag_rejects_the_minimized_tag_and_invisible_tags() {
+        let mut s = test_stack_set(3, 1);
+        s.add_invisible_workspace("NSP").unwrap();
+        s.insert(1);
+        s.minimize(&1);
+
+        for tag in [MINIMIZED_TAG, "NSP"] {
+            let res = s.remove_tag(tag);
+            assert!(matches!(res, Err(Error::ReservedTag { .. })), "{tag}");
+            assert!(s.contains_tag(tag), "{tag}");
+        }
+        assert!(s.is_minimized(&1));
+    }
+
+    #[test_case(true, "2", 1; "next")]
+    #[test_case(false, "3", 2; "previous")]
+    #[test]
+    fn move_focused_to_adjacent_screen(next: bool, tag: &str, index: usize) {
+        let mut s = test_stack_set(5, 3);
+        s.insert(1);
+        s.insert(2);
+
+        if next {
+            s.move_focused_to_next_screen();
+        } else {
+            s.move_focused_to_previous_screen();
+        }
+
+        assert_eq!(s.tag_for_client(&2), Some(tag));
+        assert_eq!(s.tag_for_client(&1), Some("1"));
+        assert_eq!(s.screens.focus.index, index);
+        assert_eq!(s.current_client(), Some(&2));
+    }
+
+    #[test]
+    fn move_focused_to_next_screen_with_a_single_screen_is_a_noop() {
+        let mut s = test_stack_set(5, 1);
+        s.insert(1);
+
+        s.move_focused_to_next_screen();
+
+        assert_eq!(s.tag_for_client(&1), Some("1"));
+        assert_eq!(s.current_client(), Some(&1));
+    }
+
     #[test_case(true, 1; "forward")]
     #[test_case(false, 2; "backward")]
     #[test]
@@ -1354,6 +1985,234 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn toggle_floating_focused_floats_and_sinks() {
+        let mut ss = test_xid_stack_set(3, 1);
+        ss.insert(Xid(0));
+        ss.insert(Xid(1));
+        let r = Rect::new(10, 20, 30, 40);
+
+        ss.toggle_floating_focused(r);
+        assert!(ss.is_floating(&Xid(1)));
+        assert!(!ss.is_floating(&Xid(0)));
+        assert_eq!(
+            ss.floating[&Xid(1)].applied_to(&ss.current_screen().r),
+            Rect::new(10, 20, 30, 40)
+        );
+
+        ss.toggle_floating_focused(r);
+        assert!(!ss.is_floating(&Xid(1)));
+        assert!(ss.floating.is_empty());
+    }
+
+    #[test_case(false; "same workspace")]
+    #[test_case(true; "after switching workspace")]
+    #[test]
+    fn toggle_floating_focused_restores_the_previous_floating_position(switch_tag: bool) {
+        let mut ss = test_xid_stack_set(3, 1);
+        ss.insert(Xid(0));
+        let r_custom = Rect::new(100, 200, 300, 400);
+        let r_tiled = Rect::new(0, 0, 1000, 2000);
+
+        ss.float(Xid(0), r_custom).unwrap();
+        ss.toggle_floating_focused(r_tiled);
+        assert!(!ss.is_floating(&Xid(0)));
+
+        if switch_tag {
+            ss.focus_tag("2");
+            ss.focus_tag("1");
+        }
+
+        ss.toggle_floating_focused(r_tiled);
+        assert_eq!(
+            ss.floating[&Xid(0)].applied_to(&ss.current_screen().r),
+            r_custom
+        );
+    }
+
+    #[test]
+    fn sink_all_remembers_floating_positions() {
+        let mut ss = test_xid_stack_set(3, 1);
+        ss.insert(Xid(0));
+        let r = Rect::new(100, 200, 300, 400);
+
+        ss.float(Xid(0), r).unwrap();
+        ss.sink_all();
+        assert!(ss.floating.is_empty());
+
+        let res = ss.toggle_floating_state(Xid(0), Rect::new(1, 2, 3, 4));
+        assert!(matches!(res, Ok(None)), "{res:?}");
+        assert_eq!(ss.floating[&Xid(0)].applied_to(&ss.current_screen().r), r);
+    }
+
+    #[test]
+    fn minimized_clients_are_restored_to_their_original_workspace() {
+        let mut ss = test_stack_set(5, 2);
+        ss.insert(1);
+        ss.insert(2);
+        ss.insert(3);
+
+        ss.minimize(&2);
+        ss.minimize_focused();
+        ss.minimize(&2); // already minimized
+
+        assert_eq!(
+            ss.current_workspace().clients().collect::<Vec<_>>(),
+            vec![&1]
+        );
+        assert_eq!(ss.minimized_clients("1").collect::<Vec<_>>(), vec![&2, &3]);
+        assert_eq!(ss.tag_for_client(&3), Some(MINIMIZED_TAG));
+        assert!(!ss.ordered_tags().contains(&MINIMIZED_TAG.to_string()));
+
+        ss.focus_tag("2");
+        assert_eq!(
+            ss.restore_last_minimized(),
+            None,
+            "nothing minimized from tag 2"
+        );
+
+        ss.focus_tag("1");
+        assert_eq!(ss.restore_last_minimized(), Some(3));
+        assert_eq!(ss.current_client(), Some(&3));
+        assert!(ss.is_minimized(&2));
+
+        ss.remove_client(&2);
+        assert!(!ss.is_minimized(&2));
+        assert_eq!(ss.restore_last_minimized(), None);
+    }
+
+    #[test]
+    fn the_minimized_tag_is_reserved() {
+        let layouts = LayoutStack::default();
+        let res = StackSet::<u8>::try_new(layouts.clone(), ["1", MINIMIZED_TAG], [Rect::default()]);
+        assert!(matches!(res, Err(Error::ReservedTag { .. })));
+
+        let mut ss = test_stack_set(2, 1);
+        let res = ss.add_workspace(MINIMIZED_TAG, layouts);
+        assert!(matches!(res, Err(Error::ReservedTag { .. })));
+
+        let res = ss.add_invisible_workspace(MINIMIZED_TAG);
+        assert!(matches!(res, Err(Error::ReservedTag { .. })));
+
+        let res = ss.rename_tag("1", MINIMIZED_TAG);
+        assert!(matches!(res, Err(Error::ReservedTag { .. })));
+
+        ss.insert(1);
+        ss.minimize(&1);
+        assert_eq!(ss.tag_for_client(&1), Some(MINIMIZED_TAG));
+        assert!(ss.rename_tag(MINIMIZED_TAG, "min").is_err());
+    }
+
+    #[test]
+    fn sticky_clients_are_pulled_to_the_current_workspace() {
+        let mut ss = test_xid_stack_set(3, 1);
+        for n in 0..3 {
+            ss.insert(Xid(n));
+        }
+        ss.set_sticky(&Xid(0), true);
+        ss.set_sticky(&Xid(42), true);
+        assert!(ss.is_sticky(&Xid(0)));
+        assert!(!ss.is_sticky(&Xid(42)), "unknown clients can't be sticky");
+
+        ss.focus_tag("2");
+        ss.insert(Xid(3));
+        ss.pull_sticky_clients();
+
+        let clients: Vec<Xid> = ss.current_workspace().clients().copied().collect();
+        assert_eq!(clients, vec![Xid(3), Xid(0)]);
+        assert_eq!(ss.current_client(), Some(&Xid(3)));
+
+        ss.remove_client(&Xid(0));
+        assert!(!ss.is_sticky(&Xid(0)));
+    }
+
+    #[test]
+    fn sink_current_workspace_only_sinks_clients_on_the_current_workspace() {
+        let mut ss = test_xid_stack_set(3, 2);
+        for n in 0..4 {
+            ss.insert(Xid(n));
+        }
+        ss.move_client_to_tag(&Xid(3), "2");
+        let r = Rect::new(100, 200, 300, 400);
+        for n in 0..4 {
+            ss.float_unchecked(Xid(n), r);
+        }
+
+        ss.sink_current_workspace();
+
+        assert_eq!(ss.floating.keys().collect::<Vec<_>>(), vec![&Xid(3)]);
+        assert_eq!(ss.previous_floating_position(&Xid(1)), Some(r));
+        let order: Vec<Xid> = ss.current_workspace().clients().copied().collect();
+        assert_eq!(order, vec![Xid(2), Xid(1), Xid(0)]);
+    }
+
+    #[test]
+    fn removed_clients_do_not_have_floating_positions_remembered() {
+        let mut ss = test_xid_stack_set(3, 1);
+        ss.insert(Xid(0));
+
+        ss.float(Xid(0), Rect::new(100, 200, 300, 400)).unwrap();
+        ss.sink(&Xid(0));
+        ss.remove_client(&Xid(0));
+
+        assert_eq!(ss.previous_floating_position(&Xid(0)), None);
+    }
+
+    // Stack is [1, 2, 3, 4] with 2 and 4 floating and focus on 1
+    #[test_case(true, vec![2, 4, 2]; "floating")]
+    #[test_case(false, vec![3, 1, 3]; "tiled")]
+    #[test]
+    fn focus_next_in_layer_stays_within_the_layer(floating: bool, expected: Vec<u8>) {
+        let mut s = test_stack_set(5, 1);
+        for n in 1..=4 {
+            s.insert_at(Position::Tail, n);
+        }
+        s.float_unchecked(2, Rect::default());
+        s.float_unchecked(4, Rect::default());
+
+        let mut focused = Vec::new();
+        for _ in 0..3 {
+            if floating {
+                s.focus_next_floating();
+            } else {
+                s.focus_next_tiled();
+            }
+            focused.push(*s.current_client().unwrap());
+        }
+
+        assert_eq!(focused, expected);
+    }
+
+    #[test_case(true; "floating")]
+    #[test_case(false; "tiled")]
+    #[test]
+    fn focus_next_in_empty_layer_is_a_noop(floating: bool) {
+        let mut s = test_stack_set(5, 1);
+        s.insert(1);
+        s.insert(2);
+        if !floating {
+            s.float_unchecked(1, Rect::default());
+            s.float_unchecked(2, Rect::default());
+        }
+
+        if floating {
+            s.focus_next_floating();
+        } else {
+            s.focus_next_tiled();
+        }
+
+        assert_eq!(s.current_client(), Some(&2));
+    }
+
+    #[test]
+    fn toggle_floating_focused_without_focus_is_a_noop() {
+        let mut ss = test_xid_stack_set(3, 1);
+
+        ss.toggle_floating_focused(Rect::new(10, 20, 30, 40));
+
+        assert!(ss.floating.is_empty());
+    }
+
     #[test_case(1, "1"; "current focus to current tag")]
     #[test_case(2, "1"; "from current tag to current tag")]
     #[test_case(6, "1"; "from other tag to current tag")]
@@ -1389,7 +2248,7 @@ pub mod tests {
     #[test_case(2, 2, 1, vec!["1", "2"], vec!["1", "2"]; "multiple to same count")]
     #[test_case(2, 3, 1, vec!["1", "2"], vec!["1", "2", "WS-3"]; "multiple to more with padding")]
     #[test]
-    fn update_screens(
+    fn update_outputs(
         n_before: usize,
         n_after: usize,
         focus_after: usize,
@@ -1402,6 +2261,7 @@ pub mod tests {
             vec![Rect::default(); n_before],
         )
         .expect("enough workspaces to cover the number of initial screens");
+        ss.update_outputs(numbered_outputs(n_before)).unwrap();
 
         // Invisible workspaces should never have focus: backfilling from the currently
         // hidden workspaces needs to not put this on a screen.
@@ -1415,7 +2275,7 @@ pub mod tests {
         assert_eq!(ss.screens.len(), n_before);
         assert_eq!(focused_tags(&ss), tags_before);
 
-        ss.update_screens(vec![Rect::default(); n_after]).unwrap();
+        ss.update_outputs(numbered_outputs(n_after)).unwrap();
 
         assert_eq!(ss.screens.len(), n_after);
         assert_eq!(ss.screens.focus.index, focus_after);
@@ -1427,16 +2287,130 @@ pub mod tests {
         assert_eq!(ss.workspaces().count(), expected);
     }
 
+    fn numbered_outputs(n: usize) -> Vec<(Option<String>, Rect)> {
+        (0..n)
+            .map(|i| (Some(i.to_string()), Rect::default()))
+            .collect()
+    }
+
+    fn named_outputs(names: &[&str]) -> Vec<(Option<String>, Rect)> {
+        names
+            .iter()
+            .map(|name| (Some(name.to_string()), Rect::default()))
+            .collect()
+    }
+
+    fn output_tags(ss: &StackSet<Xid>) -> Vec<(&str, &str)> {
+        let mut screens: Vec<_> = ss.screens().collect();
+        screens.sort_by_key(|s| s.index);
+
+        screens
+            .into_iter()
+            .map(|s| (s.output().unwrap(), s.workspace.tag()))
+            .collect()
+    }
+
+    fn test_stack_set_with_outputs(names: &[&str]) -> StackSet<Xid> {
+        let mut ss: StackSet<Xid> = StackSet::try_new(
+            LayoutStack::default(),
+            ["1", "2", "3", "4", "5"],
+            vec![Rect::default(); names.len()],
+        )
+        .expect("enough workspaces to cover the number of screens");
+        ss.update_outputs(named_outputs(names)).unwrap();
+
+        ss
+    }
+
     #[test]
-    fn update_screens_with_empty_vec_is_an_error() {
+    fn update_outputs_with_empty_vec_is_an_error() {
         let mut ss: StackSet<Xid> =
             StackSet::try_new(LayoutStack::default(), ["1", "2"], vec![Rect::default(); 2])
                 .expect("enough workspaces to cover the number of screens");
 
-        let res = ss.update_screens(vec![]);
+        let res = ss.update_outputs(vec![]);
 
         assert!(matches!(res, Err(Error::NoScreens)));
     }
+
+    #[test]
+    fn unnamed_outputs_are_matched_by_position() {
+        let mut ss: StackSet<Xid> = StackSet::try_new(
+            LayoutStack::default(),
+            ["1", "2", "3"],
+            vec![Rect::default(); 2],
+        )
+        .expect("enough workspaces to cover the number of screens");
+        ss.focus_screen(1);
+
+        ss.update_outputs(vec![(None, Rect::default())]).unwrap();
+        assert_eq!(focused_tags(&ss), vec!["1"]);
+        assert!(ss.previous_outputs.is_empty());
+
+        ss.update_outputs(vec![(None, Rect::default()); 2]).unwrap();
+        assert_eq!(focused_tags(&ss), vec!["1", "2"]);
+        assert!(ss.screens().all(|s| s.output().is_none()));
+    }
+
+    #[test]
+    fn removing_and_readding_an_output_keeps_workspaces_on_their_outputs() {
+        let mut ss = test_stack_set_with_outputs(&["eDP-1", "HDMI-1", "DP-1"]);
+        let initial = vec![("eDP-1", "1"), ("HDMI-1", "2"), ("DP-1", "3")];
+        assert_eq!(output_tags(&ss), initial);
+
+        ss.update_outputs(named_outputs(&["eDP-1", "DP-1"]))
+            .unwrap();
+        assert_eq!(output_tags(&ss), vec![("eDP-1", "1"), ("DP-1", "3")]);
+        assert!(ss.hidden_workspaces().any(|w| w.tag() == "2"));
+
+        ss.update_outputs(named_outputs(&["eDP-1", "HDMI-1", "DP-1"]))
+            .unwrap();
+        assert_eq!(output_tags(&ss), initial);
+    }
+
+    #[test]
+    fn reordered_outputs_keep_their_workspaces() {
+        let mut ss = test_stack_set_with_outputs(&["eDP-1", "HDMI-1"]);
+
+        ss.update_outputs(named_outputs(&["HDMI-1", "eDP-1"]))
+            .unwrap();
+
+        assert_eq!(output_tags(&ss), vec![("HDMI-1", "2"), ("eDP-1", "1")]);
+    }
+
+    #[test]
+    fn new_outputs_are_given_workspaces_from_removed_outputs() {
+        let mut ss = test_stack_set_with_outputs(&["eDP-1", "HDMI-1"]);
+
+        ss.update_outputs(named_outputs(&["eDP-1", "DP-1"]))
+            .unwrap();
+
+        assert_eq!(output_tags(&ss), vec![("eDP-1", "1"), ("DP-1", "2")]);
+    }
+
+    #[test]
+    fn focus_stays_with_the_focused_output() {
+        let mut ss = test_stack_set_with_outputs(&["eDP-1", "HDMI-1", "DP-1"]);
+        ss.focus_tag("3");
+
+        ss.update_outputs(named_outputs(&["eDP-1", "DP-1"]))
+            .unwrap();
+
+        assert_eq!(ss.current_tag(), "3");
+        assert_eq!(ss.current_screen().output(), Some("DP-1"));
+    }
+
+    #[test]
+    fn readded_outputs_do_not_take_workspaces_that_are_now_visible() {
+        let mut ss = test_stack_set_with_outputs(&["eDP-1", "HDMI-1"]);
+
+        ss.update_outputs(named_outputs(&["eDP-1"])).unwrap();
+        ss.focus_tag("2");
+        ss.update_outputs(named_outputs(&["eDP-1", "HDMI-1"]))
+            .unwrap();
+
+        assert_eq!(output_tags(&ss), vec![("eDP-1", "2"), ("HDMI-1", "1")]);
+    }
 }
 
 #[cfg(test)]
@@ -1450,6 +2424,8 @@ mod quickcheck_tests {
     where
         C: Copy + Clone + PartialEq + Eq + Hash,
     {
+        /// Build a [Stack] from a vec of arbitrary clients, focusing the first and splitting the
+        /// rest at a random point. Returns `None` if `up` is empty.
         pub fn try_from_arbitrary_vec(mut up: Vec<C>, g: &mut Gen) -> Option<Self> {
             let focus = match up.len() {
                 0 => return None,
@@ -1465,6 +2441,7 @@ mod quickcheck_tests {
     }
 
     impl StackSet<Xid> {
+        /// The smallest client id not present in this [StackSet].
         pub fn minimal_unknown_client(&self) -> Xid {
             let mut c = 0;
 
@@ -1475,10 +2452,12 @@ mod quickcheck_tests {
             Xid(c)
         }
 
+        /// The tag of the first hidden workspace, if there are any.
         pub fn first_hidden_tag(&self) -> Option<String> {
             self.hidden.iter().map(|w| w.tag.clone()).next()
         }
 
+        /// The tag of the last workspace.
         pub fn last_tag(&self) -> String {
             self.workspaces()
                 .last()
@@ -1487,6 +2466,7 @@ mod quickcheck_tests {
                 .clone()
         }
 
+        /// The last client on the workspace of the last screen.
         pub fn last_visible_client(&self) -> Option<&Xid> {
             self.screens
                 .down
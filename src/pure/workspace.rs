@@ -1,17 +1,23 @@
 use crate::{
     core::layout::{IntoMessage, LayoutStack},
-    pure::{Position, Stack},
+    pure::{Position, Stack, MINIMIZED_TAG},
     stack, Error, Result,
 };
 use std::{fmt, mem::take};
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 /// A wrapper around a [Stack] of windows belonging to a single "workspace" or virtual
 /// desktop. When this workspace is active on a given screen, the windows contained in
 /// its stack will be positioned using the active layout of its [LayoutStack].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, Clone)]
 pub struct Workspace<T> {
     pub(crate) id: usize,
     pub(crate) tag: String,
+    pub(crate) display_name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) layouts: LayoutStack,
     pub(crate) stack: Option<Stack<T>>,
 }
@@ -21,6 +27,7 @@ impl<T> Default for Workspace<T> {
         Self {
             id: Default::default(),
             tag: Default::default(),
+            display_name: Default::default(),
             layouts: Default::default(),
             stack: Default::default(),
         }
@@ -52,6 +59,7 @@ impl<T> Workspace<T> {
         Self {
             id,
             tag: tag.into(),
+            display_name: None,
             layouts,
             stack,
         }
@@ -76,6 +84,25 @@ impl<T> Workspace<T> {
         &self.tag
     }
 
+    /// The name to display for this workspace in status bars and pagers.
+    ///
+    /// This is the tag of the workspace unless a display name has been set using
+    /// [Workspace::set_display_name].
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.tag)
+    }
+
+    /// Set the name to display for this workspace, leaving its tag unchanged.
+    pub fn set_display_name(&mut self, name: impl Into<String>) {
+        self.display_name = Some(name.into());
+    }
+
+    /// Clear any display name that has been set for this workspace so that its tag is
+    /// displayed instead.
+    pub fn clear_display_name(&mut self) {
+        self.display_name = None;
+    }
+
     /// The name of the currently active layout being used by this workspace
     pub fn layout_name(&self) -> String {
         self.layouts.focus.name()
@@ -132,13 +159,17 @@ impl<T> Workspace<T> {
     }
 
     /// Switch to the next available layout for this workspace.
+    ///
+    /// Layouts marked as [skipped when cycling][crate::core::layout::SkipInCycle] are passed over.
     pub fn next_layout(&mut self) {
-        self.layouts.focus_down();
+        self.layouts.focus_next_in_cycle();
     }
 
     /// Switch to the previous available layout for this workspace.
+    ///
+    /// Layouts marked as [skipped when cycling][crate::core::layout::SkipInCycle] are passed over.
     pub fn previous_layout(&mut self) {
-        self.layouts.focus_up();
+        self.layouts.focus_previous_in_cycle();
     }
 
     /// Replace the current [LayoutStack] with a new one, returning the layouts that
@@ -179,6 +210,10 @@ impl<T: PartialEq> Workspace<T> {
 }
 
 pub(crate) fn check_workspace_invariants<T>(workspaces: &[Workspace<T>]) -> Result<()> {
+    if let Some(w) = workspaces.iter().find(|w| w.tag == MINIMIZED_TAG) {
+        return Err(Error::ReservedTag { tag: w.tag.clone() });
+    }
+
     let tags = workspaces.iter().map(|w| &w.tag);
     let mut seen = vec![];
     let mut duplicates = vec![];
@@ -2,11 +2,17 @@
 use crate::{
     core::layout::Layout,
     pure::{geometry::Rect, Stack},
-    Result, Xid,
+    Error, Result, Xid,
+};
+use nix::{
+    errno::Errno,
+    sys::signal::{signal, SigHandler, Signal},
 };
 use std::{
-    io::Read,
-    process::{Command, Stdio},
+    io::{ErrorKind, Read},
+    os::unix::process::{CommandExt, ExitStatusExt},
+    process::{Command, ExitStatus, Stdio},
+    thread,
 };
 use tracing::trace;
 
@@ -51,60 +57,124 @@ pub fn spawn_with_args<S: Into<String>>(cmd: S, args: &[&str]) -> Result<()> {
     }
 }
 
-/// Run an external command and return its output.
+/// Run an external command to completion and return its trimmed stdout.
 ///
 /// > [`std::process::Command::output`] will not work within penrose due to the
 /// > way that signal handling is set up. Use this function if you need to access the
 /// > output of a process that you spawn.
-pub fn spawn_for_output<S: Into<String>>(cmd: S) -> std::io::Result<String> {
+///
+/// # Errors
+/// If the command exits with a non-zero status then `Error::NonZeroExitStatus` is returned
+/// containing anything that the command wrote to stderr. An empty command is an error.
+///
+/// > **NOTE**: this previously returned a [std::io::Result]. The crate level [Result] is now
+/// > returned in order to report non-zero exit statuses: IO errors are returned as `Error::Io`.
+pub fn spawn_for_output<S: Into<String>>(cmd: S) -> Result<String> {
     let cmd = cmd.into();
-    trace!(?cmd, "spawning subprocess for output");
     let parts: Vec<&str> = cmd.split_whitespace().collect();
-    let result = if parts.len() > 1 {
-        Command::new(parts[0])
-            .stdout(Stdio::piped())
-            .args(&parts[1..])
-            .spawn()
-    } else {
-        Command::new(parts[0]).stdout(Stdio::piped()).spawn()
+    let (program, args) = match parts.split_first() {
+        Some(split) => split,
+        None => {
+            let e = std::io::Error::new(ErrorKind::InvalidInput, "empty command");
+            return Err(e.into());
+        }
     };
 
-    trace!(?cmd, "reading output");
-    let mut child = result?;
-    let mut buff = String::new();
-    child
-        .stdout
-        .take()
-        .expect("to have output")
-        .read_to_string(&mut buff)
-        .map(|_| buff)
+    trace!(?cmd, "spawning subprocess for output");
+    output_for(program, args, cmd.clone())
 }
 
-/// Run an external command with arguments and return its output.
+/// Run an external command with arguments to completion and return its trimmed stdout.
 ///
 /// > [`std::process::Command::output`] will not work within penrose due to the
 /// > way that signal handling is set up. Use this function if you need to access the
 /// > output of a process that you spawn.
-pub fn spawn_for_output_with_args<S: Into<String>>(
-    cmd: S,
-    args: &[&str],
-) -> std::io::Result<String> {
+///
+/// # Errors
+/// See [spawn_for_output]. As with [spawn_for_output], this previously returned a
+/// [std::io::Result].
+pub fn spawn_for_output_with_args<S: Into<String>>(cmd: S, args: &[&str]) -> Result<String> {
     let cmd = cmd.into();
 
     trace!(?cmd, ?args, "spawning subprocess for output");
-    let mut child = Command::new(&cmd)
-        .stdout(Stdio::piped())
+    output_for(&cmd, args, format!("{cmd} {}", args.join(" ")))
+}
+
+// Penrose ignores SIGCHLD in order to avoid leaving zombie processes, which results in the
+// kernel reaping our children before we are able to wait on them. To recover the exit status
+// of the command it is run from a shell which writes the status to stderr on a line of its own
+// following anything written by the command.
+// The shell (and the command it runs) have the default SIGCHLD handling restored so that the
+// shell is able to wait on the command.
+const STATUS_SCRIPT: &str = r#""$@"; printf '\n%s\n' "$?" >&2"#;
+
+fn output_for(program: &str, args: &[&str], cmd: String) -> Result<String> {
+    let mut command = Command::new("sh");
+    command
+        .args(["-c", STATUS_SCRIPT, "sh", program])
         .args(args)
-        .spawn()?;
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // SAFETY: signal is async-signal-safe and the child does not have any existing handler
+    //         for SIGCHLD that could be invalidated: it is either ignored or the default.
+    unsafe {
+        command.pre_exec(|| {
+            signal(Signal::SIGCHLD, SigHandler::SigDfl)?;
+            Ok(())
+        });
+    }
+
+    let mut child = command.spawn()?;
+
+    // stderr is read on its own thread so that a child writing a lot to stderr can't block
+    // while we are waiting for it to close stdout
+    let mut stderr = child.stderr.take().expect("stderr to be piped");
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = String::new();
+        stderr.read_to_string(&mut buf).map(|_| buf)
+    });
 
-    trace!(?cmd, ?args, "reading output");
-    let mut buff = String::new();
+    trace!(?cmd, "reading output");
+    let mut stdout = String::new();
     child
         .stdout
         .take()
-        .unwrap()
-        .read_to_string(&mut buff)
-        .map(|_| buff)
+        .expect("stdout to be piped")
+        .read_to_string(&mut stdout)?;
+    let stderr = stderr_reader.join().expect("stderr reader to not panic")?;
+
+    // The shell will already have been reaped if penrose is ignoring SIGCHLD
+    match child.wait() {
+        Err(e) if e.raw_os_error() != Some(Errno::ECHILD as i32) => return Err(e.into()),
+        _ => (),
+    }
+
+    let (stderr, status) = split_exit_status(&stderr)?;
+    if !status.success() {
+        return Err(Error::NonZeroExitStatus {
+            cmd,
+            status,
+            stderr: stderr.trim().to_owned(),
+        });
+    }
+
+    Ok(stdout.trim().to_owned())
+}
+
+// Split the exit status written by STATUS_SCRIPT from the rest of stderr, removing the
+// newline that separates them.
+fn split_exit_status(stderr: &str) -> Result<(&str, ExitStatus)> {
+    let stderr = stderr.strip_suffix('\n').unwrap_or(stderr);
+    let (rest, code) = stderr.rsplit_once('\n').unwrap_or(("", stderr));
+
+    match code.parse::<i32>() {
+        // The raw wait status for a normal exit holds the exit code in the second byte
+        Ok(code) => Ok((rest, ExitStatus::from_raw(code << 8))),
+        Err(_) => Err(Error::Custom(format!(
+            "unable to determine exit status from stderr: {stderr:?}"
+        ))),
+    }
 }
 
 /// Use `notify-send` to display a message to the user
@@ -172,3 +242,48 @@ pub fn print_layout_result<L: Layout>(
         println!("{chars}");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_for_output_returns_trimmed_stdout() {
+        let output = spawn_for_output("echo hello").unwrap();
+
+        assert_eq!(output, "hello");
+    }
+
+    #[test]
+    fn spawn_for_output_errors_on_non_zero_exit() {
+        let res = spawn_for_output_with_args("sh", &["-c", "echo oops >&2; exit 3"]);
+
+        match res {
+            Err(Error::NonZeroExitStatus { status, stderr, .. }) => {
+                assert_eq!(status.code(), Some(3));
+                assert_eq!(stderr, "oops");
+            }
+            _ => panic!("expected NonZeroExitStatus, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn stderr_without_a_trailing_newline_does_not_hide_the_exit_status() {
+        let output = spawn_for_output_with_args("sh", &["-c", "echo ok; printf oops >&2"]);
+        assert_eq!(output.unwrap(), "ok");
+
+        let res = spawn_for_output_with_args("sh", &["-c", "printf oops >&2; exit 2"]);
+        match res {
+            Err(Error::NonZeroExitStatus { status, stderr, .. }) => {
+                assert_eq!(status.code(), Some(2));
+                assert_eq!(stderr, "oops");
+            }
+            _ => panic!("expected NonZeroExitStatus, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn spawn_for_output_errors_on_an_empty_command() {
+        assert!(matches!(spawn_for_output("  "), Err(Error::Io(_))));
+    }
+}
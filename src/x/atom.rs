@@ -50,6 +50,9 @@ pub enum Atom {
     /// WM_TAKE_FOCUS
     #[strum(serialize = "WM_TAKE_FOCUS")]
     WmTakeFocus,
+    /// WM_WINDOW_ROLE
+    #[strum(serialize = "WM_WINDOW_ROLE")]
+    WmWindowRole,
     /// _NET_ACTIVE_WINDOW
     #[strum(serialize = "_NET_ACTIVE_WINDOW")]
     NetActiveWindow,
@@ -101,6 +104,9 @@ pub enum Atom {
     /// _NET_WM_STRUT
     #[strum(serialize = "_NET_WM_STRUT")]
     NetWmStrut,
+    /// _NET_WM_STRUT_PARTIAL
+    #[strum(serialize = "_NET_WM_STRUT_PARTIAL")]
+    NetWmStrutPartial,
     /// _NET_WM_STATE_DEMANDS_ATTENTION
     #[strum(serialize = "_NET_WM_STATE_DEMANDS_ATTENTION")]
     NetWmStateDemandsAttention,
@@ -110,9 +116,18 @@ pub enum Atom {
     /// _NET_WM_STATE_HIDDEN
     #[strum(serialize = "_NET_WM_STATE_HIDDEN")]
     NetWmStateHidden,
+    /// _NET_WM_STATE_STICKY
+    #[strum(serialize = "_NET_WM_STATE_STICKY")]
+    NetWmStateSticky,
+    /// _NET_WM_WINDOW_OPACITY
+    #[strum(serialize = "_NET_WM_WINDOW_OPACITY")]
+    NetWmWindowOpacity,
     /// _NET_WM_WINDOW_TYPE
     #[strum(serialize = "_NET_WM_WINDOW_TYPE")]
     NetWmWindowType,
+    /// _NET_WORKAREA
+    #[strum(serialize = "_NET_WORKAREA")]
+    NetWorkarea,
     /// _XEMBED
     #[strum(serialize = "_XEMBED")]
     XEmbed,
@@ -38,6 +38,8 @@ pub enum XEvent {
     Leave(PointerChange),
     /// Keybindings have changed
     MappingNotify,
+    /// A window has been mapped to the screen
+    MapNotify(Xid),
     /// A client window is requesting to be positioned and rendered on the screen.
     MapRequest(Xid),
     /// A mouse button has been pressed or released
@@ -71,6 +73,7 @@ impl std::fmt::Display for XEvent {
             KeyPress(_) => write!(f, "KeyPress"),
             Leave(_) => write!(f, "Leave"),
             MappingNotify => write!(f, "MappingNotify"),
+            MapNotify(_) => write!(f, "MapNotify"),
             MapRequest(_) => write!(f, "MapRequest"),
             MouseEvent(_) => write!(f, "MouseEvent"),
             MotionNotify(_) => write!(f, "MotionNotify"),
@@ -9,8 +9,9 @@ use crate::{
         property::{Prop, WindowAttributes, WmState},
         ClientAttr, ClientConfig, XConn,
     },
-    Result, Xid,
+    Color, Result, Xid,
 };
+use std::collections::HashMap;
 
 /// All methods on this trait that return a Result unimplemented by
 /// default unless an implementation is provided.
@@ -18,7 +19,7 @@ use crate::{
 ///
 /// Any implementation of `MockXConn` will automatically implement `XConn` by forwarding on
 /// calls to `$method` to `mock_$method`.
-#[allow(unused_variables)]
+#[allow(unused_variables, missing_docs)]
 pub trait MockXConn {
     fn mock_root(&self) -> Xid {
         Xid(0)
@@ -28,6 +29,24 @@ pub trait MockXConn {
         unimplemented!("mock_screen_details")
     }
 
+    /// Defaults to naming each screen returned by `mock_screen_details` by its index.
+    fn mock_output_details(&self) -> Result<Vec<(Option<String>, Rect)>> {
+        let rects = self.mock_screen_details()?;
+
+        Ok(rects
+            .into_iter()
+            .enumerate()
+            .map(|(i, r)| (Some(i.to_string()), r))
+            .collect())
+    }
+
+    /// Defaults to an unknown physical size for each screen returned by `mock_screen_details`.
+    fn mock_output_physical_sizes(&self) -> Result<Vec<Option<(u32, u32)>>> {
+        let rects = self.mock_screen_details()?;
+
+        Ok(vec![None; rects.len()])
+    }
+
     fn mock_cursor_position(&self) -> Result<Point> {
         unimplemented!("mock_cursor_position")
     }
@@ -36,6 +55,26 @@ pub trait MockXConn {
         unimplemented!("mock_grab")
     }
 
+    fn mock_grab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        unimplemented!("mock_grab_keys")
+    }
+
+    fn mock_ungrab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        unimplemented!("mock_ungrab_keys")
+    }
+
+    fn mock_grab_keyboard(&self) -> Result<()> {
+        unimplemented!("mock_grab_keyboard")
+    }
+
+    fn mock_ungrab_keyboard(&self) -> Result<()> {
+        unimplemented!("mock_ungrab_keyboard")
+    }
+
+    fn mock_keyboard_mapping(&self) -> Result<HashMap<u8, Vec<u32>>> {
+        unimplemented!("mock_keyboard_mapping")
+    }
+
     fn mock_next_event(&self) -> Result<XEvent> {
         unimplemented!("mock_next_event")
     }
@@ -106,6 +145,10 @@ pub trait MockXConn {
         unimplemented!("mock_set_client_attributes")
     }
 
+    fn mock_select_property_events(&self, client: Xid) -> Result<()> {
+        self.mock_set_client_attributes(client, &[ClientAttr::ClientUnmapMask])
+    }
+
     fn mock_set_client_config(&self, client: Xid, data: &[ClientConfig]) -> Result<()> {
         unimplemented!("mock_set_client_config")
     }
@@ -114,6 +157,10 @@ pub trait MockXConn {
         unimplemented!("mock_send_client_message")
     }
 
+    fn mock_set_root_background(&self, fills: &[(Rect, Color)]) -> Result<()> {
+        unimplemented!("mock_set_root_background")
+    }
+
     fn mock_warp_pointer(&self, id: Xid, x: i16, y: i16) -> Result<()> {
         unimplemented!("mock_warp_pointer")
     }
@@ -131,6 +178,14 @@ where
         self.mock_screen_details()
     }
 
+    fn output_details(&self) -> Result<Vec<(Option<String>, Rect)>> {
+        self.mock_output_details()
+    }
+
+    fn output_physical_sizes(&self) -> Result<Vec<Option<(u32, u32)>>> {
+        self.mock_output_physical_sizes()
+    }
+
     fn cursor_position(&self) -> Result<Point> {
         self.mock_cursor_position()
     }
@@ -139,6 +194,26 @@ where
         self.mock_grab(key_codes, mouse_states)
     }
 
+    fn grab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        self.mock_grab_keys(key_codes)
+    }
+
+    fn ungrab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        self.mock_ungrab_keys(key_codes)
+    }
+
+    fn grab_keyboard(&self) -> Result<()> {
+        self.mock_grab_keyboard()
+    }
+
+    fn ungrab_keyboard(&self) -> Result<()> {
+        self.mock_ungrab_keyboard()
+    }
+
+    fn keyboard_mapping(&self) -> Result<HashMap<u8, Vec<u32>>> {
+        self.mock_keyboard_mapping()
+    }
+
     fn next_event(&self) -> Result<XEvent> {
         self.mock_next_event()
     }
@@ -211,6 +286,10 @@ where
         self.mock_set_client_attributes(client, attrs)
     }
 
+    fn select_property_events(&self, client: Xid) -> Result<()> {
+        self.mock_select_property_events(client)
+    }
+
     fn set_client_config(&self, client: Xid, data: &[ClientConfig]) -> Result<()> {
         self.mock_set_client_config(client, data)
     }
@@ -219,6 +298,10 @@ where
         self.mock_send_client_message(msg)
     }
 
+    fn set_root_background(&self, fills: &[(Rect, Color)]) -> Result<()> {
+        self.mock_set_root_background(fills)
+    }
+
     fn warp_pointer(&self, id: Xid, x: i16, y: i16) -> Result<()> {
         self.mock_warp_pointer(id, x, y)
     }
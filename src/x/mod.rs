@@ -6,18 +6,26 @@ use crate::{
         ClientSet, Config, State,
     },
     pure::geometry::{Point, Rect},
-    x::{atom::AUTO_FLOAT_WINDOW_TYPES, event::ClientMessage, property::WmState},
-    Color, Result, Xid,
+    x::{
+        atom::AUTO_FLOAT_WINDOW_TYPES,
+        event::ClientMessage,
+        property::{Strut, WmState},
+    },
+    Color, Error, Result, Xid,
 };
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 use tracing::{debug, error, trace};
 
 pub mod atom;
 pub mod event;
 pub mod property;
 pub mod query;
+pub mod test;
 
 #[cfg(test)]
 pub mod mock;
@@ -30,6 +38,9 @@ pub use event::XEvent;
 pub use property::{Prop, WindowAttributes};
 pub use query::Query;
 
+/// The client message type sent to the root window by [XConn::wake_after].
+pub const WAKE_MESSAGE: &str = "_PENROSE_WAKE";
+
 /// A window type to be specified when creating a new window in the X server
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -71,6 +82,9 @@ pub enum ClientAttr {
     ClientEventMask,
     /// Set the pre-defined client event mask for sending unmap notify events
     ClientUnmapMask,
+    /// Set the pre-defined event mask for unmanaged dock windows (such as status bars) that
+    /// need to receive button press events
+    DockEventMask,
     /// Set the pre-defined root event mask
     RootEventMask,
 }
@@ -87,16 +101,91 @@ pub trait XConn {
     fn root(&self) -> Xid;
     /// Ask the X server for the dimensions of each currently available screen.
     fn screen_details(&self) -> Result<Vec<Rect>>;
+    /// Ask the X server for the name (if known) and dimensions of each currently available
+    /// output.
+    ///
+    /// Outputs must be returned in the same order as the screens from `screen_details`. Named
+    /// outputs keep their workspaces when screens are re-detected, while unnamed outputs are
+    /// matched to the previous screens by position.
+    ///
+    /// The default implementation returns the dimensions from `screen_details` without a name
+    /// for any of the outputs.
+    fn output_details(&self) -> Result<Vec<(Option<String>, Rect)>> {
+        let screens = self.screen_details()?;
+
+        Ok(screens.into_iter().map(|r| (None, r)).collect())
+    }
+    /// Ask the X server for the physical (width, height) in millimetres of each currently
+    /// available output, or `None` if the size of an output is unknown.
+    ///
+    /// Outputs must be returned in the same order as the screens from `screen_details` and
+    /// sizes should match the orientation of each screen, swapping the width and height of
+    /// rotated outputs if required.
+    ///
+    /// The default implementation reports the size of every output as unknown.
+    fn output_physical_sizes(&self) -> Result<Vec<Option<(u32, u32)>>> {
+        Ok(vec![None; self.screen_details()?.len()])
+    }
     /// Ask the X server for the current (x, y) coordinate of the mouse cursor.
     fn cursor_position(&self) -> Result<Point>;
 
     /// Grab the specified key and mouse states, intercepting them for processing within
     /// the window manager itself.
     fn grab(&self, key_codes: &[KeyCode], mouse_states: &[MouseState]) -> Result<()>;
+    /// Grab the specified key codes in addition to any that are already grabbed.
+    ///
+    /// The default implementation returns an error as incremental grabs are not supported.
+    fn grab_keys(&self, _key_codes: &[KeyCode]) -> Result<()> {
+        Err(Error::UnsupportedXConnMethod {
+            method: "grab_keys",
+        })
+    }
+    /// Release the grabs held on the specified key codes, leaving any other grabs in place.
+    ///
+    /// The default implementation returns an error as incremental grabs are not supported.
+    fn ungrab_keys(&self, _key_codes: &[KeyCode]) -> Result<()> {
+        Err(Error::UnsupportedXConnMethod {
+            method: "ungrab_keys",
+        })
+    }
+    /// Grab the keyboard so that all key presses are sent to the window manager until
+    /// `ungrab_keyboard` is called.
+    ///
+    /// The default implementation returns an error as grabbing the keyboard is not supported.
+    fn grab_keyboard(&self) -> Result<()> {
+        Err(Error::UnsupportedXConnMethod {
+            method: "grab_keyboard",
+        })
+    }
+    /// Release a keyboard grab made using `grab_keyboard`.
+    ///
+    /// The default implementation does nothing as the default `grab_keyboard` never succeeds.
+    fn ungrab_keyboard(&self) -> Result<()> {
+        Ok(())
+    }
+    /// Ask the X server for the keysyms bound to each key code in the current keyboard mapping.
+    ///
+    /// The default implementation returns an error as querying the keyboard mapping is not
+    /// supported: use [keycodes_from_xmodmap][crate::core::bindings::keycodes_from_xmodmap]
+    /// to look up key codes instead.
+    fn keyboard_mapping(&self) -> Result<HashMap<u8, Vec<u32>>> {
+        Err(Error::UnsupportedXConnMethod {
+            method: "keyboard_mapping",
+        })
+    }
     /// Block and wait for the next event from the X server so it can be processed.
     fn next_event(&self) -> Result<XEvent>;
     /// Flush any pending events to the X server.
     fn flush(&self);
+    /// Wake the main event loop once `delay` has elapsed by sending a [ClientMessage] of type
+    /// [WAKE_MESSAGE] to the root window. This allows time based state, such as pending key
+    /// chords, to be updated without waiting for the next event from the X server.
+    ///
+    /// The default implementation does nothing, in which case time based state is updated when
+    /// the next event is received.
+    fn wake_after(&self, _delay: Duration) -> Result<()> {
+        Ok(())
+    }
 
     /// Look up the [Xid] of a given [Atom] name. If it is not currently interned, intern it.
     fn intern_atom(&self, atom: &str) -> Result<Xid>;
@@ -134,10 +223,28 @@ pub trait XConn {
     fn delete_prop(&self, client: Xid, prop_name: &str) -> Result<()>;
     /// Set one or more [ClientAttr] for a given client window.
     fn set_client_attributes(&self, client: Xid, attrs: &[ClientAttr]) -> Result<()>;
+    /// Request property change events for a window that is not being managed, in addition to
+    /// any events that have already been selected for it (such as those selected by a status
+    /// bar for its own windows).
+    ///
+    /// The default implementation sets [ClientAttr::ClientUnmapMask], replacing any events that
+    /// were previously selected.
+    fn select_property_events(&self, client: Xid) -> Result<()> {
+        self.set_client_attributes(client, &[ClientAttr::ClientUnmapMask])
+    }
     /// Set the [ClientConfig] for a given client window.
     fn set_client_config(&self, client: Xid, data: &[ClientConfig]) -> Result<()>;
     /// Send a [ClientMessage] to a given client.
     fn send_client_message(&self, msg: ClientMessage) -> Result<()>;
+    /// Set the background of the root window, filling each of the given regions of the root
+    /// window with its associated [Color].
+    ///
+    /// The default implementation returns an error as setting the background is not supported.
+    fn set_root_background(&self, _fills: &[(Rect, Color)]) -> Result<()> {
+        Err(Error::UnsupportedXConnMethod {
+            method: "set_root_background",
+        })
+    }
 
     /// Reposition the mouse cursor to the given (x, y) coordinates within the specified window.
     /// This method should not be called directly: use `warp_pointer_to_window` or `warp_pointer_to_screen`
@@ -167,6 +274,7 @@ pub trait XConnExt: XConn + Sized {
     /// current X state.
     fn unmanage(&self, client: Xid, state: &mut State<Self>) -> Result<()> {
         trace!(?client, "removing client");
+        state.titles.remove(&client);
         self.modify_and_refresh(state, |cs| {
             cs.remove_client(&client);
         })
@@ -221,9 +329,12 @@ pub trait XConnExt: XConn + Sized {
         F: FnMut(&mut ClientSet),
     {
         f(&mut state.client_set); // mutating the existing state
+        state.client_set.pull_sticky_clients();
+        clear_urgency(self, state)?;
 
         let ss = state.position_and_snapshot(self);
         state.diff.update(ss);
+        state.update_focus_history();
 
         notify_killed(self, state)?;
         set_window_props(self, state)?;
@@ -243,6 +354,7 @@ pub trait XConnExt: XConn + Sized {
             }
         }
         state.config.refresh_hook = hook;
+        state.notify_subscribers();
 
         Ok(())
     }
@@ -286,6 +398,20 @@ pub trait XConnExt: XConn + Sized {
         self.set_client_attributes(id, &[ClientAttr::BorderColor(color.argb_u32())])
     }
 
+    /// Set the `_NET_WM_WINDOW_OPACITY` of the given client window.
+    ///
+    /// `opacity` is clamped to the range `0.0..=1.0` where `1.0` is fully opaque. This has no
+    /// visible effect unless a compositor is running.
+    fn set_opacity(&self, id: Xid, opacity: f32) -> Result<()> {
+        let raw = (opacity.clamp(0.0, 1.0) as f64 * u32::MAX as f64) as u32;
+
+        self.set_prop(
+            id,
+            Atom::NetWmWindowOpacity.as_ref(),
+            Prop::Cardinal(vec![raw]),
+        )
+    }
+
     /// Set the initial window properties for a newly managed window.
     fn set_initial_properties(&self, client: Xid, config: &Config<Self>) -> Result<()> {
         let Config {
@@ -317,12 +443,27 @@ pub trait XConnExt: XConn + Sized {
         self.set_client_config(client, &[ClientConfig::Position(r)])
     }
 
+    /// Shrink the given [Rect] to honour the resize increment and aspect ratio hints of a
+    /// client (if it has any).
+    ///
+    /// See [WmNormalHints::snap_to][crate::x::property::WmNormalHints::snap_to] for details.
+    fn snap_to_size_hints(&self, client: Xid, r: Rect) -> Rect {
+        match self.get_prop(client, Atom::WmNormalHints.as_ref()) {
+            Ok(Some(Prop::WmNormalHints(hints))) => hints.snap_to(r),
+            _ => r,
+        }
+    }
+
     /// Restack and set the geometry for an ordered list of client windows and their
     /// associated positions. The provided positions are shrunk by the current border
     /// size in order to position the windows correctly within the frame given by the
     /// border.
     ///
     /// See `restack` for details of stacking order is determined.
+    ///
+    /// Clients are only repositioned if their position has changed since the last time that
+    /// they were positioned: see `State::force_reposition` for forcing all clients to be
+    /// repositioned.
     fn position_clients(&self, state: &State<Self>) -> Result<()> {
         let border = state.config.border_width;
         let positions = &state.diff.after.positions;
@@ -330,11 +471,26 @@ pub trait XConnExt: XConn + Sized {
 
         self.restack(positions.iter().map(|(id, _)| id))?;
 
+        let cs = &state.client_set;
+        let mut applied = state.applied_positions.borrow_mut();
+        applied.retain(|c, _| cs.contains(c));
+
         for &(c, mut r) in positions.iter() {
             if !screen_positions.contains(&r) {
                 r = r.shrink_in(border);
             }
+
+            if state.config.respect_size_hints && !cs.is_floating(&c) {
+                r = self.snap_to_size_hints(c, r);
+            }
+
+            if applied.get(&c) == Some(&r) {
+                trace!(%c, ?r, "client position unchanged");
+                continue;
+            }
+
             self.position_client(c, r)?;
+            applied.insert(c, r);
         }
 
         Ok(())
@@ -431,6 +587,18 @@ pub trait XConnExt: XConn + Sized {
         }
     }
 
+    /// Request the space a window reserves at the edges of the screen via the
+    /// _NET_WM_STRUT_PARTIAL property, falling back to _NET_WM_STRUT if that is not set.
+    fn window_strut(&self, id: Xid) -> Result<Option<Strut>> {
+        for atom in [Atom::NetWmStrutPartial, Atom::NetWmStrut] {
+            if let Some(Prop::Cardinal(vals)) = self.get_prop(id, atom.as_ref())? {
+                return Strut::try_from_bytes(&vals).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Run the provided [Query], returning the result.
     fn query(&self, query: &dyn Query<Self>, id: Xid) -> Result<bool> {
         query.run(id, self)
@@ -481,6 +649,9 @@ pub(crate) fn manage_without_refresh<X: XConn>(
         None => state.client_set.insert(id),
     }
 
+    let title = x.window_title(id).unwrap_or_default();
+    state.titles.insert(id, title);
+
     if should_float {
         debug!(%id, "client should float");
         let r = floating_client_position(id, transient_for, state, x)?;
@@ -498,6 +669,8 @@ pub(crate) fn manage_without_refresh<X: XConn>(
     }
     state.config.manage_hook = hook;
 
+    run_one_shot_manage_hooks(id, state, x);
+
     debug!(
         floating=?state.client_set.floating, "floating clients"
     );
@@ -505,6 +678,23 @@ pub(crate) fn manage_without_refresh<X: XConn>(
     Ok(())
 }
 
+// Expired hooks are dropped and only the first matching hook is run for each new client.
+fn run_one_shot_manage_hooks<X: XConn>(id: Xid, state: &mut State<X>, x: &X) {
+    let mut hooks = std::mem::take(&mut state.one_shot_manage_hooks);
+    hooks.retain(|h| !h.has_expired());
+
+    if let Some(ix) = hooks.iter().position(|h| h.matches(id, x)) {
+        trace!(%id, "running one shot manage hook");
+        if let Err(e) = hooks.remove(ix).hook.call(id, state, x) {
+            error!(%e, "error returned from one shot manage hook");
+        }
+    }
+
+    // The hook that was run may have added new one shot hooks of its own
+    hooks.append(&mut state.one_shot_manage_hooks);
+    state.one_shot_manage_hooks = hooks;
+}
+
 /// When positioning a floating client we try to position them in priority order of:
 ///   - the client's requested position if it is not at the origin
 ///   - centered in their parent's screen (if transient)
@@ -524,22 +714,49 @@ fn floating_client_position<X: XConn>(
         return Ok(r_initial);
     }
 
-    let r_parent = transient_for
+    // Transient clients are centered over their parent window if they fit, falling back to
+    // being centered on the parent's screen and then the focused screen if they do not.
+    let r_screen = transient_for
         .and_then(|parent| state.client_set.screen_for_client(&parent))
         .unwrap_or(&state.client_set.screens.focus)
         .r;
-    debug!(?r_parent, "parent geometry");
-
-    let r_final = r_initial.centered_in(&r_parent).unwrap_or_else(|| {
-        r_initial
-            .centered_in(&state.client_set.screens.focus.r)
-            .unwrap_or(r_initial)
-    });
+    let r_parent = transient_for
+        .filter(|parent| state.client_set.contains(parent))
+        .and_then(|parent| {
+            state
+                .client_geometry(parent)
+                .or_else(|| x.client_geometry(parent).ok())
+        });
+    debug!(?r_parent, ?r_screen, "parent geometry");
+
+    let r_final = r_parent
+        .and_then(|r| r_initial.centered_in(&r))
+        .or_else(|| r_initial.centered_in(&r_screen))
+        .or_else(|| r_initial.centered_in(&state.client_set.screens.focus.r))
+        .unwrap_or(r_initial);
     debug!(?r_final, "final geometry");
 
     Ok(r_final)
 }
 
+// Urgent clients have their urgency cleared once they are focused or if they are removed
+// from the client set before ever being viewed.
+fn clear_urgency<X: XConn>(x: &X, state: &mut State<X>) -> Result<()> {
+    let focused = state.client_set.current_client().copied();
+    let cleared: Vec<Xid> = state
+        .urgent
+        .iter()
+        .filter(|&id| Some(*id) == focused || !state.client_set.contains(id))
+        .copied()
+        .collect();
+
+    for id in cleared {
+        state.set_urgent(id, false, x)?;
+    }
+
+    Ok(())
+}
+
 fn notify_killed<X: XConn>(x: &X, state: &mut State<X>) -> Result<()> {
     for &c in state.diff.killed_clients() {
         x.kill(c)?;
@@ -554,7 +771,7 @@ fn set_window_props<X: XConn>(x: &X, state: &mut State<X>) -> Result<()> {
     }
 
     if let Some(focused) = state.diff.before.focused_client {
-        x.set_client_border_color(focused, state.config.normal_border)?;
+        x.set_client_border_color(focused, state.border_color(focused))?;
     }
 
     if let Some(&focused) = state.client_set.current_client() {
@@ -577,33 +794,37 @@ fn notify_hidden_workspaces<X: XConn>(state: &mut State<X>) {
 
 // Warp the cursor if this diff resulted in a focus change
 fn handle_pointer_change<X: XConn>(x: &X, state: &mut State<X>) -> Result<()> {
-    if !state.config.focus_follow_mouse {
+    if !(state.config.focus_follow_mouse || state.config.warp_cursor_on_focus) {
+        return Ok(());
+    }
+
+    // Focus changes that were caused by the mouse never warp the cursor: doing so fights with
+    // the user moving the mouse and generates further enter events as clients are crossed.
+    if matches!(
+        state.current_event,
+        Some(XEvent::Enter(_) | XEvent::MouseEvent(_) | XEvent::MotionNotify(_))
+    ) {
         return Ok(());
     }
 
     trace!("checking if focus should change");
-    if !matches!(state.current_event, Some(XEvent::Enter(_))) {
-        if let Some(id) = state.diff.focused_client() {
-            trace!("focused client changed");
-            // NOTE: Some of the behaviour here is based on looking at whether or
-            //       not the focused client has changed position as part of this
-            //       diff. That is going to cause issues if and when mouse based
-            //       window movement is implemented.
-            let focus_changed = state.diff.focused_client_changed();
-            let focused_client_moved = state.diff.client_changed_position(&id);
-
-            if focus_changed || focused_client_moved {
-                trace!(
-                    focus_changed,
-                    focused_client_moved,
-                    "warping to focused client"
-                );
-                x.warp_pointer_to_window(id)?;
-            }
-        } else if let Some(index) = state.diff.newly_focused_screen() {
-            trace!(index, "screen changed: warping to screen");
-            x.warp_pointer_to_screen(state, index)?;
+    if let Some(id) = state.diff.focused_client() {
+        trace!("focused client changed");
+        let focus_changed = state.diff.focused_client_changed();
+        let focused_client_moved = state.diff.client_changed_position(&id);
+
+        if focus_changed || focused_client_moved {
+            trace!(
+                focus_changed,
+                focused_client_moved,
+                "warping to focused client"
+            );
+            let (px, py) = state.config.warp_to.relative_to(x.client_geometry(id)?);
+            x.warp_pointer(id, px, py)?;
         }
+    } else if let Some(index) = state.diff.newly_focused_screen() {
+        trace!(index, "screen changed: warping to screen");
+        x.warp_pointer_to_screen(state, index)?;
     }
 
     Ok(())
@@ -639,13 +860,22 @@ fn set_focus<X: XConn>(x: &X, state: &mut State<X>) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{map, Error, Result};
+    use crate::{
+        builtin::actions::spawn_and_focus,
+        map,
+        x::{
+            property::{WmNormalHints, WmNormalHintsFlags},
+            test::TestXConn,
+        },
+        Error, Result,
+    };
     use simple_test_case::test_case;
-    use std::collections::HashMap;
+    use std::{collections::HashMap, time::Duration};
 
     #[derive(Default)]
     struct TransientXConn {
         transient_ids: HashMap<Xid, Xid>,
+        window_types: HashMap<Xid, Atom>,
         geometry: HashMap<Xid, Rect>,
     }
 
@@ -662,6 +892,10 @@ mod tests {
                 self.transient_ids
                     .get(&client)
                     .map(|id| Prop::Window(vec![*id]))
+            } else if prop_name == Atom::NetWmWindowType.as_ref() {
+                self.window_types
+                    .get(&client)
+                    .map(|a| Prop::Atom(vec![a.as_ref().to_string()]))
             } else {
                 None
             };
@@ -681,7 +915,7 @@ mod tests {
         Rect::new(0, 0, 600, 400),
         Rect::new(0, 0, 20, 20),
         0,
-        Rect::new(502, 374, 20, 20);
+        Rect::new(290, 190, 20, 20);
         "fit inside parent"
     )]
     #[test_case(
@@ -708,6 +942,7 @@ mod tests {
                 Xid(1) => child,
                 Xid(2) => parent,
             },
+            ..Default::default()
         };
         let mut state = State::try_new(Default::default(), &conn).expect("test state");
         state.client_set.focus_screen(screen);
@@ -729,4 +964,141 @@ mod tests {
 
         assert_eq!(r, expected, "client position is as expected");
     }
+
+    #[test_case(false; "tiled parent")]
+    #[test_case(true; "floating parent")]
+    #[test]
+    fn transient_clients_are_focused_and_stacked_above_their_parent(parent_floats: bool) {
+        let conn = TransientXConn {
+            transient_ids: map! {
+                Xid(1) => Xid(2),
+            },
+            geometry: map! {
+                Xid(1) => Rect::new(0, 0, 20, 20),
+                Xid(2) => Rect::new(100, 100, 200, 200),
+            },
+            ..Default::default()
+        };
+        let mut state = State::try_new(Default::default(), &conn).expect("test state");
+        state.client_set.insert(Xid(2));
+        state.client_set.insert(Xid(3));
+        if parent_floats {
+            state
+                .client_set
+                .float_unchecked(Xid(2), Rect::new(100, 100, 200, 200));
+        }
+
+        manage_without_refresh(Xid(1), None, &mut state, &conn).expect("manage");
+
+        assert_eq!(state.client_set.current_client(), Some(&Xid(1)));
+        assert_eq!(
+            state.client_set.floating[&Xid(1)].applied_to(&TEST_SCREEN),
+            Rect::new(190, 190, 20, 20),
+            "transient is centered over its parent"
+        );
+
+        let positions = state.visible_client_positions(&conn);
+        let ix = |id| positions.iter().position(|&(c, _)| c == id).unwrap();
+
+        assert!(
+            ix(Xid(1)) > ix(Xid(2)),
+            "transient above parent: {positions:?}"
+        );
+    }
+
+    #[test_case(Atom::NetWindowTypeDialog; "dialog")]
+    #[test_case(Atom::NetWindowTypeUtility; "utility")]
+    #[test_case(Atom::NetWindowTypeSplash; "splash")]
+    #[test]
+    fn dialog_window_types_are_floated(ty: Atom) {
+        let conn = TransientXConn {
+            window_types: map! {
+                Xid(1) => ty,
+            },
+            geometry: map! {
+                Xid(1) => Rect::new(0, 0, 24, 28),
+            },
+            ..Default::default()
+        };
+        let mut state = State::try_new(Default::default(), &conn).expect("test state");
+
+        manage_without_refresh(Xid(1), None, &mut state, &conn).expect("manage");
+
+        assert_eq!(state.client_set.current_client(), Some(&Xid(1)));
+        assert_eq!(
+            state.client_set.floating[&Xid(1)].applied_to(&TEST_SCREEN),
+            Rect::new(500, 370, 24, 28),
+            "client is centered on the focused screen"
+        );
+    }
+
+    struct IsClient(Xid);
+
+    impl<X: XConn> Query<X> for IsClient {
+        fn run(&self, id: Xid, _: &X) -> Result<bool> {
+            Ok(id == self.0)
+        }
+    }
+
+    #[test]
+    fn spawn_and_focus_focuses_the_first_matching_client() {
+        let conn = TransientXConn::default();
+        let mut state = State::try_new(Default::default(), &conn).expect("test state");
+        let mut handler = spawn_and_focus("true", IsClient(Xid(2)));
+
+        handler.call(&mut state, &conn).expect("spawn");
+        state.client_set.focus_tag("2");
+
+        manage_without_refresh(Xid(1), Some("1"), &mut state, &conn).expect("manage");
+        assert_eq!(state.client_set.current_tag(), "2", "non-matching client");
+
+        manage_without_refresh(Xid(2), Some("1"), &mut state, &conn).expect("manage");
+        assert_eq!(state.client_set.current_tag(), "1");
+        assert_eq!(state.client_set.current_client(), Some(&Xid(2)));
+        assert!(state.one_shot_manage_hooks.is_empty(), "hook is removed");
+    }
+
+    #[test]
+    fn expired_one_shot_manage_hooks_are_not_run() {
+        let conn = TransientXConn::default();
+        let mut state = State::try_new(Default::default(), &conn).expect("test state");
+        let hook = |id: Xid, s: &mut State<TransientXConn>, _: &TransientXConn| {
+            s.client_set.focus_client(&id);
+            Ok(())
+        };
+
+        state.add_one_shot_manage_hook(IsClient(Xid(1)), hook, Duration::ZERO);
+        state.client_set.focus_tag("2");
+        manage_without_refresh(Xid(1), Some("1"), &mut state, &conn).expect("manage");
+
+        assert_eq!(state.client_set.current_tag(), "2");
+        assert!(state.one_shot_manage_hooks.is_empty(), "hook is removed");
+    }
+
+    #[test_case(false, Rect::new(0, 0, 596, 796); "disabled")]
+    #[test_case(true, Rect::new(0, 0, 590, 796); "enabled")]
+    #[test]
+    fn respect_size_hints_snaps_tiled_clients_within_their_tile(respect: bool, expected: Rect) {
+        let hints = WmNormalHints::new(WmNormalHintsFlags::empty(), None, None, None, None)
+            .with_increments(10, 1);
+        let x = TestXConn::new(vec![Rect::new(0, 0, 1000, 800)]).with_prop(
+            Xid(1),
+            Atom::WmNormalHints.as_ref(),
+            Prop::WmNormalHints(hints),
+        );
+        let config = Config {
+            respect_size_hints: respect,
+            ..Config::default()
+        };
+        let mut state = x.new_state(config).unwrap();
+
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert(Xid(2));
+            cs.insert(Xid(1));
+        })
+        .unwrap();
+
+        assert_eq!(x.geometry(Xid(1)), Some(expected));
+        assert_eq!(x.geometry(Xid(2)), Some(Rect::new(600, 0, 396, 796)));
+    }
 }
@@ -221,10 +221,9 @@ impl WmHints {
 /// Client requested hints about window geometry.
 ///
 /// See the ICCCM [spec][1] for further details or the [Xlib manual][2] for more details of the
-/// data fromat but note that Penrose does not honour the following hints:
-///   - gravity
-///   - increment
-///   - aspect ratio
+/// data fromat but note that Penrose does not honour the gravity hint. Resize increments and
+/// aspect ratios are only applied to tiled clients when `respect_size_hints` is set in your
+/// [Config][crate::core::Config] (see [WmNormalHints::snap_to]).
 ///
 /// [1]: https://www.x.org/releases/X11R7.6/doc/xorg-docs/specs/ICCCM/icccm.html#wm_normal_hints_property
 /// [2]: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/wm-normal-hints.html
@@ -236,6 +235,8 @@ pub struct WmNormalHints {
     pub(crate) min: Option<Rect>,
     pub(crate) max: Option<Rect>,
     pub(crate) user_specified: Option<Rect>,
+    pub(crate) inc: Option<(u32, u32)>,
+    pub(crate) aspect: Option<((u32, u32), (u32, u32))>,
 }
 
 impl WmNormalHints {
@@ -253,9 +254,26 @@ impl WmNormalHints {
             min,
             max,
             user_specified,
+            inc: None,
+            aspect: None,
         }
     }
 
+    /// Set the width and height resize increments for these hints.
+    pub fn with_increments(mut self, w: u32, h: u32) -> Self {
+        self.flags |= WmNormalHintsFlags::P_RESIZE_INC;
+        self.inc = Some((w, h));
+        self
+    }
+
+    /// Set the minimum and maximum aspect ratios for these hints as `(numerator, denominator)`
+    /// pairs of `width / height`.
+    pub fn with_aspect(mut self, min: (u32, u32), max: (u32, u32)) -> Self {
+        self.flags |= WmNormalHintsFlags::P_ASPECT;
+        self.aspect = Some((min, max));
+        self
+    }
+
     /// Apply these size hints to a given [Rect].
     ///
     /// > Currently only the max size is respected
@@ -277,6 +295,45 @@ impl WmNormalHints {
         r
     }
 
+    /// Shrink the size of a given [Rect] so that it satisfies the aspect ratio and resize
+    /// increment hints (if set), leaving its position unchanged.
+    ///
+    /// The result is never larger than `r`: the aspect ratio is enforced first by reducing
+    /// either the width or height, after which each dimension is snapped down to the base size
+    /// (or min size if no base size is set) plus a whole number of increments.
+    pub fn snap_to(&self, mut r: Rect) -> Rect {
+        if let Some(((min_n, min_d), (max_n, max_d))) = self.aspect {
+            let (w, h) = (r.w as u64, r.h as u64);
+            if w * (min_d as u64) < h * (min_n as u64) {
+                // too tall for the minimum aspect ratio
+                r.h = (w * min_d as u64 / min_n as u64) as u32;
+            } else if w * (max_d as u64) > h * (max_n as u64) {
+                // too wide for the maximum aspect ratio
+                r.w = (h * max_n as u64 / max_d as u64) as u32;
+            }
+        }
+
+        if let Some((inc_w, inc_h)) = self.inc {
+            let (base_w, base_h) = match self.base.or(self.min) {
+                Some(b) => (b.w, b.h),
+                None => (0, 0),
+            };
+
+            let snap = |len: u32, base: u32, inc: u32| {
+                if inc <= 1 || len <= base {
+                    len
+                } else {
+                    base + (len - base) / inc * inc
+                }
+            };
+
+            r.w = snap(r.w, base_w, inc_w);
+            r.h = snap(r.h, base_h, inc_h);
+        }
+
+        r
+    }
+
     /// Try to construct a [WmNormalHints] instance from raw bytes.
     ///
     /// This method expects a slice of 18 u32s corresponding to the C struct layout shown below.
@@ -317,10 +374,26 @@ impl WmNormalHints {
 
         let (min_w, min_h) = (raw[5], raw[6]);
         let (max_w, max_h) = (raw[7], raw[8]);
+        let (inc_w, inc_h) = (raw[9], raw[10]);
+        let (min_aspect, max_aspect) = ((raw[11], raw[12]), (raw[13], raw[14]));
         let (base_w, base_h) = (raw[15], raw[16]);
 
-        // ignoring increment, aspect ratio, gravity as they are not used in
-        // the main WindowManager logic
+        // ignoring gravity as it is not used in the main WindowManager logic
+        let inc = if flags.contains(WmNormalHintsFlags::P_RESIZE_INC) && inc_w > 0 && inc_h > 0 {
+            Some((inc_w, inc_h))
+        } else {
+            None
+        };
+
+        let valid_ratio = |(n, d): (u32, u32)| n > 0 && d > 0;
+        let aspect = if flags.contains(WmNormalHintsFlags::P_ASPECT)
+            && valid_ratio(min_aspect)
+            && valid_ratio(max_aspect)
+        {
+            Some((min_aspect, max_aspect))
+        } else {
+            None
+        };
 
         let if_set = |x, y, w, h| {
             if w > 0 && h > 0 {
@@ -336,10 +409,129 @@ impl WmNormalHints {
             min: if_set(x, y, min_w, min_h),
             max: if_set(x, y, max_w, max_h),
             user_specified: if_set(x, y, user_w, user_h),
+            inc,
+            aspect,
         })
     }
 }
 
+/// Space reserved at the edges of the screen by a client such as a status bar or dock.
+///
+/// Struts are specified relative to the edges of the root window rather than individual
+/// screens. See the EWMH [spec][1] for further details.
+///
+/// [1]: https://specifications.freedesktop.org/wm-spec/wm-spec-latest.html#idm45805407959456
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Strut {
+    pub(crate) left: u32,
+    pub(crate) right: u32,
+    pub(crate) top: u32,
+    pub(crate) bottom: u32,
+    pub(crate) left_start_y: u32,
+    pub(crate) left_end_y: u32,
+    pub(crate) right_start_y: u32,
+    pub(crate) right_end_y: u32,
+    pub(crate) top_start_x: u32,
+    pub(crate) top_end_x: u32,
+    pub(crate) bottom_start_x: u32,
+    pub(crate) bottom_end_x: u32,
+}
+
+impl Strut {
+    /// Create a new instance that reserves space along the full length of each edge.
+    pub fn new(left: u32, right: u32, top: u32, bottom: u32) -> Self {
+        Self {
+            left,
+            right,
+            top,
+            bottom,
+            left_end_y: u32::MAX,
+            right_end_y: u32::MAX,
+            top_end_x: u32::MAX,
+            bottom_end_x: u32::MAX,
+            ..Default::default()
+        }
+    }
+
+    /// Try to construct a [Strut] instance from raw bytes.
+    ///
+    /// This method expects either a slice of 4 u32s as set in `_NET_WM_STRUT` or a slice of
+    /// 12 u32s as set in `_NET_WM_STRUT_PARTIAL`, corresponding to the layout shown below.
+    ///
+    /// ```text
+    /// left, right, top, bottom,
+    /// left_start_y, left_end_y, right_start_y, right_end_y,
+    /// top_start_x, top_end_x, bottom_start_x, bottom_end_x
+    /// ```
+    pub fn try_from_bytes(raw: &[u32]) -> Result<Self> {
+        match *raw {
+            [left, right, top, bottom] => Ok(Self::new(left, right, top, bottom)),
+
+            [left, right, top, bottom, left_start_y, left_end_y, right_start_y, right_end_y, top_start_x, top_end_x, bottom_start_x, bottom_end_x] => {
+                Ok(Self {
+                    left,
+                    right,
+                    top,
+                    bottom,
+                    left_start_y,
+                    left_end_y,
+                    right_start_y,
+                    right_end_y,
+                    top_start_x,
+                    top_end_x,
+                    bottom_start_x,
+                    bottom_end_x,
+                })
+            }
+
+            _ => Err(Error::InvalidHints {
+                reason: format!(
+                    "raw bytes should be [u32; 4] or [u32; 12] for Strut, got [u32; {}]",
+                    raw.len()
+                ),
+            }),
+        }
+    }
+
+    /// Shrink the given screen [Rect] to exclude the space reserved by this strut.
+    ///
+    /// `r_root` should be the bounding [Rect] of all screens, which is what the strut
+    /// values are measured relative to.
+    pub fn apply_to(&self, mut r: Rect, r_root: Rect) -> Rect {
+        let overlaps =
+            |start: u32, end: u32, r_start: u32, len: u32| start < r_start + len && end >= r_start;
+
+        if self.top > 0 && overlaps(self.top_start_x, self.top_end_x, r.x, r.w) {
+            let edge = (r_root.y + self.top).clamp(r.y, r.y + r.h);
+            r.h -= edge - r.y;
+            r.y = edge;
+        }
+
+        if self.bottom > 0 && overlaps(self.bottom_start_x, self.bottom_end_x, r.x, r.w) {
+            let edge = (r_root.y + r_root.h)
+                .saturating_sub(self.bottom)
+                .clamp(r.y, r.y + r.h);
+            r.h = edge - r.y;
+        }
+
+        if self.left > 0 && overlaps(self.left_start_y, self.left_end_y, r.y, r.h) {
+            let edge = (r_root.x + self.left).clamp(r.x, r.x + r.w);
+            r.w -= edge - r.x;
+            r.x = edge;
+        }
+
+        if self.right > 0 && overlaps(self.right_start_y, self.right_end_y, r.y, r.h) {
+            let edge = (r_root.x + r_root.w)
+                .saturating_sub(self.right)
+                .clamp(r.x, r.x + r.w);
+            r.w = edge - r.x;
+        }
+
+        r
+    }
+}
+
 /// Window Attributes honoured by penose.
 ///
 /// Only a small subset of window attributes are checked and honoured by penrose. This list may be
@@ -386,3 +578,60 @@ impl WindowAttributes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use simple_test_case::test_case;
+
+    fn hints() -> WmNormalHints {
+        WmNormalHints::new(WmNormalHintsFlags::empty(), None, None, None, None)
+    }
+
+    #[test_case(hints(), Rect::new(10, 20, 996, 796); "no hints")]
+    #[test_case(hints().with_increments(10, 20), Rect::new(10, 20, 990, 780); "increments")]
+    #[test_case(
+        WmNormalHints::new(
+            WmNormalHintsFlags::P_BASE_SIZE,
+            Some(Rect::new(0, 0, 4, 6)),
+            None,
+            None,
+            None
+        )
+        .with_increments(10, 20),
+        Rect::new(10, 20, 994, 786);
+        "increments from base size"
+    )]
+    #[test_case(hints().with_aspect((1, 1), (1, 1)), Rect::new(10, 20, 796, 796); "square")]
+    #[test_case(hints().with_aspect((2, 1), (4, 1)), Rect::new(10, 20, 996, 498); "wide")]
+    #[test]
+    fn snap_to(hints: WmNormalHints, expected: Rect) {
+        let snapped = hints.snap_to(Rect::new(10, 20, 996, 796));
+
+        assert_eq!(snapped, expected);
+    }
+
+    #[test]
+    fn increments_and_aspect_are_parsed_when_flagged() {
+        let flags = WmNormalHintsFlags::P_RESIZE_INC | WmNormalHintsFlags::P_ASPECT;
+        let mut raw = [0; 18];
+        raw[0] = flags.bits();
+        raw[9..15].copy_from_slice(&[7, 15, 1, 2, 3, 1]);
+
+        let hints = WmNormalHints::try_from_bytes(&raw).unwrap();
+
+        assert_eq!(hints.inc, Some((7, 15)));
+        assert_eq!(hints.aspect, Some(((1, 2), (3, 1))));
+    }
+
+    #[test]
+    fn increments_and_aspect_are_ignored_when_not_flagged() {
+        let mut raw = [0; 18];
+        raw[9..15].copy_from_slice(&[7, 15, 1, 2, 3, 1]);
+
+        let hints = WmNormalHints::try_from_bytes(&raw).unwrap();
+
+        assert_eq!(hints.inc, None);
+        assert_eq!(hints.aspect, None);
+    }
+}
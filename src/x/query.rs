@@ -1,6 +1,6 @@
 //! Queries against client windows
 use crate::{
-    x::{atom::Atom, property::Prop, XConn},
+    x::{atom::Atom, property::Prop, XConn, XConnExt},
     Result, Xid,
 };
 use std::fmt;
@@ -73,7 +73,9 @@ where
     }
 }
 
-/// A [Query] for fetching a window's title following ICCCM / EWMH standards.
+/// A [Query] for matching a window's title exactly following ICCCM / EWMH standards.
+///
+/// The title is read from WM_NAME, falling back to _NET_WM_NAME if it is not set.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Title(pub &'static str);
 
@@ -82,14 +84,54 @@ where
     X: XConn,
 {
     fn run(&self, id: Xid, x: &X) -> Result<bool> {
-        let strs = str_prop(Atom::WmName, id, x)
-            .ok()
-            .or_else(|| str_prop(Atom::NetWmName, id, x).ok())
-            .flatten();
+        Ok(x.window_title(id)? == self.0)
+    }
+}
 
-        match strs {
-            Some(strs) if !strs.is_empty() => Ok(strs[0] == self.0),
-            _ => Ok(false),
+/// A [Query] for checking whether a window's title contains the given substring.
+///
+/// See [Title] for details of how the title is read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TitleContains(pub &'static str);
+
+impl<X> Query<X> for TitleContains
+where
+    X: XConn,
+{
+    fn run(&self, id: Xid, x: &X) -> Result<bool> {
+        Ok(x.window_title(id)?.contains(self.0))
+    }
+}
+
+/// A [Query] for matching a window's role (the WM_WINDOW_ROLE property) exactly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WindowRole(pub &'static str);
+
+impl<X> Query<X> for WindowRole
+where
+    X: XConn,
+{
+    fn run(&self, id: Xid, x: &X) -> Result<bool> {
+        match str_prop(Atom::WmWindowRole, id, x)? {
+            Some(strs) => Ok(strs[0] == self.0),
+            None => Ok(false),
+        }
+    }
+}
+
+/// A [Query] for checking whether a window's role (the WM_WINDOW_ROLE property) contains
+/// the given substring.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WindowRoleContains(pub &'static str);
+
+impl<X> Query<X> for WindowRoleContains
+where
+    X: XConn,
+{
+    fn run(&self, id: Xid, x: &X) -> Result<bool> {
+        match str_prop(Atom::WmWindowRole, id, x)? {
+            Some(strs) => Ok(strs[0].contains(self.0)),
+            None => Ok(false),
         }
     }
 }
@@ -184,3 +226,114 @@ impl<X: XConn> Query<X> for NotQuery<X> {
         Ok(!self.inner.run(id, x)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x::MockXConn;
+    use simple_test_case::test_case;
+
+    // Client 1 is a firefox window titled "bar", client 2 is a firefox dialog that only sets
+    // _NET_WM_NAME and all other clients are an untitled "foo"
+    struct QueryXConn;
+
+    impl MockXConn for QueryXConn {
+        fn mock_get_prop(&self, id: Xid, prop_name: &str) -> Result<Option<Prop>> {
+            let strs = match (*id, prop_name) {
+                (1, "WM_CLASS") => vec!["firefox", "Firefox"],
+                (1, "WM_NAME") => vec!["bar"],
+                (2, "WM_CLASS") => vec!["firefox", "Firefox"],
+                (2, "_NET_WM_NAME") => vec!["Save As - Mozilla Firefox"],
+                (2, "WM_WINDOW_ROLE") => vec!["GtkFileChooserDialog"],
+                (_, "WM_CLASS") => vec!["foo", "Foo"],
+                _ => return Ok(None),
+            };
+
+            Ok(Some(Prop::UTF8String(
+                strs.into_iter().map(|s| s.to_owned()).collect(),
+            )))
+        }
+    }
+
+    fn run(q: impl Query<QueryXConn>, id: u32) -> bool {
+        q.run(Xid(id), &QueryXConn).unwrap()
+    }
+
+    #[test_case(1, true; "matching client")]
+    #[test_case(3, false; "non-matching client")]
+    #[test]
+    fn and_requires_both_queries_to_match(id: u32, expected: bool) {
+        let q = ClassName("Firefox").and(Title("bar"));
+
+        assert_eq!(run(q, id), expected);
+    }
+
+    #[test]
+    fn and_fails_if_either_query_fails() {
+        assert!(!run(ClassName("Firefox").and(Title("baz")), 1));
+        assert!(!run(ClassName("Foo").and(Title("bar")), 1));
+    }
+
+    #[test_case(1, true; "first query matches")]
+    #[test_case(3, true; "second query matches")]
+    #[test]
+    fn or_requires_either_query_to_match(id: u32, expected: bool) {
+        let q = ClassName("Firefox").or(AppName("foo"));
+
+        assert_eq!(run(q, id), expected);
+    }
+
+    #[test]
+    fn or_fails_if_neither_query_matches() {
+        assert!(!run(ClassName("Alacritty").or(Title("baz")), 1));
+    }
+
+    #[test_case(1, false; "matching client")]
+    #[test_case(3, true; "non-matching client")]
+    #[test]
+    fn not_inverts_the_inner_query(id: u32, expected: bool) {
+        assert_eq!(run(ClassName("Firefox").not(), id), expected);
+    }
+
+    #[test]
+    fn combinators_can_be_nested() {
+        let q = || AppName("foo").or(ClassName("Firefox").and(Title("bar").not()));
+
+        assert!(run(q(), 3), "foo matches the first branch");
+        assert!(!run(q(), 1), "firefox titled bar matches neither branch");
+    }
+
+    #[test_case(Title("bar"), 1, true; "title matching wm name")]
+    #[test_case(Title("ba"), 1, false; "title partial wm name")]
+    #[test_case(Title("Save As - Mozilla Firefox"), 2, true; "title matching net wm name")]
+    #[test_case(Title("bar"), 3, false; "title not set")]
+    #[test]
+    fn title_matches_exactly(q: Title, id: u32, expected: bool) {
+        assert_eq!(run(q, id), expected);
+    }
+
+    #[test_case(TitleContains("ba"), 1, true; "substring of wm name")]
+    #[test_case(TitleContains("Mozilla"), 2, true; "substring of net wm name")]
+    #[test_case(TitleContains("Chromium"), 2, false; "not a substring")]
+    #[test_case(TitleContains("bar"), 3, false; "title not set")]
+    #[test]
+    fn title_contains_matches_substrings(q: TitleContains, id: u32, expected: bool) {
+        assert_eq!(run(q, id), expected);
+    }
+
+    #[test_case(WindowRole("GtkFileChooserDialog"), 2, true; "exact match")]
+    #[test_case(WindowRole("GtkFileChooser"), 2, false; "partial match")]
+    #[test_case(WindowRole("GtkFileChooserDialog"), 1, false; "role not set")]
+    #[test]
+    fn window_role_matches_exactly(q: WindowRole, id: u32, expected: bool) {
+        assert_eq!(run(q, id), expected);
+    }
+
+    #[test_case(WindowRoleContains("FileChooser"), 2, true; "substring")]
+    #[test_case(WindowRoleContains("browser"), 2, false; "not a substring")]
+    #[test_case(WindowRoleContains("FileChooser"), 1, false; "role not set")]
+    #[test]
+    fn window_role_contains_matches_substrings(q: WindowRoleContains, id: u32, expected: bool) {
+        assert_eq!(run(q, id), expected);
+    }
+}
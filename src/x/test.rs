@@ -0,0 +1,633 @@
+//! An in-memory implementation of [XConn] for testing code that interacts with the X server.
+//!
+//! A [TestXConn] never talks to a real X server. Instead it holds a small amount of in-memory
+//! state (screens, client geometry, properties and so on) that can be preloaded before a test
+//! runs and inspected afterwards, along with a record of each [XConn] method that was called.
+//! This makes it possible to unit test custom hooks, actions and layouts without needing a
+//! running X server.
+//!
+//! ```
+//! use penrose::{
+//!     core::{Config, State},
+//!     x::{test::TestXConn, Prop, XConn, XConnExt},
+//!     Result, Xid,
+//! };
+//!
+//! // A refresh hook that marks the currently focused client with a custom property
+//! fn mark_focused<X: XConn>(state: &mut State<X>, x: &X) -> Result<()> {
+//!     if let Some(&id) = state.client_set.current_client() {
+//!         x.set_prop(id, "_MY_FOCUSED", Prop::Cardinal(vec![1]))?;
+//!     }
+//!
+//!     Ok(())
+//! }
+//!
+//! # fn main() -> Result<()> {
+//! let x = TestXConn::default();
+//! let mut config = Config::default();
+//! config.compose_or_set_refresh_hook(mark_focused);
+//! let mut state = x.new_state(config)?;
+//!
+//! let id = Xid::from(1);
+//! x.modify_and_refresh(&mut state, |cs| cs.insert(id))?;
+//!
+//! assert_eq!(x.prop(id, "_MY_FOCUSED"), Some(Prop::Cardinal(vec![1])));
+//! assert!(x.is_mapped(id));
+//! assert_eq!(x.focused(), Some(id));
+//! # Ok(())
+//! # }
+//! ```
+use crate::{
+    core::{
+        bindings::{KeyCode, MouseState},
+        Config, State,
+    },
+    pure::geometry::{Point, Rect},
+    x::{
+        event::{ClientMessage, XEvent},
+        property::{MapState, Prop, WindowAttributes, WindowClass, WmState},
+        ClientAttr, ClientConfig, XConn,
+    },
+    Color, Error, Result, Xid,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    time::Duration,
+};
+
+/// The screen used by [TestXConn::default].
+pub const DEFAULT_SCREEN: Rect = Rect {
+    x: 0,
+    y: 0,
+    w: 1920,
+    h: 1080,
+};
+
+/// A configurable in-memory [XConn] for use in tests.
+///
+/// Clients, properties, screens and pending events can be preloaded using the builder methods
+/// on this struct and the resulting state of the fake X server can be inspected once the code
+/// under test has run. All [XConn] methods succeed unless they refer to an unknown atom or
+/// client geometry, or [XConn::next_event] is called once all preloaded events have been
+/// consumed.
+///
+/// Preloading client properties allows for testing hooks that query the X server:
+///
+/// ```
+/// use penrose::{
+///     core::{hooks::StateHook, Config},
+///     extensions::hooks::DynamicTagNames,
+///     x::{test::TestXConn, Atom, Prop, XConnExt},
+///     Xid,
+/// };
+///
+/// let id = Xid::from(1);
+/// let x = TestXConn::default().with_prop(
+///     id,
+///     Atom::WmClass.as_ref(),
+///     Prop::UTF8String(vec!["firefox".to_owned(), "Firefox".to_owned()]),
+/// );
+/// let mut state = x.new_state(Config::default()).unwrap();
+/// x.modify_and_refresh(&mut state, |cs| cs.insert(id)).unwrap();
+///
+/// x.clear_calls();
+/// DynamicTagNames::default().call(&mut state, &x).unwrap();
+///
+/// assert_eq!(state.client_set.current_workspace().display_name(), "Firefox");
+/// assert_eq!(x.calls(), vec![("get_prop", Some(id))]);
+/// ```
+#[derive(Debug)]
+pub struct TestXConn {
+    screens: Vec<Rect>,
+    geometry: RefCell<HashMap<Xid, Rect>>,
+    props: RefCell<HashMap<Xid, HashMap<String, Prop>>>,
+    wm_states: RefCell<HashMap<Xid, WmState>>,
+    mapped: RefCell<HashSet<Xid>>,
+    focused: RefCell<Option<Xid>>,
+    cursor: RefCell<Point>,
+    atoms: RefCell<Vec<String>>,
+    events: RefCell<VecDeque<XEvent>>,
+    root_background: RefCell<Vec<(Rect, Color)>>,
+    borders: RefCell<HashMap<Xid, u32>>,
+    stack: RefCell<Vec<Xid>>,
+    keyboard_mapping: HashMap<u8, Vec<u32>>,
+    grabbed_keys: RefCell<Vec<KeyCode>>,
+    keyboard_grabbed: RefCell<bool>,
+    client_configs: RefCell<Vec<(Xid, ClientConfig)>>,
+    calls: RefCell<Vec<(&'static str, Option<Xid>)>>,
+}
+
+impl Default for TestXConn {
+    fn default() -> Self {
+        Self::new(vec![DEFAULT_SCREEN])
+    }
+}
+
+impl TestXConn {
+    /// Create a new [TestXConn] with the given screens and no clients.
+    pub fn new(screens: Vec<Rect>) -> Self {
+        Self {
+            screens,
+            geometry: Default::default(),
+            props: Default::default(),
+            wm_states: Default::default(),
+            mapped: Default::default(),
+            focused: Default::default(),
+            cursor: Default::default(),
+            atoms: Default::default(),
+            events: Default::default(),
+            root_background: Default::default(),
+            borders: Default::default(),
+            stack: Default::default(),
+            keyboard_mapping: Default::default(),
+            grabbed_keys: Default::default(),
+            keyboard_grabbed: Default::default(),
+            client_configs: Default::default(),
+            calls: Default::default(),
+        }
+    }
+
+    /// Add an existing client window with the given geometry.
+    pub fn with_client(self, id: Xid, r: Rect) -> Self {
+        self.geometry.borrow_mut().insert(id, r);
+        self
+    }
+
+    /// Set a property on the given client window.
+    pub fn with_prop(self, id: Xid, name: impl Into<String>, val: Prop) -> Self {
+        self.props
+            .borrow_mut()
+            .entry(id)
+            .or_default()
+            .insert(name.into(), val);
+        self
+    }
+
+    /// Add an event to be returned from [XConn::next_event].
+    ///
+    /// Events are returned in the order that they are added.
+    pub fn with_event(self, event: XEvent) -> Self {
+        self.events.borrow_mut().push_back(event);
+        self
+    }
+
+    /// Set the keysyms produced by each key code, as returned from [XConn::keyboard_mapping].
+    pub fn with_keyboard_mapping(self, mapping: HashMap<u8, Vec<u32>>) -> Self {
+        Self {
+            keyboard_mapping: mapping,
+            ..self
+        }
+    }
+
+    /// Create a new [State] using this connection for tests that need to run hooks or actions
+    /// directly.
+    pub fn new_state(&self, config: Config<Self>) -> Result<State<Self>> {
+        State::try_new(config, self)
+    }
+
+    /// The current value of a property on the given client window.
+    pub fn prop(&self, id: Xid, name: &str) -> Option<Prop> {
+        self.props.borrow().get(&id)?.get(name).cloned()
+    }
+
+    /// The current [WmState] of the given client window.
+    pub fn wm_state(&self, id: Xid) -> Option<WmState> {
+        self.wm_states.borrow().get(&id).cloned()
+    }
+
+    /// The current geometry of the given client window.
+    pub fn geometry(&self, id: Xid) -> Option<Rect> {
+        self.geometry.borrow().get(&id).copied()
+    }
+
+    /// Whether or not the given client window is currently mapped.
+    pub fn is_mapped(&self, id: Xid) -> bool {
+        self.mapped.borrow().contains(&id)
+    }
+
+    /// The client window currently holding input focus.
+    pub fn focused(&self) -> Option<Xid> {
+        *self.focused.borrow()
+    }
+
+    /// The regions of the root window most recently filled by [XConn::set_root_background].
+    pub fn root_background(&self) -> Vec<(Rect, Color)> {
+        self.root_background.borrow().clone()
+    }
+
+    /// The border color most recently set for the given client window as an argb hex value.
+    pub fn border_color(&self, id: Xid) -> Option<u32> {
+        self.borders.borrow().get(&id).copied()
+    }
+
+    /// The stacking order of all client windows that have been restacked, from bottom to top.
+    pub fn stacking_order(&self) -> Vec<Xid> {
+        self.stack.borrow().clone()
+    }
+
+    /// The key codes that are currently grabbed, in the order they were grabbed.
+    pub fn grabbed_keys(&self) -> Vec<KeyCode> {
+        self.grabbed_keys.borrow().clone()
+    }
+
+    /// Whether or not the keyboard is currently grabbed.
+    pub fn is_keyboard_grabbed(&self) -> bool {
+        *self.keyboard_grabbed.borrow()
+    }
+
+    /// The [ClientConfig] changes that have been requested so far along with the client they
+    /// were requested for, in the order they were requested.
+    pub fn client_configs(&self) -> Vec<(Xid, ClientConfig)> {
+        self.client_configs.borrow().clone()
+    }
+
+    /// The number of times that the given [XConn] method has been called so far.
+    pub fn n_calls(&self, method: &str) -> usize {
+        self.calls
+            .borrow()
+            .iter()
+            .filter(|(m, _)| *m == method)
+            .count()
+    }
+
+    /// The [XConn] methods that have been called so far along with the client they were
+    /// called for (if any), in the order they were called.
+    pub fn calls(&self) -> Vec<(&'static str, Option<Xid>)> {
+        self.calls.borrow().clone()
+    }
+
+    /// The names of the [XConn] methods that have been called for the given client, in the
+    /// order they were called.
+    pub fn calls_for(&self, id: Xid) -> Vec<&'static str> {
+        self.calls
+            .borrow()
+            .iter()
+            .filter(|(_, c)| *c == Some(id))
+            .map(|(method, _)| *method)
+            .collect()
+    }
+
+    /// Clear the record of [XConn] methods that have been called so far, along with the
+    /// record of requested [ClientConfig] changes.
+    pub fn clear_calls(&self) {
+        self.calls.borrow_mut().clear();
+        self.client_configs.borrow_mut().clear();
+    }
+
+    fn record(&self, method: &'static str, id: Option<Xid>) {
+        self.calls.borrow_mut().push((method, id));
+    }
+
+    // Move a client directly above or below its peer in the stack, or to the top or bottom of
+    // the stack if no peer is given. Unknown peers are treated as though they were at the bottom
+    // of the stack.
+    fn restack(&self, client: Xid, peer: Option<Xid>, above: bool) {
+        let mut stack = self.stack.borrow_mut();
+        stack.retain(|&id| id != client);
+
+        let ix = match peer {
+            Some(peer) => match stack.iter().position(|&id| id == peer) {
+                Some(ix) => ix,
+                None => {
+                    stack.insert(0, peer);
+                    0
+                }
+            },
+            None if above => stack.len(),
+            None => 0,
+        };
+
+        let ix = if above && peer.is_some() { ix + 1 } else { ix };
+        stack.insert(ix, client);
+    }
+}
+
+impl XConn for TestXConn {
+    fn root(&self) -> Xid {
+        Xid(0)
+    }
+
+    fn screen_details(&self) -> Result<Vec<Rect>> {
+        self.record("screen_details", None);
+        Ok(self.screens.clone())
+    }
+
+    fn output_details(&self) -> Result<Vec<(Option<String>, Rect)>> {
+        self.record("output_details", None);
+        Ok(self
+            .screens
+            .iter()
+            .enumerate()
+            .map(|(i, &r)| (Some(i.to_string()), r))
+            .collect())
+    }
+
+    fn output_physical_sizes(&self) -> Result<Vec<Option<(u32, u32)>>> {
+        self.record("output_physical_sizes", None);
+        Ok(vec![None; self.screens.len()])
+    }
+
+    fn cursor_position(&self) -> Result<Point> {
+        self.record("cursor_position", None);
+        Ok(*self.cursor.borrow())
+    }
+
+    fn grab(&self, key_codes: &[KeyCode], _: &[MouseState]) -> Result<()> {
+        self.record("grab", None);
+        *self.grabbed_keys.borrow_mut() = key_codes.to_vec();
+        Ok(())
+    }
+
+    fn grab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        self.record("grab_keys", None);
+        self.grabbed_keys.borrow_mut().extend(key_codes);
+        Ok(())
+    }
+
+    fn ungrab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        self.record("ungrab_keys", None);
+        self.grabbed_keys
+            .borrow_mut()
+            .retain(|k| !key_codes.contains(k));
+        Ok(())
+    }
+
+    fn grab_keyboard(&self) -> Result<()> {
+        self.record("grab_keyboard", None);
+        *self.keyboard_grabbed.borrow_mut() = true;
+        Ok(())
+    }
+
+    fn ungrab_keyboard(&self) -> Result<()> {
+        self.record("ungrab_keyboard", None);
+        *self.keyboard_grabbed.borrow_mut() = false;
+        Ok(())
+    }
+
+    fn keyboard_mapping(&self) -> Result<HashMap<u8, Vec<u32>>> {
+        self.record("keyboard_mapping", None);
+        Ok(self.keyboard_mapping.clone())
+    }
+
+    fn next_event(&self) -> Result<XEvent> {
+        self.record("next_event", None);
+        self.events
+            .borrow_mut()
+            .pop_front()
+            .ok_or_else(|| Error::Custom("no more test events".to_owned()))
+    }
+
+    fn flush(&self) {
+        self.record("flush", None);
+    }
+
+    fn wake_after(&self, _: Duration) -> Result<()> {
+        self.record("wake_after", None);
+        Ok(())
+    }
+
+    fn intern_atom(&self, atom: &str) -> Result<Xid> {
+        let mut atoms = self.atoms.borrow_mut();
+        let ix = match atoms.iter().position(|a| a == atom) {
+            Some(ix) => ix,
+            None => {
+                atoms.push(atom.to_owned());
+                atoms.len() - 1
+            }
+        };
+
+        // Atom ids start at 1 as 0 is used to denote "None" by the X server
+        Ok(Xid(ix as u32 + 1))
+    }
+
+    fn atom_name(&self, xid: Xid) -> Result<String> {
+        self.atoms
+            .borrow()
+            .get((*xid as usize).wrapping_sub(1))
+            .cloned()
+            .ok_or_else(|| Error::Custom(format!("{xid} is not a known atom")))
+    }
+
+    fn client_geometry(&self, client: Xid) -> Result<Rect> {
+        self.record("client_geometry", Some(client));
+        self.geometry(client).ok_or(Error::UnknownClient(client))
+    }
+
+    fn existing_clients(&self) -> Result<Vec<Xid>> {
+        self.record("existing_clients", None);
+        let mut clients: Vec<Xid> = self.geometry.borrow().keys().copied().collect();
+        clients.sort();
+
+        Ok(clients)
+    }
+
+    fn map(&self, client: Xid) -> Result<()> {
+        self.record("map", Some(client));
+        self.mapped.borrow_mut().insert(client);
+        Ok(())
+    }
+
+    fn unmap(&self, client: Xid) -> Result<()> {
+        self.record("unmap", Some(client));
+        self.mapped.borrow_mut().remove(&client);
+        Ok(())
+    }
+
+    fn kill(&self, client: Xid) -> Result<()> {
+        self.record("kill", Some(client));
+        Ok(())
+    }
+
+    fn focus(&self, client: Xid) -> Result<()> {
+        self.record("focus", Some(client));
+        *self.focused.borrow_mut() = Some(client);
+        Ok(())
+    }
+
+    fn get_prop(&self, client: Xid, prop_name: &str) -> Result<Option<Prop>> {
+        self.record("get_prop", Some(client));
+        Ok(self.prop(client, prop_name))
+    }
+
+    fn list_props(&self, client: Xid) -> Result<Vec<String>> {
+        self.record("list_props", Some(client));
+        let mut names: Vec<String> = self
+            .props
+            .borrow()
+            .get(&client)
+            .map(|props| props.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+
+        Ok(names)
+    }
+
+    fn get_wm_state(&self, client: Xid) -> Result<Option<WmState>> {
+        self.record("get_wm_state", Some(client));
+        Ok(self.wm_state(client))
+    }
+
+    fn get_window_attributes(&self, client: Xid) -> Result<WindowAttributes> {
+        self.record("get_window_attributes", Some(client));
+        let map_state = if self.is_mapped(client) {
+            MapState::Viewable
+        } else {
+            MapState::Unmapped
+        };
+
+        Ok(WindowAttributes::new(
+            false,
+            map_state,
+            WindowClass::InputOutput,
+        ))
+    }
+
+    fn set_wm_state(&self, client: Xid, wm_state: WmState) -> Result<()> {
+        self.record("set_wm_state", Some(client));
+        self.wm_states.borrow_mut().insert(client, wm_state);
+        Ok(())
+    }
+
+    fn set_prop(&self, client: Xid, name: &str, val: Prop) -> Result<()> {
+        self.record("set_prop", Some(client));
+        self.props
+            .borrow_mut()
+            .entry(client)
+            .or_default()
+            .insert(name.to_owned(), val);
+        Ok(())
+    }
+
+    fn delete_prop(&self, client: Xid, prop_name: &str) -> Result<()> {
+        self.record("delete_prop", Some(client));
+        if let Some(props) = self.props.borrow_mut().get_mut(&client) {
+            props.remove(prop_name);
+        }
+        Ok(())
+    }
+
+    fn set_client_attributes(&self, client: Xid, attrs: &[ClientAttr]) -> Result<()> {
+        self.record("set_client_attributes", Some(client));
+        for attr in attrs {
+            if let ClientAttr::BorderColor(color) = attr {
+                self.borders.borrow_mut().insert(client, *color);
+            }
+        }
+        Ok(())
+    }
+
+    fn select_property_events(&self, client: Xid) -> Result<()> {
+        self.record("select_property_events", Some(client));
+        Ok(())
+    }
+
+    fn set_client_config(&self, client: Xid, data: &[ClientConfig]) -> Result<()> {
+        self.record("set_client_config", Some(client));
+        for c in data {
+            self.client_configs.borrow_mut().push((client, *c));
+            match *c {
+                ClientConfig::Position(r) => {
+                    self.geometry.borrow_mut().insert(client, r);
+                }
+                ClientConfig::StackAbove(peer) => self.restack(client, Some(peer), true),
+                ClientConfig::StackBelow(peer) => self.restack(client, Some(peer), false),
+                ClientConfig::StackTop => self.restack(client, None, true),
+                ClientConfig::StackBottom => self.restack(client, None, false),
+                ClientConfig::BorderPx(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn send_client_message(&self, msg: ClientMessage) -> Result<()> {
+        self.record("send_client_message", Some(msg.id));
+        Ok(())
+    }
+
+    fn set_root_background(&self, fills: &[(Rect, Color)]) -> Result<()> {
+        self.record("set_root_background", None);
+        *self.root_background.borrow_mut() = fills.to_vec();
+        Ok(())
+    }
+
+    fn warp_pointer(&self, id: Xid, x: i16, y: i16) -> Result<()> {
+        self.record("warp_pointer", Some(id));
+        let r = self.geometry(id).unwrap_or_default();
+        *self.cursor.borrow_mut() = Point::new(
+            (r.x as i32 + x as i32).max(0) as u32,
+            (r.y as i32 + y as i32).max(0) as u32,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::x::XConnExt;
+
+    #[test]
+    fn refreshing_updates_the_in_memory_state() {
+        let x = TestXConn::default();
+        let mut state = x.new_state(Config::default()).unwrap();
+
+        x.modify_and_refresh(&mut state, |cs| {
+            cs.insert(Xid(1));
+            cs.insert(Xid(2));
+        })
+        .unwrap();
+
+        assert!(x.is_mapped(Xid(1)));
+        assert!(x.is_mapped(Xid(2)));
+        assert_eq!(x.focused(), Some(Xid(2)));
+        assert_eq!(x.wm_state(Xid(1)), Some(WmState::Normal));
+        assert!(x.geometry(Xid(1)).is_some());
+
+        x.clear_calls();
+        x.modify_and_refresh(&mut state, |cs| cs.move_client_to_tag(&Xid(1), "2"))
+            .unwrap();
+
+        assert!(!x.is_mapped(Xid(1)));
+        assert_eq!(x.wm_state(Xid(1)), Some(WmState::Iconic));
+        assert!(x.calls_for(Xid(1)).contains(&"unmap"));
+    }
+
+    #[test]
+    fn atoms_round_trip() {
+        let x = TestXConn::default();
+        let id = x.intern_atom("_MY_ATOM").unwrap();
+
+        assert_eq!(x.intern_atom("_MY_ATOM").unwrap(), id);
+        assert_eq!(x.atom_name(id).unwrap(), "_MY_ATOM");
+        assert!(x.atom_name(Xid(0)).is_err());
+    }
+
+    #[test]
+    fn events_are_returned_in_order() {
+        let x = TestXConn::default()
+            .with_event(XEvent::MapRequest(Xid(1)))
+            .with_event(XEvent::Destroy(Xid(1)));
+
+        assert_eq!(x.next_event().unwrap(), XEvent::MapRequest(Xid(1)));
+        assert_eq!(x.next_event().unwrap(), XEvent::Destroy(Xid(1)));
+        assert!(x.next_event().is_err());
+    }
+
+    #[test]
+    fn key_grabs_are_tracked() {
+        let code = |code| KeyCode { mask: 0, code };
+        let x = TestXConn::default();
+
+        x.grab(&[code(1), code(2)], &[]).unwrap();
+        x.grab_keys(&[code(3)]).unwrap();
+        x.ungrab_keys(&[code(1)]).unwrap();
+        x.grab_keyboard().unwrap();
+
+        assert_eq!(x.grabbed_keys(), vec![code(2), code(3)]);
+        assert!(x.is_keyboard_grabbed());
+
+        x.ungrab_keyboard().unwrap();
+        assert!(!x.is_keyboard_grabbed());
+    }
+}
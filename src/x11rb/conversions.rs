@@ -34,7 +34,7 @@ pub(crate) fn convert_event<C: Connection>(conn: &Conn<C>, event: Event) -> Resu
 
         Event::ButtonPress(event) => Ok(to_mouse_state(event.detail, event.state).map(|state| {
             XEvent::MouseEvent(MouseEvent::new(
-                Xid(event.child),
+                button_event_window(event.child, event.event),
                 event.root_x,
                 event.root_y,
                 event.event_x,
@@ -46,7 +46,7 @@ pub(crate) fn convert_event<C: Connection>(conn: &Conn<C>, event: Event) -> Resu
 
         Event::ButtonRelease(event) => Ok(to_mouse_state(event.detail, event.state).map(|state| {
             XEvent::MouseEvent(MouseEvent::new(
-                Xid(event.child),
+                button_event_window(event.child, event.event),
                 event.root_x,
                 event.root_y,
                 event.event_x,
@@ -79,6 +79,8 @@ pub(crate) fn convert_event<C: Connection>(conn: &Conn<C>, event: Event) -> Resu
             )))
         }
 
+        Event::MapNotify(event) => Ok(Some(XEvent::MapNotify(Xid(event.window)))),
+
         Event::MapRequest(event) => Ok(Some(XEvent::MapRequest(Xid(event.window)))),
 
         Event::UnmapNotify(event) => Ok(Some(XEvent::UnmapNotify(Xid(event.window)))),
@@ -162,17 +164,24 @@ pub(crate) fn convert_event<C: Connection>(conn: &Conn<C>, event: Event) -> Resu
     }
 }
 
+// Events from grabs on the root window report the window that was clicked as the child but
+// windows selecting button press events directly (such as status bars) report themselves as
+// the event window with no child.
+fn button_event_window(child: u32, event: u32) -> Xid {
+    if child == x11rb::NONE {
+        Xid(event)
+    } else {
+        Xid(child)
+    }
+}
+
 fn to_mouse_state(detail: u8, state: KeyButMask) -> Option<MouseState> {
     fn is_held(key: &ModifierKey, mask: u16) -> bool {
         mask & u16::from(*key) > 0
     }
-    let button = match detail {
-        1 => MouseButton::Left,
-        2 => MouseButton::Middle,
-        3 => MouseButton::Right,
-        4 => MouseButton::ScrollUp,
-        5 => MouseButton::ScrollDown,
-        _ => {
+    let button = match MouseButton::try_from(detail) {
+        Ok(button) => button,
+        Err(_) => {
             warn!(button = detail, "dropping unknown mouse button event");
             return None;
         }
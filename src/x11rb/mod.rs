@@ -26,20 +26,27 @@ use crate::{
         property::{Prop, WindowAttributes, WmHints, WmNormalHints, WmState},
         ClientAttr, ClientConfig, WinType, XConn, XConnExt, XEvent,
     },
-    Error, Result, Xid,
+    Color, Error, Result, Xid,
+};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    thread,
+    time::Duration,
 };
-use std::{collections::HashMap, str::FromStr};
 use strum::IntoEnumIterator;
-use tracing::error;
+use tracing::{error, warn};
 use x11rb::{
     connection::Connection,
     protocol::{
         randr::{self, ConnectionExt as _, NotifyMask},
+        xkb::{self, ConnectionExt as _},
         xproto::{
-            AtomEnum, ChangeWindowAttributesAux, ClientMessageData, ClientMessageEvent,
-            ColormapAlloc, ConfigureWindowAux, ConnectionExt as _, CreateWindowAux, EventMask,
-            GrabMode, InputFocus, MapState, ModMask, PropMode, StackMode, WindowClass,
-            CLIENT_MESSAGE_EVENT,
+            AtomEnum, ButtonIndex, ChangeGCAux, ChangeWindowAttributesAux, ClientMessageData,
+            ClientMessageEvent, CloseDown, ColormapAlloc, ConfigureWindowAux, ConnectionExt as _,
+            CreateGCAux, CreateWindowAux, EventMask, GrabMode, GrabStatus, InputFocus, MapState,
+            ModMask, PropMode, Rectangle, StackMode, WindowClass, CLIENT_MESSAGE_EVENT,
         },
     },
     rust_connection::RustConnection,
@@ -56,6 +63,13 @@ use conversions::convert_event;
 
 const RANDR_VER: (u32, u32) = (1, 2);
 
+// We need to explicitly grab NumLock as an additional modifier and then drop it later on
+// when we are passing events through to the WindowManager as NumLock alters the modifier
+// mask when it is active.
+fn grab_modifiers() -> [u16; 2] {
+    [0, u16::from(ModMask::M2)]
+}
+
 #[derive(Debug)]
 pub(crate) struct Atoms {
     atoms: HashMap<Atom, u32>,
@@ -96,6 +110,7 @@ pub struct Conn<C: Connection> {
     conn: C,
     root: u32,
     atoms: Atoms,
+    waker: OnceLock<Arc<Waker>>,
 }
 
 /// A pure rust based connection to the X server using a [RustConnection].
@@ -126,6 +141,41 @@ impl Conn<XCBConnection> {
     }
 }
 
+/// A handle for waking the main event loop of a running window manager from another thread.
+///
+/// The main event loop blocks while waiting for events from the X server, so code running on a
+/// background thread has no direct way of getting its state updates handled. A [Waker] holds its
+/// own connection to the X server and sends a client message of the given type to the root window
+/// each time [Waker::wake] is called, causing all event hooks to run. The connection is held open
+/// for the lifetime of the [Waker] so it should be created once and reused.
+#[derive(Debug)]
+pub struct Waker {
+    conn: RustConnection,
+    root: u32,
+    atom: u32,
+}
+
+impl Waker {
+    /// Connect to the X server, interning `dtype` as the type of the client messages to send.
+    pub fn new(dtype: &str) -> Result<Self> {
+        let (conn, screen) = RustConnection::connect(None)?;
+        let root = conn.setup().roots[screen].root;
+        let atom = conn.intern_atom(false, dtype.as_bytes())?.reply()?.atom;
+
+        Ok(Self { conn, root, atom })
+    }
+
+    /// Send a client message to the root window in order to wake the main event loop.
+    pub fn wake(&self) -> Result<()> {
+        let event = ClientMessageEvent::new(32, self.root, self.atom, [0u32; 5]);
+        self.conn
+            .send_event(false, self.root, EventMask::SUBSTRUCTURE_NOTIFY, event)?;
+        self.conn.flush()?;
+
+        Ok(())
+    }
+}
+
 impl<C> Conn<C>
 where
     C: Connection,
@@ -154,7 +204,12 @@ where
         let mask = NotifyMask::OUTPUT_CHANGE | NotifyMask::CRTC_CHANGE | NotifyMask::SCREEN_CHANGE;
         conn.randr_select_input(root, mask)?;
 
-        let xconn = Self { conn, root, atoms };
+        let xconn = Self {
+            conn,
+            root,
+            atoms,
+            waker: OnceLock::new(),
+        };
 
         xconn.set_client_attributes(Xid(root), &[ClientAttr::RootEventMask])?;
 
@@ -234,17 +289,10 @@ where
 
         Ok(())
     }
-}
-
-impl<C> XConn for Conn<C>
-where
-    C: Connection,
-{
-    fn root(&self) -> Xid {
-        self.root.into()
-    }
 
-    fn screen_details(&self) -> Result<Vec<Rect>> {
+    // The name, position and physical size in millimetres of each active output.
+    #[allow(clippy::type_complexity)]
+    fn outputs(&self) -> Result<Vec<(String, Rect, Option<(u32, u32)>)>> {
         let resources = self.conn.randr_get_screen_resources(self.root)?.reply()?;
 
         // Send queries for all CRTCs
@@ -254,25 +302,95 @@ where
             .map(|c| {
                 self.conn
                     .randr_get_crtc_info(*c, 0)
+                    .map(|cookie| (*c, cookie))
                     .map_err(|err| err.into())
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let rects = crtcs
+        let crtcs: Vec<_> = crtcs
             .into_iter()
-            .flat_map(|cookie| cookie.reply().ok())
-            .filter(|reply| reply.width > 0)
-            .map(|reply| {
-                Rect::new(
-                    reply.x as u32,
-                    reply.y as u32,
-                    reply.width as u32,
-                    reply.height as u32,
-                )
-            })
+            .flat_map(|(crtc, cookie)| cookie.reply().ok().map(|reply| (crtc, reply)))
+            .filter(|(_, reply)| reply.width > 0)
             .collect();
 
-        Ok(rects)
+        // Send queries for the names of all outputs before waiting on any of the replies.
+        // CRTCs driving multiple outputs (mirroring) are named after the first of them.
+        let names = crtcs
+            .iter()
+            .map(|(_, reply)| match reply.outputs.first() {
+                Some(&o) => self
+                    .conn
+                    .randr_get_output_info(o, resources.config_timestamp)
+                    .map(Some)
+                    .map_err(|err| err.into()),
+                None => Ok(None),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut outputs = Vec::with_capacity(crtcs.len());
+        for ((crtc, reply), cookie) in crtcs.into_iter().zip(names) {
+            let (name, mm) = match cookie {
+                Some(cookie) => {
+                    let info = cookie.reply()?;
+                    let mm = match (info.mm_width, info.mm_height) {
+                        (0, _) | (_, 0) => None,
+                        (w, h) => Some((w, h)),
+                    };
+
+                    (String::from_utf8_lossy(&info.name).into_owned(), mm)
+                }
+                None => (format!("crtc-{crtc}"), None),
+            };
+
+            let r = Rect::new(
+                reply.x as u32,
+                reply.y as u32,
+                reply.width as u32,
+                reply.height as u32,
+            );
+
+            // Physical sizes are reported for the unrotated output so they need swapping to
+            // match the CRTC dimensions when the output is rotated by 90 or 270 degrees.
+            let rotated = reply.rotation & (randr::Rotation::ROTATE90 | randr::Rotation::ROTATE270);
+            let mm = match mm {
+                Some((w, h)) if u16::from(rotated) != 0 => Some((h, w)),
+                mm => mm,
+            };
+
+            outputs.push((name, r, mm));
+        }
+
+        Ok(outputs)
+    }
+}
+
+impl<C> XConn for Conn<C>
+where
+    C: Connection,
+{
+    fn root(&self) -> Xid {
+        self.root.into()
+    }
+
+    fn screen_details(&self) -> Result<Vec<Rect>> {
+        let outputs = self.output_details()?;
+
+        Ok(outputs.into_iter().map(|(_, r)| r).collect())
+    }
+
+    fn output_details(&self) -> Result<Vec<(Option<String>, Rect)>> {
+        let outputs = self.outputs()?;
+
+        Ok(outputs
+            .into_iter()
+            .map(|(name, r, _)| (Some(name), r))
+            .collect())
+    }
+
+    fn output_physical_sizes(&self) -> Result<Vec<Option<(u32, u32)>>> {
+        let outputs = self.outputs()?;
+
+        Ok(outputs.into_iter().map(|(_, _, mm)| mm).collect())
     }
 
     fn cursor_position(&self) -> Result<Point> {
@@ -282,35 +400,33 @@ where
     }
 
     fn grab(&self, key_codes: &[KeyCode], mouse_states: &[MouseState]) -> Result<()> {
-        // Release any grabbed keys that we currently have before attempting to grab
-        // the requested key codes.
+        // Release any grabbed keys and buttons that we currently have before attempting to
+        // grab the requested key codes and mouse states.
         // NOTE: The '0' here is XCB_GRAB_ANY
         if let Err(e) = self.conn.ungrab_key(0, self.root, ModMask::ANY) {
             error!(%e, "unable to ungrab keys");
         };
+        if let Err(e) = self
+            .conn
+            .ungrab_button(ButtonIndex::ANY, self.root, ModMask::ANY)
+        {
+            error!(%e, "unable to ungrab mouse buttons");
+        };
+
+        self.grab_keys(key_codes)?;
 
-        // We need to explicitly grab NumLock as an additional modifier and then drop it later on
-        // when we are passing events through to the WindowManager as NumLock alters the modifier
-        // mask when it is active.
-        let modifiers = &[0, u16::from(ModMask::M2)];
         let mode = GrabMode::ASYNC;
         let mask = EventMask::BUTTON_PRESS | EventMask::BUTTON_RELEASE | EventMask::BUTTON_MOTION;
 
-        for m in modifiers.iter() {
-            for k in key_codes.iter() {
-                self.conn.grab_key(
-                    false,               // don't pass grabbed events through to the client
-                    self.root,           // the window to grab: in this case the root window
-                    (k.mask | m).into(), // modifiers to grab
-                    k.code,              // keycode to grab
-                    mode,                // don't lock pointer input while grabbing
-                    mode,                // don't lock keyboard input while grabbing
-                )?;
-            }
-        }
+        // Grabbing the scroll wheel without any modifiers would prevent all other clients from
+        // being able to scroll so we rely on the root window event mask instead. This means
+        // that these bindings only apply when the pointer is over the root window.
+        let mouse_states = mouse_states
+            .iter()
+            .filter(|s| !(s.button.is_scroll() && s.modifiers.is_empty()));
 
-        for m in modifiers.iter() {
-            for state in mouse_states.iter() {
+        for m in grab_modifiers().iter() {
+            for state in mouse_states.clone() {
                 let button = state.button().into();
                 self.conn.grab_button(
                     false,                     // don't pass grabbed events through to the client
@@ -331,6 +447,108 @@ where
         Ok(())
     }
 
+    fn grab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        let mode = GrabMode::ASYNC;
+
+        for m in grab_modifiers().iter() {
+            for k in key_codes.iter() {
+                self.conn.grab_key(
+                    false,               // don't pass grabbed events through to the client
+                    self.root,           // the window to grab: in this case the root window
+                    (k.mask | m).into(), // modifiers to grab
+                    k.code,              // keycode to grab
+                    mode,                // don't lock pointer input while grabbing
+                    mode,                // don't lock keyboard input while grabbing
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ungrab_keys(&self, key_codes: &[KeyCode]) -> Result<()> {
+        for m in grab_modifiers().iter() {
+            for k in key_codes.iter() {
+                self.conn
+                    .ungrab_key(k.code, self.root, (k.mask | m).into())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn grab_keyboard(&self) -> Result<()> {
+        let mode = GrabMode::ASYNC;
+        let reply = self
+            .conn
+            .grab_keyboard(false, self.root, CURRENT_TIME, mode, mode)?
+            .reply()?;
+
+        if reply.status != GrabStatus::SUCCESS {
+            return Err(Error::Custom(format!(
+                "unable to grab keyboard: {:?}",
+                reply.status
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn ungrab_keyboard(&self) -> Result<()> {
+        self.conn.ungrab_keyboard(CURRENT_TIME)?;
+
+        Ok(())
+    }
+
+    fn keyboard_mapping(&self) -> Result<HashMap<u8, Vec<u32>>> {
+        let version = self.conn.xkb_use_extension(1, 0)?.reply()?;
+        if !version.supported {
+            return Err(Error::Custom(format!(
+                "xkb {}.{} is not supported by the X server",
+                version.server_major, version.server_minor
+            )));
+        }
+
+        let (min_code, max_code) = {
+            let setup = self.conn.setup();
+            (setup.min_keycode, setup.max_keycode)
+        };
+        let n_codes = max_code - min_code + 1;
+        let none = xkb::MapPart::from(0u16);
+
+        let reply = self
+            .conn
+            .xkb_get_map(
+                xkb::ID::USE_CORE_KBD.into(),
+                xkb::MapPart::KEY_SYMS,
+                none,
+                0,
+                0,
+                min_code,
+                n_codes,
+                0,
+                0,
+                0,
+                0,
+                xkb::VMod::from(0u16),
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+            )?
+            .reply()?;
+
+        let key_syms = reply.map.syms_rtrn.unwrap_or_default();
+
+        Ok(key_syms
+            .into_iter()
+            .enumerate()
+            .map(|(i, m)| (reply.first_key_sym + i as u8, m.syms))
+            .collect())
+    }
+
     fn next_event(&self) -> Result<XEvent> {
         loop {
             let event = self.conn.wait_for_event()?;
@@ -344,6 +562,27 @@ where
         self.conn.flush().unwrap_or(());
     }
 
+    // The connection used for waking the event loop is opened the first time that it is
+    // needed and then shared between all timers.
+    fn wake_after(&self, delay: Duration) -> Result<()> {
+        let waker = match self.waker.get() {
+            Some(waker) => waker.clone(),
+            None => {
+                let waker = Arc::new(Waker::new(x::WAKE_MESSAGE)?);
+                self.waker.get_or_init(|| waker).clone()
+            }
+        };
+
+        thread::spawn(move || {
+            thread::sleep(delay);
+            if let Err(e) = waker.wake() {
+                warn!(%e, "unable to wake event loop");
+            }
+        });
+
+        Ok(())
+    }
+
     fn intern_atom(&self, atom: &str) -> Result<Xid> {
         let id = match Atom::from_str(atom) {
             Ok(known) => self.atoms.known_atom(known),
@@ -404,7 +643,6 @@ where
         if supports_delete {
             let msg = ClientMessageKind::DeleteWindow(client).as_message(self)?;
             self.send_client_message(msg)?;
-            self.flush();
         } else {
             self.conn.kill_client(*client)?;
         }
@@ -639,9 +877,12 @@ where
         let client_unmap_mask =
             EventMask::ENTER_WINDOW | EventMask::LEAVE_WINDOW | EventMask::PROPERTY_CHANGE;
 
+        let dock_event_mask = client_unmap_mask | EventMask::BUTTON_PRESS;
+
         let root_event_mask = EventMask::PROPERTY_CHANGE
             | EventMask::SUBSTRUCTURE_REDIRECT
             | EventMask::SUBSTRUCTURE_NOTIFY
+            | EventMask::BUTTON_PRESS
             | EventMask::BUTTON_MOTION;
 
         let mut aux = ChangeWindowAttributesAux::new();
@@ -650,6 +891,7 @@ where
                 ClientAttr::BorderColor(c) => aux = aux.border_pixel(*c),
                 ClientAttr::ClientEventMask => aux = aux.event_mask(client_event_mask),
                 ClientAttr::ClientUnmapMask => aux = aux.event_mask(client_unmap_mask),
+                ClientAttr::DockEventMask => aux = aux.event_mask(dock_event_mask),
                 ClientAttr::RootEventMask => aux = aux.event_mask(root_event_mask),
             }
         }
@@ -658,6 +900,19 @@ where
         Ok(())
     }
 
+    fn select_property_events(&self, id: Xid) -> Result<()> {
+        let current = self
+            .conn
+            .get_window_attributes(*id)?
+            .reply()?
+            .your_event_mask;
+        let mask = EventMask::ENTER_WINDOW | EventMask::LEAVE_WINDOW | EventMask::PROPERTY_CHANGE;
+        let aux = ChangeWindowAttributesAux::new().event_mask(current | mask);
+        self.conn.change_window_attributes(*id, &aux)?;
+
+        Ok(())
+    }
+
     fn set_client_config(&self, id: Xid, data: &[ClientConfig]) -> Result<()> {
         let mut aux = ConfigureWindowAux::new();
         for conf in data.iter() {
@@ -703,6 +958,76 @@ where
         Ok(())
     }
 
+    // The regions are drawn to a pixmap which is then set as the background of the root window
+    // so that the X server is able to repaint the background when it is exposed.
+    //
+    // Following the convention used by Esetroot and feh, the pixmap is created on a separate
+    // connection that the X server retains once it is closed and the pixmap is published in
+    // _XROOTPMAP_ID and ESETROOT_PMAP_ID. Tools replacing the background free the old pixmap
+    // by killing the client that created it, which must not be our own connection.
+    fn set_root_background(&self, fills: &[(Rect, Color)]) -> Result<()> {
+        let (conn, _) = RustConnection::connect(None)?;
+        let atom = |name: &str| -> Result<u32> {
+            Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+        };
+        let (xrootpmap_id, esetroot_pmap_id) = (atom("_XROOTPMAP_ID")?, atom("ESETROOT_PMAP_ID")?);
+
+        // The size of the root window changes when outputs are reconfigured so we need to ask
+        // for its current geometry rather than using the values from the initial setup.
+        let geom = conn.get_geometry(self.root)?.reply()?;
+
+        let pixmap = conn.generate_id()?;
+        conn.create_pixmap(geom.depth, pixmap, self.root, geom.width, geom.height)?;
+        let gc = conn.generate_id()?;
+        conn.create_gc(gc, pixmap, &CreateGCAux::new())?;
+
+        for (r, color) in fills {
+            let rect = Rectangle {
+                x: r.x as i16,
+                y: r.y as i16,
+                width: r.w as u16,
+                height: r.h as u16,
+            };
+            conn.change_gc(gc, &ChangeGCAux::new().foreground(color.rgb_u32()))?;
+            conn.poly_fill_rectangle(pixmap, gc, &[rect])?;
+        }
+        conn.free_gc(gc)?;
+
+        // Free the previous background if it was set following the same convention
+        let current = |atom: u32| -> Result<Option<u32>> {
+            let reply = conn
+                .get_property(false, self.root, atom, AtomEnum::PIXMAP, 0, 1)?
+                .reply()?;
+
+            Ok(reply.value32().and_then(|mut vals| vals.next()))
+        };
+        if let (Some(prev), Some(esetroot)) = (current(xrootpmap_id)?, current(esetroot_pmap_id)?) {
+            if prev == esetroot {
+                conn.kill_client(prev)?;
+            }
+        }
+
+        let aux = ChangeWindowAttributesAux::new().background_pixmap(pixmap);
+        conn.change_window_attributes(self.root, &aux)?;
+        conn.clear_area(false, self.root, 0, 0, 0, 0)?;
+
+        for atom in [xrootpmap_id, esetroot_pmap_id] {
+            conn.change_property32(
+                PropMode::REPLACE,
+                self.root,
+                atom,
+                AtomEnum::PIXMAP,
+                &[pixmap],
+            )?;
+        }
+
+        conn.set_close_down_mode(CloseDown::RETAIN_PERMANENT)?;
+        // Make sure that all of the above has been processed before the connection is closed
+        conn.get_input_focus()?.reply()?;
+
+        Ok(())
+    }
+
     fn warp_pointer(&self, id: Xid, x: i16, y: i16) -> Result<()> {
         self.conn.warp_pointer(x11rb::NONE, *id, 0, 0, 0, 0, x, y)?;
 
@@ -0,0 +1,33 @@
+//! Running external commands while SIGCHLD is ignored, as it is by a running window manager
+use nix::sys::signal::{signal, SigHandler, Signal};
+use penrose::{
+    util::{spawn_for_output, spawn_for_output_with_args},
+    Error,
+};
+
+fn ignore_sigchld() {
+    // SAFETY: no handler is installed for SIGCHLD in this test binary
+    unsafe { signal(Signal::SIGCHLD, SigHandler::SigIgn) }.unwrap();
+}
+
+#[test]
+fn output_is_returned_when_sigchld_is_ignored() {
+    ignore_sigchld();
+
+    assert_eq!(spawn_for_output("echo hello").unwrap(), "hello");
+}
+
+#[test]
+fn non_zero_exit_is_an_error_when_sigchld_is_ignored() {
+    ignore_sigchld();
+
+    let res = spawn_for_output_with_args("sh", &["-c", "echo oops >&2; exit 3"]);
+
+    match res {
+        Err(Error::NonZeroExitStatus { status, stderr, .. }) => {
+            assert_eq!(status.code(), Some(3));
+            assert_eq!(stderr, "oops");
+        }
+        _ => panic!("expected NonZeroExitStatus, got {res:?}"),
+    }
+}